@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use wsbps::{Readable, VarInt, VarLong, Writable};
+
+/// Values covering every encoded size of a VarInt
+const INT_VALUES: [u32; 5] = [1, 300, 70_000, 3_000_000, u32::MAX];
+/// Values covering small, medium and maximum sized VarLongs
+const LONG_VALUES: [u64; 4] = [1, 70_000, 1 << 40, u64::MAX];
+
+fn write_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+    group.throughput(Throughput::Elements(INT_VALUES.len() as u64));
+    group.bench_function("varint", |b| {
+        let mut o = Vec::with_capacity(64);
+        b.iter(|| {
+            o.clear();
+            for value in INT_VALUES {
+                VarInt(black_box(value)).write(&mut o).unwrap();
+            }
+        })
+    });
+    group.throughput(Throughput::Elements(LONG_VALUES.len() as u64));
+    group.bench_function("varlong", |b| {
+        let mut o = Vec::with_capacity(64);
+        b.iter(|| {
+            o.clear();
+            for value in LONG_VALUES {
+                VarLong(black_box(value)).write(&mut o).unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+fn read_benchmarks(c: &mut Criterion) {
+    let mut ints = Vec::new();
+    for value in INT_VALUES {
+        VarInt(value).write(&mut ints).unwrap();
+    }
+    let mut longs = Vec::new();
+    for value in LONG_VALUES {
+        VarLong(value).write(&mut longs).unwrap();
+    }
+
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Elements(INT_VALUES.len() as u64));
+    group.bench_function("varint", |b| b.iter(|| {
+        let mut i = black_box(ints.as_slice());
+        for _ in INT_VALUES {
+            black_box(VarInt::read(&mut i).unwrap());
+        }
+    }));
+    group.bench_function("varint_decode", |b| b.iter(|| {
+        let mut bytes = black_box(ints.as_slice());
+        for _ in INT_VALUES {
+            let (value, size) = VarInt::decode(bytes).unwrap();
+            bytes = &bytes[size..];
+            black_box(value);
+        }
+    }));
+    group.throughput(Throughput::Elements(LONG_VALUES.len() as u64));
+    group.bench_function("varlong", |b| b.iter(|| {
+        let mut i = black_box(longs.as_slice());
+        for _ in LONG_VALUES {
+            black_box(VarLong::read(&mut i).unwrap());
+        }
+    }));
+    group.bench_function("varlong_decode", |b| b.iter(|| {
+        let mut bytes = black_box(longs.as_slice());
+        for _ in LONG_VALUES {
+            let (value, size) = VarLong::decode(bytes).unwrap();
+            bytes = &bytes[size..];
+            black_box(value);
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, write_benchmarks, read_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,115 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use crate::io::{read_n_with, write_all_slice, write_all_slice_with, Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{OrderedRepr, OrderedStyle};
+use crate::summary::Summary;
+
+/// ## Batch
+/// Multiple packets of the same group sent in a single websocket frame to reduce
+/// the framing overhead of high frequency small packets. This is encoded as a
+/// VarInt count followed by each of the packets (ID and contents). The generated
+/// groups also have read_batch and write_batch functions
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Batch<G>(pub Vec<G>);
+
+impl<G> Batch<G> {
+    /// Creates an empty batch
+    pub fn new() -> Self { Batch(Vec::new()) }
+
+    /// Adds the [packet] to the end of the batch
+    pub fn push(&mut self, packet: G) {
+        self.0.push(packet)
+    }
+
+    /// Consumes self returning the packets
+    pub fn into_inner(self) -> Vec<G> { self.0 }
+}
+
+impl<G> Deref for Batch<G> {
+    type Target = Vec<G>;
+
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl<G> DerefMut for Batch<G> {
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+
+impl<G> From<Vec<G>> for Batch<G> {
+    fn from(packets: Vec<G>) -> Self { Batch(packets) }
+}
+
+impl<G> FromIterator<G> for Batch<G> {
+    fn from_iter<I: IntoIterator<Item=G>>(iter: I) -> Self { Batch(iter.into_iter().collect()) }
+}
+
+impl<G> IntoIterator for Batch<G> {
+    type Item = G;
+    type IntoIter = alloc::vec::IntoIter<G>;
+
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+
+impl<G: Writable> Writable for Batch<G> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        write_batch(&mut self.0, o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        VarInt(self.0.len() as u32).write(o)?;
+        write_all_slice_with(ctx, &mut self.0, o)
+    }
+}
+
+impl<G: Readable> Readable for Batch<G> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let count = VarInt::read_with(ctx, i)?.0 as usize;
+        Ok(Batch(read_n_with(ctx, i, count)?))
+    }
+}
+
+impl<G: OrderedRepr> OrderedRepr for Batch<G> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.0.write_ordered(style, out)
+    }
+}
+
+impl<G: Summary> Summary for Batch<G> {
+    fn write_summary(&self, out: &mut String) {
+        self.0.write_summary(out)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, G: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Batch<G> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Batch(Vec::<G>::arbitrary(u)?))
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl<G> crate::Schema for Batch<G> {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("Batch") }
+}
+
+/// ## Read Batch
+/// Reads a batch of packets (a VarInt count followed by the packets) from the
+/// source [i]. Fails if the count is greater than the collection length limit
+pub fn read_batch<G: Readable, B: Read>(i: &mut B) -> ReadResult<Vec<G>> {
+    Ok(Batch::<G>::read(i)?.0)
+}
+
+/// ## Write Batch
+/// Writes the [packets] to the output [o] as a batch
+pub fn write_batch<G: Writable, B: Write>(packets: &mut [G], o: &mut B) -> WriteResult {
+    VarInt(packets.len() as u32).write(o)?;
+    write_all_slice(packets, o)
+}
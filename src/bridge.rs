@@ -0,0 +1,64 @@
+use alloc::boxed::Box;
+
+use crate::io::{PacketResult, Read, Readable, Writable, Write};
+
+/// Function used by a bridge to map a packet from one group to the other. None
+/// is returned for packets that have no equivalent and should be dropped
+type MapFn<F, T> = Box<dyn Fn(F) -> Option<T> + Send + Sync>;
+
+/// ## Bridge
+/// Translates packets between two different packet groups (e.g. two versions of a
+/// protocol) using the provided mapping functions. This is useful for gateways that
+/// front backends which speak a different group than the clients.
+pub struct Bridge<GA, GB> {
+    to_b: MapFn<GA, GB>,
+    to_a: MapFn<GB, GA>,
+}
+
+impl<GA, GB> Bridge<GA, GB> {
+    /// Creates a new bridge using [to_b] to map packets from GA to GB and [to_a]
+    /// to map packets from GB to GA
+    pub fn new<FB, FA>(to_b: FB, to_a: FA) -> Self
+        where FB: Fn(GA) -> Option<GB> + Send + Sync + 'static,
+              FA: Fn(GB) -> Option<GA> + Send + Sync + 'static {
+        Bridge { to_b: Box::new(to_b), to_a: Box::new(to_a) }
+    }
+
+    /// Maps the provided packet from GA to GB
+    pub fn to_b(&self, packet: GA) -> Option<GB> {
+        (self.to_b)(packet)
+    }
+
+    /// Maps the provided packet from GB to GA
+    pub fn to_a(&self, packet: GB) -> Option<GA> {
+        (self.to_a)(packet)
+    }
+}
+
+impl<GA: Readable, GB: Writable> Bridge<GA, GB> {
+    /// Reads a GA packet from [i] and writes it to [o] as a GB packet. Returns
+    /// whether a packet was written
+    pub fn forward<R: Read, W: Write>(&self, i: &mut R, o: &mut W) -> PacketResult<bool> {
+        match self.to_b(GA::read(i)?) {
+            Some(mut packet) => {
+                packet.write(o)?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+}
+
+impl<GA: Writable, GB: Readable> Bridge<GA, GB> {
+    /// Reads a GB packet from [i] and writes it to [o] as a GA packet. Returns
+    /// whether a packet was written
+    pub fn backward<R: Read, W: Write>(&self, i: &mut R, o: &mut W) -> PacketResult<bool> {
+        match self.to_a(GB::read(i)?) {
+            Some(mut packet) => {
+                packet.write(o)?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+
+/// ## Write Canonical
+/// Writes the [value] to [o] only if its encoding is canonical so that signatures
+/// over the bytes can be checked by peers which encode the value again. The value is
+/// encoded and then read back with read_canonical to check it, failing with
+/// PacketError::NonCanonical (e.g. for a HashMap with more than one entry or a
+/// string that isn't NFC) without writing anything
+pub fn write_canonical<T: Readable + Writable, B: Write>(value: &mut T, o: &mut B) -> WriteResult {
+    write_canonical_with(&mut WriteContext::default(), value, o)
+}
+
+/// ## Write Canonical With
+/// Writes the [value] to [o] using the context [ctx] of the connection being
+/// written to only if its encoding is canonical (see write_canonical)
+pub fn write_canonical_with<T: Readable + Writable, B: Write>(ctx: &mut WriteContext, value: &mut T, o: &mut B) -> WriteResult {
+    write_canonical_field(ctx, o, |ctx, o| value.write_with(ctx, o), |ctx, i| T::read_with(ctx, i).map(|_| ()))
+}
+
+/// ## Write Canonical Field
+/// Encodes a value with [write] and writes it to [o] only if [read] reads it back
+/// from all of its bytes with a canonical context. String enums written as IDs are
+/// read back using the StringIds they were written with. This is used by the
+/// #[canonical] field attribute for the encodings of the other field attributes
+pub fn write_canonical_field<B, W, R>(ctx: &mut WriteContext, o: &mut B, write: W, read: R) -> WriteResult
+    where B: Write,
+          W: FnOnce(&mut WriteContext, &mut Vec<u8>) -> WriteResult,
+          R: FnOnce(&mut ReadContext, &mut &[u8]) -> ReadResult<()> {
+    let mut bytes = Vec::new();
+    write(ctx, &mut bytes)?;
+    let mut read_ctx = ReadContext::default().with_canonical();
+    if let Some(string_ids) = ctx.string_ids() {
+        read_ctx = read_ctx.with_string_ids(string_ids.clone());
+    }
+    let mut remaining = bytes.as_slice();
+    read(&mut read_ctx, &mut remaining)?;
+    if !remaining.is_empty() {
+        return Err(PacketError::NonCanonical("the value to be read back from all of its bytes"));
+    }
+    o.write_all(&bytes)?;
+    Ok(())
+}
+
+/// ## Read Canonical
+/// Reads a value from the source [i] failing with PacketError::NonCanonical if it
+/// wasn't canonically encoded. Canonical encodings use VarInts and VarLongs with no
+/// extra bytes, map keys and set values in ascending order, only BTreeMaps and
+/// BTreeSets when there is more than one entry and strings in Unicode Normalization
+/// Form C (checked when the unicode feature is enabled)
+pub fn read_canonical<T: Readable, B: Read>(i: &mut B) -> ReadResult<T> {
+    T::read_with(&mut ReadContext::default().with_canonical(), i)
+}
@@ -0,0 +1,97 @@
+/// ## Chaos Config
+/// The faults injected by Chaos. Rates are the chance of the fault from 0.0 (never)
+/// to 1.0 (always)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaosConfig {
+    /// The chance that an outbound packet is dropped
+    pub drop_rate: f64,
+    /// The chance that the connection is forcibly disconnected when sending a packet
+    pub disconnect_rate: f64,
+    /// The chance that a handler is delayed
+    pub delay_rate: f64,
+    /// The maximum delay in milliseconds added to a delayed handler
+    pub max_delay: u64,
+}
+
+impl ChaosConfig {
+    /// Config which doesn't inject any faults
+    pub const NONE: ChaosConfig = ChaosConfig {
+        drop_rate: 0.0,
+        disconnect_rate: 0.0,
+        delay_rate: 0.0,
+        max_delay: 0,
+    };
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self { ChaosConfig::NONE }
+}
+
+/// What should happen to an outbound packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosAction {
+    /// The packet should be sent as normal
+    Send,
+    /// The packet should be silently dropped
+    Drop,
+    /// The packet should be dropped and the connection closed
+    Disconnect,
+}
+
+/// ## Chaos
+/// Seeded fault injector for testing how protocol logic copes with dropped
+/// packets, slow handlers and disconnects. The connection layer asks it what to do
+/// with each outbound packet and how long to delay each handler. The same seed and
+/// config always produce the same faults so failures can be reproduced
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chaos {
+    config: ChaosConfig,
+    state: u64,
+}
+
+impl Chaos {
+    /// Creates a new injector with the provided [seed] and [config]
+    pub fn new(seed: u64, config: ChaosConfig) -> Self {
+        Chaos { config, state: seed }
+    }
+
+    /// The faults being injected
+    pub fn config(&self) -> &ChaosConfig { &self.config }
+
+    /// Decides what should happen to the next outbound packet
+    pub fn outbound(&mut self) -> ChaosAction {
+        if self.chance(self.config.disconnect_rate) {
+            ChaosAction::Disconnect
+        } else if self.chance(self.config.drop_rate) {
+            ChaosAction::Drop
+        } else {
+            ChaosAction::Send
+        }
+    }
+
+    /// The delay in milliseconds to add before the next handler runs
+    pub fn handler_delay(&mut self) -> u64 {
+        if self.config.max_delay == 0 || !self.chance(self.config.delay_rate) {
+            return 0;
+        }
+        self.next_u64() % self.config.max_delay + 1
+    }
+
+    /// Whether an event with the provided [rate] happens
+    fn chance(&mut self, rate: f64) -> bool {
+        if rate <= 0.0 {
+            return false;
+        }
+        // The top 53 bits give a uniform value in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < rate
+    }
+
+    /// Advances the SplitMix64 generator
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
@@ -0,0 +1,158 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{IoError, Read, Readable, ReadResult, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{OrderedRepr, OrderedStyle};
+use crate::summary::Summary;
+
+/// Lookup table for the CRC-32 (IEEE) polynomial computed at compile time
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// Updates the running [crc] with the provided bytes
+fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    for byte in bytes {
+        crc = CRC_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+/// Computes the CRC-32 (IEEE, the same as zlib and most JS implementations) of
+/// the provided bytes
+pub fn crc32(bytes: &[u8]) -> u32 {
+    crc32_update(0xFFFF_FFFF, bytes) ^ 0xFFFF_FFFF
+}
+
+/// ## Checksummed
+/// Wraps a value (usually a packet group) so that it is followed by the CRC-32 of
+/// its encoded bytes as a u32. Reading fails with PacketError::ChecksumMismatch if
+/// the checksum doesn't match the bytes that were read which detects corruption
+/// over unreliable tunnels and encoders that disagree with each other
+///
+/// ```
+/// use wsbps::{Checksummed, Readable, Writable};
+/// let mut o = Vec::new();
+/// Checksummed(5u32).write(&mut o).unwrap();
+/// assert_eq!(Checksummed::<u32>::read(&mut o.as_slice()).unwrap(), Checksummed(5));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checksummed<T>(pub T);
+
+impl<T> Checksummed<T> {
+    /// Consumes self returning the wrapped value
+    pub fn into_inner(self) -> T { self.0 }
+}
+
+impl<T> From<T> for Checksummed<T> { fn from(v: T) -> Self { Checksummed(v) } }
+
+impl<T: Writable> Writable for Checksummed<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        let mut bytes = Vec::new();
+        self.0.write_with(ctx, &mut bytes)?;
+        o.write_all(&bytes)?;
+        crc32(&bytes).write(o)
+    }
+}
+
+impl<T: Readable> Readable for Checksummed<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let mut reader = ChecksumReader { inner: i, crc: 0xFFFF_FFFF };
+        let value = T::read_with(ctx, &mut reader)?;
+        let actual = reader.crc ^ 0xFFFF_FFFF;
+        let expected = u32::read(i)?;
+        if expected != actual {
+            return Err(PacketError::ChecksumMismatch(expected, actual));
+        }
+        Ok(Checksummed(value))
+    }
+}
+
+/// ## Checksum Reader
+/// Reader which updates a running CRC-32 with all the bytes that are read
+struct ChecksumReader<'a, B: Read> {
+    inner: &'a mut B,
+    crc: u32,
+}
+
+#[cfg(feature = "std")]
+impl<B: Read> Read for ChecksumReader<'_, B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        let count = self.inner.read(buf)?;
+        self.crc = crc32_update(self.crc, &buf[..count]);
+        Ok(count)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: Read> Read for ChecksumReader<'_, B> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        self.inner.read_exact(buf)?;
+        self.crc = crc32_update(self.crc, buf);
+        Ok(())
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for Checksummed<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.0.write_ordered(style, out)
+    }
+}
+
+impl<T: Summary> Summary for Checksummed<T> {
+    fn write_summary(&self, out: &mut String) {
+        self.0.write_summary(out)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Checksummed<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Checksummed(T::arbitrary(u)?))
+    }
+}
+
+/// The checksum covers the encoded bytes of the value which the generators
+/// don't have access to
+#[cfg(feature = "codegen")]
+impl<T: crate::Schema> crate::Schema for Checksummed<T> {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("Checksummed") }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::Definition>) { T::definitions(definitions) }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Checksummed<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Checksummed<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Checksummed)
+    }
+}
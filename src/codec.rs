@@ -0,0 +1,93 @@
+use alloc::vec::Vec;
+
+use crate::io::{PacketResult, Readable, Writable};
+
+/// ## Codec
+/// The format used when encoding packets to and decoding packets from a message.
+/// The codec can be selected at runtime so the same definitions can be sent as
+/// binary normally and as readable JSON text when debugging (e.g. so websocket
+/// frames can be read from the browser devtools)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    /// The binary wire format produced by Readable and Writable
+    #[default]
+    Binary,
+    /// JSON text containing the packet name and its fields for example
+    /// `{"Login":{"name":"Jacob","version":1}}`. Requires the "json" feature
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl Codec {
+    /// Encodes the provided value using this codec
+    pub fn encode<T: CodecWrite>(self, value: &mut T) -> PacketResult<Vec<u8>> {
+        value.encode_with(self)
+    }
+
+    /// Decodes a value from the provided message using this codec
+    pub fn decode<T: CodecRead>(self, data: &[u8]) -> PacketResult<T> {
+        T::decode_with(self, data)
+    }
+}
+
+/// Trait implemented by the generated writable types allowing them to be
+/// encoded with any of the codecs
+pub trait CodecWrite {
+    /// Encodes self into a new message using the provided codec
+    fn encode_with(&mut self, codec: Codec) -> PacketResult<Vec<u8>>;
+}
+
+/// Trait implemented by the generated readable types allowing them to be
+/// decoded with any of the codecs
+pub trait CodecRead: Sized {
+    /// Decodes a value from the provided message using the provided codec
+    fn decode_with(codec: Codec, data: &[u8]) -> PacketResult<Self>;
+}
+
+/// Encodes the provided value with the codec. Used by the generated CodecWrite
+/// implementations
+#[doc(hidden)]
+#[cfg(not(feature = "json"))]
+pub fn encode_with_codec<T: Writable>(codec: Codec, value: &mut T) -> PacketResult<Vec<u8>> {
+    match codec {
+        Codec::Binary => encode_binary(value),
+    }
+}
+
+/// Encodes the provided value with the codec. Used by the generated CodecWrite
+/// implementations
+#[doc(hidden)]
+#[cfg(feature = "json")]
+pub fn encode_with_codec<T: Writable + serde::Serialize>(codec: Codec, value: &mut T) -> PacketResult<Vec<u8>> {
+    match codec {
+        Codec::Binary => encode_binary(value),
+        Codec::Json => Ok(serde_json::to_vec(value)?),
+    }
+}
+
+/// Decodes a value from the message with the codec. Used by the generated
+/// CodecRead implementations
+#[doc(hidden)]
+#[cfg(not(feature = "json"))]
+pub fn decode_with_codec<T: Readable>(codec: Codec, data: &[u8]) -> PacketResult<T> {
+    match codec {
+        Codec::Binary => T::read(&mut &data[..]),
+    }
+}
+
+/// Decodes a value from the message with the codec. Used by the generated
+/// CodecRead implementations
+#[doc(hidden)]
+#[cfg(feature = "json")]
+pub fn decode_with_codec<T: Readable + serde::de::DeserializeOwned>(codec: Codec, data: &[u8]) -> PacketResult<T> {
+    match codec {
+        Codec::Binary => T::read(&mut &data[..]),
+        Codec::Json => Ok(serde_json::from_slice(data)?),
+    }
+}
+
+fn encode_binary<T: Writable>(value: &mut T) -> PacketResult<Vec<u8>> {
+    let mut o = Vec::new();
+    value.write(&mut o)?;
+    Ok(o)
+}
@@ -0,0 +1,1364 @@
+use core::fmt::Write;
+use std::path::Path;
+
+use crate::migration::{FieldSource, SchemaMigration};
+use crate::schema::{Definition, DefinitionKind, FieldSchema, GroupSchema, TypeSchema};
+
+/// The reader and writer used by the generated JavaScript functions. These implement
+/// the same encodings as the Readable and Writable implementations
+const JS_RUNTIME: &str = r#"const textEncoder = new TextEncoder();
+const textDecoder = new TextDecoder("utf-8", { fatal: true });
+
+function unsupported(codec) {
+    throw new Error(`fields encoded using ${codec} must be implemented by hand`);
+}
+
+class Reader {
+    constructor(bytes) {
+        this.data = bytes;
+        this.view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+        this.offset = 0;
+    }
+
+    atEnd() { return this.offset >= this.data.length; }
+
+    take(length) {
+        if (this.offset + length > this.data.length) throw new Error("unexpected end of packet");
+        const start = this.offset;
+        this.offset += length;
+        return start;
+    }
+
+    bool() {
+        const value = this.u8();
+        if (value > 1) throw new Error("expected 0 or 1 for boolean value");
+        return value === 1;
+    }
+
+    u8() { return this.view.getUint8(this.take(1)); }
+    u16() { return this.view.getUint16(this.take(2)); }
+    u32() { return this.view.getUint32(this.take(4)); }
+    u64() { return this.view.getBigUint64(this.take(8)); }
+    i8() { return this.view.getInt8(this.take(1)); }
+    i16() { return this.view.getInt16(this.take(2)); }
+    i32() { return this.view.getInt32(this.take(4)); }
+    i64() { return this.view.getBigInt64(this.take(8)); }
+    f32() { return this.view.getFloat32(this.take(4)); }
+    f64() { return this.view.getFloat64(this.take(8)); }
+
+    varInt() {
+        let result = 0;
+        for (let index = 0; index < 5; index++) {
+            const byte = this.u8();
+            result += (byte & 0x7F) * 2 ** (index * 7);
+            if ((byte & 0x80) === 0) return result % 4294967296;
+        }
+        throw new Error("var-int exceeded maximum length of 5 bytes");
+    }
+
+    varLong() {
+        let result = 0n;
+        for (let index = 0; index < 10; index++) {
+            const byte = this.u8();
+            result |= BigInt(byte & 0x7F) << BigInt(index * 7);
+            if ((byte & 0x80) === 0) return BigInt.asUintN(64, result);
+        }
+        throw new Error("var-long exceeded maximum length of 10 bytes");
+    }
+
+    bytes(length) {
+        const start = this.take(length);
+        return this.data.slice(start, start + length);
+    }
+
+    string(length) {
+        const start = this.take(length);
+        return textDecoder.decode(this.data.subarray(start, start + length));
+    }
+
+    list(length, readItem) {
+        const items = [];
+        for (let index = 0; index < length; index++) items.push(readItem());
+        return items;
+    }
+
+    map(length, readKey, readValue) {
+        const entries = new Map();
+        for (let index = 0; index < length; index++) {
+            const key = readKey();
+            entries.set(key, readValue());
+        }
+        return entries;
+    }
+
+    result(readOk, readErr) {
+        const tag = this.u8();
+        if (tag > 1) throw new Error("expected 0 or 1 for result tag");
+        return tag === 0 ? { ok: readOk() } : { err: readErr() };
+    }
+}
+
+class Writer {
+    constructor() {
+        this.data = new Uint8Array(64);
+        this.view = new DataView(this.data.buffer);
+        this.length = 0;
+    }
+
+    reserve(length) {
+        if (this.length + length > this.data.length) {
+            const bytes = new Uint8Array(Math.max(this.data.length * 2, this.length + length));
+            bytes.set(this.data);
+            this.data = bytes;
+            this.view = new DataView(bytes.buffer);
+        }
+        const start = this.length;
+        this.length += length;
+        return start;
+    }
+
+    bool(value) { this.u8(value ? 1 : 0); }
+    u8(value) { this.view.setUint8(this.reserve(1), value); }
+    u16(value) { this.view.setUint16(this.reserve(2), value); }
+    u32(value) { this.view.setUint32(this.reserve(4), value); }
+    u64(value) { this.view.setBigUint64(this.reserve(8), BigInt(value)); }
+    i8(value) { this.view.setInt8(this.reserve(1), value); }
+    i16(value) { this.view.setInt16(this.reserve(2), value); }
+    i32(value) { this.view.setInt32(this.reserve(4), value); }
+    i64(value) { this.view.setBigInt64(this.reserve(8), BigInt(value)); }
+    f32(value) { this.view.setFloat32(this.reserve(4), value); }
+    f64(value) { this.view.setFloat64(this.reserve(8), value); }
+
+    varInt(value) {
+        while (value >= 0x80) {
+            this.u8((value % 128) | 0x80);
+            value = Math.floor(value / 128);
+        }
+        this.u8(value);
+    }
+
+    varLong(value) {
+        value = BigInt.asUintN(64, BigInt(value));
+        while (value >= 0x80n) {
+            this.u8(Number(value & 0x7Fn) | 0x80);
+            value >>= 7n;
+        }
+        this.u8(Number(value));
+    }
+
+    bytes(value, prefixed = true) {
+        if (prefixed) this.varInt(value.length);
+        const start = this.reserve(value.length);
+        this.data.set(value, start);
+    }
+
+    string(value, prefixed = true) {
+        this.bytes(textEncoder.encode(value), prefixed);
+    }
+
+    list(items, writeItem, prefixed = true) {
+        if (prefixed) this.varInt(items.length);
+        for (const item of items) writeItem(item);
+    }
+
+    map(entries, writeKey, writeValue) {
+        const list = [...entries];
+        this.varInt(list.length);
+        for (const [key, value] of list) {
+            writeKey(key);
+            writeValue(value);
+        }
+    }
+
+    option(value, writeValue) {
+        if (value === null || value === undefined) {
+            this.bool(false);
+        } else {
+            this.bool(true);
+            writeValue(value);
+        }
+    }
+
+    result(value, writeOk, writeErr) {
+        if ("err" in value) {
+            this.u8(1);
+            writeErr(value.err);
+        } else {
+            this.u8(0);
+            writeOk(value.ok);
+        }
+    }
+
+    finish() { return this.data.slice(0, this.length); }
+}
+"#;
+
+/// ## JS Client
+/// Generates a self-contained JavaScript module with an encode and decode function
+/// for each packet in the provided [groups] (`encodeGroupPacket(packet)` and
+/// `decodeGroupPacket(bytes)`) along with `encodeGroup(packet)` and
+/// `decodeGroup(bytes)` for any packet in the group. Packets are plain objects
+/// with their fields and the name of the packet as `type`, 64 bit numbers and
+/// VarLongs are BigInts, `Vec<u8>` is a Uint8Array, maps are Maps, results are
+/// `{ ok }` or `{ err }` objects, the unit type is null and enums are the name of
+/// the variant
+pub fn js_client(groups: &[GroupSchema]) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by wsbps from the packet definitions. Do not edit\n\n");
+    out.push_str(JS_RUNTIME);
+    for definition in unique_definitions(groups) {
+        out.push('\n');
+        js_definition(&mut out, definition);
+    }
+    for group in groups {
+        js_group(&mut out, group);
+    }
+    out
+}
+
+/// ## Write JS Client
+/// Writes the JavaScript module generated by js_client for the provided [groups]
+/// to the file at [path]. This is intended to be called from a build script
+///
+/// ```ignore
+/// wsbps::write_js_client("web/packets.js", &[ServerPackets::group_schema(), ClientPackets::group_schema()])?;
+/// ```
+pub fn write_js_client<P: AsRef<Path>>(path: P, groups: &[GroupSchema]) -> std::io::Result<()> {
+    std::fs::write(path, js_client(groups))
+}
+
+/// The definitions used by all the [groups] without duplicates
+fn unique_definitions(groups: &[GroupSchema]) -> Vec<&Definition> {
+    dedup_definitions(groups.iter().flat_map(|group| &group.definitions))
+}
+
+/// The [all] definitions without the duplicates of earlier definitions
+fn dedup_definitions<'a>(all: impl Iterator<Item=&'a Definition>) -> Vec<&'a Definition> {
+    let mut definitions: Vec<&Definition> = Vec::new();
+    for definition in all {
+        if !definitions.iter().any(|existing| existing.name == definition.name) {
+            definitions.push(definition);
+        }
+    }
+    definitions
+}
+
+/// Writes the read and write functions for a struct or enum
+fn js_definition(out: &mut String, definition: &Definition) {
+    let name = definition.name;
+    match &definition.kind {
+        DefinitionKind::Struct(fields) => {
+            let _ = writeln!(out, "function read{}(r) {{", name);
+            out.push_str("    const value = {};\n");
+            js_read_fields(out, "value", fields);
+            out.push_str("    return value;\n}\n\n");
+            let _ = writeln!(out, "function write{}(w, value) {{", name);
+            js_write_fields(out, "value", fields);
+            out.push_str("}\n");
+        }
+        DefinitionKind::Enum { repr, variants } => {
+            let _ = writeln!(out, "function read{}(r) {{", name);
+            let _ = writeln!(out, "    switch ({}) {{", js_read(repr));
+            for (variant, value) in variants {
+                let _ = writeln!(out, "        case {}: return {:?};", js_literal(value, repr), variant);
+            }
+            out.push_str("        default: throw new Error(\"unknown enum value\");\n    }\n}\n\n");
+            let _ = writeln!(out, "function write{}(w, value) {{", name);
+            out.push_str("    switch (value) {\n");
+            for (variant, value) in variants {
+                let _ = writeln!(out, "        case {:?}: {} break;", variant, js_write(repr, &js_literal(value, repr)));
+            }
+            let _ = writeln!(out, "        default: throw new Error(`unknown {} variant ${{value}}`);\n    }}\n}}", name);
+        }
+    }
+}
+
+/// Writes the encode and decode functions for the packets of a group
+fn js_group(out: &mut String, group: &GroupSchema) {
+    let name = group.name;
+    for packet in &group.packets {
+        let function = format!("{}{}", name, packet.name);
+        let _ = writeln!(out, "\nfunction read{}(r) {{", function);
+        let _ = writeln!(out, "    const packet = {{ type: {:?} }};", packet.name);
+        js_read_fields(out, "packet", &packet.fields);
+        out.push_str("    return packet;\n}\n\n");
+
+        let _ = writeln!(out, "export function encode{}(packet) {{", function);
+        out.push_str("    const w = new Writer();\n");
+        let _ = writeln!(out, "    w.varInt({:#04x});", packet.id);
+        js_write_fields(out, "packet", &packet.fields);
+        out.push_str("    return w.finish();\n}\n\n");
+
+        let _ = writeln!(out, "export function decode{}(bytes) {{", function);
+        out.push_str("    const r = new Reader(bytes);\n    const id = r.varInt();\n");
+        let _ = writeln!(out, "    if (id !== {:#04x}) throw new Error(`expected packet id {:#04x} got ${{id}}`);", packet.id, packet.id);
+        let _ = writeln!(out, "    return read{}(r);\n}}", function);
+    }
+
+    let _ = writeln!(out, "\nexport function encode{}(packet) {{", name);
+    out.push_str("    switch (packet.type) {\n");
+    for packet in &group.packets {
+        let _ = writeln!(out, "        case {:?}: return encode{}{}(packet);", packet.name, name, packet.name);
+    }
+    let _ = writeln!(out, "        default: throw new Error(`unknown {} packet ${{packet.type}}`);\n    }}\n}}", name);
+
+    let _ = writeln!(out, "\nexport function decode{}(bytes) {{", name);
+    out.push_str("    const r = new Reader(bytes);\n    const id = r.varInt();\n    switch (id) {\n");
+    for packet in &group.packets {
+        let _ = writeln!(out, "        case {:#04x}: return read{}{}(r);", packet.id, name, packet.name);
+    }
+    out.push_str("        default: throw new Error(`packet with unknown id of ${id} received`);\n    }\n}\n");
+}
+
+/// Writes the statements reading the [fields] into the properties of [target]
+fn js_read_fields(out: &mut String, target: &str, fields: &[FieldSchema]) {
+    for field in fields {
+        let mut read = match field.length_from {
+            Some(length) => js_read_unprefixed(&field.ty, &format!("Number({}.{})", target, length)),
+            None => js_read(&field.ty),
+        };
+        if let Some(default) = field.default {
+            read = format!("r.atEnd() ? {} : {}", js_default_value(default, &field.ty), read);
+        }
+        if let Some(condition) = field.when {
+            read = format!("{}.{} ? {} : {}", target, condition, read, js_default(&field.ty));
+        }
+        let _ = writeln!(out, "    {}.{} = {};", target, field.name, read);
+    }
+}
+
+/// Writes the statements writing the [fields] from the properties of [source]
+fn js_write_fields(out: &mut String, source: &str, fields: &[FieldSchema]) {
+    for field in fields {
+        let value = format!("{}.{}", source, field.name);
+        let write = match field.length_from {
+            Some(_) => js_write_unprefixed(&field.ty, &value),
+            None => js_write(&field.ty, &value),
+        };
+        if write.is_empty() {
+            continue;
+        }
+        match field.when {
+            Some(condition) => { let _ = writeln!(out, "    if ({}.{}) {{ {} }}", source, condition, write); }
+            None => { let _ = writeln!(out, "    {}", write); }
+        }
+    }
+}
+
+/// The expression reading a value of the type [ty] from the reader `r`
+fn js_read(ty: &TypeSchema) -> String {
+    match ty {
+        TypeSchema::Bool => "r.bool()".to_string(),
+        TypeSchema::U8 => "r.u8()".to_string(),
+        TypeSchema::U16 => "r.u16()".to_string(),
+        TypeSchema::U32 => "r.u32()".to_string(),
+        TypeSchema::U64 => "r.u64()".to_string(),
+        TypeSchema::I8 => "r.i8()".to_string(),
+        TypeSchema::I16 => "r.i16()".to_string(),
+        TypeSchema::I32 => "r.i32()".to_string(),
+        TypeSchema::I64 => "r.i64()".to_string(),
+        TypeSchema::F32 => "r.f32()".to_string(),
+        TypeSchema::F64 => "r.f64()".to_string(),
+        TypeSchema::VarInt => "r.varInt()".to_string(),
+        TypeSchema::VarLong => "r.varLong()".to_string(),
+        TypeSchema::String | TypeSchema::List(_) => js_read_unprefixed(ty, "r.varInt()"),
+        TypeSchema::Map(key, value) => format!("r.map(r.varInt(), () => {}, () => {})", js_read(key), js_read(value)),
+        TypeSchema::Option(value) => format!("(r.bool() ? {} : null)", js_read(value)),
+        TypeSchema::Result(ok, err) => format!("r.result(() => {}, () => {})", js_read(ok), js_read(err)),
+        TypeSchema::Unit => "null".to_string(),
+        TypeSchema::Named(name) => format!("read{}(r)", name),
+        TypeSchema::Custom(codec) => format!("unsupported({:?})", codec),
+    }
+}
+
+/// The expression reading a string or list of the type [ty] with the provided
+/// [length] expression rather than a length prefix
+fn js_read_unprefixed(ty: &TypeSchema, length: &str) -> String {
+    match ty {
+        TypeSchema::String => format!("r.string({})", length),
+        TypeSchema::List(item) if **item == TypeSchema::U8 => format!("r.bytes({})", length),
+        TypeSchema::List(item) => format!("r.list({}, () => {})", length, js_read(item)),
+        ty => js_read(ty),
+    }
+}
+
+/// The statement writing the [value] expression of the type [ty] to the writer `w`.
+/// This is empty for the unit type which isn't written
+fn js_write(ty: &TypeSchema, value: &str) -> String {
+    match ty {
+        TypeSchema::Bool => format!("w.bool({});", value),
+        TypeSchema::U8 => format!("w.u8({});", value),
+        TypeSchema::U16 => format!("w.u16({});", value),
+        TypeSchema::U32 => format!("w.u32({});", value),
+        TypeSchema::U64 => format!("w.u64({});", value),
+        TypeSchema::I8 => format!("w.i8({});", value),
+        TypeSchema::I16 => format!("w.i16({});", value),
+        TypeSchema::I32 => format!("w.i32({});", value),
+        TypeSchema::I64 => format!("w.i64({});", value),
+        TypeSchema::F32 => format!("w.f32({});", value),
+        TypeSchema::F64 => format!("w.f64({});", value),
+        TypeSchema::VarInt => format!("w.varInt({});", value),
+        TypeSchema::VarLong => format!("w.varLong({});", value),
+        TypeSchema::String => format!("w.string({});", value),
+        TypeSchema::List(item) if **item == TypeSchema::U8 => format!("w.bytes({});", value),
+        TypeSchema::List(item) => format!("w.list({}, (item) => {{ {} }});", value, js_write(item, "item")),
+        TypeSchema::Map(key, item) => format!(
+            "w.map({}, (key) => {{ {} }}, (item) => {{ {} }});",
+            value, js_write(key, "key"), js_write(item, "item")
+        ),
+        TypeSchema::Option(item) => format!("w.option({}, (item) => {{ {} }});", value, js_write(item, "item")),
+        TypeSchema::Result(ok, err) => format!(
+            "w.result({}, (item) => {{ {} }}, (item) => {{ {} }});",
+            value, js_write(ok, "item"), js_write(err, "item")
+        ),
+        TypeSchema::Unit => String::new(),
+        TypeSchema::Named(name) => format!("write{}(w, {});", name, value),
+        TypeSchema::Custom(codec) => format!("unsupported({:?});", codec),
+    }
+}
+
+/// The statement writing a string or list without its length prefix
+fn js_write_unprefixed(ty: &TypeSchema, value: &str) -> String {
+    match ty {
+        TypeSchema::String => format!("w.string({}, false);", value),
+        TypeSchema::List(item) if **item == TypeSchema::U8 => format!("w.bytes({}, false);", value),
+        TypeSchema::List(item) => format!("w.list({}, (item) => {{ {} }}, false);", value, js_write(item, "item")),
+        ty => js_write(ty, value),
+    }
+}
+
+/// Whether values of the type [ty] are BigInts
+fn is_big_int(ty: &TypeSchema) -> bool {
+    matches!(ty, TypeSchema::U64 | TypeSchema::I64 | TypeSchema::VarLong)
+}
+
+/// The default value of the type [ty] used for conditional fields that aren't present
+fn js_default(ty: &TypeSchema) -> &'static str {
+    match ty {
+        TypeSchema::Bool => "false",
+        ty if is_big_int(ty) => "0n",
+        TypeSchema::U8 | TypeSchema::U16 | TypeSchema::U32 | TypeSchema::I8 | TypeSchema::I16
+        | TypeSchema::I32 | TypeSchema::F32 | TypeSchema::F64 | TypeSchema::VarInt => "0",
+        TypeSchema::String => "\"\"",
+        TypeSchema::List(item) if **item == TypeSchema::U8 => "new Uint8Array()",
+        TypeSchema::List(_) => "[]",
+        TypeSchema::Map(_, _) => "new Map()",
+        _ => "null",
+    }
+}
+
+/// Converts the Rust source of a default field value to JavaScript. Only literals can
+/// be converted, anything else uses the default value of the type
+fn js_default_value(source: &str, ty: &TypeSchema) -> String {
+    let source = source.trim();
+    if is_literal(source) {
+        js_literal(source, ty)
+    } else {
+        js_default(ty).to_string()
+    }
+}
+
+/// Whether the Rust [source] of a default field value is a literal (a bool, number
+/// or string) which can be converted to other languages
+pub(crate) fn is_literal(source: &str) -> bool {
+    source == "true" || source == "false"
+        || (source.starts_with('"') && source.ends_with('"'))
+        || source.starts_with(|c: char| c.is_ascii_digit() || c == '-')
+}
+
+/// Removes the type suffix (e.g. `5u8`) from the Rust [source] of a literal
+pub(crate) fn strip_literal_suffix(source: &str) -> &str {
+    const SUFFIXES: [&str; 12] = ["u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize", "f32", "f64"];
+    let literal = source.trim();
+    if literal.starts_with('"') {
+        return literal;
+    }
+    // Float suffixes are hex digits so only the integer suffixes are removed from hex
+    let hex = literal.starts_with("0x");
+    let suffix = SUFFIXES.iter()
+        .filter(|suffix| !hex || !suffix.starts_with('f'))
+        .find(|suffix| literal.ends_with(*suffix));
+    match suffix {
+        Some(suffix) => literal[..literal.len() - suffix.len()].trim_end_matches('_'),
+        None => literal,
+    }
+}
+
+/// Converts the Rust source of a literal of the type [ty] to JavaScript by removing
+/// any type suffix and making 64 bit numbers BigInts
+fn js_literal(source: &str, ty: &TypeSchema) -> String {
+    let literal = strip_literal_suffix(source);
+    if is_big_int(ty) && !literal.starts_with('"') {
+        format!("{}n", literal)
+    } else {
+        literal.to_string()
+    }
+}
+
+/// The reader and writer used by the generated Kotlin classes. These implement the
+/// same encodings as the Readable and Writable implementations
+const KOTLIN_RUNTIME: &str = r#"class PacketException(message: String) : Exception(message)
+
+sealed interface PacketResult<out T, out E> {
+    data class Ok<out T>(val value: T) : PacketResult<T, Nothing>
+    data class Err<out E>(val error: E) : PacketResult<Nothing, E>
+}
+
+class PacketReader(bytes: ByteArray) {
+    private val buffer = java.nio.ByteBuffer.wrap(bytes)
+
+    fun atEnd(): Boolean = !buffer.hasRemaining()
+
+    private fun <T> read(read: () -> T): T =
+        try { read() } catch (e: java.nio.BufferUnderflowException) { throw PacketException("unexpected end of packet") }
+
+    fun bool(): Boolean = when (i8().toInt()) {
+        0 -> false
+        1 -> true
+        else -> throw PacketException("expected 0 or 1 for boolean value")
+    }
+
+    fun u8(): UByte = i8().toUByte()
+    fun u16(): UShort = i16().toUShort()
+    fun u32(): UInt = i32().toUInt()
+    fun u64(): ULong = i64().toULong()
+    fun i8(): Byte = read { buffer.get() }
+    fun i16(): Short = read { buffer.getShort() }
+    fun i32(): Int = read { buffer.getInt() }
+    fun i64(): Long = read { buffer.getLong() }
+    fun f32(): Float = read { buffer.getFloat() }
+    fun f64(): Double = read { buffer.getDouble() }
+
+    fun varInt(): UInt {
+        var result = 0u
+        for (index in 0 until 5) {
+            val byte = u8().toUInt()
+            result = result or ((byte and 0x7Fu) shl (index * 7))
+            if ((byte and 0x80u) == 0u) return result
+        }
+        throw PacketException("var-int exceeded maximum length of 5 bytes")
+    }
+
+    fun varLong(): ULong {
+        var result = 0uL
+        for (index in 0 until 10) {
+            val byte = u8().toULong()
+            result = result or ((byte and 0x7FuL) shl (index * 7))
+            if ((byte and 0x80uL) == 0uL) return result
+        }
+        throw PacketException("var-long exceeded maximum length of 10 bytes")
+    }
+
+    fun bytes(length: Int): ByteArray {
+        if (length > buffer.remaining()) throw PacketException("unexpected end of packet")
+        val bytes = ByteArray(length)
+        buffer.get(bytes)
+        return bytes
+    }
+
+    fun string(length: Int): String = try {
+        Charsets.UTF_8.newDecoder().decode(java.nio.ByteBuffer.wrap(bytes(length))).toString()
+    } catch (e: java.nio.charset.CharacterCodingException) {
+        throw PacketException("failed to convert string bytes to utf-8 string")
+    }
+
+    fun <T> list(length: Int, readItem: () -> T): List<T> = List(length) { readItem() }
+
+    fun <K, V> map(length: Int, readKey: () -> K, readValue: () -> V): Map<K, V> {
+        val entries = LinkedHashMap<K, V>()
+        repeat(length) {
+            val key = readKey()
+            entries[key] = readValue()
+        }
+        return entries
+    }
+
+    fun <T, E> result(readOk: () -> T, readErr: () -> E): PacketResult<T, E> = when (u8().toInt()) {
+        0 -> PacketResult.Ok(readOk())
+        1 -> PacketResult.Err(readErr())
+        else -> throw PacketException("expected 0 or 1 for result tag")
+    }
+}
+
+class PacketWriter {
+    private val bytes = java.io.ByteArrayOutputStream()
+    private val data = java.io.DataOutputStream(bytes)
+
+    fun bool(value: Boolean) = data.writeByte(if (value) 1 else 0)
+    fun u8(value: UByte) = data.writeByte(value.toInt())
+    fun u16(value: UShort) = data.writeShort(value.toInt())
+    fun u32(value: UInt) = data.writeInt(value.toInt())
+    fun u64(value: ULong) = data.writeLong(value.toLong())
+    fun i8(value: Byte) = data.writeByte(value.toInt())
+    fun i16(value: Short) = data.writeShort(value.toInt())
+    fun i32(value: Int) = data.writeInt(value)
+    fun i64(value: Long) = data.writeLong(value)
+    fun f32(value: Float) = data.writeFloat(value)
+    fun f64(value: Double) = data.writeDouble(value)
+
+    fun varInt(value: UInt) {
+        var remaining = value
+        while (remaining >= 0x80u) {
+            data.writeByte(((remaining and 0x7Fu) or 0x80u).toInt())
+            remaining = remaining shr 7
+        }
+        data.writeByte(remaining.toInt())
+    }
+
+    fun varLong(value: ULong) {
+        var remaining = value
+        while (remaining >= 0x80uL) {
+            data.writeByte(((remaining and 0x7FuL) or 0x80uL).toInt())
+            remaining = remaining shr 7
+        }
+        data.writeByte(remaining.toInt())
+    }
+
+    fun bytes(value: ByteArray, prefixed: Boolean = true) {
+        if (prefixed) varInt(value.size.toUInt())
+        data.write(value)
+    }
+
+    fun string(value: String, prefixed: Boolean = true) = bytes(value.toByteArray(Charsets.UTF_8), prefixed)
+
+    fun <T> list(items: List<T>, prefixed: Boolean = true, writeItem: (T) -> Unit) {
+        if (prefixed) varInt(items.size.toUInt())
+        items.forEach(writeItem)
+    }
+
+    fun <K, V> map(entries: Map<K, V>, writeKey: (K) -> Unit, writeValue: (V) -> Unit) {
+        varInt(entries.size.toUInt())
+        for ((key, value) in entries) {
+            writeKey(key)
+            writeValue(value)
+        }
+    }
+
+    fun <T : Any> option(value: T?, writeValue: (T) -> Unit) {
+        bool(value != null)
+        if (value != null) writeValue(value)
+    }
+
+    fun <T, E> result(value: PacketResult<T, E>, writeOk: (T) -> Unit, writeErr: (E) -> Unit) = when (value) {
+        is PacketResult.Ok -> {
+            u8(0u)
+            writeOk(value.value)
+        }
+        is PacketResult.Err -> {
+            u8(1u)
+            writeErr(value.error)
+        }
+    }
+
+    fun finish(): ByteArray = bytes.toByteArray()
+}
+"#;
+
+/// The Kotlin keywords that have to be escaped when used as names
+const KOTLIN_KEYWORDS: [&str; 28] = [
+    "as", "break", "class", "continue", "do", "else", "false", "for", "fun", "if", "in", "interface", "is",
+    "null", "object", "package", "return", "super", "this", "throw", "true", "try", "typealias", "typeof",
+    "val", "var", "when", "while",
+];
+
+/// ## Kotlin Client
+/// Generates a Kotlin source file in the provided [package] with a data class for each
+/// struct and packet in the provided [groups] along with read and write functions
+/// matching the wire format. Each group is a sealed interface containing its packets
+/// (`ServerPackets.Login`) with `encode()` on the packets and `ServerPackets.decode(bytes)`
+/// for reading any packet in the group. Unsigned numbers use the Kotlin unsigned types,
+/// `Vec<u8>` is a ByteArray, results are a PacketResult and enums are enum classes
+pub fn kotlin_client(package: &str, groups: &[GroupSchema]) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by wsbps from the packet definitions. Do not edit\n");
+    out.push_str("@file:Suppress(\"UNUSED_PARAMETER\", \"ArrayInDataClass\", \"RemoveRedundantQualifierName\")\n\n");
+    let _ = writeln!(out, "package {}\n", package);
+    out.push_str(KOTLIN_RUNTIME);
+    for definition in unique_definitions(groups) {
+        out.push('\n');
+        kotlin_definition(&mut out, definition);
+    }
+    for group in groups {
+        out.push('\n');
+        kotlin_group(&mut out, group);
+    }
+    out
+}
+
+/// ## Write Kotlin Client
+/// Writes the Kotlin source generated by kotlin_client for the provided [groups] to
+/// the file at [path]. This is intended to be called from a build script
+///
+/// ```ignore
+/// wsbps::write_kotlin_client("client/src/main/kotlin/Packets.kt", "com.example.packets", &[ServerPackets::group_schema()])?;
+/// ```
+pub fn write_kotlin_client<P: AsRef<Path>>(path: P, package: &str, groups: &[GroupSchema]) -> std::io::Result<()> {
+    std::fs::write(path, kotlin_client(package, groups))
+}
+
+/// Writes the class for a struct or enum
+fn kotlin_definition(out: &mut String, definition: &Definition) {
+    let name = definition.name;
+    match &definition.kind {
+        DefinitionKind::Struct(fields) => {
+            kotlin_class(out, "", name, None, fields);
+        }
+        DefinitionKind::Enum { repr, variants } => {
+            let names: Vec<String> = variants.iter().map(|(variant, _)| kotlin_name(variant)).collect();
+            let _ = writeln!(out, "enum class {} {{\n    {};\n", name, names.join(", "));
+            out.push_str("    fun write(w: PacketWriter) = when (this) {\n");
+            for (variant, (_, value)) in names.iter().zip(variants) {
+                let _ = writeln!(out, "        {} -> {}", variant, kotlin_write(repr, &kotlin_literal(value, repr), 0));
+            }
+            out.push_str("    }\n\n    companion object {\n");
+            let _ = writeln!(out, "        fun read(r: PacketReader): {} = when ({}) {{", name, kotlin_read(repr));
+            for (variant, (_, value)) in names.iter().zip(variants) {
+                let _ = writeln!(out, "            {} -> {}", kotlin_literal(value, repr), variant);
+            }
+            out.push_str("            else -> throw PacketException(\"unknown enum value\")\n        }\n    }\n}\n");
+        }
+    }
+}
+
+/// Writes the sealed interface for a group containing the classes for its packets
+fn kotlin_group(out: &mut String, group: &GroupSchema) {
+    let name = group.name;
+    let _ = writeln!(out, "sealed interface {} {{", name);
+    out.push_str("    val packetId: UInt\n\n    fun write(w: PacketWriter)\n\n");
+    out.push_str("    fun encode(): ByteArray {\n        val w = PacketWriter()\n        w.varInt(packetId)\n        write(w)\n        return w.finish()\n    }\n");
+    for packet in &group.packets {
+        out.push('\n');
+        let mut class = String::new();
+        kotlin_class(&mut class, name, packet.name, Some(packet.id), &packet.fields);
+        push_indented(out, &class, "    ");
+    }
+    out.push_str("\n    companion object {\n");
+    let _ = writeln!(out, "        fun decode(bytes: ByteArray): {} {{", name);
+    out.push_str("            val r = PacketReader(bytes)\n            return when (val id = r.varInt().toLong()) {\n");
+    for packet in &group.packets {
+        let _ = writeln!(out, "                {:#04x}L -> {}.read(r)", packet.id, packet.name);
+    }
+    out.push_str("                else -> throw PacketException(\"packet with unknown id of $id received\")\n");
+    out.push_str("            }\n        }\n    }\n}\n");
+}
+
+/// Writes the data class for a struct or a packet of the [group] with the [id]
+fn kotlin_class(out: &mut String, group: &str, name: &str, id: Option<u32>, fields: &[FieldSchema]) {
+    let parent = if group.is_empty() { String::new() } else { format!(" : {}", group) };
+    let modifier = if id.is_some() { "override " } else { "" };
+    if fields.is_empty() {
+        let _ = writeln!(out, "data object {}{} {{", name, parent);
+    } else {
+        let _ = writeln!(out, "data class {}(", name);
+        for field in fields {
+            let _ = writeln!(out, "    val {}: {},", kotlin_name(field.name), kotlin_field_type(field));
+        }
+        let _ = writeln!(out, "){} {{", parent);
+    }
+    if let Some(id) = id {
+        let _ = writeln!(out, "    override val packetId: UInt get() = {:#04x}u\n", id);
+    }
+    let _ = writeln!(out, "    {}fun write(w: PacketWriter) {{", modifier);
+    for field in fields {
+        let _ = writeln!(out, "        {}", kotlin_write_field(field));
+    }
+    // Objects can't have companion objects so read is a member of the object itself
+    if fields.is_empty() {
+        let _ = writeln!(out, "    }}\n\n    fun read(r: PacketReader) = {}\n}}", name);
+        return;
+    }
+    out.push_str("    }\n\n    companion object {\n");
+    let _ = writeln!(out, "        fun read(r: PacketReader): {} {{", name);
+    for field in fields {
+        let _ = writeln!(out, "            val {} = {}", kotlin_name(field.name), kotlin_read_field(field, kotlin_name));
+    }
+    let names: Vec<String> = fields.iter().map(|field| kotlin_name(field.name)).collect();
+    let _ = writeln!(out, "            return {}({})\n        }}\n    }}\n}}", name, names.join(", "));
+}
+
+/// The statement writing the [field] from the variable with its name
+fn kotlin_write_field(field: &FieldSchema) -> String {
+    let value = kotlin_name(field.name);
+    let value = if kotlin_is_nullable(field) && !matches!(field.ty, TypeSchema::Option(_)) {
+        format!("{}!!", value)
+    } else {
+        value
+    };
+    let write = match field.length_from {
+        Some(_) => kotlin_write_unprefixed(&field.ty, &value),
+        None => kotlin_write(&field.ty, &value, 0),
+    };
+    match field.when {
+        Some(condition) => format!("if ({}) {}", kotlin_name(condition), write),
+        None => write,
+    }
+}
+
+/// The expression reading the [field] where [variable] gives the names of the
+/// variables holding the earlier fields
+fn kotlin_read_field(field: &FieldSchema, variable: fn(&str) -> String) -> String {
+    let mut read = match field.length_from {
+        Some(length) => kotlin_read_unprefixed(&field.ty, &format!("{}.toInt()", variable(length))),
+        None => kotlin_read(&field.ty),
+    };
+    if let Some(default) = field.default {
+        read = format!("if (r.atEnd()) {} else {}", kotlin_default_value(default, &field.ty), read);
+    }
+    if let Some(condition) = field.when {
+        read = format!("if ({}) {} else {}", variable(condition), read, kotlin_default(&field.ty).unwrap_or("null"));
+    }
+    read
+}
+
+/// Escapes names which are Kotlin keywords
+fn kotlin_name(name: &str) -> String {
+    if KOTLIN_KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Whether the Kotlin type of the [field] is nullable. This is the case for options
+/// and for conditional and default fields whose type doesn't have a default value
+fn kotlin_is_nullable(field: &FieldSchema) -> bool {
+    matches!(field.ty, TypeSchema::Option(_))
+        || ((field.when.is_some() || field.default.is_some()) && kotlin_default(&field.ty).is_none())
+}
+
+/// The Kotlin type of the [field]
+fn kotlin_field_type(field: &FieldSchema) -> String {
+    let ty = kotlin_type(&field.ty);
+    if kotlin_is_nullable(field) && !ty.ends_with('?') {
+        format!("{}?", ty)
+    } else {
+        ty
+    }
+}
+
+/// The Kotlin type for values of the type [ty]
+fn kotlin_type(ty: &TypeSchema) -> String {
+    match ty {
+        TypeSchema::Bool => "Boolean".to_string(),
+        TypeSchema::U8 => "UByte".to_string(),
+        TypeSchema::U16 => "UShort".to_string(),
+        TypeSchema::U32 | TypeSchema::VarInt => "UInt".to_string(),
+        TypeSchema::U64 | TypeSchema::VarLong => "ULong".to_string(),
+        TypeSchema::I8 => "Byte".to_string(),
+        TypeSchema::I16 => "Short".to_string(),
+        TypeSchema::I32 => "Int".to_string(),
+        TypeSchema::I64 => "Long".to_string(),
+        TypeSchema::F32 => "Float".to_string(),
+        TypeSchema::F64 => "Double".to_string(),
+        TypeSchema::String => "String".to_string(),
+        TypeSchema::List(item) if **item == TypeSchema::U8 => "ByteArray".to_string(),
+        TypeSchema::List(item) => format!("List<{}>", kotlin_type(item)),
+        TypeSchema::Map(key, value) => format!("Map<{}, {}>", kotlin_type(key), kotlin_type(value)),
+        TypeSchema::Option(value) => format!("{}?", kotlin_type(value)),
+        TypeSchema::Result(ok, err) => format!("PacketResult<{}, {}>", kotlin_type(ok), kotlin_type(err)),
+        TypeSchema::Unit => "Unit".to_string(),
+        TypeSchema::Named(name) => name.to_string(),
+        TypeSchema::Custom(_) => "Any?".to_string(),
+    }
+}
+
+/// The expression reading a value of the type [ty] from the reader `r`
+fn kotlin_read(ty: &TypeSchema) -> String {
+    match ty {
+        TypeSchema::Bool => "r.bool()".to_string(),
+        TypeSchema::U8 => "r.u8()".to_string(),
+        TypeSchema::U16 => "r.u16()".to_string(),
+        TypeSchema::U32 => "r.u32()".to_string(),
+        TypeSchema::U64 => "r.u64()".to_string(),
+        TypeSchema::I8 => "r.i8()".to_string(),
+        TypeSchema::I16 => "r.i16()".to_string(),
+        TypeSchema::I32 => "r.i32()".to_string(),
+        TypeSchema::I64 => "r.i64()".to_string(),
+        TypeSchema::F32 => "r.f32()".to_string(),
+        TypeSchema::F64 => "r.f64()".to_string(),
+        TypeSchema::VarInt => "r.varInt()".to_string(),
+        TypeSchema::VarLong => "r.varLong()".to_string(),
+        TypeSchema::String | TypeSchema::List(_) => kotlin_read_unprefixed(ty, "r.varInt().toInt()"),
+        TypeSchema::Map(key, value) => format!("r.map(r.varInt().toInt(), {{ {} }}, {{ {} }})", kotlin_read(key), kotlin_read(value)),
+        TypeSchema::Option(value) => format!("(if (r.bool()) {} else null)", kotlin_read(value)),
+        TypeSchema::Result(ok, err) => format!("r.result({{ {} }}, {{ {} }})", kotlin_read(ok), kotlin_read(err)),
+        TypeSchema::Unit => "Unit".to_string(),
+        TypeSchema::Named(name) => format!("{}.read(r)", name),
+        TypeSchema::Custom(codec) => format!("throw UnsupportedOperationException(\"fields encoded using {} must be implemented by hand\")", codec),
+    }
+}
+
+/// The expression reading a string or list of the type [ty] with the provided
+/// [length] expression rather than a length prefix
+fn kotlin_read_unprefixed(ty: &TypeSchema, length: &str) -> String {
+    match ty {
+        TypeSchema::String => format!("r.string({})", length),
+        TypeSchema::List(item) if **item == TypeSchema::U8 => format!("r.bytes({})", length),
+        TypeSchema::List(item) => format!("r.list({}) {{ {} }}", length, kotlin_read(item)),
+        ty => kotlin_read(ty),
+    }
+}
+
+/// The statement writing the [value] expression of the type [ty] to the writer `w`.
+/// The [depth] is used to name the parameters of nested lambdas
+fn kotlin_write(ty: &TypeSchema, value: &str, depth: usize) -> String {
+    let item = format!("item{}", depth);
+    match ty {
+        TypeSchema::Bool => format!("w.bool({})", value),
+        TypeSchema::U8 => format!("w.u8({})", value),
+        TypeSchema::U16 => format!("w.u16({})", value),
+        TypeSchema::U32 => format!("w.u32({})", value),
+        TypeSchema::U64 => format!("w.u64({})", value),
+        TypeSchema::I8 => format!("w.i8({})", value),
+        TypeSchema::I16 => format!("w.i16({})", value),
+        TypeSchema::I32 => format!("w.i32({})", value),
+        TypeSchema::I64 => format!("w.i64({})", value),
+        TypeSchema::F32 => format!("w.f32({})", value),
+        TypeSchema::F64 => format!("w.f64({})", value),
+        TypeSchema::VarInt => format!("w.varInt({})", value),
+        TypeSchema::VarLong => format!("w.varLong({})", value),
+        TypeSchema::String => format!("w.string({})", value),
+        TypeSchema::List(inner) if **inner == TypeSchema::U8 => format!("w.bytes({})", value),
+        TypeSchema::List(inner) => format!("w.list({}) {{ {} -> {} }}", value, item, kotlin_write(inner, &item, depth + 1)),
+        TypeSchema::Map(key, inner) => format!(
+            "w.map({}, {{ key{} -> {} }}, {{ {} -> {} }})",
+            value, depth, kotlin_write(key, &format!("key{}", depth), depth + 1), item, kotlin_write(inner, &item, depth + 1)
+        ),
+        TypeSchema::Option(inner) => format!("w.option({}) {{ {} -> {} }}", value, item, kotlin_write(inner, &item, depth + 1)),
+        TypeSchema::Result(ok, err) => format!(
+            "w.result({}, {{ {} -> {} }}, {{ {} -> {} }})",
+            value, item, kotlin_write(ok, &item, depth + 1), item, kotlin_write(err, &item, depth + 1)
+        ),
+        TypeSchema::Unit => "Unit".to_string(),
+        TypeSchema::Named(_) => format!("{}.write(w)", value),
+        TypeSchema::Custom(codec) => format!("throw UnsupportedOperationException(\"fields encoded using {} must be implemented by hand\")", codec),
+    }
+}
+
+/// The statement writing a string or list without its length prefix
+fn kotlin_write_unprefixed(ty: &TypeSchema, value: &str) -> String {
+    match ty {
+        TypeSchema::String => format!("w.string({}, false)", value),
+        TypeSchema::List(inner) if **inner == TypeSchema::U8 => format!("w.bytes({}, false)", value),
+        TypeSchema::List(inner) => format!("w.list({}, false) {{ item0 -> {} }}", value, kotlin_write(inner, "item0", 1)),
+        ty => kotlin_write(ty, value, 0),
+    }
+}
+
+/// The default value of the type [ty] used for conditional fields that aren't present.
+/// Structs and enums don't have a default value so their fields are nullable instead
+fn kotlin_default(ty: &TypeSchema) -> Option<&'static str> {
+    Some(match ty {
+        TypeSchema::Bool => "false",
+        TypeSchema::U8 => "(0).toUByte()",
+        TypeSchema::U16 => "(0).toUShort()",
+        TypeSchema::U32 | TypeSchema::VarInt => "0u",
+        TypeSchema::U64 | TypeSchema::VarLong => "0uL",
+        TypeSchema::I8 => "(0).toByte()",
+        TypeSchema::I16 => "(0).toShort()",
+        TypeSchema::I32 => "0",
+        TypeSchema::I64 => "0L",
+        TypeSchema::F32 => "0f",
+        TypeSchema::F64 => "0.0",
+        TypeSchema::String => "\"\"",
+        TypeSchema::List(item) if **item == TypeSchema::U8 => "ByteArray(0)",
+        TypeSchema::List(_) => "emptyList()",
+        TypeSchema::Map(_, _) => "emptyMap()",
+        TypeSchema::Option(_) => "null",
+        TypeSchema::Unit => "Unit",
+        TypeSchema::Result(_, _) | TypeSchema::Named(_) | TypeSchema::Custom(_) => return None,
+    })
+}
+
+/// Converts the Rust source of a default field value to Kotlin. Only literals can be
+/// converted, anything else uses the default value of the type
+fn kotlin_default_value(source: &str, ty: &TypeSchema) -> String {
+    let source = source.trim();
+    if is_literal(source) {
+        kotlin_literal(source, ty)
+    } else {
+        kotlin_default(ty).unwrap_or("null").to_string()
+    }
+}
+
+/// Converts the Rust source of a literal of the type [ty] to a Kotlin expression of
+/// the matching Kotlin type
+fn kotlin_literal(source: &str, ty: &TypeSchema) -> String {
+    // The JavaScript literal has the type suffix removed
+    let literal = js_literal(source, &TypeSchema::I32);
+    if literal.starts_with('"') {
+        return literal;
+    }
+    let literal = literal.replace('_', "");
+    match ty {
+        TypeSchema::U8 => format!("({}).toUByte()", literal),
+        TypeSchema::U16 => format!("({}).toUShort()", literal),
+        TypeSchema::U32 | TypeSchema::VarInt => format!("{}u", literal),
+        TypeSchema::U64 | TypeSchema::VarLong => format!("{}uL", literal),
+        TypeSchema::I8 => format!("({}).toByte()", literal),
+        TypeSchema::I16 => format!("({}).toShort()", literal),
+        TypeSchema::I64 => format!("{}L", literal),
+        TypeSchema::F32 => format!("{}f", literal),
+        TypeSchema::F64 if !literal.contains('.') => format!("{}.0", literal),
+        _ => literal,
+    }
+}
+
+/// ## Markdown Spec
+/// Generates Markdown documentation of the wire format of the packets in the provided
+/// [groups] with a table for each packet listing its fields in order along with their
+/// types, sizes and byte offsets (while the earlier fields have a fixed size) and a
+/// table for each struct and enum the packets use. Generating the documentation from
+/// the packet definitions means it can't drift from the code
+pub fn markdown_spec(groups: &[GroupSchema]) -> String {
+    let mut out = String::new();
+    out.push_str("<!-- Generated by wsbps from the packet definitions. Do not edit -->\n\n");
+    out.push_str("# Protocol\n\n");
+    out.push_str("Packets are encoded as the packet ID (VarInt) followed by the packet fields in order. ");
+    out.push_str("Integers are big endian, VarInts and VarLongs use 7 bits per byte with the high bit set ");
+    out.push_str("when more bytes follow, strings are UTF-8 and lists, strings and maps are prefixed with ");
+    out.push_str("their length as a VarInt. Optional values are prefixed with a bool for whether they are present ");
+    out.push_str("and results are prefixed with a u8 which is 0 for Ok and 1 for Err. ");
+    out.push_str("Offsets are in bytes from the end of the packet ID and are only known up to the first field ");
+    out.push_str("without a fixed size\n");
+    for group in groups {
+        let _ = writeln!(out, "\n## {}\n", group.name);
+        out.push_str("| ID | Packet |\n| --- | --- |\n");
+        for packet in &group.packets {
+            let _ = writeln!(out, "| 0x{:02X} | [{}](#{}) |", packet.id, packet.name, markdown_anchor(group.name, packet.name, packet.id));
+        }
+        for packet in &group.packets {
+            let _ = writeln!(out, "\n### {} {} (0x{:02X})\n", group.name, packet.name, packet.id);
+            markdown_fields(&mut out, &packet.fields);
+        }
+    }
+    let definitions = unique_definitions(groups);
+    if !definitions.is_empty() {
+        out.push_str("\n## Types\n");
+    }
+    for definition in definitions {
+        let _ = writeln!(out, "\n### {}\n", definition.name);
+        match &definition.kind {
+            DefinitionKind::Struct(fields) => markdown_fields(&mut out, fields),
+            DefinitionKind::Enum { repr, variants } => {
+                let _ = writeln!(out, "Encoded as {}\n", markdown_type(repr));
+                out.push_str("| Variant | Value |\n| --- | --- |\n");
+                for (variant, value) in variants {
+                    let _ = writeln!(out, "| {} | `{}` |", variant, value);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// ## Write Markdown Spec
+/// Writes the documentation generated by markdown_spec for the provided [groups] to
+/// the file at [path]. This is intended to be called from a build script
+///
+/// ```ignore
+/// wsbps::write_markdown_spec("docs/protocol.md", &[ServerPackets::group_schema(), ClientPackets::group_schema()])?;
+/// ```
+pub fn write_markdown_spec<P: AsRef<Path>>(path: P, groups: &[GroupSchema]) -> std::io::Result<()> {
+    std::fs::write(path, markdown_spec(groups))
+}
+
+/// Writes the table of [fields]
+fn markdown_fields(out: &mut String, fields: &[FieldSchema]) {
+    if fields.is_empty() {
+        out.push_str("No fields\n");
+        return;
+    }
+    out.push_str("| Offset | Field | Type | Size | Notes |\n| --- | --- | --- | --- | --- |\n");
+    // The offset is only known while all the earlier fields have a fixed size
+    let mut offset = Some(0);
+    for field in fields {
+        let conditional = field.when.is_some() || field.default.is_some();
+        let size = if field.length_from.is_some() { None } else { fixed_size(&field.ty) };
+        let mut notes = Vec::new();
+        if let Some(length_from) = field.length_from {
+            notes.push(format!("Length is `{}` without a prefix", length_from));
+        }
+        if let Some(when) = field.when {
+            notes.push(format!("Only present when `{}` is true", when));
+        }
+        if let Some(default) = field.default {
+            notes.push(format!("`{}` when the packet ends before this field", default));
+        }
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            offset.map_or_else(|| "-".to_string(), |offset: usize| offset.to_string()),
+            field.name,
+            markdown_type(&field.ty),
+            markdown_size(&field.ty, size, conditional),
+            notes.join(". ")
+        );
+        offset = offset.zip(size.filter(|_| !conditional)).map(|(offset, size)| offset + size);
+    }
+}
+
+/// The number of bytes values of the type [ty] always take
+fn fixed_size(ty: &TypeSchema) -> Option<usize> {
+    match ty {
+        TypeSchema::Bool | TypeSchema::U8 | TypeSchema::I8 => Some(1),
+        TypeSchema::U16 | TypeSchema::I16 => Some(2),
+        TypeSchema::U32 | TypeSchema::I32 | TypeSchema::F32 => Some(4),
+        TypeSchema::U64 | TypeSchema::I64 | TypeSchema::F64 => Some(8),
+        TypeSchema::Unit => Some(0),
+        _ => None,
+    }
+}
+
+/// The size column for a value of the type [ty] with the [fixed] size which may be
+/// missing when the field is [conditional]
+fn markdown_size(ty: &TypeSchema, fixed: Option<usize>, conditional: bool) -> String {
+    match (fixed, ty) {
+        (Some(size), _) if conditional => format!("0 or {}", size),
+        (Some(size), _) => size.to_string(),
+        (None, TypeSchema::VarInt) => "1-5".to_string(),
+        (None, TypeSchema::VarLong) => "1-10".to_string(),
+        _ => "Variable".to_string(),
+    }
+}
+
+/// The name of the type [ty] linking to the tables of named types
+fn markdown_type(ty: &TypeSchema) -> String {
+    match ty {
+        TypeSchema::Bool => "bool".to_string(),
+        TypeSchema::U8 => "u8".to_string(),
+        TypeSchema::U16 => "u16".to_string(),
+        TypeSchema::U32 => "u32".to_string(),
+        TypeSchema::U64 => "u64".to_string(),
+        TypeSchema::I8 => "i8".to_string(),
+        TypeSchema::I16 => "i16".to_string(),
+        TypeSchema::I32 => "i32".to_string(),
+        TypeSchema::I64 => "i64".to_string(),
+        TypeSchema::F32 => "f32".to_string(),
+        TypeSchema::F64 => "f64".to_string(),
+        TypeSchema::VarInt => "VarInt".to_string(),
+        TypeSchema::VarLong => "VarLong".to_string(),
+        TypeSchema::String => "String".to_string(),
+        TypeSchema::List(value) => format!("List&lt;{}&gt;", markdown_type(value)),
+        TypeSchema::Map(key, value) => format!("Map&lt;{}, {}&gt;", markdown_type(key), markdown_type(value)),
+        TypeSchema::Option(value) => format!("Option&lt;{}&gt;", markdown_type(value)),
+        TypeSchema::Result(ok, err) => format!("Result&lt;{}, {}&gt;", markdown_type(ok), markdown_type(err)),
+        TypeSchema::Unit => "()".to_string(),
+        TypeSchema::Named(name) => format!("[{}](#{})", name, name.to_lowercase()),
+        TypeSchema::Custom(codec) => format!("Custom ({})", codec),
+    }
+}
+
+/// The anchor GitHub generates for the heading of a packet
+fn markdown_anchor(group: &str, packet: &str, id: u32) -> String {
+    format!("{}-{}-0x{:02x}", group, packet, id).to_lowercase()
+}
+
+/// Writes the lines of [text] to [out] with the [indent] added to the non-empty lines
+fn push_indented(out: &mut String, text: &str, indent: &str) {
+    for line in text.lines() {
+        if line.is_empty() {
+            out.push('\n');
+        } else {
+            let _ = writeln!(out, "{}{}", indent, line);
+        }
+    }
+}
+
+/// ## JS Shim
+/// Generates a self-contained JavaScript module which translates frames encoded using
+/// the old version of each group in the provided [migrations] into the new version
+/// with `migrateGroup(bytes)`. This returns the translated frame, null for packets
+/// which were removed from the new version or throws for packets which can't be
+/// translated (see SchemaMigration::unsupported). Clients can use this to keep
+/// sending the old version until they are updated to the new one
+pub fn js_shim(migrations: &[SchemaMigration]) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by wsbps from the packet definitions. Do not edit\n\n");
+    out.push_str(JS_RUNTIME);
+    for definition in dedup_definitions(migrations.iter().flat_map(|migration| &migration.new_definitions)) {
+        out.push('\n');
+        js_definition(&mut out, definition);
+    }
+    for migration in migrations {
+        js_migration(&mut out, migration);
+    }
+    out
+}
+
+/// ## Write JS Shim
+/// Writes the JavaScript module generated by js_shim for the provided [migrations]
+/// to the file at [path]. This is intended to be called from a build script
+///
+/// ```ignore
+/// let migration = wsbps::SchemaMigration::new(&old_schema, &ClientPackets::group_schema());
+/// wsbps::write_js_shim("web/migrate.js", &[migration])?;
+/// ```
+pub fn write_js_shim<P: AsRef<Path>>(path: P, migrations: &[SchemaMigration]) -> std::io::Result<()> {
+    std::fs::write(path, js_shim(migrations))
+}
+
+/// Writes the readers for the old version of a group and the migrate function
+fn js_migration(out: &mut String, migration: &SchemaMigration) {
+    let name = migration.name;
+    // The old packets are read in their own scope so the old structs and enums don't
+    // replace the new ones
+    let mut scope = String::new();
+    for definition in &migration.old_definitions {
+        js_definition(&mut scope, definition);
+        scope.push('\n');
+    }
+    let mut readers = Vec::new();
+    for packet in &migration.packets {
+        let function = format!("read{}{}", name, packet.name);
+        let _ = writeln!(scope, "function {}(r) {{", function);
+        scope.push_str("    const packet = {};\n");
+        js_read_fields(&mut scope, "packet", &packet.old_fields);
+        scope.push_str("    return packet;\n}\n\n");
+        readers.push(function);
+    }
+    let _ = writeln!(scope, "return {{ {} }};", readers.join(", "));
+    let _ = writeln!(out, "\nconst Old{} = (() => {{", name);
+    push_indented(out, &scope, "    ");
+    out.push_str("})();\n");
+
+    let _ = writeln!(out, "\nexport function migrate{}(bytes) {{", name);
+    out.push_str("    const r = new Reader(bytes);\n    const w = new Writer();\n    const id = r.varInt();\n    switch (id) {\n");
+    for packet in &migration.packets {
+        if !packet.is_supported(&migration.old_definitions) {
+            let _ = writeln!(
+                out,
+                "        case {:#04x}: throw new Error(\"{} {} can't be migrated between the schema versions\");",
+                packet.old_id, name, packet.name
+            );
+            continue;
+        }
+        let _ = writeln!(out, "        case {:#04x}: {{", packet.old_id);
+        let _ = writeln!(out, "            const old = Old{}.read{}{}(r);", name, name, packet.name);
+        let values: Vec<String> = packet.fields.iter()
+            .map(|migration| {
+                let field = &migration.field;
+                let value = match (migration.source, field.default) {
+                    (FieldSource::Old, _) => format!("old.{}", field.name),
+                    (_, Some(default)) => js_default_value(default, &field.ty),
+                    (_, None) => js_default(&field.ty).to_string(),
+                };
+                format!("{}: {}", field.name, value)
+            })
+            .collect();
+        if values.is_empty() {
+            out.push_str("            const packet = {};\n");
+        } else {
+            let _ = writeln!(out, "            const packet = {{ {} }};", values.join(", "));
+        }
+        let _ = writeln!(out, "            w.varInt({:#04x});", packet.new_id);
+        let fields: Vec<FieldSchema> = packet.fields.iter().map(|migration| migration.field.clone()).collect();
+        let mut writes = String::new();
+        js_write_fields(&mut writes, "packet", &fields);
+        push_indented(out, &writes, "        ");
+        out.push_str("            break;\n        }\n");
+    }
+    for packet in &migration.removed {
+        let _ = writeln!(out, "        case {:#04x}: return null;", packet.id);
+    }
+    out.push_str("        default: throw new Error(`packet with unknown id of ${id} received`);\n    }\n    return w.finish();\n}\n");
+}
+
+/// ## Kotlin Shim
+/// Generates a Kotlin source file in the provided [package] which translates frames
+/// encoded using the old version of each group in the provided [migrations] into the
+/// new version with `GroupMigration.migrate(bytes)`. This returns the translated
+/// frame, null for packets which were removed from the new version or throws for
+/// packets which can't be translated. The file includes its own reader and writer so
+/// it should be generated into a different package than the client
+pub fn kotlin_shim(package: &str, migrations: &[SchemaMigration]) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by wsbps from the packet definitions. Do not edit\n");
+    out.push_str("@file:Suppress(\"UNUSED_PARAMETER\", \"UNUSED_VARIABLE\", \"ArrayInDataClass\", \"RemoveRedundantQualifierName\")\n\n");
+    let _ = writeln!(out, "package {}\n", package);
+    out.push_str(KOTLIN_RUNTIME);
+    for migration in migrations {
+        out.push('\n');
+        kotlin_migration(&mut out, migration);
+    }
+    out
+}
+
+/// ## Write Kotlin Shim
+/// Writes the Kotlin source generated by kotlin_shim for the provided [migrations] to
+/// the file at [path]. This is intended to be called from a build script
+///
+/// ```ignore
+/// let migration = wsbps::SchemaMigration::new(&old_schema, &ClientPackets::group_schema());
+/// wsbps::write_kotlin_shim("server/src/main/kotlin/Migrate.kt", "com.example.migrate", &[migration])?;
+/// ```
+pub fn write_kotlin_shim<P: AsRef<Path>>(path: P, package: &str, migrations: &[SchemaMigration]) -> std::io::Result<()> {
+    std::fs::write(path, kotlin_shim(package, migrations))
+}
+
+/// Writes the object containing the old structs and enums of a group along with the
+/// migrate function
+fn kotlin_migration(out: &mut String, migration: &SchemaMigration) {
+    let name = migration.name;
+    let mut body = String::new();
+    for definition in &migration.old_definitions {
+        kotlin_definition(&mut body, definition);
+        body.push('\n');
+    }
+    body.push_str("fun migrate(bytes: ByteArray): ByteArray? {\n    val r = PacketReader(bytes)\n    val w = PacketWriter()\n");
+    body.push_str("    when (val id = r.varInt().toLong()) {\n");
+    for packet in &migration.packets {
+        if packet.is_supported(&migration.old_definitions) {
+            let _ = writeln!(body, "        {:#04x}L -> migrate{}(r, w)", packet.old_id, packet.name);
+        } else {
+            let _ = writeln!(
+                body,
+                "        {:#04x}L -> throw PacketException(\"{} {} can't be migrated between the schema versions\")",
+                packet.old_id, name, packet.name
+            );
+        }
+    }
+    for packet in &migration.removed {
+        let _ = writeln!(body, "        {:#04x}L -> return null", packet.id);
+    }
+    body.push_str("        else -> throw PacketException(\"packet with unknown id of $id received\")\n    }\n    return w.finish()\n}\n");
+    for packet in &migration.packets {
+        if !packet.is_supported(&migration.old_definitions) {
+            continue;
+        }
+        let _ = writeln!(body, "\nprivate fun migrate{}(r: PacketReader, w: PacketWriter) {{", packet.name);
+        for field in &packet.old_fields {
+            let _ = writeln!(body, "    val {} = {}", kotlin_old_name(field.name), kotlin_read_field(field, kotlin_old_name));
+        }
+        for migration in &packet.fields {
+            let field = &migration.field;
+            match (migration.source, field.default) {
+                (FieldSource::Old, _) => {
+                    let _ = writeln!(body, "    val {} = {}", kotlin_name(field.name), kotlin_old_name(field.name));
+                }
+                (_, default) => {
+                    let value = match default {
+                        Some(default) => kotlin_default_value(default, &field.ty),
+                        None => kotlin_default(&field.ty).unwrap_or("null").to_string(),
+                    };
+                    let _ = writeln!(body, "    val {}: {} = {}", kotlin_name(field.name), kotlin_field_type(field), value);
+                }
+            }
+        }
+        let _ = writeln!(body, "    w.varInt({:#04x}u)", packet.new_id);
+        for migration in &packet.fields {
+            let _ = writeln!(body, "    {}", kotlin_write_field(&migration.field));
+        }
+        body.push_str("}\n");
+    }
+    let _ = writeln!(out, "object {}Migration {{", name);
+    push_indented(out, &body, "    ");
+    out.push_str("}\n");
+}
+
+/// The name of the variable holding the value of the old field with the [name]
+fn kotlin_old_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let first = chars.next().map(|c| c.to_ascii_uppercase()).unwrap_or_default();
+    format!("old{}{}", first, chars.as_str())
+}
@@ -0,0 +1,80 @@
+use core::fmt;
+use alloc::vec::Vec;
+
+/// ## IO Error
+/// Errors produced by the Read and Write traits used when the "std"
+/// feature is disabled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IoError {
+    /// The source ran out of bytes before the read was complete
+    UnexpectedEof,
+    /// The output was full before everything was written
+    WriteZero,
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoError::UnexpectedEof => f.write_str("failed to fill whole buffer"),
+            IoError::WriteZero => f.write_str("failed to write whole buffer"),
+        }
+    }
+}
+
+impl core::error::Error for IoError {}
+
+/// Minimal replacement for std::io::Read used without std
+pub trait Read {
+    /// Reads exactly enough bytes to fill [buf]
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError>;
+}
+
+/// Minimal replacement for std::io::Write used without std
+pub trait Write {
+    /// Writes all the bytes in [buf]
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), IoError>;
+}
+
+impl<R: Read + ?Sized> Read for &mut R {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        (**self).read_exact(buf)
+    }
+}
+
+impl Read for &[u8] {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        if buf.len() > self.len() {
+            *self = &self[self.len()..];
+            return Err(IoError::UnexpectedEof);
+        }
+        let (bytes, rest) = self.split_at(buf.len());
+        buf.copy_from_slice(bytes);
+        *self = rest;
+        Ok(())
+    }
+}
+
+impl<W: Write + ?Sized> Write for &mut W {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), IoError> {
+        (**self).write_all(buf)
+    }
+}
+
+impl Write for Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), IoError> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+impl Write for &mut [u8] {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), IoError> {
+        if buf.len() > self.len() {
+            return Err(IoError::WriteZero);
+        }
+        let (bytes, rest) = core::mem::take(self).split_at_mut(buf.len());
+        bytes.copy_from_slice(buf);
+        *self = rest;
+        Ok(())
+    }
+}
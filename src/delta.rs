@@ -0,0 +1,116 @@
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, Write, Writable, WriteResult};
+use crate::limits::ReadContext;
+
+/// ## Diffable
+/// Trait for writing a value as the delta from an earlier value of the same type.
+/// The delta is a bitmask with a bit for each field (one byte per 8 fields, the
+/// first field is the lowest bit of the first byte) followed by only the fields
+/// which changed encoded as they normally are.
+///
+/// This is implemented for the generated writable structs when the "delta" feature
+/// is enabled
+pub trait Diffable: Writable + Clone + PartialEq {
+    /// Writes the fields of self which differ from [base] to [o]
+    fn write_delta<B: Write>(&mut self, base: &Self, o: &mut B) -> WriteResult;
+}
+
+/// ## Delta Readable
+/// Trait for reading a delta written by Diffable and applying it to the earlier
+/// value it was made from.
+///
+/// This is implemented for the generated readable structs when the "delta" feature
+/// is enabled
+pub trait DeltaReadable: Readable + Clone {
+    /// Reads a delta from the source [i] returning [base] with the changes applied
+    fn read_delta<B: Read>(base: &Self, i: &mut B) -> ReadResult<Self> {
+        Self::read_delta_with(&mut ReadContext::default(), base, i)
+    }
+
+    /// Reads a delta from the source [i] using the provided context
+    fn read_delta_with<B: Read>(ctx: &mut ReadContext, base: &Self, i: &mut B) -> ReadResult<Self>;
+}
+
+/// ## Delta Encoder
+/// Encodes consecutive snapshots of a state packet as deltas from the previous
+/// snapshot. Each snapshot is a bool for whether it is a delta followed by the
+/// delta or the full value, the first snapshot is always the full value. The
+/// receiver decodes them with a DeltaDecoder. The transport must deliver every
+/// snapshot in order so after reconnecting both sides should be reset
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaEncoder<T> {
+    last: Option<T>,
+}
+
+impl<T: Diffable> DeltaEncoder<T> {
+    /// Creates an encoder which sends the full value first
+    pub fn new() -> Self {
+        DeltaEncoder { last: None }
+    }
+
+    /// Writes the [value] to [o] as the delta from the previous value
+    pub fn encode<B: Write>(&mut self, value: &mut T, o: &mut B) -> WriteResult {
+        match &self.last {
+            Some(last) => {
+                true.write(o)?;
+                value.write_delta(last, o)?;
+            }
+            None => {
+                false.write(o)?;
+                value.write(o)?;
+            }
+        }
+        self.last = Some(value.clone());
+        Ok(())
+    }
+
+    /// Forgets the previous value so the next value is sent in full
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+impl<T: Diffable> Default for DeltaEncoder<T> {
+    fn default() -> Self { DeltaEncoder::new() }
+}
+
+/// ## Delta Decoder
+/// Decodes the snapshots written by a DeltaEncoder by applying each delta to the
+/// previously decoded snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaDecoder<T> {
+    last: Option<T>,
+}
+
+impl<T: DeltaReadable> DeltaDecoder<T> {
+    /// Creates a decoder without a previous value
+    pub fn new() -> Self {
+        DeltaDecoder { last: None }
+    }
+
+    /// Reads the next snapshot from the source [i] failing if it is a delta and
+    /// there is no previous snapshot to apply it to
+    pub fn decode<B: Read>(&mut self, i: &mut B) -> ReadResult<T> {
+        let value = if bool::read(i)? {
+            let last = self.last.as_ref()
+                .ok_or(PacketError::UnexpectedValue("full snapshot before the first delta"))?;
+            T::read_delta(last, i)?
+        } else {
+            T::read(i)?
+        };
+        self.last = Some(value.clone());
+        Ok(value)
+    }
+
+    /// The most recently decoded snapshot
+    pub fn last(&self) -> Option<&T> { self.last.as_ref() }
+
+    /// Forgets the previous value so the next value must be sent in full
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+impl<T: DeltaReadable> Default for DeltaDecoder<T> {
+    fn default() -> Self { DeltaDecoder::new() }
+}
@@ -0,0 +1,309 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+use crate::error::PacketError;
+use crate::io::{read_string_with, Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::ReadContext;
+use crate::ordered::{write_ordered_struct, OrderedRepr, OrderedStyle};
+use crate::summary::{write_summary_fields, Summary};
+
+/// ## Compression Dictionary
+/// A versioned table of strings that are common in the traffic of a connection.
+/// DictionaryStrings which are in the dictionary are sent as their ID rather than
+/// their bytes. The sender can ship an updated dictionary at any point in the
+/// session (e.g. one built by a DictionaryTrainer from the observed traffic) as a
+/// field of one of its packets and the receiver applies it with
+/// Session::set_dictionary. The strings written after the update can use the new
+/// IDs as packets are read in order. This is encoded as the version as a VarInt
+/// followed by the entries as a `Vec<String>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressionDictionary {
+    version: u32,
+    entries: Vec<String>,
+}
+
+impl CompressionDictionary {
+    /// Creates the dictionary with the provided [version] and [entries]. The first
+    /// entries get the smallest IDs so they should be the most common strings
+    pub fn new(version: u32, entries: Vec<String>) -> Self {
+        CompressionDictionary { version, entries }
+    }
+
+    /// The version of the dictionary which increases with each update
+    pub fn version(&self) -> u32 { self.version }
+
+    /// The strings of the dictionary in ID order
+    pub fn entries(&self) -> &[String] { &self.entries }
+
+    /// The number of entries
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    /// Whether the dictionary has no entries
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// The entry with the provided [id]
+    pub fn get(&self, id: u32) -> Option<&str> {
+        self.entries.get(id as usize).map(String::as_str)
+    }
+
+    /// The ID of the entry with the provided [value]
+    pub fn id_of(&self, value: &str) -> Option<u32> {
+        self.entries.iter().position(|entry| entry == value).map(|id| id as u32)
+    }
+
+    /// Creates the DictionaryString for [value] which is written as its ID when it
+    /// is one of the entries
+    pub fn compress(&self, value: impl Into<String>) -> DictionaryString {
+        let value = value.into();
+        let id = self.id_of(&value);
+        DictionaryString { value, id }
+    }
+}
+
+impl Writable for CompressionDictionary {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        VarInt(self.version).write(o)?;
+        self.entries.write(o)
+    }
+}
+
+impl Readable for CompressionDictionary {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let version = VarInt::read_with(ctx, i)?.0;
+        let entries = Vec::read_with(ctx, i)?;
+        Ok(CompressionDictionary { version, entries })
+    }
+}
+
+impl OrderedRepr for CompressionDictionary {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_struct(style, out, "CompressionDictionary", &[
+            ("version", &self.version),
+            ("entries", &self.entries),
+        ]);
+    }
+}
+
+impl Summary for CompressionDictionary {
+    fn write_summary(&self, out: &mut String) {
+        write_summary_fields(out, "CompressionDictionary", None, &[
+            ("version", &self.version),
+            ("entries", &self.entries),
+        ]);
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for CompressionDictionary {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(CompressionDictionary::new(u32::arbitrary(u)?, Vec::arbitrary(u)?))
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl crate::schema::Schema for CompressionDictionary {
+    fn type_schema() -> crate::schema::TypeSchema {
+        crate::schema::TypeSchema::Named("CompressionDictionary")
+    }
+
+    fn definitions(definitions: &mut Vec<crate::schema::Definition>) {
+        use alloc::boxed::Box;
+        use crate::schema::{add_definition, Definition, DefinitionKind, FieldSchema, TypeSchema};
+        add_definition(definitions, Definition {
+            name: "CompressionDictionary",
+            kind: DefinitionKind::Struct(alloc::vec![
+                FieldSchema::new("version", TypeSchema::VarInt),
+                FieldSchema::new("entries", TypeSchema::List(Box::new(TypeSchema::String))),
+            ]),
+        });
+    }
+}
+
+/// ## Dictionary String
+/// A string which is written as its ID when it was created by
+/// CompressionDictionary::compress and is one of the entries. Otherwise it is
+/// encoded the same as a String. IDs are written as an empty string (a zero length)
+/// followed by the ID plus one as a VarInt where an ID of zero is the empty string.
+/// The receiver looks the IDs up in the dictionary of its ReadContext (see
+/// Session::set_dictionary). Strings are equal when their values are equal
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub struct DictionaryString {
+    value: String,
+    id: Option<u32>,
+}
+
+impl DictionaryString {
+    /// Creates a string which is always written as its bytes
+    pub fn new(value: impl Into<String>) -> Self {
+        DictionaryString { value: value.into(), id: None }
+    }
+
+    /// The string
+    pub fn as_str(&self) -> &str { &self.value }
+
+    /// Consumes self returning the string
+    pub fn into_inner(self) -> String { self.value }
+
+    /// The ID the string is written as if it is compressed
+    pub fn id(&self) -> Option<u32> { self.id }
+}
+
+impl PartialEq for DictionaryString {
+    fn eq(&self, other: &Self) -> bool { self.value == other.value }
+}
+
+impl Eq for DictionaryString {}
+
+impl Hash for DictionaryString {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.value.hash(state) }
+}
+
+impl Deref for DictionaryString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target { &self.value }
+}
+
+impl Display for DictionaryString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl From<String> for DictionaryString {
+    fn from(value: String) -> Self { DictionaryString::new(value) }
+}
+
+impl From<&str> for DictionaryString {
+    fn from(value: &str) -> Self { DictionaryString::new(value) }
+}
+
+impl From<DictionaryString> for String {
+    fn from(value: DictionaryString) -> Self { value.value }
+}
+
+impl Writable for DictionaryString {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        match self.id {
+            Some(id) => {
+                VarInt(0).write(o)?;
+                VarInt(id + 1).write(o)
+            }
+            None if self.value.is_empty() => {
+                VarInt(0).write(o)?;
+                VarInt(0).write(o)
+            }
+            None => self.value.write(o)
+        }
+    }
+}
+
+impl Readable for DictionaryString {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    /// The IDs of the peer aren't kept as they only apply to its dictionary
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        if length > 0 {
+            return Ok(DictionaryString::new(read_string_with(ctx, i, length)?));
+        }
+        let value = match VarInt::read_with(ctx, i)?.0 {
+            0 => String::new(),
+            id => String::from(ctx.dictionary()
+                .and_then(|dictionary| dictionary.get(id - 1))
+                .ok_or(PacketError::UnexpectedValue("a dictionary string id within the negotiated dictionary"))?)
+        };
+        Ok(DictionaryString::new(value))
+    }
+}
+
+impl OrderedRepr for DictionaryString {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.value.write_ordered(style, out)
+    }
+}
+
+impl Summary for DictionaryString {
+    fn write_summary(&self, out: &mut String) {
+        self.value.write_summary(out)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DictionaryString {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(DictionaryString::new(String::arbitrary(u)?))
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl crate::schema::Schema for DictionaryString {
+    fn type_schema() -> crate::schema::TypeSchema {
+        crate::schema::TypeSchema::Custom("DictionaryString")
+    }
+}
+
+/// ## Dictionary Trainer
+/// Counts the strings sent over a connection so the sender can build a
+/// CompressionDictionary of the strings that save the most bytes. Only the first
+/// [max_tracked] distinct strings are counted so the memory used is bounded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryTrainer {
+    counts: BTreeMap<String, u32>,
+    max_tracked: usize,
+}
+
+impl DictionaryTrainer {
+    /// Creates a trainer which counts up to [max_tracked] distinct strings
+    pub fn new(max_tracked: usize) -> Self {
+        DictionaryTrainer { counts: BTreeMap::new(), max_tracked }
+    }
+
+    /// Counts a string that was sent
+    pub fn observe(&mut self, value: &str) {
+        if let Some(count) = self.counts.get_mut(value) {
+            *count = count.saturating_add(1);
+        } else if self.counts.len() < self.max_tracked {
+            self.counts.insert(String::from(value), 1);
+        }
+    }
+
+    /// The number of distinct strings counted
+    pub fn tracked(&self) -> usize { self.counts.len() }
+
+    /// Clears the counts so the next dictionary is built from new traffic
+    pub fn reset(&mut self) {
+        self.counts.clear();
+    }
+
+    /// Builds a dictionary with the provided [version] (which should be newer than
+    /// the one the peer has) of up to [max_entries] strings. Strings are ranked by
+    /// the bytes they would have saved and strings seen once are left out
+    pub fn build(&self, version: u32, max_entries: usize) -> CompressionDictionary {
+        // IDs take at least two bytes so single characters aren't worth including
+        let mut ranked: Vec<(&String, u64)> = self.counts.iter()
+            .filter(|(value, count)| **count > 1 && value.len() > 1)
+            .map(|(value, count)| (value, u64::from(*count) * value.len() as u64))
+            .collect();
+        // Ties keep the string order so the dictionary is deterministic
+        ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+        let entries = ranked.into_iter()
+            .take(max_entries)
+            .map(|(value, _)| value.clone())
+            .collect();
+        CompressionDictionary::new(version, entries)
+    }
+}
@@ -1,14 +1,13 @@
-use std::io;
-use std::string::FromUtf8Error;
+use alloc::boxed::Box;
+use alloc::string::{FromUtf8Error, String};
 use thiserror::Error;
-use crate::Readable;
 
-trait ValueTrait: Readable + Sized {}
+use crate::io::IoError;
 
 #[derive(Error, Debug)]
 pub enum PacketError {
     #[error(transparent)]
-    IO(#[from] io::Error),
+    IO(#[from] IoError),
     #[error("failed to convert string bytes to utf-8 string {0:?}")]
     BadEncoding(#[from] FromUtf8Error),
     #[error("string length ({0}) was greater than max string length size ({1})")]
@@ -20,5 +19,71 @@ pub enum PacketError {
     #[error("packet with unknown id of {0} received")]
     UnknownPacket(u32),
     #[error("unknown enum value")]
-    UnknownEnumValue
-}
\ No newline at end of file
+    UnknownEnumValue,
+    #[error("transaction was aborted by the sender")]
+    TransactionAborted,
+    #[error("field {0} has a length of {1} but its length field {2} is {3}")]
+    LengthMismatch(&'static str, usize, &'static str, usize),
+    #[error("decoded value doesn't fit in {0}")]
+    NarrowingOverflow(&'static str),
+    #[error("collection length ({0}) was greater than max collection length ({1})")]
+    CollectionTooLarge(usize, usize),
+    #[error("packet was larger than the max packet size of {0} bytes")]
+    PacketTooLarge(usize),
+    #[error("more than {0} fragmented messages were incomplete at once")]
+    TooManyPendingMessages(usize),
+    #[error("values were nested deeper than the max depth of {0}")]
+    DepthLimitExceeded(usize),
+    #[error("checksum mismatch. expected {0:#010x} but the bytes had {1:#010x}")]
+    ChecksumMismatch(u32, u32),
+    #[error("packet with id of {0} is not allowed in the {1} state")]
+    OutOfStatePacket(u32, &'static str),
+    #[error("packet type {0} is not registered")]
+    UnregisteredPacket(&'static str),
+    #[error("string rejected by the text options: {0}")]
+    TextRejected(&'static str),
+    #[error("packet with id of {0} exceeded the rate limit")]
+    RateLimited(u32),
+    #[error("string had a non-ascii character at byte {0}")]
+    NonAsciiString(usize),
+    #[error("invalid identifier. expected {0}")]
+    InvalidIdentifier(&'static str),
+    #[error("string length ({0}) was greater than the bound of {1} bytes")]
+    StringTooLong(usize, usize),
+    #[error("{0} can't be migrated between the schema versions")]
+    UnsupportedMigration(&'static str),
+    #[error("packet wasn't decoded before its deadline")]
+    DeadlineExceeded,
+    #[error("value wasn't canonically encoded. expected {0}")]
+    NonCanonical(&'static str),
+    #[error("field {field} of {packet} failed validation: {reason}")]
+    ValidationFailed { packet: &'static str, field: &'static str, reason: String },
+    #[error("failed to read field {field} (index {index}) of {packet} at byte {offset}: {source}")]
+    FieldDecode { packet: &'static str, field: &'static str, index: usize, offset: usize, source: Box<PacketError> },
+    #[cfg(feature = "json")]
+    #[error("invalid json message: {0}")]
+    Json(#[from] serde_json::Error)
+}
+
+impl PacketError {
+    /// The error that caused this one. Errors from the fields of the generated
+    /// structs and packets are wrapped in PacketError::FieldDecode for each level of
+    /// nesting and this is the error they wrap
+    pub fn root_cause(&self) -> &PacketError {
+        match self {
+            PacketError::FieldDecode { source, .. } => source.root_cause(),
+            err => err
+        }
+    }
+
+    /// Sets the offset of this error and of the PacketError::FieldDecode errors it
+    /// wraps when this is a PacketError::FieldDecode
+    pub fn at_offset(mut self, offset: usize) -> Self {
+        let mut err = &mut self;
+        while let PacketError::FieldDecode { offset: field_offset, source, .. } = err {
+            *field_offset = offset;
+            err = source;
+        }
+        self
+    }
+}
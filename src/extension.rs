@@ -0,0 +1,201 @@
+use core::any::{Any, TypeId};
+use core::fmt::Debug;
+use alloc::boxed::Box;
+
+use crate::error::PacketError;
+use crate::limits::{ReadContext, WriteContext};
+use crate::io::{Read, ReadPacket, Readable, ReadResult, VarInt, Writable, Write, WriteResult};
+
+/// ## Extension
+/// An extension packet registered into a group by a downstream crate using the
+/// register_extension macro. Extensions are collected at link time so plugins
+/// can add packets to a base protocol without changing its definition.
+pub struct Extension {
+    /// The TypeId of the group this extension is registered into
+    pub group: fn() -> TypeId,
+    /// The packet ID of the extension. This must be an ID not used by the group
+    pub id: u32,
+    /// The name of the extension packet type
+    pub name: &'static str,
+    /// Reads the extension packet contents (without the ID) using the context of
+    /// the Extensible being read
+    pub read: fn(&mut ReadContext, &mut dyn Read) -> ReadResult<Box<dyn ExtensionValue>>,
+}
+
+inventory::collect!(Extension);
+
+/// Iterates over all the extensions registered into the group G
+pub fn extensions<G: 'static>() -> impl Iterator<Item=&'static Extension> {
+    inventory::iter::<Extension>
+        .into_iter()
+        .filter(|extension| (extension.group)() == TypeId::of::<G>())
+}
+
+/// Finds the extension registered into the group G with the provided ID
+pub fn find_extension<G: 'static>(id: u32) -> Option<&'static Extension> {
+    extensions::<G>().find(|extension| extension.id == id)
+}
+
+/// Object safe trait for the values of extension packets which is implemented
+/// for all writable types
+pub trait ExtensionValue: Any + Debug + Send + Sync {
+    /// Writes the packet contents (without the ID) to [o] using the provided context
+    fn write_dyn(&mut self, ctx: &mut WriteContext, o: &mut dyn Write) -> WriteResult;
+
+    /// Provides the value as Any so it can be downcast
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Writable + Debug + Any> ExtensionValue for T {
+    fn write_dyn(&mut self, ctx: &mut WriteContext, mut o: &mut dyn Write) -> WriteResult {
+        self.write_with(ctx, &mut o)
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+/// A packet read from or to be written to an extension of a group
+#[derive(Debug)]
+pub struct ExtensionPacket {
+    /// The packet ID of the extension
+    pub id: u32,
+    /// The packet value
+    pub value: Box<dyn ExtensionValue>,
+}
+
+impl ExtensionPacket {
+    /// Creates a new extension packet with the provided ID and value
+    pub fn new<T: ExtensionValue>(id: u32, value: T) -> Self {
+        ExtensionPacket { id, value: Box::new(value) }
+    }
+
+    /// Returns the packet value if it is of the type T
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.as_any().downcast_ref()
+    }
+}
+
+/// ## Extensible
+/// Wraps a packet group so that packets registered as extensions of the group are
+/// read as well as the packets of the group itself. Packet IDs belonging to the
+/// group are always read as the group packets.
+#[derive(Debug)]
+pub enum Extensible<G> {
+    /// A packet defined by the group itself
+    Core(G),
+    /// A packet registered as an extension of the group
+    Extension(ExtensionPacket),
+}
+
+impl<G> From<G> for Extensible<G> { fn from(v: G) -> Self { Extensible::Core(v) } }
+
+impl<G: ReadPacket + 'static> Readable for Extensible<G> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, mut i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let id = VarInt::read_with(ctx, i)?.0;
+        match G::read_packet(ctx, id, i) {
+            Ok(packet) => Ok(Extensible::Core(packet)),
+            Err(PacketError::UnknownPacket(id)) => {
+                let extension = find_extension::<G>(id)
+                    .ok_or(PacketError::UnknownPacket(id))?;
+                let value = (extension.read)(ctx, &mut i)?;
+                Ok(Extensible::Extension(ExtensionPacket { id, value }))
+            }
+            Err(err) => Err(err)
+        }
+    }
+}
+
+impl<G: Writable> Writable for Extensible<G> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        match self {
+            Extensible::Core(packet) => packet.write_with(ctx, o),
+            Extensible::Extension(packet) => {
+                VarInt(packet.id).write(o)?;
+                packet.value.write_dyn(ctx, o)
+            }
+        }
+    }
+}
+
+/// # Register Extension Macro
+/// Registers a readable and writable type as an extension packet of a group using
+/// an ID that isn't used by the group. Packets are then read as extensions when
+/// reading Extensible of the group.
+///
+/// ```ignore
+/// register_extension!(ServerPackets, 0x80, PluginPacket);
+/// ```
+#[macro_export]
+macro_rules! register_extension {
+    ($Group:ty, $ID:literal, $Packet:ty) => {
+        $crate::inventory::submit! {
+            $crate::Extension {
+                group: core::any::TypeId::of::<$Group>,
+                id: $ID,
+                name: stringify!($Packet),
+                read: |ctx, mut i| Ok($crate::alloc::boxed::Box::new(<$Packet as $crate::Readable>::read_with(ctx, &mut i)?)),
+            }
+        }
+    };
+}
+
+/// # Extend Packets Macro
+/// Declares extension packets for a group in another crate. Each packet is declared
+/// as a bi-directional struct, registered as an extension of the group using
+/// register_extension and can be converted into an Extensible of the group so that
+/// it can be written. Extension IDs must not be used by the group.
+///
+/// ```ignore
+/// extend_packets! {
+///     ServerPackets {
+///         PluginPing (0x80) {
+///             time: u64
+///         }
+///     }
+/// }
+///
+/// Extensible::<ServerPackets>::from(PluginPing { time: 5 }).write(&mut o)?;
+/// ```
+#[macro_export]
+macro_rules! extend_packets {
+    (
+        $(
+            $Group:ty {
+                $(
+                    $Name:ident ($ID:literal) {
+                        $(
+                            $(#[$($FieldAttr:tt)*])*
+                            $Field:ident: $Type:ty $(= $Default:expr)?
+                        ),* $(,)?
+                    }
+                )*
+            }
+        )*
+    ) => {
+        $(
+            $(
+                $crate::packet_data! {
+                    struct $Name (<->) {
+                        $($(#[$($FieldAttr)*])* $Field: $Type $(= $Default)?),*
+                    }
+                }
+
+                $crate::register_extension!($Group, $ID, $Name);
+
+                impl From<$Name> for $crate::Extensible<$Group> {
+                    fn from(v: $Name) -> Self {
+                        $crate::Extensible::Extension($crate::ExtensionPacket::new($ID, v))
+                    }
+                }
+            )*
+        )*
+    };
+}
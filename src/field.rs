@@ -0,0 +1,75 @@
+use alloc::string::String;
+
+use crate::io::{Read, ReadResult, VarInt, VarLong, Write, WriteResult};
+use crate::limits::ReadContext;
+
+/// ## Into Field
+/// Conversion used by the generated packet constructors so values can be passed
+/// without wrapping them (e.g. a u32 for a VarInt field or a &str for a String
+/// field). This is used rather than Into so that integer literals passed to the
+/// constructors are still inferred as the type of the field
+pub trait IntoField<T> {
+    /// Converts self into the field type
+    fn into_field(self) -> T;
+}
+
+impl<T> IntoField<T> for T {
+    fn into_field(self) -> T { self }
+}
+
+impl IntoField<VarInt> for u32 {
+    fn into_field(self) -> VarInt { VarInt(self) }
+}
+
+impl IntoField<VarLong> for u64 {
+    fn into_field(self) -> VarLong { VarLong(self) }
+}
+
+impl IntoField<String> for &str {
+    fn into_field(self) -> String { String::from(self) }
+}
+
+impl<T> IntoField<Option<T>> for T {
+    fn into_field(self) -> Option<T> { Some(self) }
+}
+
+/// ## Field Codec
+/// Custom encoding for fields of the type T which is used instead of the Readable
+/// and Writable implementations of the field type for fields marked with
+/// #[with(Codec)] in the packet macros. This lets legacy or unusual layouts be used
+/// for single fields while the rest of the packet is still generated
+///
+/// ```
+/// use wsbps::{FieldCodec, Read, Readable, ReadContext, ReadResult, Write, WriteResult, packet_data};
+///
+/// /// Bytes prefixed with their length as a u8
+/// struct ShortBytes;
+///
+/// impl FieldCodec<Vec<u8>> for ShortBytes {
+///     fn read_field<B: Read>(_ctx: &mut ReadContext, i: &mut B) -> ReadResult<Vec<u8>> {
+///         let mut bytes = vec![0u8; u8::read(i)? as usize];
+///         i.read_exact(&mut bytes)?;
+///         Ok(bytes)
+///     }
+///
+///     fn write_field<B: Write>(value: &Vec<u8>, o: &mut B) -> WriteResult {
+///         o.write_all(&[value.len() as u8])?;
+///         o.write_all(value)?;
+///         Ok(())
+///     }
+/// }
+///
+/// packet_data! {
+///     struct Legacy (<->) {
+///         #[with(ShortBytes)]
+///         data: Vec<u8>
+///     }
+/// }
+/// ```
+pub trait FieldCodec<T> {
+    /// Reads a field value from the source [i]
+    fn read_field<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<T>;
+
+    /// Writes the field [value] to the output [o]
+    fn write_field<B: Write>(value: &T, o: &mut B) -> WriteResult;
+}
@@ -0,0 +1,90 @@
+use core::fmt::{self, Display, Formatter};
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{Readable, VarInt};
+
+/// The number of bytes either side of the failing byte captured by
+/// decode_with_report unless another window is provided
+pub const DEFAULT_REPORT_WINDOW: usize = 16;
+
+/// The number of bytes shown on each line of the hex dump in a DecodeReport
+const DUMP_LINE_LENGTH: usize = 16;
+
+/// ## Decode Report
+/// Forensic report of a packet that failed to decode. This captures the error, the
+/// offset of the byte the decoder had reached when it failed, the packet ID (when
+/// one could be read) and the raw bytes surrounding the offset. The Display output
+/// is a hex dump of the captured bytes with the failing byte marked which is far
+/// more useful in a log than the bare error
+#[derive(Debug)]
+pub struct DecodeReport {
+    /// The error that decoding failed with
+    pub error: PacketError,
+    /// The offset of the byte the decoder had reached when it failed
+    pub offset: usize,
+    /// The total length of the bytes that were decoded
+    pub length: usize,
+    /// The packet ID at the start of the bytes if there was one
+    pub id: Option<u32>,
+    /// The offset of the first byte in the window
+    pub window_start: usize,
+    /// The raw bytes surrounding the offset
+    pub window: Vec<u8>,
+}
+
+impl DecodeReport {
+    /// Creates a report for the [error] that decoding [data] failed with at
+    /// [offset] capturing up to [window] bytes either side of the offset
+    pub fn new(error: PacketError, data: &[u8], offset: usize, window: usize) -> Self {
+        let offset = offset.min(data.len());
+        let window_start = offset.saturating_sub(window);
+        let window_end = offset.saturating_add(window).saturating_add(1).min(data.len());
+        DecodeReport {
+            error,
+            offset,
+            length: data.len(),
+            id: VarInt::read(&mut &data[..]).ok().map(|id| id.0),
+            window_start,
+            window: data[window_start..window_end].to_vec(),
+        }
+    }
+}
+
+impl Display for DecodeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "decode failed: {}", self.error)?;
+        match self.id {
+            Some(id) => writeln!(f, "packet id: {:#04x}", id)?,
+            None => writeln!(f, "packet id: unknown")?,
+        }
+        write!(f, "offset: {} of {} bytes", self.offset, self.length)?;
+        if self.offset >= self.length {
+            write!(f, " (reached the end of the bytes)")?;
+        }
+        for (index, line) in self.window.chunks(DUMP_LINE_LENGTH).enumerate() {
+            let line_start = self.window_start + index * DUMP_LINE_LENGTH;
+            write!(f, "\n{:08x} ", line_start)?;
+            for (column, byte) in line.iter().enumerate() {
+                if line_start + column == self.offset {
+                    write!(f, "[{:02x}]", byte)?;
+                } else {
+                    write!(f, " {:02x} ", byte)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// ## Decode With Report
+/// Decodes a value (usually a packet group) from [data] and when decoding fails
+/// returns a DecodeReport capturing [window] bytes either side of the offset the
+/// decoder had reached instead of the bare error
+pub fn decode_with_report<T: Readable>(data: &[u8], window: usize) -> Result<T, DecodeReport> {
+    let mut remaining = data;
+    T::read(&mut remaining).map_err(|error| {
+        let offset = data.len() - remaining.len();
+        DecodeReport::new(error, data, offset, window)
+    })
+}
@@ -0,0 +1,227 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{PacketResult, Readable, ReadResult, VarInt, Writable};
+
+/// The largest size of the header of a fragment (three VarInts)
+pub const MAX_FRAGMENT_HEADER_SIZE: usize = 15;
+
+/// The number of incomplete messages a Reassembler holds at once unless another
+/// maximum is set with with_max_pending
+pub const DEFAULT_MAX_PENDING_MESSAGES: usize = 16;
+
+/// ## Fragment
+/// A numbered piece of a packet that was too large for a single frame. This is
+/// encoded as a header of the message ID, the index of the fragment and the number
+/// of fragments in the message (all VarInts) followed by the bytes of the fragment
+/// which fill the rest of the frame. The fragments of messages split into more
+/// than one fragment always carry at least one byte
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    /// The ID of the message the fragment is part of
+    pub message_id: u32,
+    /// The index of the fragment within the message
+    pub index: u32,
+    /// The number of fragments in the message
+    pub count: u32,
+    /// The bytes of the fragment
+    pub payload: Vec<u8>,
+}
+
+impl Fragment {
+    /// Encodes the fragment as a frame
+    pub fn to_frame(&self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(MAX_FRAGMENT_HEADER_SIZE + self.payload.len());
+        write_header(&mut frame, self.message_id, self.index, self.count);
+        frame.extend_from_slice(&self.payload);
+        frame
+    }
+
+    /// Decodes a fragment from the [frame]
+    pub fn from_frame(mut frame: &[u8]) -> ReadResult<Self> {
+        let message_id = VarInt::read(&mut frame)?.0;
+        let index = VarInt::read(&mut frame)?.0;
+        let count = VarInt::read(&mut frame)?.0;
+        if index >= count {
+            return Err(PacketError::UnexpectedValue("fragment index less than the fragment count"));
+        }
+        if count > 1 && frame.is_empty() {
+            return Err(PacketError::UnexpectedValue("fragment of a split message to have a payload"));
+        }
+        Ok(Fragment { message_id, index, count, payload: frame.to_vec() })
+    }
+}
+
+/// Writes the header of a fragment to [frame]
+fn write_header(frame: &mut Vec<u8>, message_id: u32, index: u32, count: u32) {
+    for value in [message_id, index, count] {
+        VarInt(value).write(frame).expect("writing to a vec can't fail");
+    }
+}
+
+/// ## Fragmenter
+/// Splits serialized packets into fragments which fit within the maximum frame
+/// size for when websocket intermediaries cap the size of frames. Each packet is
+/// given the next message ID so the fragments of different packets can be told
+/// apart. The fragments are put back together by a Reassembler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragmenter {
+    max_frame_size: usize,
+    next_id: u32,
+}
+
+impl Fragmenter {
+    /// Creates a fragmenter producing frames of at most [max_frame_size] bytes
+    /// which must be larger than MAX_FRAGMENT_HEADER_SIZE
+    pub fn new(max_frame_size: usize) -> Self {
+        assert!(max_frame_size > MAX_FRAGMENT_HEADER_SIZE, "max frame size must be larger than the fragment header");
+        Fragmenter { max_frame_size, next_id: 0 }
+    }
+
+    /// The maximum size of the frames produced
+    pub fn max_frame_size(&self) -> usize { self.max_frame_size }
+
+    /// Splits the serialized [packet] into frames
+    pub fn fragment(&mut self, packet: &[u8]) -> Vec<Vec<u8>> {
+        let message_id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let payload_size = self.max_frame_size - MAX_FRAGMENT_HEADER_SIZE;
+        let count = packet.len().div_ceil(payload_size).max(1) as u32;
+        let mut frames = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let start = index as usize * payload_size;
+            let end = (start + payload_size).min(packet.len());
+            let mut frame = Vec::with_capacity(MAX_FRAGMENT_HEADER_SIZE + end - start);
+            write_header(&mut frame, message_id, index, count);
+            frame.extend_from_slice(&packet[start..end]);
+            frames.push(frame);
+        }
+        frames
+    }
+
+    /// Writes the [packet] and splits it into frames
+    pub fn fragment_packet<P: Writable>(&mut self, packet: &mut P) -> PacketResult<Vec<Vec<u8>>> {
+        let mut bytes = Vec::new();
+        packet.write(&mut bytes)?;
+        Ok(self.fragment(&bytes))
+    }
+}
+
+/// A message which is still missing fragments
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Partial {
+    fragments: BTreeMap<u32, Vec<u8>>,
+    count: u32,
+    size: usize,
+    started: u64,
+}
+
+/// ## Reassembler
+/// Puts the fragments made by a Fragmenter back together. The bytes held for all
+/// the incomplete messages together are limited to the maximum message size and
+/// fragments going over it fail with PacketError::PacketTooLarge. The number of
+/// incomplete messages is limited too so fragments starting more messages fail
+/// with PacketError::TooManyPendingMessages. Messages which don't receive all
+/// their fragments within the timeout are dropped by expire. Times are
+/// milliseconds provided by the caller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reassembler {
+    max_message_size: usize,
+    max_pending: usize,
+    timeout: u64,
+    partial: BTreeMap<u32, Partial>,
+}
+
+impl Reassembler {
+    /// Creates a reassembler for messages of up to [max_message_size] bytes which
+    /// must receive all their fragments within [timeout] milliseconds
+    pub fn new(max_message_size: usize, timeout: u64) -> Self {
+        Reassembler { max_message_size, max_pending: DEFAULT_MAX_PENDING_MESSAGES, timeout, partial: BTreeMap::new() }
+    }
+
+    /// Sets the maximum number of incomplete messages held at once
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+
+    /// Adds the fragment in the [frame] received at the time [now] returning the
+    /// serialized packet once all of its fragments have been received
+    pub fn push(&mut self, now: u64, frame: &[u8]) -> ReadResult<Option<Vec<u8>>> {
+        let fragment = Fragment::from_frame(frame)?;
+        if fragment.count == 1 {
+            self.check_size(fragment.payload.len())?;
+            return Ok(Some(fragment.payload));
+        }
+        // Every fragment carries at least one byte (checked by from_frame)
+        if fragment.count as usize > self.max_message_size {
+            return Err(PacketError::PacketTooLarge(self.max_message_size));
+        }
+        if !self.partial.contains_key(&fragment.message_id) && self.partial.len() >= self.max_pending {
+            return Err(PacketError::TooManyPendingMessages(self.max_pending));
+        }
+        let partial = self.partial.entry(fragment.message_id).or_insert_with(|| Partial {
+            fragments: BTreeMap::new(),
+            count: fragment.count,
+            size: 0,
+            started: now,
+        });
+        if partial.count != fragment.count {
+            self.partial.remove(&fragment.message_id);
+            return Err(PacketError::UnexpectedValue("the same fragment count for every fragment of a message"));
+        }
+        partial.size += fragment.payload.len();
+        if let Some(previous) = partial.fragments.insert(fragment.index, fragment.payload) {
+            partial.size -= previous.len();
+        }
+        let complete = partial.fragments.len() == partial.count as usize;
+        if self.pending_bytes() > self.max_message_size {
+            self.partial.remove(&fragment.message_id);
+            return Err(PacketError::PacketTooLarge(self.max_message_size));
+        }
+        if !complete {
+            return Ok(None);
+        }
+        let partial = self.partial.remove(&fragment.message_id).expect("partial message is present");
+        let mut packet = Vec::with_capacity(partial.size);
+        for payload in partial.fragments.into_values() {
+            packet.extend_from_slice(&payload);
+        }
+        Ok(Some(packet))
+    }
+
+    /// Adds the fragment in the [frame] and reads the packet once all of its
+    /// fragments have been received
+    pub fn push_packet<P: Readable>(&mut self, now: u64, frame: &[u8]) -> ReadResult<Option<P>> {
+        match self.push(now, frame)? {
+            Some(packet) => P::read(&mut packet.as_slice()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Drops the messages that didn't receive all their fragments within the
+    /// timeout at the time [now] returning the number dropped
+    pub fn expire(&mut self, now: u64) -> usize {
+        let before = self.partial.len();
+        let timeout = self.timeout;
+        self.partial.retain(|_, partial| now.saturating_sub(partial.started) < timeout);
+        before - self.partial.len()
+    }
+
+    /// The number of messages waiting for more fragments
+    pub fn pending(&self) -> usize { self.partial.len() }
+
+    /// The number of bytes held by the messages waiting for more fragments
+    pub fn pending_bytes(&self) -> usize {
+        self.partial.values().map(|partial| partial.size).sum()
+    }
+
+    /// Checks that a message of [size] bytes is within the maximum message size
+    fn check_size(&self, size: usize) -> ReadResult<()> {
+        if size > self.max_message_size {
+            return Err(PacketError::PacketTooLarge(self.max_message_size));
+        }
+        Ok(())
+    }
+}
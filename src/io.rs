@@ -1,10 +1,27 @@
-use std::collections::HashMap;
-use std::hash::Hash;
-use std::io::{Read, Write};
-use std::iter;
+use core::iter;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::boxed::Box;
+use alloc::borrow::Cow;
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
-use byteorder::{ReadBytesExt, WriteBytesExt};
 use crate::error::PacketError;
+use crate::limits::{check_collection_length, check_string_length, read_chunked, ReadContext, WriteContext};
+
+/// The Read and Write traits used by the Readable and Writable traits. These are
+/// the std::io traits when the "std" feature is enabled and minimal replacements
+/// otherwise so that packets can be used without std. Seek and BufRead are only
+/// available with std and are used to peek packet IDs
+#[cfg(feature = "std")]
+pub use std::io::{Read, Write, Seek, BufRead, Error as IoError};
+#[cfg(not(feature = "std"))]
+pub use crate::core_io::{Read, Write, IoError};
 
 pub type PacketResult<T> = Result<T, PacketError>;
 pub type WriteResult = PacketResult<()>;
@@ -13,52 +30,69 @@ pub type ReadResult<T> = PacketResult<T>;
 pub trait Readable: Send + Sync {
     /// Reads self from the provided source [i]
     fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized;
+
+    /// Reads self from the provided source [i] using the provided context. Types
+    /// that contain other values override this to pass the context on to them
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let _ = ctx;
+        Self::read(i)
+    }
+}
+
+/// Trait implemented by readable packet groups for reading the packet with the
+/// provided ID after the ID has already been read from the source
+pub trait ReadPacket: Readable {
+    /// Reads the packet with the provided [id] from the source [i] failing with
+    /// PacketError::UnknownPacket if the ID isn't part of the group
+    fn read_packet<B: Read>(ctx: &mut ReadContext, id: u32, i: &mut B) -> ReadResult<Self> where Self: Sized;
 }
 
 pub trait Writable: Send + Sync {
     // Writes self to the the provided source [o]
     fn write<B: Write>(&mut self, o: &mut B) -> WriteResult;
-}
-
 
-/// Read write traits on u8 & i8 need to be implemented manually because
-/// the underlying function in ReadBytesExt doesn't take a generic
-/// argument like the other primitive number ones do
-impl Writable for u8 {
-    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
-        o.write_u8(*self)?;
-        Ok(())
+    /// Writes self to the provided output [o] using the provided context. Types
+    /// that contain other values override this to pass the context on to them
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        let _ = ctx;
+        self.write(o)
     }
 }
 
-impl Readable for u8 {
-    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
-        B::read_u8(i).map_err(PacketError::from)
-    }
+/// ## Fixed Size
+/// Writable types which are always encoded as the same number of bytes. This is
+/// implemented for the number types and for generated structs when all of their
+/// fields are fixed size which gives them a write_to_array function
+pub trait FixedSize: Writable {
+    /// The number of bytes this type is always encoded as
+    const ENCODED_SIZE: usize;
 }
 
-impl Writable for i8 {
-    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
-        o.write_i8(*self)?;
-        Ok(())
-    }
-}
+/// Marker type used by the packet macros in place of the type of a conditional field
+/// so that structs with conditional fields never implement FixedSize
+#[doc(hidden)]
+pub struct NotFixedSize<T>(core::marker::PhantomData<T>);
 
-impl Readable for i8 {
-    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
-        B::read_i8(i).map_err(PacketError::from)
-    }
+/// Reads exactly N bytes from the provided source [i]
+fn read_bytes<B: Read, const N: usize>(i: &mut B) -> ReadResult<[u8; N]> {
+    let mut bytes = [0u8; N];
+    i.read_exact(&mut bytes)?;
+    Ok(bytes)
 }
 
 /// Boolean values are encoded as a single unsigned byte (u8)
 /// 1 being true and 0 being false
 impl Writable for bool {
     fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
-        o.write_u8(*self as u8)?;
+        o.write_all(&[*self as u8])?;
         Ok(())
     }
 }
 
+impl FixedSize for bool {
+    const ENCODED_SIZE: usize = 1;
+}
+
 impl Readable for bool {
     fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
         let byte = u8::read(i)?;
@@ -90,94 +124,220 @@ impl Readable for bool {
 /// | 255    | 11111111 00000001          |
 /// | 300    | 10101100 00000010          |
 /// | 16384  | 10000000 10000000 00000001 |
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct VarInt(pub u32);
 
 impl From<u32> for VarInt { fn from(v: u32) -> Self { VarInt(v) } }
 
 impl From<VarInt> for u32 { fn from(v: VarInt) -> Self { v.0 } }
 
-impl Writable for VarInt {
-    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
-        let mut x = self.0;
-        loop {
-            let mut temp = (x & 0b0111_1111  /* 0x7F */) as u8;
-            x >>= 7;
-            if x != 0 {
-                temp |= 0b1000_0000 /* 0x80 */;
-            }
-            o.write_u8(temp).unwrap();
-            if x == 0 {
-                break;
-            }
-        }
-        Ok(())
-    }
+/// Conversions from the smaller number types are implemented using TryFrom rather
+/// than From so that integer literals passed to VarInt::from are still inferred as u32
+#[allow(clippy::infallible_try_from)]
+impl TryFrom<u8> for VarInt {
+    type Error = core::convert::Infallible;
+    fn try_from(v: u8) -> Result<Self, Self::Error> { Ok(VarInt(v as u32)) }
 }
 
-impl Readable for VarInt {
-    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
-        let mut byte_offset = 0;
-        let mut result = 0;
-        loop {
-            let read = i.read_u8()?;
-            let value = u32::from(read & 0b0111_1111 /* 0x7F */);
-            result |= value.overflowing_shl(byte_offset).0;
-            byte_offset += 7;
-            if byte_offset > 35 {
-                Err(PacketError::VarOverflow("int", 5))?;
-            }
-            if read & 0b1000_0000 /* 0x80 */ == 0 {
-                break;
-            }
-        }
-        Ok(VarInt(result))
+#[allow(clippy::infallible_try_from)]
+impl TryFrom<u16> for VarInt {
+    type Error = core::convert::Infallible;
+    fn try_from(v: u16) -> Result<Self, Self::Error> { Ok(VarInt(v as u32)) }
+}
+
+impl TryFrom<VarInt> for u8 {
+    type Error = core::num::TryFromIntError;
+    fn try_from(v: VarInt) -> Result<Self, Self::Error> { u8::try_from(v.0) }
+}
+
+impl TryFrom<VarInt> for u16 {
+    type Error = core::num::TryFromIntError;
+    fn try_from(v: VarInt) -> Result<Self, Self::Error> { u16::try_from(v.0) }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for VarInt {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(VarInt(u32::arbitrary(u)?))
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct VarLong(pub u64);
 
 impl From<u64> for VarLong { fn from(v: u64) -> Self { VarLong(v) } }
 
 impl From<VarLong> for u64 { fn from(v: VarLong) -> Self { v.0 } }
 
-impl Writable for VarLong {
-    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
-        let mut x = self.0;
-        loop {
-            let mut temp = (x & 0b0111_1111  /* 0x7F */) as u8;
-            x >>= 7;
-            if x != 0 {
-                temp |= 0b1000_0000 /* 0x80 */;
-            }
-            o.write_u8(temp).unwrap();
-            if x == 0 {
-                break;
-            }
-        }
-        Ok(())
+/// Conversions from the smaller number types are implemented using TryFrom rather
+/// than From so that integer literals passed to VarLong::from are still inferred as u64
+#[allow(clippy::infallible_try_from)]
+impl TryFrom<u8> for VarLong {
+    type Error = core::convert::Infallible;
+    fn try_from(v: u8) -> Result<Self, Self::Error> { Ok(VarLong(v as u64)) }
+}
+
+#[allow(clippy::infallible_try_from)]
+impl TryFrom<u16> for VarLong {
+    type Error = core::convert::Infallible;
+    fn try_from(v: u16) -> Result<Self, Self::Error> { Ok(VarLong(v as u64)) }
+}
+
+#[allow(clippy::infallible_try_from)]
+impl TryFrom<u32> for VarLong {
+    type Error = core::convert::Infallible;
+    fn try_from(v: u32) -> Result<Self, Self::Error> { Ok(VarLong(v as u64)) }
+}
+
+impl TryFrom<VarLong> for u8 {
+    type Error = core::num::TryFromIntError;
+    fn try_from(v: VarLong) -> Result<Self, Self::Error> { u8::try_from(v.0) }
+}
+
+impl TryFrom<VarLong> for u16 {
+    type Error = core::num::TryFromIntError;
+    fn try_from(v: VarLong) -> Result<Self, Self::Error> { u16::try_from(v.0) }
+}
+
+impl TryFrom<VarLong> for u32 {
+    type Error = core::num::TryFromIntError;
+    fn try_from(v: VarLong) -> Result<Self, Self::Error> { u32::try_from(v.0) }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for VarLong {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(VarLong(u64::arbitrary(u)?))
     }
 }
 
-impl Readable for VarLong {
-    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
-        let mut byte_offset = 0;
-        let mut result = 0;
-        loop {
-            let read = i.read_u8()?;
-            let value = u64::from(read & 0b0111_1111 /* 0x7F */);
-            result |= value.overflowing_shl(byte_offset).0;
-            byte_offset += 7;
-            if byte_offset > 70 {
-                Err(PacketError::VarOverflow("long", 10))?;
+/// Macro for implementing the encoding of the var number types. Values are encoded
+/// into a stack buffer so they can be written with a single write_all call and
+/// decode can be used to read directly from byte slices without going through Read
+macro_rules! impl_var_number {
+    ($($name:ident, $type:ty, $label:literal, $max:literal),*) => {
+        $(
+            impl $name {
+                /// The maximum number of bytes this type can be encoded as
+                pub const MAX_SIZE: usize = $max;
+
+                /// The number of bytes this value will be encoded as
+                pub fn size(&self) -> usize {
+                    let bits = (<$type>::BITS - self.0.leading_zeros()) as usize;
+                    if bits == 0 { 1 } else { bits.div_ceil(7) }
+                }
+
+                /// Encodes this value into the provided buffer returning the number
+                /// of bytes that were used
+                pub fn encode(&self, buffer: &mut [u8; $max]) -> usize {
+                    let size = self.size();
+                    let mut x = self.0;
+                    for byte in &mut buffer[..size - 1] {
+                        *byte = (x as u8) | 0b1000_0000 /* 0x80 */;
+                        x >>= 7;
+                    }
+                    buffer[size - 1] = x as u8;
+                    size
+                }
+
+                /// Decodes a value from the start of the provided bytes returning the
+                /// value and the number of bytes that it used
+                pub fn decode(bytes: &[u8]) -> ReadResult<(Self, usize)> {
+                    // Single byte values are the most common so they are handled first
+                    if let Some(&byte) = bytes.first() {
+                        if byte & 0b1000_0000 /* 0x80 */ == 0 {
+                            return Ok(($name(<$type>::from(byte)), 1));
+                        }
+                    }
+                    let mut result: $type = 0;
+                    for index in 0..bytes.len().min($max) {
+                        let byte = bytes[index];
+                        result |= <$type>::from(byte & 0b0111_1111 /* 0x7F */) << (index * 7);
+                        if byte & 0b1000_0000 /* 0x80 */ == 0 {
+                            return Ok(($name(result), index + 1));
+                        }
+                    }
+                    if bytes.len() < $max {
+                        // Let read produce the unexpected end of input error
+                        let value = Self::read(&mut &bytes[..])?;
+                        return Ok((value, bytes.len()));
+                    }
+                    Err(PacketError::VarOverflow($label, $max))
+                }
             }
-            if read & 0b1000_0000 /* 0x80 */ == 0 {
-                break;
+
+            impl Writable for $name {
+                fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+                    let mut buffer = [0u8; $max];
+                    let size = self.encode(&mut buffer);
+                    o.write_all(&buffer[..size])?;
+                    Ok(())
+                }
             }
-        }
-        Ok(VarLong(result))
-    }
+
+            impl $name {
+                /// Reads a value from [i] returning it along with the number of bytes
+                /// that it used
+                fn read_sized<B: Read>(i: &mut B) -> ReadResult<(Self, usize)> {
+                    let mut result: $type = 0;
+                    for index in 0..$max {
+                        let [read] = read_bytes::<B, 1>(i)?;
+                        result |= <$type>::from(read & 0b0111_1111 /* 0x7F */) << (index * 7);
+                        if read & 0b1000_0000 /* 0x80 */ == 0 {
+                            return Ok(($name(result), index + 1));
+                        }
+                    }
+                    Err(PacketError::VarOverflow($label, $max))
+                }
+            }
+
+            impl Readable for $name {
+                fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+                    Self::read_sized(i).map(|(value, _)| value)
+                }
+
+                /// Canonical values must use the fewest bytes possible
+                fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+                    let (value, size) = Self::read_sized(i)?;
+                    if ctx.is_canonical() && size != value.size() {
+                        return Err(PacketError::NonCanonical(concat!("a minimal var", $label)));
+                    }
+                    Ok(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_var_number!(VarInt, u32, "int", 5, VarLong, u64, "long", 10);
+
+impl core::fmt::Display for VarInt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { self.0.fmt(f) }
+}
+
+impl core::fmt::Display for VarLong {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { self.0.fmt(f) }
+}
+
+/// Reads the packet ID (VarInt) from the start of [r] then seeks back to where it
+/// started so the packet can still be read or forwarded in full without decoding
+#[cfg(feature = "std")]
+pub fn peek_id<R: Read + Seek>(r: &mut R) -> ReadResult<u32> {
+    let start = r.stream_position()?;
+    let id = VarInt::read(r);
+    r.seek(std::io::SeekFrom::Start(start))?;
+    Ok(id?.0)
+}
+
+/// Reads the packet ID (VarInt) from the buffered bytes of [r] without consuming
+/// them for streams that can't seek. This fails with an unexpected end of input
+/// error if the buffer ends before the end of the ID
+#[cfg(feature = "std")]
+pub fn peek_id_buffered<R: BufRead>(r: &mut R) -> ReadResult<u32> {
+    let buffer = r.fill_buf()?;
+    VarInt::decode(buffer).map(|(id, _)| id.0)
 }
 
 /// Strings are encoded with a VarInt that represents the length of the string
@@ -194,14 +354,12 @@ impl Writable for String {
 impl Readable for String {
     fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
         let length = VarInt::read(i)?.0 as usize;
-        let max_length = i16::MAX as usize;
-        if length > max_length {
-            Err(PacketError::InvalidStringLength(length, max_length))?;
-        }
-        let mut bytes = vec![0u8; length];
-        i.read_exact(&mut bytes)
-            .map_err(PacketError::from)?;
-        String::from_utf8(bytes).map_err(PacketError::from)
+        read_string(&ReadContext::default(), i, length)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        read_string_with(ctx, i, length)
     }
 }
 
@@ -210,32 +368,408 @@ impl Readable for String {
 /// respective encodings.
 impl<T: Writable> Writable for Vec<T> {
     fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
         VarInt(self.len() as u32).write(o)?;
-        self.iter_mut()
-            .for_each(|it|
-                it.write(o).expect("couldn't write vec contents"));
-        Ok(())
+        write_all_slice_with(ctx, self, o)
     }
 }
 
 impl<T: Readable> Readable for Vec<T> {
     fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
-        let length = VarInt::read(i)?.0 as usize;
-        iter::repeat_with(|| T::read(i))
-            .take(length)
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        read_n_with(ctx, i, length)
+    }
+}
+
+/// ## Read N
+/// Reads [n] values of T from the provided source [i] without a length
+/// prefix. This is used for sequences whose length is known from another
+/// field that was read earlier. Fails if n is greater than the collection
+/// length limit
+pub fn read_n<T: Readable, B: Read>(i: &mut B, n: usize) -> ReadResult<Vec<T>> {
+    read_n_with(&mut ReadContext::default(), i, n)
+}
+
+/// Reads [n] values of T one level deeper than the provided context
+pub fn read_n_with<T: Readable, B: Read>(ctx: &mut ReadContext, i: &mut B, n: usize) -> ReadResult<Vec<T>> {
+    check_collection_length(ctx, n)?;
+    ctx.enter(|ctx| {
+        iter::repeat_with(|| T::read_with(ctx, i))
+            .take(n)
             .collect::<ReadResult<Vec<T>>>()
+    })
+}
+
+/// ## Write All Slice
+/// Writes all the provided values in order to the output [o] without a
+/// length prefix. This is the counterpart to read_n
+pub fn write_all_slice<T: Writable, B: Write>(values: &mut [T], o: &mut B) -> WriteResult {
+    write_all_slice_with(&mut WriteContext::default(), values, o)
+}
+
+/// Writes all the provided values in order using the provided context
+pub fn write_all_slice_with<T: Writable, B: Write>(ctx: &mut WriteContext, values: &mut [T], o: &mut B) -> WriteResult {
+    for value in values {
+        value.write_with(ctx, o)?;
+    }
+    Ok(())
+}
+
+/// ## Length Fields
+/// Types which can be used as the length of a field that is marked with
+/// #[len_from(field)] in the packet macros
+pub trait LengthField: Sized {
+    /// Converts this value into a length
+    fn to_length(&self) -> usize;
+
+    /// Creates a value from the provided length returning None if the
+    /// length can't be represented by this type
+    fn from_length(length: usize) -> Option<Self>;
+}
+
+/// Macro for implementing LengthField for the unsigned number types
+macro_rules! impl_length_field {
+    ($($type:ty)*) => {
+        $(
+            impl LengthField for $type {
+                fn to_length(&self) -> usize { *self as usize }
+
+                fn from_length(length: usize) -> Option<Self> { <$type>::try_from(length).ok() }
+            }
+        )*
+    };
+}
+
+impl_length_field!(u8 u16 u32 u64 usize);
+
+impl LengthField for VarInt {
+    fn to_length(&self) -> usize { self.0 as usize }
+
+    fn from_length(length: usize) -> Option<Self> { u32::try_from(length).ok().map(VarInt) }
+}
+
+impl LengthField for VarLong {
+    fn to_length(&self) -> usize { self.0 as usize }
+
+    fn from_length(length: usize) -> Option<Self> { u64::try_from(length).ok().map(VarLong) }
+}
+
+/// ## Unprefixed Reading
+/// Collections and strings that can be read using a length that is already known
+/// (e.g. from another field) rather than reading a VarInt length prefix
+pub trait ReadUnprefixed: Sized {
+    /// Reads self with the provided [length] from the source [i]
+    fn read_with_length<B: Read>(ctx: &mut ReadContext, i: &mut B, length: usize) -> ReadResult<Self>;
+}
+
+/// ## Unprefixed Writing
+/// Collections and strings that can be written without a VarInt length prefix
+pub trait WriteUnprefixed {
+    /// The length that is expected by ReadUnprefixed::read_with_length
+    fn length(&self) -> usize;
+
+    /// Writes self to the output [o] without a length prefix
+    fn write_without_length<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult;
+}
+
+impl<T: Readable> ReadUnprefixed for Vec<T> {
+    fn read_with_length<B: Read>(ctx: &mut ReadContext, i: &mut B, length: usize) -> ReadResult<Self> {
+        read_n_with(ctx, i, length)
+    }
+}
+
+impl<T: Writable> WriteUnprefixed for Vec<T> {
+    fn length(&self) -> usize { self.len() }
+
+    fn write_without_length<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        write_all_slice_with(ctx, self, o)
+    }
+}
+
+/// Unprefixed string lengths are the number of utf8 encoded bytes
+impl ReadUnprefixed for String {
+    fn read_with_length<B: Read>(ctx: &mut ReadContext, i: &mut B, length: usize) -> ReadResult<Self> {
+        read_string_with(ctx, i, length)
+    }
+}
+
+/// Reads a utf8 string of [length] bytes from the source [i] within the limits of [ctx]
+fn read_string<B: Read>(ctx: &ReadContext, i: &mut B, length: usize) -> ReadResult<String> {
+    check_string_length(ctx, length)?;
+    let bytes = read_chunked(i, length)?;
+    String::from_utf8(bytes).map_err(PacketError::from)
+}
+
+/// Reads a utf8 string of [length] bytes from the source [i] checking it against the
+/// text options of the context. The length is checked before the bytes are read
+pub(crate) fn read_string_with<B: Read>(ctx: &mut ReadContext, i: &mut B, length: usize) -> ReadResult<String> {
+    ctx.text().check_length(length)?;
+    let value = read_string(ctx, i, length)?;
+    ctx.text().check(&value)?;
+    #[cfg(feature = "unicode")]
+    if ctx.is_canonical() && !unicode_normalization::is_nfc(&value) {
+        return Err(PacketError::NonCanonical("strings in Unicode Normalization Form C"));
+    }
+    Ok(value)
+}
+
+impl WriteUnprefixed for String {
+    fn length(&self) -> usize { self.len() }
+
+    fn write_without_length<B: Write>(&mut self, _ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        o.write_all(self.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// ## Read Or Default
+/// Reads the value of a field marked with a default value (`field: u32 = 0`) in the
+/// packet macros. If the source [i] has already ended before the field (e.g. the packet
+/// is from an older client which doesn't send the field) the [default] is used instead.
+/// The field is only treated as missing if none of its bytes are present so truncated
+/// values are still errors
+pub fn read_or_default<T, B, F>(ctx: &mut ReadContext, i: &mut B, default: F) -> ReadResult<T>
+    where T: Readable,
+          B: Read,
+          F: FnOnce() -> T {
+    let mut first = [0u8; 1];
+    match i.read_exact(&mut first) {
+        Ok(()) => T::read_with(ctx, &mut LookaheadReader { first: Some(first[0]), inner: i }),
+        Err(err) if is_eof(&err) => Ok(default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Whether the provided error is from the source ending before a read was complete
+#[cfg(feature = "std")]
+pub(crate) fn is_eof(err: &IoError) -> bool {
+    err.kind() == std::io::ErrorKind::UnexpectedEof
+}
+
+/// Whether the provided error is from the source ending before a read was complete
+#[cfg(not(feature = "std"))]
+pub(crate) fn is_eof(err: &IoError) -> bool {
+    *err == IoError::UnexpectedEof
+}
+
+/// ## Lookahead Reader
+/// Reader which returns the byte that was read ahead before the rest of the source
+struct LookaheadReader<'a, B: Read> {
+    first: Option<u8>,
+    inner: &'a mut B,
+}
+
+#[cfg(feature = "std")]
+impl<B: Read> Read for LookaheadReader<'_, B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        match self.first {
+            Some(first) if !buf.is_empty() => {
+                buf[0] = first;
+                self.first = None;
+                Ok(1)
+            }
+            _ => self.inner.read(buf)
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: Read> Read for LookaheadReader<'_, B> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        match self.first {
+            Some(first) if !buf.is_empty() => {
+                buf[0] = first;
+                self.first = None;
+                self.inner.read_exact(&mut buf[1..])
+            }
+            _ => self.inner.read_exact(buf)
+        }
+    }
+}
+
+/// ## Counting Reader
+/// Reader which counts the bytes read from the inner reader so the position in a
+/// frame that a read failed at can be reported. This is used for the offsets of
+/// PacketError::FieldDecode and by read_limited to enforce the max packet size
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CountingReader<R> {
+    inner: R,
+    position: usize,
+}
+
+impl<R: Read> CountingReader<R> {
+    /// Creates a reader counting the bytes read from [inner]
+    pub fn new(inner: R) -> Self {
+        CountingReader { inner, position: 0 }
+    }
+
+    /// The number of bytes that have been read
+    pub fn position(&self) -> usize { self.position }
+
+    /// The inner reader
+    pub fn get_ref(&self) -> &R { &self.inner }
+
+    /// Consumes self returning the inner reader
+    pub fn into_inner(self) -> R { self.inner }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        let count = self.inner.read(buf)?;
+        self.position += count;
+        Ok(count)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        self.inner.read_exact(buf)?;
+        self.position += buf.len();
+        Ok(())
     }
 }
 
+#[cfg(not(feature = "std"))]
+impl<R: Read> Read for CountingReader<R> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        self.inner.read_exact(buf)?;
+        self.position += buf.len();
+        Ok(())
+    }
+}
+
+/// ## Counting Writer
+/// Writer which counts the bytes written to the inner writer. encoded_size uses
+/// this to find the size of a value without allocating
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CountingWriter<W> {
+    inner: W,
+    position: usize,
+}
+
+impl<W: Write> CountingWriter<W> {
+    /// Creates a writer counting the bytes written to [inner]
+    pub fn new(inner: W) -> Self {
+        CountingWriter { inner, position: 0 }
+    }
+
+    /// The number of bytes that have been written
+    pub fn position(&self) -> usize { self.position }
+
+    /// The inner writer
+    pub fn get_ref(&self) -> &W { &self.inner }
+
+    /// Consumes self returning the inner writer
+    pub fn into_inner(self) -> W { self.inner }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+        let count = self.inner.write(buf)?;
+        self.position += count;
+        Ok(count)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), IoError> {
+        self.inner.write_all(buf)?;
+        self.position += buf.len();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), IoError> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: Write> Write for CountingWriter<W> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), IoError> {
+        self.inner.write_all(buf)?;
+        self.position += buf.len();
+        Ok(())
+    }
+}
+
+/// Writer which discards everything written to it
+struct Discard;
+
+#[cfg(feature = "std")]
+impl Write for Discard {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> { Ok(buf.len()) }
+
+    fn flush(&mut self) -> Result<(), IoError> { Ok(()) }
+}
+
+#[cfg(not(feature = "std"))]
+impl Write for Discard {
+    fn write_all(&mut self, _: &[u8]) -> Result<(), IoError> { Ok(()) }
+}
+
+/// ## Encoded Size
+/// The number of bytes the [value] is encoded as. The value is written to a
+/// CountingWriter which discards the bytes so nothing is allocated
+pub fn encoded_size<T: Writable + ?Sized>(value: &mut T) -> PacketResult<usize> {
+    let mut o = CountingWriter::new(Discard);
+    value.write(&mut o)?;
+    Ok(o.position())
+}
+
+/// ## Read Field With Context
+/// Calls [read] to read the field at [index] of the struct or packet named [packet]
+/// from [i] wrapping any error in PacketError::FieldDecode. The offset of the error
+/// is set by the enclosing read_counted once the error reaches it
+pub fn read_field_with_context<T, B, F>(
+    ctx: &mut ReadContext,
+    i: &mut B,
+    packet: &'static str,
+    field: &'static str,
+    index: usize,
+    read: F,
+) -> ReadResult<T>
+    where B: Read,
+          F: FnOnce(&mut ReadContext, &mut B) -> ReadResult<T> {
+    read(ctx, i).map_err(|source| PacketError::FieldDecode {
+        packet,
+        field,
+        index,
+        offset: 0,
+        source: Box::new(source),
+    })
+}
+
+/// ## Read Counted
+/// Calls [read] with the source [i] wrapped in a CountingReader and sets the
+/// offset of the PacketError::FieldDecode errors it fails with (at every level of
+/// nesting) to the number of bytes that were read. The generated packets use this
+/// so the offsets are positions in the packet and the nested structs read from the
+/// same reader rather than wrapping it again
+pub fn read_counted<T, B, F>(ctx: &mut ReadContext, i: &mut B, read: F) -> ReadResult<T>
+    where B: Read,
+          F: FnOnce(&mut ReadContext, &mut CountingReader<&mut B>) -> ReadResult<T> {
+    let mut i = CountingReader::new(i);
+    read(ctx, &mut i).map_err(|err| err.at_offset(i.position()))
+}
+
 /// Optional values are encoded with 1 byte identifier (0 or 1) which tells
 /// whether or not the value is present. If the value is present the respective
 /// Writable/Readable will be used.
 impl<T: Writable> Writable for Option<T> {
     fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
         match self {
             Some(value) => {
                 true.write(o)?;
-                value.write(o)?;
+                value.write_with(ctx, o)?;
             }
             None => {
                 false.write(o)?;
@@ -247,15 +781,252 @@ impl<T: Writable> Writable for Option<T> {
 
 impl<T: Readable> Readable for Option<T> {
     fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
         let exists = bool::read(i)?;
         if exists {
-            Ok(Some(T::read(i)?))
+            Ok(Some(ctx.enter(|ctx| T::read_with(ctx, i))?))
         } else {
             Ok(None)
         }
     }
 }
 
+/// Results are encoded with 1 byte tag (0 for Ok and 1 for Err) followed by
+/// the value or the error so responses can carry success and error payloads
+impl<T: Writable, E: Writable> Writable for Result<T, E> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        match self {
+            Ok(value) => {
+                0u8.write(o)?;
+                value.write_with(ctx, o)
+            }
+            Err(error) => {
+                1u8.write(o)?;
+                error.write_with(ctx, o)
+            }
+        }
+    }
+}
+
+impl<T: Readable, E: Readable> Readable for Result<T, E> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        match u8::read(i)? {
+            0 => Ok(Ok(ctx.enter(|ctx| T::read_with(ctx, i))?)),
+            1 => Ok(Err(ctx.enter(|ctx| E::read_with(ctx, i))?)),
+            _ => Err(PacketError::UnexpectedValue("0 or 1 for result tag"))
+        }
+    }
+}
+
+/// The unit type is encoded as zero bytes so it can be used for empty
+/// placeholder fields and responses without a value
+impl Writable for () {
+    fn write<B: Write>(&mut self, _o: &mut B) -> WriteResult {
+        Ok(())
+    }
+}
+
+impl FixedSize for () {
+    const ENCODED_SIZE: usize = 0;
+}
+
+impl Readable for () {
+    fn read<B: Read>(_i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Ok(())
+    }
+}
+
+/// IPv4 addresses are encoded as their 4 bytes in network order
+impl Writable for Ipv4Addr {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        o.write_all(&self.octets())?;
+        Ok(())
+    }
+}
+
+impl FixedSize for Ipv4Addr {
+    const ENCODED_SIZE: usize = 4;
+}
+
+impl Readable for Ipv4Addr {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let mut octets = [0u8; 4];
+        i.read_exact(&mut octets)?;
+        Ok(Ipv4Addr::from(octets))
+    }
+}
+
+/// IPv6 addresses are encoded as their 16 bytes in network order
+impl Writable for Ipv6Addr {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        o.write_all(&self.octets())?;
+        Ok(())
+    }
+}
+
+impl FixedSize for Ipv6Addr {
+    const ENCODED_SIZE: usize = 16;
+}
+
+impl Readable for Ipv6Addr {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let mut octets = [0u8; 16];
+        i.read_exact(&mut octets)?;
+        Ok(Ipv6Addr::from(octets))
+    }
+}
+
+/// IP addresses are encoded with a 1 byte tag (0 for IPv4 and 1 for IPv6)
+/// followed by the bytes of the address
+impl Writable for IpAddr {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        match self {
+            IpAddr::V4(address) => {
+                0u8.write(o)?;
+                address.write(o)
+            }
+            IpAddr::V6(address) => {
+                1u8.write(o)?;
+                address.write(o)
+            }
+        }
+    }
+}
+
+impl Readable for IpAddr {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        match u8::read(i)? {
+            0 => Ok(IpAddr::V4(Ipv4Addr::read(i)?)),
+            1 => Ok(IpAddr::V6(Ipv6Addr::read(i)?)),
+            _ => Err(PacketError::UnexpectedValue("0 or 1 for ip address tag"))
+        }
+    }
+}
+
+/// Socket addresses are encoded as the IP address (tag and bytes) followed
+/// by the port as a u16. The flow info and scope ID of IPv6 socket addresses
+/// aren't encoded so they are always 0 when read
+impl Writable for SocketAddr {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.ip().write(o)?;
+        self.port().write(o)
+    }
+}
+
+impl Readable for SocketAddr {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let ip = IpAddr::read(i)?;
+        Ok(SocketAddr::new(ip, u16::read(i)?))
+    }
+}
+
+/// Boxed values are encoded the same as the value they contain. This allows
+/// recursive structures such as `next: Option<Box<Node>>`
+impl<T: Writable> Writable for Box<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        (**self).write(o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        (**self).write_with(ctx, o)
+    }
+}
+
+impl<T: FixedSize> FixedSize for Box<T> {
+    const ENCODED_SIZE: usize = T::ENCODED_SIZE;
+}
+
+impl<T: Readable> Readable for Box<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        T::read(i).map(Box::new)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        T::read_with(ctx, i).map(Box::new)
+    }
+}
+
+/// Shared values are encoded the same as the value they contain. Writing
+/// requires a mutable value so the value is cloned if it is shared.
+///
+/// Note: Rc can't be used as packets must be Send + Sync
+impl<T: Writable + Clone> Writable for Arc<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        Arc::make_mut(self).write(o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        Arc::make_mut(self).write_with(ctx, o)
+    }
+}
+
+impl<T: FixedSize + Clone> FixedSize for Arc<T> {
+    const ENCODED_SIZE: usize = T::ENCODED_SIZE;
+}
+
+impl<T: Readable> Readable for Arc<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        T::read(i).map(Arc::new)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        T::read_with(ctx, i).map(Arc::new)
+    }
+}
+
+/// Borrowed or owned strings are encoded the same as String and are
+/// always owned when read
+impl Writable for Cow<'_, str> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        VarInt(self.len() as u32).write(o)?;
+        o.write_all(self.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Readable for Cow<'_, str> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        String::read(i).map(Cow::Owned)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        String::read_with(ctx, i).map(Cow::Owned)
+    }
+}
+
+/// Borrowed or owned bytes are encoded the same as Vec<u8> and are
+/// always owned when read
+impl Writable for Cow<'_, [u8]> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        VarInt(self.len() as u32).write(o)?;
+        o.write_all(self)?;
+        Ok(())
+    }
+}
+
+impl Readable for Cow<'_, [u8]> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        check_collection_length(ctx, length)?;
+        read_chunked(i, length).map(Cow::Owned)
+    }
+}
+
 /// ## Hashmaps
 /// Hashmaps are encoded as a VarInt for the total number of entries that are
 /// being encoded then each entry is encoded as the key then the value. Using
@@ -270,51 +1041,194 @@ impl<T: Readable> Readable for Option<T> {
 ///     Value: V
 /// }
 ///
+/// Note: HashMap is only available with the "std" feature
 ///
+#[cfg(feature = "std")]
 impl<K: Writable + Eq + Hash + Clone, V: Writable> Writable for HashMap<K, V> {
     fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
         VarInt(self.len() as u32).write(o)?;
         for (key, value) in self {
             let mut kc = key.clone();
-            K::write(&mut kc, o)?;
-            V::write(value, o)?;
+            K::write_with(&mut kc, ctx, o)?;
+            V::write_with(value, ctx, o)?;
         }
         Ok(())
     }
 }
 
+#[cfg(feature = "std")]
 impl<K: Readable + Eq + Hash + Clone, V: Readable> Readable for HashMap<K, V> {
     fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
-        let length = VarInt::read(i)?.0 as usize;
-        let mut out = HashMap::with_capacity(length);
-        for _ in 0..length {
-            let key = K::read(i)?;
-            let value = V::read(i)?;
-            out.insert(key, value);
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        check_collection_length(ctx, length)?;
+        // HashMaps don't have an order so canonical maps with more entries must be BTreeMaps
+        if ctx.is_canonical() && length > 1 {
+            return Err(PacketError::NonCanonical("a BTreeMap rather than a HashMap"));
+        }
+        ctx.enter(|ctx| {
+            let mut out = HashMap::new();
+            for _ in 0..length {
+                let key = K::read_with(ctx, i)?;
+                let value = V::read_with(ctx, i)?;
+                out.insert(key, value);
+            }
+            Ok(out)
+        })
+    }
+}
+
+/// BTreeMaps are encoded the same as HashMaps. Entries are written in key order
+impl<K: Writable + Ord + Clone, V: Writable> Writable for BTreeMap<K, V> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        VarInt(self.len() as u32).write(o)?;
+        for (key, value) in self {
+            let mut kc = key.clone();
+            K::write_with(&mut kc, ctx, o)?;
+            V::write_with(value, ctx, o)?;
         }
-        Ok(out)
+        Ok(())
+    }
+}
+
+impl<K: Readable + Ord + Clone, V: Readable> Readable for BTreeMap<K, V> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        check_collection_length(ctx, length)?;
+        ctx.enter(|ctx| {
+            let mut out = BTreeMap::new();
+            for _ in 0..length {
+                let key = K::read_with(ctx, i)?;
+                if ctx.is_canonical() && out.last_key_value().is_some_and(|(last, _)| key <= *last) {
+                    return Err(PacketError::NonCanonical("map keys in ascending order without duplicates"));
+                }
+                let value = V::read_with(ctx, i)?;
+                out.insert(key, value);
+            }
+            Ok(out)
+        })
+    }
+}
+
+/// ## Sets
+/// Sets are encoded as a VarInt for the number of values followed by each of
+/// the values. This is the same encoding as Vec so a set can be read as a Vec
+///
+/// Note: HashSet is only available with the "std" feature
+///
+macro_rules! impl_set {
+    ($($(#[$Attr:meta])* $set:ident<T: $($bound:path),*> where $canonical:expr, $expected:literal),*) => {
+        $(
+            $(#[$Attr])*
+            impl<T: Writable + Clone $(+ $bound)*> Writable for $set<T> {
+                fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+                    self.write_with(&mut WriteContext::default(), o)
+                }
+
+                fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+                    VarInt(self.len() as u32).write(o)?;
+                    for value in self.iter() {
+                        value.clone().write_with(ctx, o)?;
+                    }
+                    Ok(())
+                }
+            }
+
+            $(#[$Attr])*
+            impl<T: Readable $(+ $bound)*> Readable for $set<T> {
+                fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+                    Self::read_with(&mut ReadContext::default(), i)
+                }
+
+                fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+                    let length = VarInt::read_with(ctx, i)?.0 as usize;
+                    check_collection_length(ctx, length)?;
+                    ctx.enter(|ctx| {
+                        let mut out = $set::new();
+                        for _ in 0..length {
+                            let value = T::read_with(ctx, i)?;
+                            if ctx.is_canonical() && !($canonical)(&out, &value) {
+                                return Err(PacketError::NonCanonical($expected));
+                            }
+                            out.insert(value);
+                        }
+                        Ok(out)
+                    })
+                }
+            }
+        )*
+    };
+}
+
+// Canonical sets are in ascending order and HashSets (which don't have an order)
+// can't have more than one value
+impl_set!(
+    #[cfg(feature = "std")] HashSet<T: Eq, Hash> where |set: &HashSet<T>, _: &T| set.is_empty(), "a BTreeSet rather than a HashSet",
+    BTreeSet<T: Ord> where |set: &BTreeSet<T>, value: &T| set.last().is_none_or(|last| last < value), "set values in ascending order without duplicates"
+);
+
+/// VecDeques are encoded the same as Vec
+impl<T: Writable> Writable for VecDeque<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        VarInt(self.len() as u32).write(o)?;
+        for value in self.iter_mut() {
+            value.write_with(ctx, o)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Readable> Readable for VecDeque<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        read_n_with(ctx, i, length).map(VecDeque::from)
     }
 }
 
 /// Macro for automatically generating the RW trait implementations for
-/// the other primitive number types which all take in generic arguments
-/// for the byte order which in this case is Big Endian
+/// the primitive number types which are all encoded as Big Endian
 macro_rules! generate_rw {
     (
-        $($type:ident: ($read_fn:ident, $write_fn:ident))*
+        $($type:ident)*
     ) => {
         $(
             impl Writable for $type {
                 fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
-                    o.$write_fn::<byteorder::BigEndian>(*self)?;
+                    o.write_all(&self.to_be_bytes())?;
                     Ok(())
                 }
             }
 
+            impl FixedSize for $type {
+                const ENCODED_SIZE: usize = core::mem::size_of::<$type>();
+            }
+
             impl Readable for $type {
                 fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
-                    i.$read_fn::<byteorder::BigEndian>()
-                        .map_err(PacketError::from)
+                    Ok($type::from_be_bytes(read_bytes(i)?))
                 }
             }
         )*
@@ -322,14 +1236,7 @@ macro_rules! generate_rw {
 }
 
 generate_rw! {
-    u16: (read_u16, write_u16)
-    u32: (read_u32, write_u32)
-    u64: (read_u64, write_u64)
-
-    i16: (read_i16, write_i16)
-    i32: (read_i32, write_i32)
-    i64: (read_i64, write_i64)
-
-    f32: (read_f32, write_f32)
-    f64: (read_f64, write_f64)
-}
\ No newline at end of file
+    u8 u16 u32 u64
+    i8 i16 i32 i64
+    f32 f64
+}
@@ -0,0 +1,164 @@
+/// ## Ping Packet
+/// Implemented by packet groups which contain a heartbeat ping packet carrying a
+/// sequence number and a timestamp so they can be used with Heartbeat
+pub trait PingPacket: Sized {
+    /// Creates the ping packet with the provided sequence number and timestamp
+    fn ping(sequence: u32, timestamp: u64) -> Self;
+
+    /// The sequence number and timestamp if this is the ping packet
+    fn as_ping(&self) -> Option<(u32, u64)>;
+}
+
+/// ## Pong Packet
+/// Implemented by packet groups which contain the pong packet sent in response to
+/// a ping which echoes back the sequence number and timestamp of the ping
+pub trait PongPacket: Sized {
+    /// Creates the pong packet with the provided sequence number and timestamp
+    fn pong(sequence: u32, timestamp: u64) -> Self;
+
+    /// The sequence number and timestamp if this is the pong packet
+    fn as_pong(&self) -> Option<(u32, u64)>;
+}
+
+/// Creates the pong that should be sent in response to [packet] if it is a ping
+pub fn pong_for<P: PingPacket, R: PongPacket>(packet: &P) -> Option<R> {
+    packet.as_ping().map(|(sequence, timestamp)| R::pong(sequence, timestamp))
+}
+
+/// ## RTT Stats
+/// Statistics of the round trip times (RTT) measured for a connection which can be
+/// used for lag compensation and to show the quality of the connection. The smoothed
+/// RTT is a moving average of the samples (with a gain of 1/8 like TCP) and the
+/// jitter is the average difference between consecutive samples (with a gain of
+/// 1/16 like RTP). Times are milliseconds
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RttStats {
+    samples: u64,
+    latest: Option<u64>,
+    min: u64,
+    max: u64,
+    smoothed: f64,
+    jitter: f64,
+}
+
+impl RttStats {
+    /// Creates empty stats
+    pub fn new() -> Self {
+        RttStats::default()
+    }
+
+    /// Adds a measured [rtt]
+    pub fn record(&mut self, rtt: u64) {
+        match self.latest {
+            Some(latest) => {
+                let difference = rtt.abs_diff(latest) as f64;
+                self.smoothed += (rtt as f64 - self.smoothed) / 8.0;
+                self.jitter += (difference - self.jitter) / 16.0;
+                self.min = self.min.min(rtt);
+                self.max = self.max.max(rtt);
+            }
+            None => {
+                self.smoothed = rtt as f64;
+                self.min = rtt;
+                self.max = rtt;
+            }
+        }
+        self.latest = Some(rtt);
+        self.samples += 1;
+    }
+
+    /// The number of RTTs recorded
+    pub fn samples(&self) -> u64 { self.samples }
+
+    /// The most recent RTT
+    pub fn latest(&self) -> Option<u64> { self.latest }
+
+    /// The lowest RTT
+    pub fn min(&self) -> Option<u64> { self.latest.map(|_| self.min) }
+
+    /// The highest RTT
+    pub fn max(&self) -> Option<u64> { self.latest.map(|_| self.max) }
+
+    /// The moving average of the RTTs
+    pub fn smoothed(&self) -> Option<f64> { self.latest.map(|_| self.smoothed) }
+
+    /// The average variation between consecutive RTTs
+    pub fn jitter(&self) -> Option<f64> { self.latest.map(|_| self.jitter) }
+}
+
+/// ## Heartbeat
+/// Keeps track of the heartbeat for one side of a connection. Pings are created
+/// every interval and the pong responses are validated to track the round trip
+/// time (RTT) and its statistics. The connection is timed out if no valid pong is
+/// received within the timeout.
+///
+/// Times are milliseconds from any monotonic clock provided by the caller so this
+/// can be used without std (e.g. from the browser using performance.now())
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heartbeat {
+    interval: u64,
+    timeout: u64,
+    sequence: u32,
+    /// The sequence number and timestamp of the ping awaiting a pong
+    pending: Option<(u32, u64)>,
+    last_sent: Option<u64>,
+    last_pong: u64,
+    stats: RttStats,
+}
+
+impl Heartbeat {
+    /// Creates a new heartbeat which pings every [interval] milliseconds and
+    /// times out after [timeout] milliseconds without a pong starting at [now]
+    pub fn new(interval: u64, timeout: u64, now: u64) -> Self {
+        Heartbeat {
+            interval,
+            timeout,
+            sequence: 0,
+            pending: None,
+            last_sent: None,
+            last_pong: now,
+            stats: RttStats::new(),
+        }
+    }
+
+    /// Returns the ping packet to send if the interval has passed since the last
+    /// ping was sent. A new ping replaces any ping still awaiting a pong
+    pub fn poll<P: PingPacket>(&mut self, now: u64) -> Option<P> {
+        if let Some(last_sent) = self.last_sent {
+            if now.saturating_sub(last_sent) < self.interval {
+                return None;
+            }
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+        self.pending = Some((self.sequence, now));
+        self.last_sent = Some(now);
+        Some(P::ping(self.sequence, now))
+    }
+
+    /// Handles a received [packet] returning whether it was the pong for the
+    /// pending ping. Pongs that don't match the pending ping are ignored
+    pub fn receive<P: PongPacket>(&mut self, packet: &P, now: u64) -> bool {
+        match packet.as_pong() {
+            Some(pong) if self.pending == Some(pong) => {
+                self.pending = None;
+                self.last_pong = now;
+                self.stats.record(now.saturating_sub(pong.1));
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// The round trip time in milliseconds of the last ping if a pong has been
+    /// received
+    pub fn rtt(&self) -> Option<u64> { self.stats.latest() }
+
+    /// The statistics of the RTTs of all the pongs received
+    pub fn rtt_stats(&self) -> &RttStats { &self.stats }
+
+    /// Whether the timeout has passed since the last pong was received (or since
+    /// the heartbeat was created if there hasn't been one)
+    pub fn timed_out(&self, now: u64) -> bool {
+        now.saturating_sub(self.last_pong) > self.timeout
+    }
+}
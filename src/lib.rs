@@ -1,15 +1,178 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Re-exported so the code generated by the macros can reference alloc types
+// when used within no_std crates
+#[doc(hidden)]
+pub extern crate alloc;
+
 pub mod packets;
 pub mod io;
 pub mod error;
+#[cfg(not(feature = "std"))]
+mod core_io;
+pub mod transaction;
+pub mod roundtrip;
+pub mod ordered;
+pub mod metrics;
+pub mod narrow;
+pub mod codec;
+pub mod limits;
+pub mod bridge;
+pub mod seq;
+pub mod request;
+pub mod field;
+pub mod replication;
+pub mod proxy;
+pub mod checksum;
+pub mod keepalive;
+pub mod prediction;
+pub mod rpc;
+pub mod session;
+pub mod summary;
+pub mod tick;
+pub mod minimize;
+pub mod forensics;
+pub mod registry;
+pub mod replay;
+pub mod trace;
+pub mod delta;
+pub mod snapshot;
+pub mod logging;
+pub mod text;
+pub mod ratelimit;
+pub mod outbound;
+pub mod batch;
+pub mod string_enum;
+pub mod fragment;
+pub mod strings;
+pub mod tagged;
+pub mod version;
+pub mod canonical;
+pub mod validate;
+pub mod dictionary;
+#[cfg(feature = "extensions")]
+pub mod extension;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[cfg(feature = "codegen")]
+pub mod schema;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "codegen")]
+pub mod migration;
+#[cfg(feature = "devtools")]
+pub mod loadgen;
+#[cfg(feature = "devtools")]
+pub mod soak;
+#[cfg(feature = "unicode")]
+pub mod unicode;
 
 pub use io::*;
 pub use error::*;
+pub use transaction::*;
+pub use roundtrip::*;
+pub use ordered::*;
+pub use metrics::*;
+pub use narrow::*;
+pub use codec::{Codec, CodecRead, CodecWrite};
+pub use limits::{ReadLimits, ReadContext, WriteContext, ConnectionContext, DecodeDeadline, read_limited, read_limited_with};
+pub use bridge::*;
+pub use seq::*;
+pub use request::*;
+pub use field::{IntoField, FieldCodec};
+pub use replication::*;
+pub use proxy::*;
+pub use checksum::*;
+pub use keepalive::*;
+pub use prediction::*;
+pub use rpc::*;
+pub use session::*;
+pub use summary::*;
+pub use tick::*;
+pub use minimize::*;
+pub use forensics::*;
+pub use registry::*;
+pub use replay::*;
+pub use trace::*;
+pub use delta::*;
+pub use snapshot::*;
+pub use logging::*;
+pub use text::*;
+pub use ratelimit::*;
+pub use outbound::*;
+pub use batch::*;
+pub use string_enum::*;
+pub use fragment::*;
+pub use strings::*;
+pub use tagged::*;
+pub use version::*;
+pub use canonical::*;
+pub use validate::*;
+pub use dictionary::*;
+#[cfg(feature = "extensions")]
+pub use extension::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+#[cfg(feature = "chaos")]
+pub use chaos::*;
+#[cfg(feature = "codegen")]
+pub use schema::*;
+#[cfg(feature = "codegen")]
+pub use codegen::*;
+#[cfg(feature = "codegen")]
+pub use migration::*;
+#[cfg(feature = "devtools")]
+pub use loadgen::*;
+#[cfg(feature = "devtools")]
+pub use soak::*;
+#[cfg(feature = "unicode")]
+pub use unicode::*;
+
+// Re-exported so the code generated by the macros can reference it
+#[doc(hidden)]
+pub use pastey;
+#[cfg(feature = "arbitrary")]
+#[doc(hidden)]
+pub use arbitrary;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde;
+#[cfg(feature = "extensions")]
+#[doc(hidden)]
+pub use inventory;
+
+/// Glob imported next to the generated types so their serde derives can refer to
+/// serde under a name that doesn't depend on the name this crate is imported as
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_crate {
+    pub use serde as __wsbps_serde;
+}
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::io::{Cursor};
 
-    use crate::{Writable, Readable, packet_data, packets, VarInt};
+    use crate::{Writable, Readable, packet_data, packets, VarInt, Transaction, TransactionWriter, PacketError};
+
+    packets! {
+        #[wsbps(tests)]
+        PropertyPackets (<->) {
+            Login (0x01) {
+                name: String,
+                token: Option<Vec<u8>>,
+                version: VarInt
+            }
+            Logout (0x02) {}
+            Data (0x03) {
+                count: u8,
+                #[len_from(count)]
+                data: Vec<u8>
+            }
+        }
+    }
 
     #[test]
     fn it_works() {
@@ -53,17 +216,2692 @@ mod tests {
                     Err(e) => println!("{:?}",e),
                     Ok(p) => {
                         println!("{:?}",p);
-                        match p {
-                            BiPackets::TestA {b, a} => {
-                                print!("{:?} {:?}", b, a)
-                            }
-                            _ => {}
+                        if let BiPackets::TestA {b, a} = p {
+                            print!("{:?} {:?}", b, a)
                         }
                     }
                 };
             }
         };
     }
-}
 
+    #[test]
+    fn transaction_round_trip() {
+        use crate::{ReadContext, ReadLimits, TextOptions, WriteContext};
+
+        packets! {
+            TxPackets (<->) {
+                SetName (0x01) {
+                    name: String
+                }
+                SetAge (0x02) {
+                    age: u8
+                }
+            }
+        }
+
+        let mut tx = Transaction(vec![
+            TxPackets::SetName { name: String::from("Test") },
+            TxPackets::SetAge { age: 20 },
+        ]);
+        let mut o = Vec::new();
+        tx.write(&mut o).unwrap();
+        let read = Transaction::<TxPackets>::read(&mut Cursor::new(o)).unwrap();
+        assert_eq!(read, tx);
+
+        let mut o = Vec::new();
+        let mut writer = TransactionWriter::begin(&mut o).unwrap();
+        writer.push(&mut WriteContext::default(), &mut TxPackets::SetAge { age: 1 }).unwrap();
+        writer.abort().unwrap();
+        match Transaction::<TxPackets>::read(&mut Cursor::new(o)) {
+            Err(PacketError::TransactionAborted) => {}
+            other => panic!("expected aborted transaction got {:?}", other)
+        }
+
+        // Transactions are read with the context so the packets inside them are
+        // checked against the limits and text options of the connection
+        let mut o = Vec::new();
+        tx.write(&mut o).unwrap();
+        let limits = ReadLimits { max_collection_length: 1, ..ReadLimits::DEFAULT };
+        let mut ctx = ReadContext::default().with_limits(limits);
+        assert!(matches!(Transaction::<TxPackets>::read_with(&mut ctx, &mut o.as_slice()), Err(PacketError::CollectionTooLarge(2, 1))));
+        let mut ctx = ReadContext::default().with_text(TextOptions::new(2, 0));
+        let err = Transaction::<TxPackets>::read_with(&mut ctx, &mut o.as_slice()).unwrap_err();
+        assert!(matches!(err.root_cause(), PacketError::InvalidStringLength(4, 2)));
+        let mut ctx = ReadContext::new(1);
+        assert!(matches!(Transaction::<TxPackets>::read_with(&mut ctx, &mut o.as_slice()), Err(PacketError::DepthLimitExceeded(1))));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_round_trip() {
+        packet_data! {
+            enum Colour (<->) (u8) {
+                Red: 1,
+                Blue: 2
+            }
+
+            struct Item (<->) {
+                id: VarInt,
+                colour: Colour
+            }
+        }
+
+        packets! {
+            FuzzPackets (<->) {
+                Items (0x01) {
+                    items: Vec<Item>,
+                    owner: Option<String>
+                }
+                Empty (0x02) {}
+            }
+        }
+
+        let data: Vec<u8> = (0..2048u32).map(|v| (v * 31 % 251) as u8).collect();
+        for start in 0..64 {
+            crate::roundtrip_check::<FuzzPackets>(&data[start..]);
+        }
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn ordered_output() {
+        use std::collections::HashMap;
+        use crate::{to_ordered_debug, to_ordered_json};
+
+        packets! {
+            MapPackets (<->) {
+                Scores (0x01) {
+                    id: VarInt,
+                    scores: HashMap<String, u32>
+                }
+            }
+        }
+
+        let mut scores = HashMap::new();
+        for (index, name) in ["d", "b", "a", "c"].iter().enumerate() {
+            scores.insert(name.to_string(), index as u32);
+        }
+        let packet = MapPackets::Scores { id: VarInt(3), scores };
+        assert_eq!(
+            to_ordered_debug(&packet),
+            r#"Scores { id: VarInt(3), scores: {"a": 2, "b": 1, "c": 3, "d": 0} }"#
+        );
+        assert_eq!(
+            to_ordered_json(&packet),
+            r#"{"Scores":{"id":3,"scores":{"a":2,"b":1,"c":3,"d":0}}}"#
+        );
+    }
+
+    #[test]
+    fn metric_keys() {
+        let packet = PropertyPackets::Logout {};
+        assert_eq!(packet.metric_key(), "PropertyPackets.Logout");
+        assert_eq!(packet.metric_id(), crate::metric_id("PropertyPackets.Logout"));
+        assert_ne!(packet.metric_id(), PropertyPackets::Login {
+            name: String::new(),
+            token: None,
+            version: VarInt(0),
+        }.metric_id());
+        assert_eq!(PropertyPackets::METRIC_KEYS, &["PropertyPackets.Login", "PropertyPackets.Logout", "PropertyPackets.Data"]);
+        // Known FNV-1a test vector
+        assert_eq!(crate::metric_id("a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn fixed_count_sequences() {
+        let mut values = vec![VarInt(1), VarInt(300), VarInt(2)];
+        let mut o = Vec::new();
+        crate::write_all_slice(&mut values, &mut o).unwrap();
+        assert_eq!(o, vec![1, 172, 2, 2]);
+        let read: Vec<VarInt> = crate::read_n(&mut o.as_slice(), 3).unwrap();
+        assert_eq!(read, values);
+    }
+
+    #[test]
+    fn length_from_field() {
+        packet_data! {
+            struct Inventory (<->) {
+                count: u16,
+                #[len_from(count)]
+                items: Vec<u8>
+            }
+        }
+
+        packets! {
+            LengthPackets (<->) {
+                Chat (0x01) {
+                    length: u8,
+                    #[len_from(length)]
+                    message: String
+                }
+            }
+        }
+
+        let mut inventory = Inventory { count: 2, items: vec![4, 5] };
+        let mut o = Vec::new();
+        inventory.write(&mut o).unwrap();
+        assert_eq!(o, vec![0, 2, 4, 5]);
+        assert_eq!(Inventory::read(&mut o.as_slice()).unwrap(), inventory);
+
+        let mut chat = LengthPackets::Chat { length: 2, message: String::from("Hi") };
+        let mut o = Vec::new();
+        chat.write(&mut o).unwrap();
+        assert_eq!(o, vec![1, 2, b'H', b'i']);
+        assert_eq!(LengthPackets::read(&mut o.as_slice()).unwrap(), chat);
+
+        let mut mismatched = Inventory { count: 3, items: vec![1] };
+        match mismatched.write(&mut Vec::new()) {
+            Err(PacketError::LengthMismatch("items", 1, "count", 3)) => {}
+            other => panic!("expected length mismatch got {:?}", other)
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_bridge() {
+        fn assert_serde<T: serde::Serialize + serde::de::DeserializeOwned>() {}
+
+        packet_data! {
+            enum Mode (<->) (u8) {
+                Fast: 1
+            }
+
+            struct Settings (<->) {
+                mode: Mode,
+                level: VarInt
+            }
+        }
+
+        assert_serde::<Mode>();
+        assert_serde::<Settings>();
+        assert_serde::<PropertyPackets>();
+    }
+
+    #[test]
+    fn narrowing() {
+        use crate::Narrow;
+
+        let mut o = Vec::new();
+        Narrow::<VarInt, u16>::new(300).write(&mut o).unwrap();
+        assert_eq!(o, vec![172, 2]);
+        assert_eq!(*Narrow::<VarInt, u16>::read(&mut o.as_slice()).unwrap(), 300);
+
+        let o = 70000u32.to_be_bytes();
+        match Narrow::<u32, u16>::read(&mut &o[..]) {
+            Err(PacketError::NarrowingOverflow("u16")) => {}
+            other => panic!("expected narrowing overflow got {:?}", other)
+        }
+
+        // The wire value is read with the context so canonical reads check it
+        assert_eq!(*crate::read_canonical::<Narrow<VarInt, u16>, _>(&mut &[0x05][..]).unwrap(), 5);
+        assert!(matches!(crate::read_canonical::<Narrow<VarInt, u16>, _>(&mut &[0x85, 0x00][..]), Err(PacketError::NonCanonical(_))));
+        assert_eq!(*Narrow::<VarInt, u16>::read(&mut &[0x85, 0x00][..]).unwrap(), 5);
+    }
+
+    #[test]
+    fn codecs() {
+        use crate::Codec;
+
+        let mut packet = PropertyPackets::Login { name: "Jacob".to_string(), token: None, version: VarInt(1) };
+        let binary = Codec::Binary.encode(&mut packet).unwrap();
+        assert_eq!(binary[0], 0x01);
+        assert_eq!(Codec::Binary.decode::<PropertyPackets>(&binary).unwrap(), packet);
+
+        #[cfg(feature = "json")]
+        {
+            let json = Codec::Json.encode(&mut packet).unwrap();
+            assert_eq!(json, br#"{"Login":{"name":"Jacob","token":null,"version":1}}"#);
+            assert_eq!(Codec::Json.decode::<PropertyPackets>(&json).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn var_number_encoding() {
+        use crate::VarLong;
+
+        for (value, bytes) in [(0u32, &[0u8][..]), (300, &[172, 2]), (16384, &[128, 128, 1]), (u32::MAX, &[255, 255, 255, 255, 15])] {
+            let mut o = Vec::new();
+            VarInt(value).write(&mut o).unwrap();
+            assert_eq!(o, bytes);
+            assert_eq!(VarInt(value).size(), bytes.len());
+            assert_eq!(VarInt::decode(&o).unwrap(), (VarInt(value), bytes.len()));
+            assert_eq!(VarInt::read(&mut o.as_slice()).unwrap(), VarInt(value));
+        }
+
+        let mut o = Vec::new();
+        VarLong(u64::MAX).write(&mut o).unwrap();
+        assert_eq!(o.len(), VarLong::MAX_SIZE);
+        assert_eq!(VarLong::decode(&o).unwrap(), (VarLong(u64::MAX), 10));
+
+        assert!(matches!(VarInt::decode(&[128, 128, 128, 128, 128, 1]), Err(PacketError::VarOverflow("int", 5))));
+        assert!(matches!(VarInt::decode(&[128, 128]), Err(PacketError::IO(_))));
+    }
+
+    #[test]
+    fn newtypes() {
+        crate::wire_newtype! {
+            Millimeters(u32);
+            PlayerId(VarInt)
+        }
+
+        packet_data! {
+            struct Move (<->) {
+                player: PlayerId,
+                distance: Millimeters
+            }
+        }
+
+        let mut o = Vec::new();
+        Move { player: PlayerId(VarInt(300)), distance: Millimeters::from(5) }.write(&mut o).unwrap();
+        assert_eq!(o, vec![172, 2, 0, 0, 0, 5]);
+
+        let value = Move::read(&mut o.as_slice()).unwrap();
+        assert_eq!(value.player.to_string(), "300");
+        assert_eq!(u32::from(value.distance), 5);
+    }
+
+    #[cfg(feature = "extensions")]
+    #[test]
+    fn extensions() {
+        use crate::{Extensible, ExtensionPacket, ReadContext, TextOptions};
+
+        packet_data! {
+            struct Plugin (<->) {
+                value: u8
+            }
+        }
+
+        crate::register_extension!(PropertyPackets, 0x80, Plugin);
+
+        let mut o = Vec::new();
+        Extensible::<PropertyPackets>::Extension(ExtensionPacket::new(0x80, Plugin { value: 5 })).write(&mut o).unwrap();
+        PropertyPackets::Logout {}.write(&mut o).unwrap();
+
+        let mut i = o.as_slice();
+        match Extensible::<PropertyPackets>::read(&mut i).unwrap() {
+            Extensible::Extension(packet) => assert_eq!(packet.downcast_ref::<Plugin>(), Some(&Plugin { value: 5 })),
+            other => panic!("expected extension packet got {:?}", other)
+        }
+        assert!(matches!(Extensible::<PropertyPackets>::read(&mut i).unwrap(), Extensible::Core(PropertyPackets::Logout {})));
+        assert!(matches!(Extensible::<PropertyPackets>::read(&mut &[0x7Fu8][..]), Err(PacketError::UnknownPacket(0x7F))));
+
+        // Extensions are read with the context of the connection
+        packet_data! {
+            struct PluginName (<->) {
+                name: String
+            }
+        }
+
+        crate::register_extension!(PropertyPackets, 0x82, PluginName);
+
+        let mut o = Vec::new();
+        Extensible::<PropertyPackets>::Extension(ExtensionPacket::new(0x82, PluginName { name: "long".to_string() })).write(&mut o).unwrap();
+        let mut ctx = ReadContext::default().with_text(TextOptions::new(2, 0));
+        let err = Extensible::<PropertyPackets>::read_with(&mut ctx, &mut o.as_slice()).unwrap_err();
+        assert!(matches!(err.root_cause(), PacketError::InvalidStringLength(4, 2)));
+    }
+
+    #[test]
+    fn read_limits() {
+        use crate::{read_limited, ReadLimits};
+
+        assert_eq!(ReadLimits::global(), ReadLimits::DEFAULT);
+
+        // Lengths are rejected before any memory is allocated for them
+        let mut o = Vec::new();
+        VarInt(u32::MAX).write(&mut o).unwrap();
+        assert!(matches!(String::read(&mut o.as_slice()), Err(PacketError::InvalidStringLength(..))));
+        assert!(matches!(Vec::<u8>::read(&mut o.as_slice()), Err(PacketError::CollectionTooLarge(..))));
+
+        let mut o = Vec::new();
+        PropertyPackets::Login { name: "Jacob".to_string(), token: Some(vec![1, 2]), version: VarInt(1) }.write(&mut o).unwrap();
+        assert!(read_limited::<PropertyPackets, _>(&mut o.as_slice()).is_ok());
+    }
+
+    #[test]
+    fn depth_limit() {
+        use crate::ReadContext;
+
+        let o = [1u8, 1, 1, 5];
+        let mut ctx = ReadContext::new(3);
+        assert_eq!(Vec::<Vec<Vec<u8>>>::read_with(&mut ctx, &mut &o[..]).unwrap(), vec![vec![vec![5]]]);
+        assert_eq!(ctx.depth(), 0);
+
+        let mut ctx = ReadContext::new(2);
+        assert!(matches!(Vec::<Vec<Vec<u8>>>::read_with(&mut ctx, &mut &o[..]), Err(PacketError::DepthLimitExceeded(2))));
+    }
+
+    #[test]
+    fn decode_deadline() {
+        use core::sync::atomic::{AtomicU64, Ordering};
+        use std::time::Duration;
+        use crate::{DecodeDeadline, ReadContext};
+
+        // Every read of the clock moves the time forward by one
+        static TIME: AtomicU64 = AtomicU64::new(0);
+        fn clock() -> u64 { TIME.fetch_add(1, Ordering::Relaxed) }
+
+        let o = [2u8, 1, 5, 1, 6];
+        let mut ctx = ReadContext::default().with_deadline(DecodeDeadline::Clock { clock, at: 10 });
+        assert_eq!(Vec::<Vec<u8>>::read_with(&mut ctx, &mut &o[..]).unwrap(), vec![vec![5], vec![6]]);
+
+        let mut ctx = ReadContext::default().with_deadline(DecodeDeadline::Clock { clock, at: TIME.load(Ordering::Relaxed) + 2 });
+        assert!(matches!(Vec::<Vec<u8>>::read_with(&mut ctx, &mut &o[..]), Err(PacketError::DeadlineExceeded)));
+
+        let mut ctx = ReadContext::default().with_deadline(DecodeDeadline::after(Duration::from_secs(60)));
+        assert!(Vec::<Vec<u8>>::read_with(&mut ctx, &mut &o[..]).is_ok());
+        let mut ctx = ReadContext::default().with_deadline(DecodeDeadline::after(Duration::ZERO));
+        assert!(matches!(Vec::<Vec<u8>>::read_with(&mut ctx, &mut &o[..]), Err(PacketError::DeadlineExceeded)));
+    }
+
+    #[test]
+    fn smart_pointers() {
+        use std::borrow::Cow;
+        use std::sync::Arc;
+
+        packet_data! {
+            struct Node (<->) {
+                value: u8,
+                next: Option<Box<Node>>
+            }
+        }
+
+        let mut node = Node { value: 1, next: Some(Box::new(Node { value: 2, next: None })) };
+        let mut o = Vec::new();
+        node.write(&mut o).unwrap();
+        assert_eq!(o, vec![1, 1, 2, 0]);
+        assert_eq!(Node::read(&mut o.as_slice()).unwrap(), node);
+
+        let mut o = Vec::new();
+        let mut shared = Arc::new(VarInt(300));
+        let _other = shared.clone();
+        shared.write(&mut o).unwrap();
+        Cow::Borrowed("hi").write(&mut o).unwrap();
+        Cow::Borrowed(&[1u8, 2][..]).write(&mut o).unwrap();
+        assert_eq!(o, vec![172, 2, 2, b'h', b'i', 2, 1, 2]);
+
+        let mut i = o.as_slice();
+        assert_eq!(*Arc::<VarInt>::read(&mut i).unwrap(), VarInt(300));
+        assert_eq!(Cow::<str>::read(&mut i).unwrap(), "hi");
+        assert_eq!(Cow::<[u8]>::read(&mut i).unwrap(), &[1u8, 2][..]);
+    }
+
+    #[test]
+    fn collections() {
+        use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+        let mut o = Vec::new();
+        BTreeMap::from([(2u8, 20u8), (1, 10)]).write(&mut o).unwrap();
+        BTreeSet::from([3u8, 1]).write(&mut o).unwrap();
+        HashSet::from([7u8]).write(&mut o).unwrap();
+        VecDeque::from([5u8, 6]).write(&mut o).unwrap();
+        assert_eq!(o, vec![2, 1, 10, 2, 20, 2, 1, 3, 1, 7, 2, 5, 6]);
+
+        let mut i = o.as_slice();
+        assert_eq!(BTreeMap::<u8, u8>::read(&mut i).unwrap(), BTreeMap::from([(1, 10), (2, 20)]));
+        assert_eq!(BTreeSet::<u8>::read(&mut i).unwrap(), BTreeSet::from([1, 3]));
+        assert_eq!(HashSet::<u8>::read(&mut i).unwrap(), HashSet::from([7]));
+        assert_eq!(VecDeque::<u8>::read(&mut i).unwrap(), VecDeque::from([5, 6]));
+    }
+
+    #[test]
+    fn bridge() {
+        use crate::Bridge;
+
+        packets! {
+            LegacyPackets (<->) {
+                Login (0x05) {
+                    name: String
+                }
+                Ping (0x06) {}
+            }
+        }
+
+        let bridge = Bridge::new(
+            |packet| match packet {
+                LegacyPackets::Login { name } => Some(PropertyPackets::Login { name, token: None, version: VarInt(0) }),
+                LegacyPackets::Ping {} => None,
+            },
+            |packet| match packet {
+                PropertyPackets::Login { name, .. } => Some(LegacyPackets::Login { name }),
+                _ => None,
+            },
+        );
+
+        let mut i = Vec::new();
+        LegacyPackets::Login { name: "Jacob".to_string() }.write(&mut i).unwrap();
+        LegacyPackets::Ping {}.write(&mut i).unwrap();
+
+        let mut i = i.as_slice();
+        let mut o = Vec::new();
+        assert!(bridge.forward(&mut i, &mut o).unwrap());
+        assert!(!bridge.forward(&mut i, &mut o).unwrap());
+
+        let mut back = Vec::new();
+        assert!(bridge.backward(&mut o.as_slice(), &mut back).unwrap());
+        assert_eq!(LegacyPackets::read(&mut back.as_slice()).unwrap(), LegacyPackets::Login { name: "Jacob".to_string() });
+    }
+
+    #[test]
+    fn seq_writer() {
+        use crate::SeqWriter;
+
+        packet_data! {
+            struct Snapshot (<->) {
+                chunks: SeqWriter<u16>
+            }
+        }
+
+        let mut snapshot = Snapshot { chunks: SeqWriter::new(3, (1..=3).map(|i| i * 100)) };
+        let mut o = Vec::new();
+        snapshot.write(&mut o).unwrap();
+        assert_eq!(o, vec![3, 0, 100, 0, 200, 1, 44]);
+        assert_eq!(Snapshot::read(&mut o.as_slice()).unwrap(), snapshot);
+
+        let mut wrong = SeqWriter::new(2, [1u8, 2, 3]);
+        assert!(matches!(wrong.write(&mut Vec::new()), Err(PacketError::LengthMismatch("SeqWriter", 3, "len", 2))));
+    }
+
+    #[test]
+    fn packet_sets() {
+        crate::packet_set! {
+            Common {
+                Ping (0x01) {}
+                Chat (0x02) {
+                    message: String
+                }
+            }
+        }
+
+        packets! {
+            GamePackets (<->) {
+                include Common + 0x10;
+                Move (0x01) {
+                    x: u8
+                }
+            }
+        }
+
+        assert_eq!(GamePackets::Ping {}.id(), VarInt(0x11));
+        assert_eq!(GamePackets::Move { x: 1 }.id(), VarInt(0x01));
+
+        let mut o = Vec::new();
+        GamePackets::Chat { message: "hi".to_string() }.write(&mut o).unwrap();
+        assert_eq!(o[0], 0x12);
+        assert_eq!(GamePackets::read(&mut o.as_slice()).unwrap(), GamePackets::Chat { message: "hi".to_string() });
+    }
+
+    #[test]
+    fn requests() {
+        use crate::{requests, respond};
+
+        packet_data! {
+            struct GetUser (<->) {
+                id: u32
+            }
+
+            struct GetUserResult (<->) {
+                name: String
+            }
+        }
+
+        requests! {
+            GetUser => GetUserResult;
+        }
 
+        let mut i = Vec::new();
+        GetUser { id: 5 }.write(&mut i).unwrap();
+        let mut o = Vec::new();
+        respond(&mut i.as_slice(), &mut o, |request: GetUser| GetUserResult { name: request.id.to_string() }).unwrap();
+        assert_eq!(GetUserResult::read(&mut o.as_slice()).unwrap(), GetUserResult { name: "5".to_string() });
+    }
+
+    #[test]
+    fn constructors() {
+        use crate::VarLong;
+
+        packets! {
+            ConstructorPackets (<->) {
+                TestA (0x01) {
+                    b: VarInt,
+                    a: Vec<u8>
+                }
+                TestB (0x02) {
+                    name: String,
+                    count: u8,
+                    time: VarLong,
+                    token: Option<u32>
+                }
+            }
+        }
+
+        assert_eq!(
+            ConstructorPackets::test_a(5, vec![1, 2]),
+            ConstructorPackets::TestA { b: VarInt(5), a: vec![1, 2] }
+        );
+        assert_eq!(
+            ConstructorPackets::test_b("Jacob", 2, 10, 3),
+            ConstructorPackets::TestB { name: "Jacob".to_string(), count: 2, time: VarLong(10), token: Some(3) }
+        );
+        assert_eq!(
+            ConstructorPackets::test_b(String::from("Jacob"), 2, VarLong(10), None),
+            ConstructorPackets::TestB { name: "Jacob".to_string(), count: 2, time: VarLong(10), token: None }
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        packets! {
+            AccessorPackets (<->) {
+                TestA (0x01) {
+                    b: VarInt,
+                    a: Vec<u8>
+                }
+                TestB (0x02) {
+                    name: String
+                }
+                TestC (0x03) {}
+            }
+        }
+
+        let packet = AccessorPackets::test_a(5, vec![1, 2]);
+        assert_eq!(packet.as_test_a(), Some((&VarInt(5), &vec![1, 2])));
+        assert_eq!(packet.as_test_b(), None);
+        assert_eq!(packet.as_test_c(), None);
+        assert_eq!(packet.clone().try_into_test_a(), Ok((VarInt(5), vec![1, 2])));
+        assert_eq!(packet.clone().try_into_test_b(), Err(packet));
+
+        let packet = AccessorPackets::test_b("Jacob");
+        assert_eq!(packet.try_into_test_b(), Ok("Jacob".to_string()));
+        assert_eq!(AccessorPackets::test_c().as_test_c(), Some(()));
+    }
+
+    packets! {
+        #[wsbps(structs, tests = struct_roundtrip_tests)]
+        StructPackets (<->) {
+            TestA (0x01) {
+                b: VarInt,
+                a: Vec<u8>
+            }
+            TestB (0x02) {
+                count: u8,
+                #[len_from(count)]
+                name: String
+            }
+        }
+    }
+
+    #[test]
+    fn packet_structs() {
+        fn size(packet: &TestA) -> usize { packet.a.len() }
+
+        let packet = StructPackets::from(TestA { b: VarInt(5), a: vec![1, 2] });
+        assert_eq!(packet, StructPackets::test_a(5, vec![1, 2]));
+        assert_eq!(packet.id(), VarInt(0x01));
+        assert_eq!(packet.as_test_a().map(size), Some(2));
+        assert_eq!(packet.as_test_b(), None);
+
+        let mut o = Vec::new();
+        packet.clone().write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0x05, 0x02, 0x01, 0x02]);
+        assert_eq!(StructPackets::read(&mut o.as_slice()).unwrap(), packet);
+
+        let mut o = Vec::new();
+        StructPackets::test_b(4, "test").write(&mut o).unwrap();
+        let packet = StructPackets::read(&mut o.as_slice()).unwrap();
+        assert_eq!(packet.try_into_test_b(), Ok(TestB { count: 4, name: "test".to_string() }));
+
+        #[cfg(feature = "ordered")]
+        {
+            use crate::{to_ordered_debug, to_ordered_json};
+
+            let packet = StructPackets::test_a(5, vec![1]);
+            assert_eq!(to_ordered_debug(&packet), "TestA(TestA { b: VarInt(5), a: [1] })");
+            assert_eq!(to_ordered_json(&packet), r#"{"TestA":{"b":5,"a":[1]}}"#);
+        }
+    }
+
+    #[test]
+    fn fixed_size() {
+        use crate::FixedSize;
+
+        packet_data! {
+            enum Facing (<->) (u8) {
+                North: 0,
+                South: 1
+            }
+
+            struct Position (<->) {
+                x: f32,
+                y: f32,
+                facing: Facing,
+                moving: bool
+            }
+
+            // Not fixed size so doesn't implement FixedSize
+            struct Named (<->) {
+                name: String
+            }
+        }
+
+        assert_eq!(Position::ENCODED_SIZE, 10);
+
+        let position = Position { x: 1.0, y: 2.0, facing: Facing::South, moving: true };
+        let bytes: [u8; Position::ENCODED_SIZE] = position.write_to_array();
+        let mut o = Vec::new();
+        position.clone().write(&mut o).unwrap();
+        assert_eq!(bytes.as_slice(), o.as_slice());
+        assert_eq!(Position::read(&mut bytes.as_slice()).unwrap(), position);
+    }
+
+    #[test]
+    fn replication() {
+        use crate::{EntityStore, EntityUpdate, Replicator};
+
+        packet_data! {
+            struct Player (<->) {
+                name: String,
+                x: f32
+            }
+        }
+
+        let mut replicator = Replicator::new();
+        let mut store = EntityStore::new();
+        let mut world = vec![
+            (1, Player { name: "A".to_string(), x: 0.0 }),
+            (2, Player { name: "B".to_string(), x: 5.0 }),
+        ];
+
+        let mut sync = |world: &Vec<(u32, Player)>| {
+            let mut updates = replicator.diff(world.iter().map(|(id, player)| (*id, player)));
+            let mut o = Vec::new();
+            updates.write(&mut o).unwrap();
+            let received = Vec::<EntityUpdate<Player>>::read(&mut o.as_slice()).unwrap();
+            assert_eq!(received, updates);
+            for update in received {
+                store.apply(update);
+            }
+            assert!(store.iter().eq(world.iter().map(|(id, player)| (*id, player))));
+            updates
+        };
+
+        assert_eq!(sync(&world).len(), 2);
+        assert!(sync(&world).is_empty());
+
+        world[1].1.x = 6.0;
+        world.remove(0);
+        world.push((3, Player { name: "C".to_string(), x: 1.0 }));
+        let updates = sync(&world);
+        assert_eq!(updates, vec![
+            EntityUpdate::Update { id: 2, entity: Player { name: "B".to_string(), x: 6.0 } },
+            EntityUpdate::Create { id: 3, entity: Player { name: "C".to_string(), x: 1.0 } },
+            EntityUpdate::Destroy { id: 1 },
+        ]);
+
+        // Only players near the viewer are replicated
+        let mut nearby = Replicator::new();
+        let near = |_: u32, player: &Player| player.x < 5.0;
+        let updates = nearby.diff_interest(world.iter().map(|(id, player)| (*id, player)), near);
+        assert_eq!(updates, vec![
+            EntityUpdate::Create { id: 3, entity: Player { name: "C".to_string(), x: 1.0 } },
+        ]);
+
+        world[0].1.x = 2.0;
+        world[1].1.x = 8.0;
+        let updates = nearby.diff_interest(world.iter().map(|(id, player)| (*id, player)), near);
+        assert_eq!(updates, vec![
+            EntityUpdate::Create { id: 2, entity: Player { name: "B".to_string(), x: 2.0 } },
+            EntityUpdate::Destroy { id: 3 },
+        ]);
+    }
+
+    #[test]
+    fn peek_id() {
+        use std::io::{BufReader, Read};
+
+        let mut o = Vec::new();
+        PropertyPackets::Data { count: 1, data: vec![7] }.write(&mut o).unwrap();
+
+        let mut cursor = Cursor::new(o.clone());
+        assert_eq!(PropertyPackets::peek_id(&mut cursor).unwrap(), 0x03);
+        assert_eq!(cursor.position(), 0);
+
+        let mut reader = BufReader::new(o.as_slice());
+        assert_eq!(PropertyPackets::peek_id_buffered(&mut reader).unwrap(), 0x03);
+        let mut forwarded = Vec::new();
+        reader.read_to_end(&mut forwarded).unwrap();
+        assert_eq!(forwarded, o);
+
+        assert!(PropertyPackets::peek_id(&mut Cursor::new(vec![0x80])).is_err());
+    }
+
+    #[test]
+    fn proxy() {
+        use crate::{copy_packet, copy_packet_sized};
+
+        let mut o = Vec::new();
+        PropertyPackets::Data { count: 2, data: vec![7, 8] }.write(&mut o).unwrap();
+
+        let mut forwarded = Vec::new();
+        assert_eq!(copy_packet(&mut o.as_slice(), &mut forwarded).unwrap(), 0x03);
+        assert_eq!(forwarded, o);
+
+        // Non-minimal IDs are copied verbatim
+        let packet = [0x83, 0x00, 0x01, 0x09];
+        let mut forwarded = Vec::new();
+        let mut i = &packet[..];
+        assert_eq!(copy_packet_sized(&mut i, &mut forwarded, 3).unwrap(), 0x03);
+        assert_eq!(forwarded, [0x83, 0x00, 0x01]);
+        assert_eq!(i, [0x09]);
+
+        assert!(copy_packet_sized(&mut &packet[..], &mut Vec::new(), 1).is_err());
+    }
+
+    #[test]
+    fn raw_packets() {
+        use crate::{OutboundQueue, OverflowPolicy, QueueClass, RawPacket};
+
+        let mut o = Vec::new();
+        PropertyPackets::Data { count: 2, data: vec![7, 8] }.write(&mut o).unwrap();
+        let mut raw = RawPacket::from_frame(&o).unwrap();
+        assert_eq!(raw, RawPacket::new(0x03, vec![0x02, 0x07, 0x08]));
+
+        // Raw packets go through the same queues as typed packets
+        let mut queue = OutboundQueue::new(&[QueueClass::new(4, 64, OverflowPolicy::DropNewest)], |_: &RawPacket| 0);
+        assert!(queue.push(&mut raw).unwrap());
+        let frame = queue.pop().unwrap();
+        assert_eq!(frame, o);
+        assert_eq!(
+            PropertyPackets::read(&mut frame.as_slice()).unwrap(),
+            PropertyPackets::Data { count: 2, data: vec![7, 8] }
+        );
+        assert!(RawPacket::from_frame(&[0x80]).is_err());
+    }
+
+    #[test]
+    fn checksums() {
+        use crate::{crc32, Checksummed};
+
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+
+        let packet = PropertyPackets::Data { count: 2, data: vec![7, 8] };
+        let mut o = Vec::new();
+        Checksummed(packet.clone()).write(&mut o).unwrap();
+        assert_eq!(o.len(), 4 + 4);
+        assert_eq!(Checksummed::<PropertyPackets>::read(&mut o.as_slice()).unwrap().into_inner(), packet);
+
+        o[3] = 9;
+        assert!(matches!(
+            Checksummed::<PropertyPackets>::read(&mut o.as_slice()),
+            Err(PacketError::ChecksumMismatch(..))
+        ));
+    }
+
+    #[test]
+    fn heartbeat() {
+        use crate::{pong_for, Heartbeat, PingPacket, PongPacket};
+
+        packets! {
+            HeartbeatPackets (<->) {
+                Ping (0x01) {
+                    sequence: u32,
+                    timestamp: u64
+                }
+                Pong (0x02) {
+                    sequence: u32,
+                    timestamp: u64
+                }
+            }
+        }
+
+        impl PingPacket for HeartbeatPackets {
+            fn ping(sequence: u32, timestamp: u64) -> Self { HeartbeatPackets::ping(sequence, timestamp) }
+
+            fn as_ping(&self) -> Option<(u32, u64)> { self.as_ping().map(|(s, t)| (*s, *t)) }
+        }
+
+        impl PongPacket for HeartbeatPackets {
+            fn pong(sequence: u32, timestamp: u64) -> Self { HeartbeatPackets::pong(sequence, timestamp) }
+
+            fn as_pong(&self) -> Option<(u32, u64)> { self.as_pong().map(|(s, t)| (*s, *t)) }
+        }
+
+        let mut heartbeat = Heartbeat::new(1000, 5000, 0);
+        let ping: HeartbeatPackets = heartbeat.poll(0).unwrap();
+        assert_eq!(ping, HeartbeatPackets::Ping { sequence: 1, timestamp: 0 });
+        assert!(heartbeat.poll::<HeartbeatPackets>(500).is_none());
+
+        // Pongs for other pings are ignored
+        assert!(!heartbeat.receive(&HeartbeatPackets::Pong { sequence: 2, timestamp: 0 }, 40));
+        let pong: HeartbeatPackets = pong_for(&ping).unwrap();
+        assert!(heartbeat.receive(&pong, 40));
+        assert_eq!(heartbeat.rtt(), Some(40));
+        assert_eq!(heartbeat.rtt_stats().smoothed(), Some(40.0));
+
+        assert!(heartbeat.poll::<HeartbeatPackets>(1000).is_some());
+        assert!(!heartbeat.timed_out(5040));
+        assert!(heartbeat.timed_out(5041));
+    }
+
+    #[test]
+    fn rtt_stats() {
+        use crate::RttStats;
+
+        let mut stats = RttStats::new();
+        assert_eq!((stats.latest(), stats.smoothed(), stats.jitter()), (None, None, None));
+        stats.record(100);
+        assert_eq!((stats.smoothed(), stats.jitter()), (Some(100.0), Some(0.0)));
+        stats.record(180);
+        assert_eq!((stats.smoothed(), stats.jitter()), (Some(110.0), Some(5.0)));
+        stats.record(20);
+        assert_eq!((stats.min(), stats.max(), stats.latest()), (Some(20), Some(180), Some(20)));
+        assert_eq!(stats.smoothed(), Some(98.75));
+        assert_eq!(stats.jitter(), Some(14.6875));
+        assert_eq!(stats.samples(), 3);
+    }
+
+    #[test]
+    fn prediction() {
+        use crate::{PredictionBuffer, Ticked};
+
+        let mut buffer = PredictionBuffer::new();
+        let mut predicted = 0i32;
+        for input in [1, 2, 3] {
+            let mut packet = buffer.push(input);
+            predicted += input;
+
+            let mut o = Vec::new();
+            packet.write(&mut o).unwrap();
+            assert_eq!(Ticked::<i32>::read(&mut o.as_slice()).unwrap(), packet);
+        }
+        assert_eq!(predicted, 6);
+
+        // The server processed the first input but disagreed with its result
+        let state = Ticked::new(0, 10);
+        let predicted = buffer.reconcile(state.tick, state.value, |state, input| state + input);
+        assert_eq!(predicted, 15);
+        assert_eq!(buffer.pending().map(|input| input.tick).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(buffer.next_tick(), 3);
+
+        buffer.acknowledge(2);
+        assert_eq!(buffer.pending().count(), 0);
+    }
+
+    #[test]
+    fn rpc() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use crate::{Correlated, RequestTracker};
+
+        let responses = Rc::new(RefCell::new(Vec::new()));
+        let mut tracker = RequestTracker::<String>::new(1000);
+
+        let handle = responses.clone();
+        let mut request = tracker.request(5u32, 0, move |response| handle.borrow_mut().push(response));
+        let handle = responses.clone();
+        tracker.request(6u32, 0, move |response| handle.borrow_mut().push(response));
+        assert_eq!(tracker.len(), 2);
+
+        let mut o = Vec::new();
+        request.write(&mut o).unwrap();
+        assert_eq!(o, [0, 0, 0, 5, 0]);
+        let request = Correlated::<u32>::read(&mut o.as_slice()).unwrap();
+
+        let mut response = request.reply(String::from("Five"));
+        let mut o = Vec::new();
+        response.write(&mut o).unwrap();
+        let response = Correlated::<String>::read(&mut o.as_slice()).unwrap();
+        assert!(tracker.receive(response.clone()));
+        assert!(!tracker.receive(response));
+
+        assert_eq!(tracker.poll(999), 0);
+        assert_eq!(tracker.poll(1000), 1);
+        assert!(tracker.is_empty());
+        assert_eq!(*responses.borrow(), [Some(String::from("Five")), None]);
+    }
+
+    #[test]
+    fn session() {
+        use crate::{protocol_states, Session};
+
+        protocol_states! {
+            TestState => TestStatePacket {
+                Login => PropertyPackets,
+                Play => StructPackets,
+            }
+        }
+
+        let mut session = Session::new(TestState::Login);
+        let mut o = Vec::new();
+        PropertyPackets::Logout {}.write(&mut o).unwrap();
+        assert_eq!(session.read(&mut o.as_slice()).unwrap(), TestStatePacket::Login(PropertyPackets::Logout {}));
+
+        // Data (0x03) is only part of the Login state
+        session.transition(TestState::Play);
+        assert_eq!(session.state(), TestState::Play);
+        assert!(matches!(session.read(&mut &[0x03, 0x00][..]), Err(PacketError::OutOfStatePacket(0x03, "Play"))));
+    }
+
+    #[cfg(feature = "summary")]
+    #[test]
+    fn summary() {
+        use crate::to_summary;
+
+        let packet = PropertyPackets::Login {
+            name: "a".repeat(40),
+            token: Some(vec![1, 2, 3]),
+            version: VarInt(42),
+        };
+        assert_eq!(
+            packet.to_string(),
+            format!("Login(id=0x01, name={:?}... (40 chars), token=Some([3 bytes]), version=42)", "a".repeat(32))
+        );
+        assert_eq!(PropertyPackets::Logout {}.to_string(), "Logout(id=0x02)");
+        assert_eq!(StructPackets::test_a(5, vec![7; 100]).to_string(), "TestA(id=0x01, b=5, a=[100 bytes])");
+        assert_eq!(to_summary(&vec![1u32, 2]), "[1, 2]");
+        assert_eq!(to_summary(&vec![0u32; 20]), "[20 items]");
+    }
+
+    #[test]
+    fn default_fields() {
+        packet_data! {
+            struct DefaultStruct (<->) {
+                a: u8,
+                b: u16 = 7
+            }
+        }
+
+        packets! {
+            DefaultPackets (<->) {
+                Join (0x01) {
+                    name: String,
+                    version: VarInt = 3,
+                    flags: Option<u8> = Some(1)
+                }
+            }
+        }
+
+        // Packets from older clients which end before the fields use the defaults
+        let packet = DefaultPackets::read(&mut &[0x01, 0x01, b'a'][..]).unwrap();
+        assert_eq!(packet, DefaultPackets::join("a", 3, 1));
+        let packet = DefaultPackets::read(&mut &[0x01, 0x01, b'a', 0x05][..]).unwrap();
+        assert_eq!(packet, DefaultPackets::join("a", 5, 1));
+        assert_eq!(DefaultStruct::read(&mut &[0x02][..]).unwrap(), DefaultStruct { a: 2, b: 7 });
+
+        // Truncated fields are still errors
+        assert!(DefaultStruct::read(&mut &[0x02, 0x00][..]).is_err());
+
+        // Fields with defaults are always written
+        let mut packet = DefaultPackets::join("a", 3, None);
+        let mut o = Vec::new();
+        packet.write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0x01, b'a', 0x03, 0x00]);
+        assert_eq!(DefaultPackets::read(&mut o.as_slice()).unwrap(), packet);
+    }
+
+    #[test]
+    fn tick_loop() {
+        use crate::{OverflowPolicy, TickLimits, TickLoop};
+
+        let limits = TickLimits { max_packets_per_tick: 2, max_queued: 3, overflow: OverflowPolicy::DropOldest };
+        let mut tick_loop = TickLoop::with_limits(50, limits, 0);
+        for packet in 0..4 {
+            assert!(tick_loop.push(packet));
+        }
+        assert_eq!(tick_loop.dropped(), 1);
+
+        let mut events = Vec::new();
+        let handle = |events: &mut Vec<i32>, packet| events.push(packet);
+        let tick = |events: &mut Vec<i32>, tick| events.push(100 + tick as i32);
+        assert_eq!(tick_loop.poll(0, &mut events, handle, tick), 1);
+        assert_eq!(tick_loop.poll(49, &mut events, handle, tick), 0);
+        assert_eq!(tick_loop.poll(100, &mut events, handle, tick), 2);
+        assert_eq!(events, [1, 2, 100, 3, 101, 102]);
+        assert_eq!(tick_loop.tick(), 3);
+        assert_eq!(tick_loop.next_tick_at(), 150);
+        assert_eq!(tick_loop.queued(), 0);
+    }
+
+    packets! {
+        #[wsbps(tests = conditional_roundtrip_tests)]
+        ConditionalPackets (<->) {
+            Attack (0x01) {
+                has_target: bool,
+                #[when(has_target)]
+                target: VarInt,
+                count: u8,
+                #[when(has_target)]
+                #[len_from(count)]
+                damage: Vec<u16>
+            }
+        }
+    }
+
+    #[test]
+    fn conditional_fields() {
+        // Conditional fields are skipped when their condition is false
+        let mut packet = ConditionalPackets::attack(false, 0, 0, vec![]);
+        let mut o = Vec::new();
+        packet.write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0x00, 0x00]);
+        assert_eq!(ConditionalPackets::read(&mut o.as_slice()).unwrap(), packet);
+
+        let mut packet = ConditionalPackets::attack(true, 5, 2, vec![1, 2]);
+        let mut o = Vec::new();
+        packet.write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0x01, 0x05, 0x02, 0x00, 0x01, 0x00, 0x02]);
+        assert_eq!(ConditionalPackets::read(&mut o.as_slice()).unwrap(), packet);
+    }
+
+    #[test]
+    fn field_codecs() {
+        use crate::{FieldCodec, Read, ReadContext, ReadResult, Write, WriteResult};
+
+        /// Numbers written as their decimal digits prefixed with the number of digits
+        struct Decimal;
+
+        impl FieldCodec<u32> for Decimal {
+            fn read_field<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<u32> {
+                String::read_with(ctx, i)?
+                    .parse()
+                    .map_err(|_| PacketError::UnexpectedValue("decimal number"))
+            }
+
+            fn write_field<B: Write>(value: &u32, o: &mut B) -> WriteResult {
+                value.to_string().write(o)
+            }
+        }
+
+        packets! {
+            CodecPackets (<->) {
+                Score (0x01) {
+                    #[with(Decimal)]
+                    score: u32,
+                    has_bonus: bool,
+                    #[when(has_bonus)]
+                    #[with(Decimal)]
+                    bonus: u32
+                }
+            }
+        }
+
+        let mut packet = CodecPackets::score(125, true, 7);
+        let mut o = Vec::new();
+        packet.write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0x03, b'1', b'2', b'5', 0x01, 0x01, b'7']);
+        assert_eq!(CodecPackets::read(&mut o.as_slice()).unwrap(), packet);
+    }
+
+    #[test]
+    fn minimize() {
+        use crate::{decode_fails, minimize_bytes, minimize_frames};
+
+        let valid = |mut packet: PropertyPackets| {
+            let mut o = Vec::new();
+            packet.write(&mut o).unwrap();
+            o
+        };
+        let frames = vec![
+            valid(PropertyPackets::Logout {}),
+            valid(PropertyPackets::login("Jacob", None, 1)),
+            vec![0x7F, 0x01, 0x02],
+            valid(PropertyPackets::Logout {}),
+        ];
+        let minimized = minimize_frames(frames, decode_fails::<PropertyPackets>);
+        assert_eq!(minimized, [vec![0x7F, 0x01, 0x02]]);
+
+        // Only the unknown packet ID is needed to reproduce the failure
+        let minimized = minimize_bytes(minimized[0].clone(), |bytes| {
+            matches!(PropertyPackets::read(&mut &bytes[..]), Err(PacketError::UnknownPacket(_)))
+        });
+        assert_eq!(minimized, [0x7F]);
+
+        // Captures that don't fail are returned unchanged
+        let frames = vec![valid(PropertyPackets::Logout {})];
+        assert_eq!(minimize_frames(frames.clone(), decode_fails::<PropertyPackets>), frames);
+    }
+
+    #[test]
+    fn decode_report() {
+        use crate::decode_with_report;
+
+        let data = [0x7F, 0x01, 0x02, 0x03];
+        let report = decode_with_report::<PropertyPackets>(&data, 2).unwrap_err();
+        assert!(matches!(report.error, PacketError::UnknownPacket(0x7F)));
+        assert_eq!(report.offset, 1);
+        assert_eq!(report.id, Some(0x7F));
+        assert_eq!(report.window_start, 0);
+        assert_eq!(report.window, [0x7F, 0x01, 0x02, 0x03]);
+        assert_eq!(
+            report.to_string(),
+            "decode failed: packet with unknown id of 127 received\n\
+             packet id: 0x7f\n\
+             offset: 1 of 4 bytes\n\
+             00000000  7f [01] 02  03 "
+        );
+
+        let mut o = Vec::new();
+        PropertyPackets::Logout {}.write(&mut o).unwrap();
+        assert!(decode_with_report::<PropertyPackets>(&o, 2).is_ok());
+    }
+
+    #[test]
+    fn packet_registry() {
+        use crate::{PacketDispatcher, PacketRegistry};
+
+        packet_data! {
+            struct Chat (<->) {
+                message: String
+            }
+
+            struct Move (<->) {
+                x: i32,
+                y: i32
+            }
+        }
+
+        let mut registry = PacketRegistry::new();
+        assert!(registry.register::<Chat>(0x10));
+        assert!(registry.register::<Move>(0x11));
+        assert_eq!(registry.id_of::<Move>(), Some(0x11));
+
+        let mut o = Vec::new();
+        registry.write(&mut Chat { message: "Hi".to_string() }, &mut o).unwrap();
+        assert_eq!(o, [0x10, 0x02, b'H', b'i']);
+        let packet = registry.read(&mut o.as_slice()).unwrap();
+        assert_eq!(packet.downcast_ref::<Chat>(), Some(&Chat { message: "Hi".to_string() }));
+
+        let mut moved = Vec::new();
+        let mut dispatcher = PacketDispatcher::new();
+        dispatcher.on(|moved: &mut Vec<(i32, i32)>, packet: Move| moved.push((packet.x, packet.y)));
+        let mut o = Vec::new();
+        registry.write(&mut Move { x: 1, y: -1 }, &mut o).unwrap();
+        dispatcher.dispatch(&mut moved, registry.read(&mut o.as_slice()).unwrap()).unwrap();
+        assert_eq!(moved, [(1, -1)]);
+        assert!(dispatcher.dispatch(&mut moved, packet).is_err());
+
+        // Registering the type again moves it to the new ID
+        assert!(registry.register::<Move>(0x12));
+        assert!(!registry.contains(0x11));
+        assert!(matches!(registry.read(&mut [0x11u8, 0, 0, 0, 0].as_slice()), Err(PacketError::UnknownPacket(0x11))));
+        assert!(registry.unregister(0x10));
+        assert!(matches!(
+            registry.write(&mut Chat { message: String::new() }, &mut Vec::new()),
+            Err(PacketError::UnregisteredPacket(_))
+        ));
+    }
+
+    #[cfg(feature = "chaos")]
+    #[test]
+    fn chaos() {
+        use crate::{Chaos, ChaosAction, ChaosConfig};
+
+        let config = ChaosConfig { drop_rate: 0.25, disconnect_rate: 0.01, delay_rate: 0.5, max_delay: 100 };
+        let run = |seed| {
+            let mut chaos = Chaos::new(seed, config);
+            (0..1000).map(|_| (chaos.outbound(), chaos.handler_delay())).collect::<Vec<_>>()
+        };
+        // The same seed always injects the same faults
+        let faults = run(7);
+        assert_eq!(faults, run(7));
+        assert_ne!(faults, run(8));
+
+        let dropped = faults.iter().filter(|(action, _)| *action == ChaosAction::Drop).count();
+        assert!((150..350).contains(&dropped));
+        assert!(faults.iter().all(|(_, delay)| *delay <= 100));
+        assert!(faults.iter().any(|(_, delay)| *delay > 0));
+
+        let mut chaos = Chaos::new(7, ChaosConfig::NONE);
+        assert!((0..1000).all(|_| chaos.outbound() == ChaosAction::Send && chaos.handler_delay() == 0));
+    }
+
+    #[cfg(feature = "codegen")]
+    #[test]
+    fn codegen_schema() {
+        use std::collections::BTreeMap;
+        use crate::{js_client, kotlin_client, markdown_spec, Definition, DefinitionKind, FieldSchema, PacketGroupSchema, TypeSchema};
+
+        packet_data! {
+            enum Team (<->) (u8) {
+                Red: 1,
+                Blue: 2
+            }
+
+            struct Player (<->) {
+                name: String,
+                team: Team,
+                scores: BTreeMap<String, u64>
+            }
+        }
+
+        packets! {
+            GamePackets (<->) {
+                Join (0x01) {
+                    player: Player,
+                    flags: Option<Vec<u8>>
+                }
+                Chunk (0x02) {
+                    length: u16,
+                    #[len_from(length)]
+                    data: Vec<u8>,
+                    has_owner: bool,
+                    #[when(has_owner)]
+                    owner: String,
+                    seed: u64 = 7
+                }
+            }
+        }
+
+        let schema = GamePackets::group_schema();
+        assert_eq!(schema.name, "GamePackets");
+        assert_eq!(schema.packets[1].id, 0x02);
+        assert_eq!(schema.packets[1].fields[1], FieldSchema {
+            length_from: Some("length"),
+            ..FieldSchema::new("data", TypeSchema::List(Box::new(TypeSchema::U8)))
+        });
+        assert_eq!(schema.packets[1].fields[3].when, Some("has_owner"));
+        assert_eq!(schema.packets[1].fields[4].default, Some("7"));
+        assert_eq!(schema.definitions[1], Definition {
+            name: "Team",
+            kind: DefinitionKind::Enum { repr: TypeSchema::U8, variants: vec![("Red", "1"), ("Blue", "2")] },
+        });
+
+        let js = js_client(&[schema]);
+        assert!(js.contains("export function encodeGamePacketsJoin(packet) {"));
+        assert!(js.contains("export function decodeGamePackets(bytes) {"));
+        assert!(js.contains("    packet.owner = packet.has_owner ? r.string(r.varInt()) : \"\";"));
+        assert!(js.contains("    packet.seed = r.atEnd() ? 7n : r.u64();"));
+
+        let kotlin = kotlin_client("com.example", &[GamePackets::group_schema()]);
+        assert!(kotlin.contains("package com.example"));
+        assert!(kotlin.contains("sealed interface GamePackets {"));
+        assert!(kotlin.contains("    data class Chunk(\n        val length: UShort,\n        val data: ByteArray,"));
+        assert!(kotlin.contains("            val owner = if (has_owner) r.string(r.varInt().toInt()) else \"\""));
+        assert!(kotlin.contains("            val seed = if (r.atEnd()) 7uL else r.u64()"));
+
+        let spec = markdown_spec(&[GamePackets::group_schema()]);
+        assert!(spec.contains("| 0x02 | [Chunk](#gamepackets-chunk-0x02) |"));
+        assert!(spec.contains("| 0 | length | u16 | 2 |  |\n| 2 | data | List&lt;u8&gt; | Variable | Length is `length` without a prefix |"));
+        assert!(spec.contains("| - | seed | u64 | 0 or 8 | `7` when the packet ends before this field |"));
+        assert!(spec.contains("Encoded as u8\n\n| Variant | Value |\n| --- | --- |\n| Red | `1` |"));
+
+        // Wrappers are implemented by hand but still bring the definitions they wrap
+        use crate::{Schema, Ticked};
+        assert_eq!(Ticked::<Player>::type_schema(), TypeSchema::Custom("Ticked"));
+        let mut definitions = Vec::new();
+        Ticked::<Player>::definitions(&mut definitions);
+        assert!(definitions.iter().any(|definition| definition.name == "Player"));
+    }
+
+    #[cfg(feature = "codegen")]
+    #[test]
+    fn schema_migration() {
+        use crate::{js_shim, kotlin_shim, FieldSource, PacketError, PacketGroupSchema, SchemaMigration};
+
+        mod v1 {
+            crate::packet_data! {
+                struct Style (<->) {
+                    bold: bool,
+                    size: u8
+                }
+            }
+
+            crate::packets! {
+                ChatPackets (<->) {
+                    Join (0x01) {
+                        name: String,
+                        color: u8
+                    }
+                    Message (0x02) {
+                        style: Style,
+                        length: u8,
+                        #[len_from(length)]
+                        text: String
+                    }
+                    Typing (0x03) {}
+                    Leave (0x04) {}
+                }
+            }
+        }
+
+        mod v2 {
+            use crate::VarInt;
+
+            crate::packet_data! {
+                struct Style (<->) {
+                    bold: bool,
+                    size: u8
+                }
+
+                struct Mood (<->) {
+                    happy: bool
+                }
+            }
+
+            crate::packets! {
+                ChatPackets (<->) {
+                    Join (0x01) {
+                        name: String,
+                        color: u16,
+                        avatar: Option<String>,
+                        priority: u32 = 3
+                    }
+                    Message (0x05) {
+                        channel: VarInt,
+                        style: Style,
+                        length: u8,
+                        #[len_from(length)]
+                        text: String
+                    }
+                    Typing (0x03) {
+                        mood: Mood
+                    }
+                }
+            }
+        }
+
+        let migration = SchemaMigration::new(&v1::ChatPackets::group_schema(), &v2::ChatPackets::group_schema());
+        assert_eq!(migration.unsupported(), ["Typing"]);
+        assert_eq!(migration.removed[0].name, "Leave");
+        let sources: Vec<FieldSource> = migration.packets[1].fields.iter().map(|field| field.source).collect();
+        assert_eq!(sources, [FieldSource::Default, FieldSource::Old, FieldSource::Old, FieldSource::Old]);
+
+        let migrate = |packet: v1::ChatPackets| {
+            let mut o = Vec::new();
+            packet.clone().write(&mut o).unwrap();
+            migration.migrate(&o).map(|frame| frame.map(|frame| v2::ChatPackets::read(&mut frame.as_slice()).unwrap()))
+        };
+        assert_eq!(
+            migrate(v1::ChatPackets::Join { name: "Jacob".to_string(), color: 4 }).unwrap(),
+            Some(v2::ChatPackets::Join { name: "Jacob".to_string(), color: 0, avatar: None, priority: 3 })
+        );
+        assert_eq!(
+            migrate(v1::ChatPackets::Message { style: v1::Style { bold: true, size: 12 }, length: 2, text: "hi".to_string() }).unwrap(),
+            Some(v2::ChatPackets::Message { channel: VarInt(0), style: v2::Style { bold: true, size: 12 }, length: 2, text: "hi".to_string() })
+        );
+        assert_eq!(migrate(v1::ChatPackets::Leave {}).unwrap(), None);
+        assert!(matches!(migrate(v1::ChatPackets::Typing {}), Err(PacketError::UnsupportedMigration("Typing"))));
+        assert!(matches!(migration.migrate(&[0x09]), Err(PacketError::UnknownPacket(0x09))));
+
+        let migrations = [migration];
+        let js = js_shim(&migrations);
+        assert!(js.contains("export function migrateChatPackets(bytes) {"));
+        assert!(js.contains("            const packet = { name: old.name, color: 0, avatar: null, priority: 3 };"));
+        assert!(js.contains("            w.varInt(0x05);\n            w.varInt(packet.channel);\n            writeStyle(w, packet.style);"));
+        assert!(js.contains("        case 0x03: throw new Error(\"ChatPackets Typing can't be migrated between the schema versions\");"));
+        assert!(js.contains("        case 0x04: return null;"));
+
+        let kotlin = kotlin_shim("com.example.migrate", &migrations);
+        assert!(kotlin.contains("object ChatPacketsMigration {\n    data class Style("));
+        assert!(kotlin.contains("    private fun migrateMessage(r: PacketReader, w: PacketWriter) {"));
+        assert!(kotlin.contains("        val oldText = r.string(oldLength.toInt())"));
+        assert!(kotlin.contains("        val channel: UInt = 0u\n        val style = oldStyle"));
+        assert!(kotlin.contains("        0x04L -> return null"));
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn load_gen() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+        use crate::{LoadGen, MemoryTransport};
+
+        let logouts = Arc::new(AtomicUsize::new(0));
+        let report = LoadGen::new(4, 200.0, Duration::from_millis(100))
+            .packet(3, || PropertyPackets::login("Jacob", None, 1))
+            .packet(1, || PropertyPackets::Logout {})
+            .run(|_| {
+                let logouts = logouts.clone();
+                Ok(MemoryTransport(move |packet: &[u8]| {
+                    if let PropertyPackets::Logout {} = PropertyPackets::read(&mut &packet[..]).unwrap() {
+                        logouts.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(())
+                }))
+            })
+            .unwrap();
+
+        // Each client sends about 20 packets over the 100ms
+        assert_eq!(report.errors, 0);
+        assert!((40..=120).contains(&report.handled()), "handled {}", report.handled());
+        let logouts = logouts.load(Ordering::Relaxed);
+        assert!(logouts > 0 && logouts < report.handled() / 2);
+        assert!(report.percentile(50.0).unwrap() <= report.percentile(99.0).unwrap());
+        assert!(report.throughput() > 0.0);
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn soak_monitor() {
+        use crate::SoakMonitor;
+
+        let mut monitor = SoakMonitor::with_max_samples(8);
+        for minute in 0..60u64 {
+            let now = minute * 60_000;
+            // Sessions go up and down while the queue grows by 2 every minute
+            monitor.sample(now, "sessions", 10 + (minute % 3) as usize);
+            monitor.sample(now, "queue", 2 * minute as usize);
+        }
+
+        // Every other sample is discarded when a series is full
+        assert!(monitor.samples("queue").len() <= 8);
+        assert_eq!(monitor.samples("queue")[0], (0, 0));
+        assert_eq!(monitor.samples("queue").last(), Some(&(59 * 60_000, 118)));
+
+        let report = monitor.report();
+        let queue = report.trend("queue").unwrap();
+        assert_eq!((queue.first, queue.last, queue.min, queue.max), (0, 118, 0, 118));
+        assert!((queue.growth_per_hour - 120.0).abs() < 0.001);
+        assert!(report.trend("sessions").unwrap().growth_per_hour.abs() < 5.0);
+
+        let growing: Vec<_> = report.growing(10.0).map(|trend| trend.name).collect();
+        assert_eq!(growing, vec!["queue"]);
+        assert!(report.to_string().contains("queue"));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn packet_metrics() {
+        use crate::PacketMetrics;
+
+        let mut metrics = PacketMetrics::new();
+        metrics.register(PropertyPackets::METRIC_KEYS);
+        metrics.connection_opened();
+        metrics.connection_opened();
+        metrics.connection_closed();
+        let packet = PropertyPackets::Logout {};
+        metrics.record_received(packet.metric_key(), 3);
+        metrics.record_received(packet.metric_key(), 3);
+        metrics.record_sent(packet.metric_key(), 3);
+        metrics.record_decode_error();
+
+        assert_eq!(metrics.packet("PropertyPackets.Logout").received_bytes, 6);
+        let text = metrics.metrics_text();
+        assert!(text.contains("# TYPE wsbps_packets_received_total counter\n"));
+        assert!(text.contains("wsbps_packets_received_total{packet=\"PropertyPackets.Logout\"} 2\n"));
+        assert!(text.contains("wsbps_packets_received_total{packet=\"PropertyPackets.Login\"} 0\n"));
+        assert!(text.contains("wsbps_packet_bytes_sent_total{packet=\"PropertyPackets.Logout\"} 3\n"));
+        assert!(text.contains("# TYPE wsbps_connections_open gauge\nwsbps_connections_open 1\n"));
+        assert!(text.contains("wsbps_connections_total 2\n"));
+        assert!(text.contains("wsbps_decode_errors_total 1\n"));
+    }
+
+    #[test]
+    fn replay() {
+        use crate::{Player, Recorder};
+
+        let mut recorder = Recorder::new(Vec::new()).unwrap();
+        recorder.record(1000, &mut PropertyPackets::login("Jacob", None, 1)).unwrap();
+        recorder.record(1040, &mut PropertyPackets::Logout {}).unwrap();
+        let file = recorder.into_inner();
+        assert_eq!(&file[..5], b"WSBR\x01");
+
+        let mut player = Player::new(file.as_slice()).unwrap();
+        let (time, packet) = player.next_packet::<PropertyPackets>().unwrap().unwrap();
+        assert_eq!(time, 1000);
+        assert!(matches!(packet, PropertyPackets::Login { name, .. } if name == "Jacob"));
+        assert!(matches!(player.next_packet::<PropertyPackets>().unwrap(), Some((1040, PropertyPackets::Logout {}))));
+        assert!(player.next_packet::<PropertyPackets>().unwrap().is_none());
+
+        // The player is a source of the recorded packets like the original stream
+        let mut player = Player::new(file.as_slice()).unwrap().realtime(true);
+        let start = std::time::Instant::now();
+        assert!(matches!(PropertyPackets::read(&mut player).unwrap(), PropertyPackets::Login { .. }));
+        assert!(matches!(PropertyPackets::read(&mut player).unwrap(), PropertyPackets::Logout {}));
+        assert!(start.elapsed() >= std::time::Duration::from_millis(40));
+        assert!(PropertyPackets::read(&mut player).is_err());
+
+        // A record cut off within its time is the end of the file but one cut off within
+        // its packet is an error
+        assert_eq!(Player::new(&file[..file.len() - 5]).unwrap().frames().unwrap().len(), 1);
+        assert!(Player::new(&file[..file.len() - 1]).unwrap().frames().is_err());
+        assert!(Player::new(&b"WSBR\x02"[..]).is_err());
+    }
+
+    #[test]
+    fn trace_context() {
+        use crate::{TraceContext, Traced};
+
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TraceContext::from_traceparent(header).unwrap();
+        assert_eq!(context.span_id, [0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7]);
+        assert!(context.is_sampled());
+        assert_eq!(context.traceparent(), header);
+        assert!(TraceContext::from_traceparent("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::from_traceparent("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+        assert!(TraceContext::from_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none());
+
+        let mut o = Vec::new();
+        Traced::new(Some(context), PropertyPackets::Logout {}).write(&mut o).unwrap();
+        assert_eq!(o.len(), 1 + 25 + 1);
+        let traced = Traced::<PropertyPackets>::read(&mut o.as_slice()).unwrap();
+        assert_eq!(traced.context, Some(context));
+        assert!(matches!(traced.value, PropertyPackets::Logout {}));
+
+        // Untraced packets only cost a single byte
+        let mut o = Vec::new();
+        Traced::untraced(PropertyPackets::Logout {}).write(&mut o).unwrap();
+        assert_eq!(o, vec![0, 0x02]);
+    }
+
+    #[cfg(feature = "delta")]
+    #[test]
+    fn delta_snapshots() {
+        use crate::{DeltaDecoder, DeltaEncoder, DeltaReadable, Diffable};
+
+        packet_data! {
+            struct PlayerState (<->) {
+                x: i32,
+                y: i32,
+                name: String,
+                has_target: bool,
+                #[when(has_target)]
+                target: VarInt,
+                count: u8,
+                #[len_from(count)]
+                items: Vec<u8>
+            }
+        }
+
+        let base = PlayerState {
+            x: 10, y: 20, name: "Jacob".to_string(), has_target: false, target: VarInt(0), count: 2, items: vec![1, 2],
+        };
+        let mut moved = PlayerState { y: 21, items: vec![3, 4], ..base.clone() };
+        let mut o = Vec::new();
+        moved.write_delta(&base, &mut o).unwrap();
+        // The mask marks y and items which are the only fields written
+        assert_eq!(o, vec![0b0100_0010, 0, 0, 0, 21, 3, 4]);
+        assert_eq!(PlayerState::read_delta(&base, &mut o.as_slice()).unwrap(), moved);
+
+        let mut encoder = DeltaEncoder::new();
+        let mut decoder = DeltaDecoder::<PlayerState>::new();
+        let snapshots = [
+            base.clone(),
+            moved.clone(),
+            PlayerState { has_target: true, target: VarInt(7), ..moved.clone() },
+            PlayerState { count: 0, items: vec![], ..moved },
+        ];
+        for mut snapshot in snapshots {
+            let mut o = Vec::new();
+            encoder.encode(&mut snapshot, &mut o).unwrap();
+            assert_eq!(decoder.decode(&mut o.as_slice()).unwrap(), snapshot);
+        }
+
+        // Deltas can't be decoded without the snapshot they were made from
+        let mut o = Vec::new();
+        encoder.encode(&mut base.clone(), &mut o).unwrap();
+        assert!(DeltaDecoder::<PlayerState>::new().decode(&mut o.as_slice()).is_err());
+    }
+
+    #[test]
+    fn snapshot_buffer() {
+        use crate::{Interpolate, SnapshotBuffer};
+
+        packet_data! {
+            struct Position (<->) {
+                x: f32,
+                y: f32
+            }
+        }
+
+        impl Interpolate for Position {
+            fn interpolate(&self, to: &Self, t: f64) -> Self {
+                Position { x: self.x.interpolate(&to.x, t), y: self.y.interpolate(&to.y, t) }
+            }
+        }
+
+        let mut buffer = SnapshotBuffer::with_capacity(3);
+        assert!(buffer.interpolate(0).is_none());
+        assert!(buffer.push(100, Position { x: 0.0, y: 10.0 }));
+        assert!(buffer.push(200, Position { x: 10.0, y: 10.0 }));
+        // Stale snapshots are dropped
+        assert!(!buffer.push(150, Position { x: 5.0, y: 0.0 }));
+        assert!(!buffer.push(200, Position { x: 5.0, y: 0.0 }));
+
+        let mut o = Vec::new();
+        Position { x: 30.0, y: 0.0 }.write(&mut o).unwrap();
+        assert!(buffer.read(300, &mut o.as_slice()).unwrap());
+        assert_eq!(buffer.latest().map(|(time, _)| time), Some(300));
+
+        assert_eq!(buffer.interpolate(150), Some(Position { x: 5.0, y: 10.0 }));
+        assert_eq!(buffer.interpolate(250), Some(Position { x: 20.0, y: 5.0 }));
+        assert_eq!(buffer.interpolate(50), Some(Position { x: 0.0, y: 10.0 }));
+        assert_eq!(buffer.interpolate(400), Some(Position { x: 30.0, y: 0.0 }));
+
+        // The oldest snapshot is dropped when the buffer is full
+        buffer.push(400, Position { x: 40.0, y: 0.0 });
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.interpolate(150), Some(Position { x: 10.0, y: 10.0 }));
+
+        buffer.discard_before(350);
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.interpolate(350), Some(Position { x: 35.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn log_fields() {
+        use crate::{log_fields, LogFields};
+
+        packet_data! {
+            struct Room (<->) {
+                #[log]
+                room_id: u32,
+                name: String
+            }
+        }
+
+        packets! {
+            AuditPackets (<->) {
+                Join (0x01) {
+                    #[log]
+                    user_id: u64,
+                    room: Room,
+                    count: u8,
+                    #[len_from(count)]
+                    #[log]
+                    tags: Vec<u8>,
+                    #[log]
+                    admin: bool = false
+                }
+                Leave (0x02) {}
+            }
+        }
+
+        let mut packet = AuditPackets::Join { user_id: 7, room: Room { room_id: 3, name: "Lobby".to_string() }, count: 1, tags: vec![9], admin: true };
+        assert_eq!(log_fields(&packet), vec![("user_id", "7".to_string()), ("tags", "[9]".to_string()), ("admin", "true".to_string())]);
+        assert_eq!(log_fields(&Room { room_id: 3, name: "Lobby".to_string() }), vec![("room_id", "3".to_string())]);
+        assert!(log_fields(&AuditPackets::Leave {}).is_empty());
+
+        let mut names = Vec::new();
+        packet.visit_log_fields(&mut |name, _| names.push(name));
+        assert_eq!(names, vec!["user_id", "tags", "admin"]);
+
+        // The attribute doesn't change the encoding
+        let mut o = Vec::new();
+        packet.write(&mut o).unwrap();
+        assert_eq!(o, vec![0x01, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 3, 5, b'L', b'o', b'b', b'b', b'y', 1, 9, 1]);
+        assert_eq!(AuditPackets::read(&mut o.as_slice()).unwrap(), packet);
+    }
+
+    #[test]
+    fn text_options() {
+        use crate::{protocol_states, PacketError, ReadContext, Session, TextOptions};
+
+        let client = TextOptions::new(256, TextOptions::NO_CONTROL);
+        let server = TextOptions::new(16, TextOptions::TRIMMED | 0x80);
+        let mut o = Vec::new();
+        server.clone().write(&mut o).unwrap();
+        let negotiated = client.negotiate(&TextOptions::read(&mut o.as_slice()).unwrap());
+        assert_eq!(negotiated, TextOptions::new(16, TextOptions::NO_CONTROL | TextOptions::TRIMMED));
+
+        let read = |value: &str| {
+            let mut o = Vec::new();
+            value.to_string().write(&mut o).unwrap();
+            String::read_with(&mut ReadContext::default().with_text(negotiated), &mut o.as_slice())
+        };
+        assert_eq!(read("Hello\tWorld").unwrap(), "Hello\tWorld");
+        assert!(matches!(read(" Hello"), Err(PacketError::TextRejected("surrounding whitespace"))));
+        assert!(matches!(read("Hello\u{7}"), Err(PacketError::TextRejected("control character"))));
+        assert!(matches!(read("Hello World Hello World"), Err(PacketError::InvalidStringLength(23, 16))));
+        assert!(TextOptions::new(16, TextOptions::ASCII_ONLY).check("Héllo").is_err());
+        assert!(TextOptions::new(16, TextOptions::NO_NEWLINES).check("a\nb").is_err());
+
+        packets! {
+            ChatPackets (<-) {
+                Chat (0x01) { messages: Vec<String> }
+            }
+        }
+
+        protocol_states! {
+            ChatState => ChatPacket {
+                Chat => ChatPackets,
+            }
+        }
+
+        // The options of the session apply to the strings of every packet
+        let mut session = Session::new(ChatState::Chat);
+        let packet = [0x01, 0x01, 0x03, b'a', b'\n', b'b'];
+        assert!(session.read(&mut &packet[..]).is_ok());
+        session.set_text_options(TextOptions::new(64, TextOptions::NO_NEWLINES));
+        assert!(matches!(session.read(&mut &packet[..]).unwrap_err().root_cause(), PacketError::TextRejected("new line")));
+    }
+
+    #[test]
+    fn rate_limiter() {
+        use crate::{PacketError, RateLimit, RateLimiter};
+
+        let mut limiter = RateLimiter::new()
+            .limit(0x01, RateLimit::UNLIMITED.packets(2))
+            .default_limit(RateLimit::UNLIMITED.bytes(10));
+
+        // Login is limited to 2 packets per second
+        let mut login = Vec::new();
+        PropertyPackets::login("Jacob", None, 1).write(&mut login).unwrap();
+        assert!(limiter.read::<PropertyPackets>(0, &login).is_ok());
+        assert!(limiter.read::<PropertyPackets>(0, &login).is_ok());
+        assert!(matches!(limiter.read::<PropertyPackets>(0, &login), Err(PacketError::RateLimited(0x01))));
+        // Half a second refills one packet
+        assert!(limiter.read::<PropertyPackets>(500, &login).is_ok());
+        assert!(limiter.check(500, 0x01, login.len()).is_err());
+
+        // Other packets are limited to 10 bytes per second each
+        assert!(limiter.check(500, 0x02, 8).is_ok());
+        assert!(matches!(limiter.check(500, 0x02, 8), Err(PacketError::RateLimited(0x02))));
+        assert!(limiter.check(500, 0x03, 8).is_ok());
+
+        // The connection is limited to 3 packets per second combined
+        let mut limiter = RateLimiter::new().total_limit(RateLimit::UNLIMITED.packets(3));
+        for id in 0..3 {
+            assert!(limiter.check(0, id, 1).is_ok());
+        }
+        assert!(matches!(limiter.check(0, 0x04, 1), Err(PacketError::RateLimited(0x04))));
+        assert!(limiter.check(1000, 0x04, 1).is_ok());
+    }
+
+    #[test]
+    fn outbound_queue() {
+        use crate::{OutboundQueue, OverflowPolicy, QueueClass};
+
+        let classes = [
+            QueueClass::new(2, 1024, OverflowPolicy::DropNewest),
+            QueueClass::new(2, 1024, OverflowPolicy::DropOldest),
+        ];
+        // Logins and logouts have priority over data which is coalesced by its count
+        let mut queue = OutboundQueue::new(&classes, |packet: &PropertyPackets| match packet {
+            PropertyPackets::Data { .. } => 1,
+            _ => 0,
+        }).coalesce(|packet| match packet {
+            PropertyPackets::Data { count, .. } => Some(*count as u32),
+            _ => None,
+        });
+
+        let data = |count: u8, value: u8| PropertyPackets::Data { count, data: vec![value; count as usize] };
+        assert!(queue.push(&mut data(1, 1)).unwrap());
+        assert!(queue.push(&mut data(2, 1)).unwrap());
+        // Superseded data is replaced in place
+        assert!(queue.push(&mut data(1, 2)).unwrap());
+        assert_eq!(queue.class_len(1), 2);
+        // The oldest data is dropped when the class is full
+        assert!(queue.push(&mut data(3, 1)).unwrap());
+        assert_eq!(queue.dropped(), 1);
+
+        assert!(queue.push(&mut PropertyPackets::Logout {}).unwrap());
+        assert!(queue.push(&mut PropertyPackets::Logout {}).unwrap());
+        assert!(!queue.push(&mut PropertyPackets::Logout {}).unwrap());
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.queued_bytes(), 1 + 1 + 4 + 5);
+
+        // Higher priority packets are written first
+        let mut out = Vec::new();
+        assert_eq!(queue.flush(&mut out, 6).unwrap(), 6);
+        assert_eq!(out, [0x02, 0x02, 0x03, 0x02, 0x01, 0x01]);
+        let frame = queue.pop().unwrap();
+        assert_eq!(PropertyPackets::read(&mut frame.as_slice()).unwrap(), data(3, 1));
+        assert!(queue.is_empty());
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn nfc_strings() {
+        use crate::NfcString;
+
+        packet_data! {
+            struct Profile (<->) {
+                name: NfcString
+            }
+        }
+
+        // "e" followed by a combining acute accent
+        let decomposed = "Rene\u{301}";
+        let mut o = Vec::new();
+        decomposed.to_string().write(&mut o).unwrap();
+        let profile = Profile::read(&mut o.as_slice()).unwrap();
+        assert_eq!(profile.name.as_str(), "Ren\u{e9}");
+        assert!(profile.name.changed());
+
+        let mut profile = Profile { name: "Ren\u{e9}".into() };
+        assert!(!profile.name.changed());
+        let mut o2 = Vec::new();
+        profile.write(&mut o2).unwrap();
+        let read = Profile::read(&mut o2.as_slice()).unwrap();
+        assert_eq!(read.name, profile.name);
+        assert!(o2.len() < o.len());
+
+        // The composed and decomposed forms are the same name
+        assert_eq!(NfcString::new("\u{e9}"), NfcString::new("e\u{301}"));
+        assert_eq!(NfcString::new("e\u{301}").cmp(&NfcString::new("\u{e9}")), core::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn batches() {
+        use crate::Batch;
+
+        let mut packets = vec![
+            PropertyPackets::Logout {},
+            PropertyPackets::Data { count: 2, data: vec![1, 2] },
+            PropertyPackets::Logout {},
+        ];
+        let mut o = Vec::new();
+        PropertyPackets::write_batch(&mut packets, &mut o).unwrap();
+        assert_eq!(o, [0x03, 0x02, 0x03, 0x02, 0x01, 0x02, 0x02]);
+        assert_eq!(PropertyPackets::read_batch(&mut o.as_slice()).unwrap(), packets);
+
+        let mut batch: Batch<PropertyPackets> = packets.clone().into();
+        batch.push(PropertyPackets::Logout {});
+        let mut o2 = Vec::new();
+        batch.write(&mut o2).unwrap();
+        assert_eq!(o2[0], 0x04);
+        assert_eq!(Batch::<PropertyPackets>::read(&mut o2.as_slice()).unwrap(), batch);
+        assert!(Batch::<PropertyPackets>::read(&mut &o2[..o2.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn string_enum_ids() {
+        use crate::{protocol_states, PacketError, ReadContext, Session, StringIds, WriteContext};
+
+        packet_data! {
+            enum Weather (<->) (String) {
+                Sunny: "sunny",
+                Rain: "rain",
+                Snow: "snow"
+            }
+        }
+
+        packets! {
+            WeatherPackets (<->) {
+                Forecast (0x01) {
+                    today: Weather,
+                    week: Vec<Weather>
+                }
+            }
+        }
+
+        protocol_states! {
+            WeatherState => WeatherStatePacket {
+                Play => WeatherPackets,
+            }
+        }
+
+        // Values are sent as strings until the peer has sent its string IDs
+        let mut o = Vec::new();
+        Weather::Rain.write(&mut o).unwrap();
+        assert_eq!(o, [0x04, b'r', b'a', b'i', b'n']);
+        assert_eq!(Weather::read(&mut o.as_slice()).unwrap(), Weather::Rain);
+        assert!(matches!(Weather::read(&mut &[0x01, b'x'][..]), Err(PacketError::UnknownEnumValue)));
+
+        let mut ids = StringIds::new().with::<Weather>();
+        let mut o = Vec::new();
+        ids.write(&mut o).unwrap();
+        let ids = StringIds::read(&mut o.as_slice()).unwrap();
+        assert_eq!(ids.get("Weather", 2), Some("snow"));
+
+        let mut ctx = WriteContext::default().with_string_ids(ids.clone());
+        let mut o = Vec::new();
+        Weather::Snow.write_with(&mut ctx, &mut o).unwrap();
+        assert_eq!(o, [0x00, 0x02]);
+        assert!(matches!(Weather::read(&mut o.as_slice()), Err(PacketError::UnexpectedValue(_))));
+        let mut read_ctx = ReadContext::default().with_string_ids(ids.clone());
+        assert_eq!(Weather::read_with(&mut read_ctx, &mut o.as_slice()).unwrap(), Weather::Snow);
+
+        // Enums without a table in the acknowledged IDs are still sent as strings
+        let mut ctx = WriteContext::default().with_string_ids(StringIds::new());
+        let mut o = Vec::new();
+        Weather::Snow.write_with(&mut ctx, &mut o).unwrap();
+        assert_eq!(o, [0x04, b's', b'n', b'o', b'w']);
+
+        // The IDs are looked up in the table of the peer so the orders can differ
+        mod peer {
+            use crate::packet_data;
+            packet_data! {
+                enum Weather (<->) (String) {
+                    Snow: "snow",
+                    Sunny: "sunny",
+                    Rain: "rain"
+                }
+            }
+        }
+        let mut ctx = WriteContext::default().with_string_ids(StringIds::new().with::<peer::Weather>());
+        let mut o = Vec::new();
+        peer::Weather::Sunny.write_with(&mut ctx, &mut o).unwrap();
+        assert_eq!(o, [0x00, 0x01]);
+        let mut read_ctx = ReadContext::default().with_string_ids(StringIds::new().with::<peer::Weather>());
+        assert_eq!(Weather::read_with(&mut read_ctx, &mut o.as_slice()).unwrap(), Weather::Sunny);
+        let mut read_ctx = ReadContext::default().with_string_ids(ids.clone());
+        assert_eq!(Weather::read_with(&mut read_ctx, &mut o.as_slice()).unwrap(), Weather::Rain);
+
+        // Each direction uses the tables of the side writing it so IDs are only sent
+        // once the peer has acknowledged the tables of this side
+        let mut forecast = WeatherPackets::Forecast { today: Weather::Rain, week: vec![Weather::Sunny, Weather::Snow] };
+        let mut server = Session::new(WeatherState::Play);
+        let mut client = Session::new(WeatherState::Play);
+        server.set_peer_string_ids(StringIds::new().with::<peer::Weather>());
+        let mut o = Vec::new();
+        server.write(&mut forecast, &mut o).unwrap();
+        assert_eq!(o.len(), 18);
+        assert_eq!(o, {
+            let mut o = Vec::new();
+            forecast.write(&mut o).unwrap();
+            o
+        });
+        assert_eq!(client.read(&mut o.as_slice()).unwrap(), WeatherStatePacket::Play(forecast.clone()));
+
+        server.set_local_string_ids(ids.clone());
+        client.set_peer_string_ids(ids.clone());
+        let mut compressed = Vec::new();
+        server.write(&mut forecast, &mut compressed).unwrap();
+        assert_eq!(compressed, [0x01, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00, 0x02]);
+        assert_eq!(client.read(&mut compressed.as_slice()).unwrap(), WeatherStatePacket::Play(forecast.clone()));
+
+        // The client writes IDs from its own tables which the server reads with the
+        // tables the client sent
+        let mut reply = WeatherPackets::Forecast { today: Weather::Rain, week: vec![Weather::Snow] };
+        client.set_local_string_ids(StringIds::new().with::<peer::Weather>());
+        let mut o = Vec::new();
+        client.write(&mut reply, &mut o).unwrap();
+        assert_eq!(o, [0x01, 0x00, 0x02, 0x01, 0x00, 0x00]);
+        assert_eq!(server.read(&mut o.as_slice()).unwrap(), WeatherStatePacket::Play(reply));
+        assert!(Session::new(WeatherState::Play).read(&mut o.as_slice()).is_err());
+    }
+
+    #[test]
+    fn fragments() {
+        use crate::{Fragment, Fragmenter, PacketError, Reassembler};
+
+        let mut fragmenter = Fragmenter::new(20);
+        let mut packet = PropertyPackets::Data { count: 40, data: (0..40).collect() };
+        let frames = fragmenter.fragment_packet(&mut packet).unwrap();
+        // 42 bytes with 5 bytes per fragment
+        assert_eq!(frames.len(), 9);
+        assert!(frames.iter().all(|frame| frame.len() <= 20));
+        assert_eq!(Fragment::from_frame(&frames[8]).unwrap().payload.len(), 2);
+
+        // Fragments can arrive out of order and interleaved with other messages
+        let mut reassembler = Reassembler::new(64, 1000);
+        let small = fragmenter.fragment(&[0x02]);
+        assert_eq!(small.len(), 1);
+        for frame in frames.iter().skip(1) {
+            assert_eq!(reassembler.push_packet::<PropertyPackets>(0, frame).unwrap(), None);
+        }
+        assert_eq!(reassembler.push(0, &small[0]).unwrap(), Some(vec![0x02]));
+        assert_eq!(reassembler.pending(), 1);
+        assert_eq!(reassembler.push_packet(0, &frames[0]).unwrap(), Some(packet.clone()));
+        assert_eq!(reassembler.pending(), 0);
+
+        // Messages over the limit are rejected and incomplete messages time out
+        let mut reassembler = Reassembler::new(32, 1000);
+        let mut results = frames.iter().map(|frame| reassembler.push(0, frame));
+        assert!(results.any(|result| matches!(result, Err(PacketError::PacketTooLarge(32)))));
+        let mut reassembler = Reassembler::new(64, 1000);
+        reassembler.push(0, &frames[0]).unwrap();
+        assert_eq!(reassembler.pending_bytes(), 5);
+        assert_eq!(reassembler.expire(999), 0);
+        assert_eq!(reassembler.expire(1000), 1);
+        assert!(Fragment::from_frame(&[0x00, 0x02, 0x02]).is_err());
+
+        // Split messages can't be opened with empty fragments which would hold
+        // entries without adding to the pending bytes
+        assert!(Fragment::from_frame(&[0x00, 0x00, 0x02]).is_err());
+        assert_eq!(Fragment::from_frame(&[0x00, 0x00, 0x01]).unwrap().payload.len(), 0);
+        let mut reassembler = Reassembler::new(1 << 20, 1000);
+        for index in 0..4u8 {
+            assert!(reassembler.push(0, &[0x00, index, 0x7F]).is_err());
+        }
+        assert_eq!(reassembler.pending(), 0);
+
+        // The number of incomplete messages is limited
+        let mut reassembler = Reassembler::new(1 << 20, 1000).with_max_pending(2);
+        assert_eq!(reassembler.push(0, &[0x00, 0x00, 0x02, 0x01]).unwrap(), None);
+        assert_eq!(reassembler.push(0, &[0x01, 0x00, 0x02, 0x01]).unwrap(), None);
+        assert!(matches!(reassembler.push(0, &[0x02, 0x00, 0x02, 0x01]), Err(PacketError::TooManyPendingMessages(2))));
+        assert_eq!(reassembler.push(0, &[0x01, 0x01, 0x02, 0x02]).unwrap(), Some(vec![0x01, 0x02]));
+        assert_eq!(reassembler.push(0, &[0x02, 0x00, 0x02, 0x01]).unwrap(), None);
+        assert_eq!(reassembler.pending(), 2);
+    }
+
+    #[test]
+    fn connection_contexts() {
+        use std::sync::Arc;
+        use crate::{ConnectionContext, PacketError, Read, ReadContext, ReadResult, TextOptions};
+
+        /// A score which was a u8 before version 2
+        #[derive(Debug, PartialEq)]
+        struct Score(u32);
+
+        impl Readable for Score {
+            fn read<B: Read>(i: &mut B) -> ReadResult<Self> {
+                Self::read_with(&mut ReadContext::default(), i)
+            }
+
+            fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> {
+                if ctx.version() < 2 {
+                    Ok(Score(u8::read(i)? as u32))
+                } else {
+                    Ok(Score(u32::read(i)?))
+                }
+            }
+        }
+
+        let connection = Arc::new(ConnectionContext {
+            text: TextOptions::new(4, 0),
+            version: 2,
+            ..ConnectionContext::new()
+        });
+        // Each thread reads with its own context sharing the connection settings
+        let handles: Vec<_> = (0..4u32).map(|n| {
+            let connection = connection.clone();
+            std::thread::spawn(move || {
+                let mut ctx = ReadContext::default().with_connection(connection);
+                let score = Score::read_with(&mut ctx, &mut &n.to_be_bytes()[..]).unwrap();
+                let text = String::read_with(&mut ctx, &mut &[0x05, b'a', b'b', b'c', b'd', b'e'][..]);
+                (score, text)
+            })
+        }).collect();
+        for (n, handle) in handles.into_iter().enumerate() {
+            let (score, text) = handle.join().unwrap();
+            assert_eq!(score, Score(n as u32));
+            assert!(matches!(text, Err(PacketError::InvalidStringLength(5, 4))));
+        }
+
+        // Changing the settings of a context copies them rather than changing the shared settings
+        let mut ctx = ReadContext::default().with_connection(connection.clone()).with_version(1);
+        assert_eq!(Score::read_with(&mut ctx, &mut &[0x07][..]).unwrap(), Score(7));
+        assert_eq!(ctx.text().max_string_length, 4);
+        assert_eq!(connection.version, 2);
+        assert_eq!(ReadContext::default().version(), 0);
+    }
+
+    #[test]
+    fn results_and_unit() {
+        use crate::{to_ordered_debug, to_ordered_json, FixedSize, PacketError};
+
+        packets! {
+            ResponsePackets (<->) {
+                Lookup (0x01) {
+                    result: Result<String, VarInt>,
+                    reserved: ()
+                }
+            }
+        }
+
+        let mut packet = ResponsePackets::Lookup { result: Ok("Jacob".to_string()), reserved: () };
+        let mut o = Vec::new();
+        packet.write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0x00, 0x05, b'J', b'a', b'c', b'o', b'b']);
+        assert_eq!(ResponsePackets::read(&mut o.as_slice()).unwrap(), packet);
+
+        let mut error: Result<String, VarInt> = Err(VarInt(404));
+        let mut o = Vec::new();
+        error.write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0x94, 0x03]);
+        assert_eq!(Result::<String, VarInt>::read(&mut o.as_slice()).unwrap(), error);
+        assert!(matches!(Result::<String, VarInt>::read(&mut &[0x02][..]), Err(PacketError::UnexpectedValue(_))));
+
+        assert_eq!(<() as FixedSize>::ENCODED_SIZE, 0);
+        assert_eq!(to_ordered_debug(&error), "Err(VarInt(404))");
+        assert_eq!(to_ordered_json(&Ok::<(), u8>(())), "{\"Ok\":null}");
+    }
+
+    #[test]
+    fn ip_addresses() {
+        use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+        use crate::{to_ordered_json, FixedSize, PacketError};
+
+        packet_data! {
+            struct Peer (<->) {
+                address: SocketAddr,
+                gateway: Ipv4Addr
+            }
+        }
+
+        let mut peer = Peer {
+            address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 2)), 8080),
+            gateway: Ipv4Addr::new(192, 168, 0, 1),
+        };
+        let mut o = Vec::new();
+        peer.write(&mut o).unwrap();
+        assert_eq!(o, [0x00, 192, 168, 0, 2, 0x1F, 0x90, 192, 168, 0, 1]);
+        assert_eq!(Peer::read(&mut o.as_slice()).unwrap(), peer);
+
+        let mut address = IpAddr::V6(Ipv6Addr::LOCALHOST);
+        let mut o = Vec::new();
+        address.write(&mut o).unwrap();
+        assert_eq!(o.len(), 17);
+        assert_eq!(o[0], 0x01);
+        assert_eq!(IpAddr::read(&mut o.as_slice()).unwrap(), address);
+        assert!(matches!(IpAddr::read(&mut &[0x02][..]), Err(PacketError::UnexpectedValue(_))));
+
+        assert_eq!(<Ipv6Addr as FixedSize>::ENCODED_SIZE, 16);
+        assert_eq!(to_ordered_json(&peer.address), "\"192.168.0.2:8080\"");
+    }
+
+    #[test]
+    fn enum_values() {
+        use core::str::FromStr;
+        use crate::PacketError;
+
+        packet_data! {
+            enum Rank (<->) (VarInt) {
+                Member: 1,
+                Moderator: 5,
+                Admin: 300
+            }
+
+            enum Biome (<->) (String) {
+                Plains: "plains",
+                Desert: "desert"
+            }
+        }
+
+        assert_eq!(Rank::ALL, &[Rank::Member, Rank::Moderator, Rank::Admin]);
+        assert_eq!(Rank::iter().map(|rank| rank.name()).collect::<Vec<_>>(), ["Member", "Moderator", "Admin"]);
+        assert_eq!(Rank::try_from(VarInt(300)).unwrap(), Rank::Admin);
+        assert!(matches!(Rank::try_from(VarInt(2)), Err(PacketError::UnknownEnumValue)));
+        assert_eq!(VarInt::from(Rank::Moderator), VarInt(5));
+        assert_eq!(Rank::from_str("Moderator").unwrap(), Rank::Moderator);
+        assert!(Rank::from_str("moderator").is_err());
+
+        assert_eq!(Biome::try_from("desert").unwrap(), Biome::Desert);
+        assert!(Biome::try_from("ocean".to_string()).is_err());
+        assert_eq!(String::from(Biome::Plains), "plains");
+        assert_eq!("Desert".parse::<Biome>().unwrap(), Biome::Desert);
+        assert_eq!(Biome::ALL.len(), 2);
+    }
+
+    #[test]
+    fn tagged_structs() {
+        use crate::PacketError;
+
+        mod v1 {
+            crate::packet_data! {
+                #[wsbps(tagged)]
+                struct Profile (<->) {
+                    #[tag(1)]
+                    name: String,
+                    #[tag(2)]
+                    level: u8,
+                    #[tag(3)]
+                    nickname: String
+                }
+            }
+        }
+
+        mod v2 {
+            crate::packet_data! {
+                #[wsbps(tagged)]
+                struct Profile (<->) {
+                    #[tag(2)]
+                    level: u8,
+                    #[tag(1)]
+                    #[log]
+                    name: String,
+                    #[tag(4)]
+                    score: u64 = 100
+                }
+            }
+        }
+
+        let mut profile = v1::Profile { name: "Jacob".to_string(), level: 7, nickname: "J".to_string() };
+        let mut o = Vec::new();
+        profile.write(&mut o).unwrap();
+        // Count, then the name (delimited), level (fixed 1) and nickname (delimited)
+        assert_eq!(o, [0x03, 0x0C, 0x06, 0x05, b'J', b'a', b'c', b'o', b'b', 0x10, 0x07, 0x1C, 0x02, 0x01, b'J']);
+        assert_eq!(v1::Profile::read(&mut o.as_slice()).unwrap(), profile);
+
+        // The removed nickname is skipped and the added score uses its default
+        let profile = v2::Profile::read(&mut o.as_slice()).unwrap();
+        assert_eq!(profile, v2::Profile { level: 7, name: "Jacob".to_string(), score: 100 });
+
+        // Fields the older version doesn't know are skipped and missing fields are the Default
+        let mut o = Vec::new();
+        v2::Profile { level: 3, name: "Ann".to_string(), score: 5 }.write(&mut o).unwrap();
+        assert_eq!(v1::Profile::read(&mut o.as_slice()).unwrap(), v1::Profile { name: "Ann".to_string(), level: 3, nickname: String::new() });
+
+        assert!(matches!(v1::Profile::read(&mut &[0x01, 0x0D][..]), Err(PacketError::UnexpectedValue(_))));
+        assert!(!crate::check_field_tags(&[1, 2, 1]));
+
+        // The wire type is chosen from the type so it must match the field
+        let mut o = Vec::new();
+        v2::Profile { level: 3, name: String::new(), score: 5 }.write(&mut o).unwrap();
+        assert_eq!(o, [0x03, 0x10, 0x03, 0x0C, 0x01, 0x00, 0x23, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05]);
+        assert!(matches!(
+            v1::Profile::read(&mut &[0x01, 0x08, 0x41][..]),
+            Err(PacketError::UnexpectedValue("the wire type of the tagged field to match its type"))
+        ));
+        assert!(matches!(
+            v1::Profile::read(&mut &[0x01, 0x14, 0x01, 0x07][..]),
+            Err(PacketError::UnexpectedValue("the wire type of the tagged field to match its type"))
+        ));
+
+        // Values must use their whole payload and each field can only be sent once
+        assert!(matches!(
+            v1::Profile::read(&mut &[0x01, 0x0C, 0x03, 0x01, b'J', b'x'][..]),
+            Err(PacketError::UnexpectedValue("the tagged field value to use its whole payload"))
+        ));
+        assert!(matches!(
+            v1::Profile::read(&mut &[0x02, 0x10, 0x07, 0x10, 0x08][..]),
+            Err(PacketError::UnexpectedValue("each field of a tagged struct at most once"))
+        ));
+    }
+
+    #[test]
+    fn string_enum_aliases() {
+        use crate::{PacketError, StringEnum};
+
+        packet_data! {
+            enum Colour (<->) (String) {
+                Red: "red" | "RED" | "crimson",
+                Green: "green"
+            }
+
+            #[wsbps(case_insensitive)]
+            enum Shape (<->) (String) {
+                Circle: "circle" | "round",
+                Square: "square"
+            }
+        }
+
+        let read = |value: &str| {
+            let mut o = Vec::new();
+            value.to_string().write(&mut o).unwrap();
+            o
+        };
+        assert_eq!(Colour::read(&mut read("RED").as_slice()).unwrap(), Colour::Red);
+        assert_eq!(Colour::read(&mut read("crimson").as_slice()).unwrap(), Colour::Red);
+        assert!(matches!(Colour::read(&mut read("Red").as_slice()), Err(PacketError::UnknownEnumValue)));
+        assert_eq!(Colour::try_from("RED").unwrap(), Colour::Red);
+        assert_eq!(Colour::VALUES, &["red", "green"]);
+
+        // Aliases are only accepted when reading, the first value is always written
+        let mut o = Vec::new();
+        Colour::Red.write(&mut o).unwrap();
+        assert_eq!(o, read("red"));
+
+        assert_eq!(Shape::read(&mut read("SQUARE").as_slice()).unwrap(), Shape::Square);
+        assert_eq!(Shape::try_from("Round").unwrap(), Shape::Circle);
+        assert!(Shape::try_from("triangle").is_err());
+    }
+
+    #[test]
+    fn field_decode_errors() {
+        packet_data! {
+            struct Position (<->) {
+                x: i32,
+                y: i32
+            }
+        }
+
+        packets! {
+            MovePackets (<->) {
+                Move (0x01) {
+                    entity: u16,
+                    name: String,
+                    position: Position
+                }
+            }
+        }
+
+        // The position is cut off part way through y
+        let bytes = [0x01, 0x00, 0x07, 0x02, b'a', b'b', 0x00, 0x00, 0x00, 0x01, 0x00, 0x00];
+        let err = MovePackets::read(&mut &bytes[..]).unwrap_err();
+        let PacketError::FieldDecode { packet, field, index, offset, source } = &err else {
+            panic!("expected a field decode error but got {:?}", err);
+        };
+        assert_eq!((*packet, *field, *index, *offset), ("Move", "position", 2, 9));
+        // Nested structs report the offset in the packet too
+        assert!(matches!(**source, PacketError::FieldDecode { packet: "Position", field: "y", index: 1, offset: 9, .. }));
+        assert!(matches!(err.root_cause(), PacketError::IO(_)));
+        assert!(err.to_string().starts_with("failed to read field position (index 2) of Move at byte 9: failed to read field y"));
+
+        let bytes = [0x01, 0x00, 0x07, 0x02, 0xFF, 0xFF];
+        let err = MovePackets::read(&mut &bytes[..]).unwrap_err();
+        assert!(matches!(err, PacketError::FieldDecode { field: "name", index: 1, offset: 5, .. }));
+        assert!(matches!(err.root_cause(), PacketError::BadEncoding(_)));
+
+        // Structs read on their own report the offset in the struct
+        let err = Position::read(&mut &[0x00, 0x00, 0x00, 0x01, 0x00][..]).unwrap_err();
+        assert!(matches!(err, PacketError::FieldDecode { packet: "Position", field: "y", index: 1, offset: 4, .. }));
+    }
+
+    #[test]
+    fn counting_io() {
+        use crate::{encoded_size, CountingReader, CountingWriter};
+
+        let mut packet = PropertyPackets::Login { name: "Jacob".to_string(), token: None, version: VarInt(300) };
+        let mut o = CountingWriter::new(Vec::new());
+        packet.write(&mut o).unwrap();
+        assert_eq!(o.position(), 10);
+        assert_eq!(encoded_size(&mut packet).unwrap(), 10);
+        let frame = o.into_inner();
+
+        // The position shows how much of the frame was used
+        let mut i = CountingReader::new(&frame[..]);
+        assert_eq!(PropertyPackets::read(&mut i).unwrap(), packet);
+        assert_eq!(i.position(), frame.len());
+        let mut i = CountingReader::new(&frame[..7]);
+        assert!(PropertyPackets::read(&mut i).is_err());
+        assert_eq!(i.position(), 7);
+    }
+
+    #[test]
+    fn compression_dictionary() {
+        use crate::{protocol_states, CompressionDictionary, DictionaryString, DictionaryTrainer, Session};
+
+        packets! {
+            ChatPackets (<->) {
+                UpdateDictionary (0x01) {
+                    dictionary: CompressionDictionary
+                }
+                Chat (0x02) {
+                    channel: DictionaryString,
+                    message: DictionaryString
+                }
+            }
+        }
+
+        protocol_states! {
+            ChatState => ChatStatePacket {
+                Play => ChatPackets,
+            }
+        }
+
+        let mut trainer = DictionaryTrainer::new(16);
+        for channel in ["general", "general", "trade", "general", "trade", "help"] {
+            trainer.observe(channel);
+        }
+        let dictionary = trainer.build(1, 8);
+        assert_eq!(dictionary.entries(), ["general", "trade"]);
+
+        let write = |packet: ChatPackets| {
+            let mut o = Vec::new();
+            packet.clone().write(&mut o).unwrap();
+            o
+        };
+        let mut session = Session::new(ChatState::Play);
+        let update = write(ChatPackets::UpdateDictionary { dictionary: dictionary.clone() });
+        let ChatStatePacket::Play(ChatPackets::UpdateDictionary { dictionary: received }) = session.read(&mut update.as_slice()).unwrap() else {
+            panic!("expected the dictionary update");
+        };
+        session.set_dictionary(received).unwrap();
+
+        // Strings in the dictionary are sent as their ID
+        let chat = ChatPackets::Chat { channel: dictionary.compress("trade"), message: dictionary.compress("") };
+        let o = write(chat.clone());
+        assert_eq!(o, [0x02, 0x00, 0x02, 0x00, 0x00]);
+        assert_eq!(session.read(&mut o.as_slice()).unwrap(), ChatStatePacket::Play(chat));
+        let o = write(ChatPackets::Chat { channel: dictionary.compress("help"), message: DictionaryString::new("hi") });
+        assert_eq!(o, [0x02, 0x04, b'h', b'e', b'l', b'p', 0x02, b'h', b'i']);
+        assert!(session.read(&mut o.as_slice()).is_ok());
+
+        // IDs can't be read without the dictionary and stale updates are rejected
+        assert!(ChatPackets::read(&mut &[0x02, 0x00, 0x01, 0x00, 0x00][..]).is_err());
+        assert!(session.set_dictionary(CompressionDictionary::new(1, vec![])).is_err());
+        session.set_dictionary(CompressionDictionary::new(2, vec!["help".to_string()])).unwrap();
+        assert_eq!(session.dictionary().map(CompressionDictionary::version), Some(2));
+        let ChatStatePacket::Play(ChatPackets::Chat { channel, .. }) = session.read(&mut &[0x02, 0x00, 0x01, 0x00, 0x00][..]).unwrap() else {
+            panic!("expected a chat packet");
+        };
+        assert_eq!(channel.as_str(), "help");
+    }
+
+    #[test]
+    fn field_validation() {
+        packet_data! {
+            struct Profile (<->) {
+                #[range(0..=120)]
+                age: u8,
+                #[max_len(8)]
+                name: String,
+                #[max_len(2)]
+                tags: Vec<u8>,
+                ranked: bool,
+                #[when(ranked)]
+                #[range(1..)]
+                rank: u32
+            }
+        }
+
+        let read = |profile: Profile| {
+            let mut o = Vec::new();
+            profile.clone().write(&mut o).unwrap();
+            Profile::read(&mut o.as_slice())
+        };
+        let profile = Profile { age: 30, name: "Jacob".to_string(), tags: vec![1, 2], ranked: true, rank: 3 };
+        assert_eq!(read(profile.clone()).unwrap(), profile);
+
+        // Fields that aren't present aren't checked
+        let unranked = Profile { ranked: false, rank: 0, ..profile.clone() };
+        assert_eq!(read(unranked.clone()).unwrap(), unranked);
+
+        match read(Profile { age: 121, ..profile.clone() }) {
+            Err(PacketError::ValidationFailed { packet, field, reason }) => {
+                assert_eq!((packet, field), ("Profile", "age"));
+                assert_eq!(reason, "121 is outside of the range 0..=120");
+            }
+            result => panic!("expected a validation failure but got {:?}", result)
+        }
+        let err = read(Profile { name: "a".repeat(9), ..profile.clone() }).unwrap_err();
+        assert_eq!(err.to_string(), "field name of Profile failed validation: length of 9 is greater than the max length of 8");
+        assert!(matches!(read(Profile { tags: vec![1, 2, 3], ..profile.clone() }), Err(PacketError::ValidationFailed { field: "tags", .. })));
+        assert!(matches!(read(Profile { rank: 0, ..profile }), Err(PacketError::ValidationFailed { field: "rank", .. })));
+    }
+
+    #[test]
+    fn canonical_encoding() {
+        use alloc::collections::BTreeMap;
+        use std::collections::HashMap;
+        use crate::{read_canonical, ReadContext, StringIds, Ticked, VarInt, WriteContext};
+
+        packet_data! {
+            struct Signed (<->) {
+                #[canonical]
+                claims: BTreeMap<String, u8>,
+                #[canonical]
+                extra: HashMap<String, u8>,
+                signature: Vec<u8>
+            }
+        }
+
+        let mut signed = Signed {
+            claims: BTreeMap::from([("b".to_string(), 2), ("a".to_string(), 1)]),
+            extra: HashMap::from([("c".to_string(), 3)]),
+            signature: vec![1, 2, 3],
+        };
+        let mut o = Vec::new();
+        signed.write(&mut o).unwrap();
+        assert_eq!(Signed::read(&mut o.as_slice()).unwrap(), signed);
+
+        // VarInts must use the fewest bytes
+        assert_eq!(read_canonical::<VarInt, _>(&mut &[0x05][..]).unwrap(), VarInt(5));
+        assert!(matches!(read_canonical::<VarInt, _>(&mut &[0x85, 0x00][..]), Err(PacketError::NonCanonical(_))));
+        assert_eq!(VarInt::read(&mut &[0x85, 0x00][..]).unwrap(), VarInt(5));
+
+        // Map keys must be in ascending order
+        let unsorted = [2, 1, b'b', 2, 1, b'a', 1];
+        assert!(matches!(read_canonical::<BTreeMap<String, u8>, _>(&mut &unsorted[..]), Err(PacketError::NonCanonical(_))));
+        assert_eq!(BTreeMap::<String, u8>::read(&mut &unsorted[..]).unwrap().len(), 2);
+
+        // Only the canonical fields are checked
+        assert!(Signed::read(&mut &[0x00, 0x00, 0x80, 0x00][..]).is_ok());
+        assert!(matches!(Signed::read(&mut &[0x80, 0x00, 0x00, 0x00][..]).unwrap_err().root_cause(), PacketError::NonCanonical(_)));
+
+        // HashMaps with more than one entry don't have a canonical order
+        signed.extra.insert("d".to_string(), 4);
+        assert!(matches!(signed.write(&mut Vec::new()), Err(PacketError::NonCanonical(_))));
+
+        // Packet IDs and the VarInts of the wrapper types are checked as well
+        assert!(matches!(read_canonical::<PropertyPackets, _>(&mut &[0x02][..]), Ok(PropertyPackets::Logout {})));
+        assert!(matches!(read_canonical::<PropertyPackets, _>(&mut &[0x82, 0x00][..]), Err(PacketError::NonCanonical(_))));
+        assert_eq!(read_canonical::<Ticked<u8>, _>(&mut &[0x01, 0x05][..]).unwrap(), Ticked::new(1, 5));
+        assert!(matches!(read_canonical::<Ticked<u8>, _>(&mut &[0x81, 0x00, 0x05][..]), Err(PacketError::NonCanonical(_))));
+        assert_eq!(Ticked::<u8>::read(&mut &[0x81, 0x00, 0x05][..]).unwrap(), Ticked::new(1, 5));
+
+        // Canonical fields can have the other attributes and are written with the
+        // context so string enums are still written as IDs
+        packet_data! {
+            enum Level (<->) (String) {
+                Low: "low",
+                High: "high"
+            }
+
+            struct Ranked (<->) {
+                count: u8,
+                #[canonical]
+                #[range(0..=5)]
+                rank: u8,
+                #[canonical]
+                #[max_len(2)]
+                claims: BTreeMap<String, u8>,
+                #[canonical]
+                #[len_from(count)]
+                scores: Vec<VarInt>,
+                #[canonical]
+                level: Level
+            }
+        }
+
+        let mut ranked = Ranked {
+            count: 2,
+            rank: 3,
+            claims: BTreeMap::from([("a".to_string(), 1)]),
+            scores: vec![VarInt(1), VarInt(300)],
+            level: Level::High,
+        };
+        let string_ids = StringIds::new().with::<Level>();
+        let mut o = Vec::new();
+        ranked.write_with(&mut WriteContext::default().with_string_ids(string_ids.clone()), &mut o).unwrap();
+        assert_eq!(o[o.len() - 2..], [0x00, 0x01]);
+        let mut ctx = ReadContext::default().with_string_ids(string_ids);
+        assert_eq!(Ranked::read_with(&mut ctx, &mut o.as_slice()).unwrap(), ranked);
+        let mut o = Vec::new();
+        ranked.write(&mut o).unwrap();
+        assert_eq!(Ranked::read(&mut o.as_slice()).unwrap(), ranked);
+        // The VarInts without a length prefix are checked too
+        let padded = [0x02, 0x03, 0x01, 0x01, b'a', 0x01, 0x81, 0x00, 0x01, 0x04, b'h', b'i', b'g', b'h'];
+        assert!(matches!(Ranked::read(&mut &padded[..]).unwrap_err().root_cause(), PacketError::NonCanonical(_)));
+    }
+
+    #[test]
+    fn versions() {
+        use crate::{to_ordered_json, Version};
+
+        packet_data! {
+            struct Hello (<->) {
+                version: Version
+            }
+        }
+
+        let mut hello = Hello { version: Version::new(1, 200, 3) };
+        let mut o = Vec::new();
+        hello.write(&mut o).unwrap();
+        assert_eq!(o, [0x01, 0xC8, 0x01, 0x03]);
+        assert_eq!(Hello::read(&mut o.as_slice()).unwrap(), hello);
+
+        let version: Version = "1.4.0".parse().unwrap();
+        assert_eq!(version.to_string(), "1.4.0");
+        assert!(version > Version::new(1, 3, 9));
+        assert!(version.satisfies(&Version::new(1, 2, 0)));
+        assert!(!version.satisfies(&Version::new(1, 5, 0)));
+        assert!(!version.is_compatible_with(&Version::new(2, 0, 0)));
+        assert!(!Version::new(0, 1, 0).is_compatible_with(&Version::new(0, 2, 0)));
+        assert!("1.4".parse::<Version>().is_err());
+        assert!("1.4.0.1".parse::<Version>().is_err());
+        assert!("1.x.0".parse::<Version>().is_err());
+        assert_eq!(to_ordered_json(&version), "{\"major\":1,\"minor\":4,\"patch\":0}");
+    }
+
+    #[test]
+    fn read_limit_presets() {
+        use alloc::sync::Arc;
+        use crate::{read_limited_with, ConnectionContext, ReadContext, ReadLimits};
+
+        packet_data! {
+            struct Upload (<->) {
+                name: String,
+                #[limits(ReadLimits::TRUSTED_PEER)]
+                data: Vec<u8>
+            }
+        }
+
+        let mut upload = Upload { name: "a".repeat(2000), data: vec![0; 5000] };
+        let mut o = Vec::new();
+        upload.write(&mut o).unwrap();
+
+        // The field limits override the limits of the connection
+        let connection = Arc::new(ConnectionContext { limits: Some(ReadLimits::STRICT_SERVER), ..Default::default() });
+        let mut ctx = ReadContext::default().with_connection(connection);
+        assert_eq!(ctx.limits(), ReadLimits::STRICT_SERVER);
+        assert_eq!(Upload::read_with(&mut ctx, &mut o.as_slice()).unwrap(), upload);
+        assert_eq!(ctx.limits(), ReadLimits::STRICT_SERVER);
+
+        let mut ctx = ReadContext::default().with_limits(ReadLimits::EMBEDDED_CLIENT);
+        assert!(matches!(Upload::read_with(&mut ctx, &mut o.as_slice()).unwrap_err().root_cause(), PacketError::InvalidStringLength(2000, 1024)));
+        let mut o = Vec::new();
+        Upload { name: "a".to_string(), data: vec![0; 9000] }.write(&mut o).unwrap();
+        assert!(matches!(read_limited_with::<Upload, _>(&mut ctx, &mut o.as_slice()), Err(PacketError::PacketTooLarge(8192))));
+        assert!(matches!(Vec::<u8>::read_with(&mut ctx, &mut &[0x81, 0x02][..]), Err(PacketError::CollectionTooLarge(257, 256))));
+    }
+
+    #[test]
+    fn validated_strings() {
+        use crate::{AsciiString, BoundedString, Identifier, PacketError};
+
+        packet_data! {
+            struct Item (<->) {
+                id: Identifier,
+                label: AsciiString,
+                note: BoundedString<8>
+            }
+        }
+
+        let mut item = Item {
+            id: Identifier::new("game:items/sword").unwrap(),
+            label: AsciiString::new("Sword").unwrap(),
+            note: BoundedString::new("sharp").unwrap(),
+        };
+        assert_eq!(item.id.namespace(), "game");
+        assert_eq!(item.id.path(), "items/sword");
+        let mut o = Vec::new();
+        item.write(&mut o).unwrap();
+        assert_eq!(Item::read(&mut o.as_slice()).unwrap(), item);
+
+        let read = |id: &str, label: &str, note: &str| {
+            let mut o = Vec::new();
+            for value in [id, label, note] {
+                value.to_string().write(&mut o).unwrap();
+            }
+            Item::read(&mut o.as_slice())
+        };
+        assert!(matches!(read("game:sword", "Sw\u{f6}rd", "sharp").unwrap_err().root_cause(), PacketError::NonAsciiString(2)));
+        assert!(matches!(read("sword", "Sword", "sharp").unwrap_err().root_cause(), PacketError::InvalidIdentifier(_)));
+        assert!(matches!(read(":sword", "Sword", "sharp").unwrap_err().root_cause(), PacketError::InvalidIdentifier(_)));
+        assert!(matches!(read("game:", "Sword", "sharp").unwrap_err().root_cause(), PacketError::InvalidIdentifier(_)));
+        assert!(matches!(read("Game:sword", "Sword", "sharp").unwrap_err().root_cause(), PacketError::InvalidIdentifier(_)));
+        assert!(matches!(read("game:sword", "Sword", "very sharp").unwrap_err().root_cause(), PacketError::StringTooLong(10, 8)));
+        assert!(Identifier::from_parts("game", "a/b").is_ok());
+        assert!(BoundedString::<4>::new("hello").is_err());
+    }
+
+    #[cfg(feature = "extensions")]
+    #[test]
+    fn extend_packets() {
+        use crate::Extensible;
+
+        crate::extend_packets! {
+            PropertyPackets {
+                PluginPing (0x81) {
+                    time: u64
+                }
+            }
+        }
+
+        let mut o = Vec::new();
+        Extensible::<PropertyPackets>::from(PluginPing { time: 5 }).write(&mut o).unwrap();
+        match Extensible::<PropertyPackets>::read(&mut o.as_slice()).unwrap() {
+            Extensible::Extension(packet) => assert_eq!(packet.downcast_ref::<PluginPing>(), Some(&PluginPing { time: 5 })),
+            other => panic!("expected extension packet got {:?}", other)
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::dictionary::CompressionDictionary;
+use crate::error::PacketError;
+use crate::io::{CountingReader, IoError, Read, Readable, ReadResult};
+use crate::string_enum::StringIds;
+use crate::text::TextOptions;
+
+/// The size of the chunks that length prefixed bytes are read in so that memory
+/// is only allocated as the data actually arrives
+const CHUNK_SIZE: usize = 4096;
+
+static MAX_STRING_LENGTH: AtomicUsize = AtomicUsize::new(ReadLimits::DEFAULT.max_string_length);
+static MAX_COLLECTION_LENGTH: AtomicUsize = AtomicUsize::new(ReadLimits::DEFAULT.max_collection_length);
+static MAX_PACKET_SIZE: AtomicUsize = AtomicUsize::new(ReadLimits::DEFAULT.max_packet_size);
+static MAX_DEPTH: AtomicUsize = AtomicUsize::new(ReadLimits::DEFAULT.max_depth);
+
+/// ## Read Limits
+/// Limits enforced while reading so that a peer can't claim huge lengths to make
+/// the reader allocate large amounts of memory. The global limits apply to all the
+/// String, Vec and HashMap reads unless other limits are set on the ReadContext or
+/// ConnectionContext. The presets cover the common deployments so the limits don't
+/// need to be guessed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    /// The maximum length in bytes of strings
+    pub max_string_length: usize,
+    /// The maximum number of elements in collections
+    pub max_collection_length: usize,
+    /// The maximum number of bytes read for a single packet by read_limited
+    pub max_packet_size: usize,
+    /// The maximum depth of nested structs and containers
+    pub max_depth: usize,
+}
+
+impl ReadLimits {
+    /// The limits used unless others are set with set_global
+    pub const DEFAULT: ReadLimits = ReadLimits {
+        max_string_length: i16::MAX as usize,
+        max_collection_length: 1 << 20,
+        max_packet_size: 1 << 24,
+        max_depth: 64,
+    };
+
+    /// Limits for servers accepting connections from untrusted clients which only
+    /// allow small packets and shallow nesting
+    pub const STRICT_SERVER: ReadLimits = ReadLimits {
+        max_string_length: 4096,
+        max_collection_length: 4096,
+        max_packet_size: 1 << 16,
+        max_depth: 16,
+    };
+
+    /// Limits for peers that are trusted (e.g. other services in the same cluster)
+    /// which allow large transfers and deep nesting
+    pub const TRUSTED_PEER: ReadLimits = ReadLimits {
+        max_string_length: 1 << 24,
+        max_collection_length: 1 << 24,
+        max_packet_size: 1 << 28,
+        max_depth: 256,
+    };
+
+    /// Limits for clients running on devices with little memory
+    pub const EMBEDDED_CLIENT: ReadLimits = ReadLimits {
+        max_string_length: 1024,
+        max_collection_length: 256,
+        max_packet_size: 1 << 13,
+        max_depth: 8,
+    };
+
+    /// The limits that are currently being enforced
+    pub fn global() -> ReadLimits {
+        ReadLimits {
+            max_string_length: MAX_STRING_LENGTH.load(Ordering::Relaxed),
+            max_collection_length: MAX_COLLECTION_LENGTH.load(Ordering::Relaxed),
+            max_packet_size: MAX_PACKET_SIZE.load(Ordering::Relaxed),
+            max_depth: MAX_DEPTH.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Replaces the limits that are enforced for all future reads
+    pub fn set_global(self) {
+        MAX_STRING_LENGTH.store(self.max_string_length, Ordering::Relaxed);
+        MAX_COLLECTION_LENGTH.store(self.max_collection_length, Ordering::Relaxed);
+        MAX_PACKET_SIZE.store(self.max_packet_size, Ordering::Relaxed);
+        MAX_DEPTH.store(self.max_depth, Ordering::Relaxed);
+    }
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self { ReadLimits::DEFAULT }
+}
+
+/// ## Connection Context
+/// The settings of a connection which all of its reads and writes share. This is
+/// immutable once built and is shared through an Arc so connections decoding in
+/// parallel on a threadpool each give their reads a cheap clone of their own
+/// settings rather than contending on a global structure
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConnectionContext {
+    /// The options strings are checked against
+    pub text: TextOptions,
+    /// The string IDs the peer sent which the string enum IDs it sends are read with
+    pub string_ids: Option<StringIds>,
+    /// The string IDs this side sent once the peer has acknowledged them. String
+    /// enums are only written as IDs (from these tables) once these are set
+    pub local_string_ids: Option<StringIds>,
+    /// The protocol version negotiated for the connection which custom Readable
+    /// implementations can use to read older layouts
+    pub version: u32,
+    /// The limits of the connection which are used instead of the global limits
+    pub limits: Option<ReadLimits>,
+    /// The latest compression dictionary the peer sent for reading DictionaryStrings
+    pub dictionary: Option<CompressionDictionary>,
+}
+
+impl ConnectionContext {
+    /// Creates a new context with the default settings
+    pub fn new() -> Self {
+        ConnectionContext::default()
+    }
+}
+
+/// ## Decode Deadline
+/// A time budget for decoding a single packet so frames that are slow to decode
+/// (e.g. huge nested structures within the limits) can't stall a connection task.
+/// The deadline is checked each time the decode enters a nested value and once it
+/// has passed the decode fails with PacketError::DeadlineExceeded
+#[derive(Debug, Clone, Copy, Eq)]
+pub enum DecodeDeadline {
+    /// Passes once the [clock] returns a time at or after [at]. This allows any
+    /// time source including without std
+    Clock {
+        /// Provides the current time
+        clock: fn() -> u64,
+        /// The time the deadline passes
+        at: u64,
+    },
+    /// Passes at the provided instant
+    #[cfg(feature = "std")]
+    Instant(std::time::Instant),
+}
+
+/// Clocks are compared by address which may be different for the same function
+/// across codegen units
+impl PartialEq for DecodeDeadline {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DecodeDeadline::Clock { clock, at }, DecodeDeadline::Clock { clock: other_clock, at: other_at }) => {
+                at == other_at && core::ptr::fn_addr_eq(*clock, *other_clock)
+            }
+            #[cfg(feature = "std")]
+            (DecodeDeadline::Instant(at), DecodeDeadline::Instant(other_at)) => at == other_at,
+            #[cfg(feature = "std")]
+            _ => false,
+        }
+    }
+}
+
+impl DecodeDeadline {
+    /// Creates a deadline which passes after the [timeout] from now
+    #[cfg(feature = "std")]
+    pub fn after(timeout: std::time::Duration) -> Self {
+        DecodeDeadline::Instant(std::time::Instant::now() + timeout)
+    }
+
+    /// Whether the deadline has passed
+    pub fn has_passed(&self) -> bool {
+        match self {
+            DecodeDeadline::Clock { clock, at } => clock() >= *at,
+            #[cfg(feature = "std")]
+            DecodeDeadline::Instant(at) => std::time::Instant::now() >= *at,
+        }
+    }
+}
+
+/// ## Read Context
+/// Context passed through Readable::read_with which tracks how deeply nested the
+/// value currently being read is. Reading fails with PacketError::DepthLimitExceeded
+/// rather than overflowing the stack when the values are nested too deeply. The
+/// context also carries the shared ConnectionContext of the connection being read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadContext {
+    depth: usize,
+    max_depth: usize,
+    limits: Option<ReadLimits>,
+    deadline: Option<DecodeDeadline>,
+    canonical: bool,
+    connection: Option<Arc<ConnectionContext>>,
+}
+
+impl ReadContext {
+    /// Creates a new context with the provided maximum depth
+    pub fn new(max_depth: usize) -> Self {
+        ReadContext { depth: 0, max_depth, limits: None, deadline: None, canonical: false, connection: None }
+    }
+
+    /// Sets the shared [connection] context. The limits of the connection are
+    /// used if it has any
+    pub fn with_connection(mut self, connection: Arc<ConnectionContext>) -> Self {
+        if let Some(limits) = connection.limits {
+            self = self.with_limits(limits);
+        }
+        self.connection = Some(connection);
+        self
+    }
+
+    /// Sets the [limits] used instead of the global limits including the
+    /// maximum depth
+    pub fn with_limits(mut self, limits: ReadLimits) -> Self {
+        self.max_depth = limits.max_depth;
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Sets the [deadline] the reads using this context must finish before
+    pub fn with_deadline(mut self, deadline: DecodeDeadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Makes the reads using this context only accept canonical encodings (see
+    /// read_canonical)
+    pub fn with_canonical(mut self) -> Self {
+        self.canonical = true;
+        self
+    }
+
+    /// Sets the [text] options strings are checked against
+    pub fn with_text(mut self, text: TextOptions) -> Self {
+        self.connection_mut().text = text;
+        self
+    }
+
+    /// Sets the [string_ids] used to read the string enum values sent as IDs
+    pub fn with_string_ids(mut self, string_ids: StringIds) -> Self {
+        self.connection_mut().string_ids = Some(string_ids);
+        self
+    }
+
+    /// Sets the [dictionary] used to read the DictionaryStrings sent as IDs
+    pub fn with_dictionary(mut self, dictionary: CompressionDictionary) -> Self {
+        self.connection_mut().dictionary = Some(dictionary);
+        self
+    }
+
+    /// Sets the protocol [version] of the connection
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.connection_mut().version = version;
+        self
+    }
+
+    /// The connection context which is copied first if it is shared
+    fn connection_mut(&mut self) -> &mut ConnectionContext {
+        Arc::make_mut(self.connection.get_or_insert_with(Default::default))
+    }
+
+    /// The current depth
+    pub fn depth(&self) -> usize { self.depth }
+
+    /// Whether only canonical encodings are accepted
+    pub fn is_canonical(&self) -> bool { self.canonical }
+
+    /// The deadline reads using this context must finish before
+    pub fn deadline(&self) -> Option<DecodeDeadline> { self.deadline }
+
+    /// The limits that reads using this context are checked against
+    pub fn limits(&self) -> ReadLimits {
+        self.limits.unwrap_or_else(ReadLimits::global)
+    }
+
+    /// The shared connection context if one was set
+    pub fn connection(&self) -> Option<&ConnectionContext> { self.connection.as_deref() }
+
+    /// The options strings are checked against
+    pub fn text(&self) -> &TextOptions {
+        self.connection.as_ref().map_or(&TextOptions::UNRESTRICTED, |connection| &connection.text)
+    }
+
+    /// The string IDs sent by the peer
+    pub fn string_ids(&self) -> Option<&StringIds> {
+        self.connection.as_ref()?.string_ids.as_ref()
+    }
+
+    /// The compression dictionary sent by the peer
+    pub fn dictionary(&self) -> Option<&CompressionDictionary> {
+        self.connection.as_ref()?.dictionary.as_ref()
+    }
+
+    /// The protocol version of the connection which is 0 unless one was set
+    pub fn version(&self) -> u32 {
+        self.connection.as_ref().map_or(0, |connection| connection.version)
+    }
+
+    /// Calls [read] one level deeper than the current depth failing if that
+    /// would exceed the maximum depth or the deadline has passed
+    pub fn enter<T, F: FnOnce(&mut ReadContext) -> ReadResult<T>>(&mut self, read: F) -> ReadResult<T> {
+        if self.depth >= self.max_depth {
+            Err(PacketError::DepthLimitExceeded(self.max_depth))?;
+        }
+        if self.deadline.is_some_and(|deadline| deadline.has_passed()) {
+            Err(PacketError::DeadlineExceeded)?;
+        }
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Calls [read] with the [limits] replacing the limits of this context. This
+    /// is used by the #[limits(..)] field attribute to override the limits of the
+    /// connection for a single field
+    pub fn with_field_limits<T, F: FnOnce(&mut ReadContext) -> ReadResult<T>>(&mut self, limits: ReadLimits, read: F) -> ReadResult<T> {
+        let previous = (self.limits.replace(limits), self.max_depth);
+        self.max_depth = limits.max_depth;
+        let result = read(self);
+        (self.limits, self.max_depth) = previous;
+        result
+    }
+
+    /// Calls [read] only accepting canonical encodings. This is used by the
+    /// #[canonical] field attribute
+    pub fn with_field_canonical<T, F: FnOnce(&mut ReadContext) -> ReadResult<T>>(&mut self, read: F) -> ReadResult<T> {
+        let previous = core::mem::replace(&mut self.canonical, true);
+        let result = read(self);
+        self.canonical = previous;
+        result
+    }
+}
+
+/// The default context uses the global max_depth
+impl Default for ReadContext {
+    fn default() -> Self { ReadContext::new(MAX_DEPTH.load(Ordering::Relaxed)) }
+}
+
+/// ## Write Context
+/// Context passed through Writable::write_with which carries the shared
+/// ConnectionContext of the connection being written to. Values whose encoding
+/// depends on what the peer has sent during the handshake use it to choose how
+/// they are written (e.g. string enums are only written as IDs to peers that
+/// have acknowledged the StringIds this side sent)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WriteContext {
+    connection: Option<Arc<ConnectionContext>>,
+}
+
+impl WriteContext {
+    /// Creates a new context without a connection
+    pub fn new() -> Self {
+        WriteContext::default()
+    }
+
+    /// Sets the shared [connection] context
+    pub fn with_connection(mut self, connection: Arc<ConnectionContext>) -> Self {
+        self.connection = Some(connection);
+        self
+    }
+
+    /// Sets the [string_ids] this side sent which the peer has acknowledged.
+    /// Values of the string enums that have a table in them are written as IDs
+    pub fn with_string_ids(mut self, string_ids: StringIds) -> Self {
+        Arc::make_mut(self.connection.get_or_insert_with(Default::default)).local_string_ids = Some(string_ids);
+        self
+    }
+
+    /// The shared connection context if one was set
+    pub fn connection(&self) -> Option<&ConnectionContext> { self.connection.as_deref() }
+
+    /// The string IDs this side sent which the peer has acknowledged
+    pub fn string_ids(&self) -> Option<&StringIds> {
+        self.connection.as_ref()?.local_string_ids.as_ref()
+    }
+}
+
+/// Checks that a string with the provided length is within the limits of [ctx]
+pub(crate) fn check_string_length(ctx: &ReadContext, length: usize) -> ReadResult<()> {
+    let max_length = ctx.limits.map_or_else(|| MAX_STRING_LENGTH.load(Ordering::Relaxed), |limits| limits.max_string_length);
+    if length > max_length {
+        Err(PacketError::InvalidStringLength(length, max_length))?;
+    }
+    Ok(())
+}
+
+/// Checks that a collection with the provided length is within the limits of [ctx]
+pub(crate) fn check_collection_length(ctx: &ReadContext, length: usize) -> ReadResult<()> {
+    let max_length = ctx.limits.map_or_else(|| MAX_COLLECTION_LENGTH.load(Ordering::Relaxed), |limits| limits.max_collection_length);
+    if length > max_length {
+        Err(PacketError::CollectionTooLarge(length, max_length))?;
+    }
+    Ok(())
+}
+
+/// Reads [length] bytes from the source [i] in chunks so the buffer only
+/// grows as the bytes are received rather than all at once
+pub(crate) fn read_chunked<B: Read>(i: &mut B, length: usize) -> ReadResult<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(length.min(CHUNK_SIZE));
+    while bytes.len() < length {
+        let start = bytes.len();
+        let end = length.min(start + CHUNK_SIZE);
+        bytes.resize(end, 0);
+        i.read_exact(&mut bytes[start..end])?;
+    }
+    Ok(bytes)
+}
+
+/// ## Limited Reader
+/// Reader which fails once more than the provided number of bytes have been read
+struct LimitedReader<'a, B: Read> {
+    inner: CountingReader<&'a mut B>,
+    max_size: usize,
+    exceeded: bool,
+}
+
+impl<B: Read> LimitedReader<'_, B> {
+    /// The number of bytes that can still be read
+    fn remaining(&self) -> usize {
+        self.max_size.saturating_sub(self.inner.position())
+    }
+
+    /// Marks the max size as exceeded returning the error for the read
+    fn exceed(&mut self) -> IoError {
+        self.exceeded = true;
+        unexpected_eof()
+    }
+}
+
+#[cfg(feature = "std")]
+fn unexpected_eof() -> IoError { IoError::from(std::io::ErrorKind::UnexpectedEof) }
+
+#[cfg(not(feature = "std"))]
+fn unexpected_eof() -> IoError { IoError::UnexpectedEof }
+
+#[cfg(feature = "std")]
+impl<B: Read> Read for LimitedReader<'_, B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        // One byte past the max size is read so that going over it can be told
+        // apart from the source ending
+        let remaining = self.remaining();
+        let length = buf.len().min(remaining.max(1));
+        let count = self.inner.read(&mut buf[..length])?;
+        if count > remaining {
+            return Err(self.exceed());
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: Read> Read for LimitedReader<'_, B> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), IoError> {
+        if buf.len() > self.remaining() {
+            return Err(self.exceed());
+        }
+        self.inner.read_exact(buf)
+    }
+}
+
+/// ## Read Limited
+/// Reads a value (usually a packet group) from the source [i] failing with
+/// PacketError::PacketTooLarge if more than the global max_packet_size
+/// bytes are read
+pub fn read_limited<T: Readable, B: Read>(i: &mut B) -> ReadResult<T> {
+    read_limited_with(&mut ReadContext::default(), i)
+}
+
+/// ## Read Limited With
+/// Reads a value from the source [i] using the context [ctx] failing with
+/// PacketError::PacketTooLarge if more than the max_packet_size of the context
+/// limits are read
+pub fn read_limited_with<T: Readable, B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<T> {
+    let max_size = ctx.limits.map_or_else(|| MAX_PACKET_SIZE.load(Ordering::Relaxed), |limits| limits.max_packet_size);
+    let mut reader = LimitedReader { inner: CountingReader::new(i), max_size, exceeded: false };
+    match T::read_with(ctx, &mut reader) {
+        Err(_) if reader.exceeded => Err(PacketError::PacketTooLarge(max_size)),
+        result => result
+    }
+}
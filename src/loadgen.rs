@@ -0,0 +1,200 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::io::Writable;
+
+/// ## Load Transport
+/// The connection used by one simulated client of a LoadGen. Each packet is sent
+/// with round_trip which returns once the server has handled it (e.g. when its
+/// response has been received) so the time it takes is the latency
+pub trait LoadTransport: Send {
+    /// Sends the encoded [packet] and waits for the server to handle it
+    fn round_trip(&mut self, packet: &[u8]) -> io::Result<()>;
+}
+
+/// ## Memory Transport
+/// In-memory transport which passes each packet straight to a handler (e.g. the
+/// server's packet handler) for measuring the handling without any network
+pub struct MemoryTransport<F>(pub F);
+
+impl<F: FnMut(&[u8]) -> io::Result<()> + Send> LoadTransport for MemoryTransport<F> {
+    fn round_trip(&mut self, packet: &[u8]) -> io::Result<()> {
+        (self.0)(packet)
+    }
+}
+
+/// ## Stream Transport
+/// Transport for real connections (e.g. a TcpStream) which writes each packet to
+/// the stream then waits for the server's response using [F] which should read one
+/// response from the stream
+pub struct StreamTransport<S, F> {
+    stream: S,
+    read_response: F,
+}
+
+impl<S, F> StreamTransport<S, F> {
+    /// Creates a transport over the [stream] which reads each response using
+    /// [read_response]
+    pub fn new(stream: S, read_response: F) -> Self {
+        StreamTransport { stream, read_response }
+    }
+}
+
+impl<S, F> LoadTransport for StreamTransport<S, F>
+    where S: io::Read + Write + Send,
+          F: FnMut(&mut S) -> io::Result<()> + Send {
+    fn round_trip(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.stream.write_all(packet)?;
+        self.stream.flush()?;
+        (self.read_response)(&mut self.stream)
+    }
+}
+
+/// Creates a packet for the mix of a LoadGen
+type PacketFactory<G> = Arc<dyn Fn() -> G + Send + Sync>;
+
+/// ## Load Gen
+/// Capacity testing tool which runs a number of simulated clients on their own
+/// threads each sending a weighted mix of packets at a target rate for a duration
+/// and reports the throughput and latency percentiles
+pub struct LoadGen<G> {
+    clients: usize,
+    rate: f64,
+    duration: Duration,
+    mix: Vec<(u32, PacketFactory<G>)>,
+    seed: u64,
+}
+
+impl<G: Writable + 'static> LoadGen<G> {
+    /// Creates a load test of [clients] clients which each send [rate] packets per
+    /// second for the [duration]
+    pub fn new(clients: usize, rate: f64, duration: Duration) -> Self {
+        LoadGen { clients, rate, duration, mix: Vec::new(), seed: 0 }
+    }
+
+    /// Adds a packet created by [packet] to the mix which is sent in proportion to
+    /// its [weight] compared to the other packets
+    pub fn packet<F>(mut self, weight: u32, packet: F) -> Self
+        where F: Fn() -> G + Send + Sync + 'static {
+        self.mix.push((weight, Arc::new(packet)));
+        self
+    }
+
+    /// Sets the seed that the packets are picked from the mix with
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs the load test connecting each client using [connect] which is given the
+    /// index of the client and returns the report once all the clients finish
+    pub fn run<T, C>(&self, connect: C) -> io::Result<LoadReport>
+        where T: LoadTransport + 'static,
+              C: Fn(usize) -> io::Result<T> {
+        let total_weight: u64 = self.mix.iter().map(|(weight, _)| u64::from(*weight)).sum();
+        if total_weight == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the packet mix is empty"));
+        }
+        let interval = Duration::from_secs_f64(1.0 / self.rate.max(f64::MIN_POSITIVE));
+        let started = Instant::now();
+        let mut handles = Vec::with_capacity(self.clients);
+        for index in 0..self.clients {
+            let mut transport = connect(index)?;
+            let mix = self.mix.clone();
+            let duration = self.duration;
+            let mut state = self.seed ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            handles.push(thread::spawn(move || {
+                let mut result = ClientResult::default();
+                let start = Instant::now();
+                let mut next = start;
+                while next.duration_since(start) < duration {
+                    let now = Instant::now();
+                    if next > now {
+                        thread::sleep(next - now);
+                    }
+                    next += interval;
+                    let mut packet = pick(&mix, total_weight, &mut state)();
+                    let mut bytes = Vec::new();
+                    if packet.write(&mut bytes).is_err() {
+                        result.errors += 1;
+                        continue;
+                    }
+                    let sent = Instant::now();
+                    match transport.round_trip(&bytes) {
+                        Ok(()) => result.latencies.push(sent.elapsed()),
+                        Err(_) => result.errors += 1,
+                    }
+                }
+                result
+            }));
+        }
+        let mut latencies = Vec::new();
+        let mut errors = 0;
+        for handle in handles {
+            let result = handle.join()
+                .map_err(|_| io::Error::other("a load test client panicked"))?;
+            latencies.extend(result.latencies);
+            errors += result.errors;
+        }
+        latencies.sort_unstable();
+        Ok(LoadReport { elapsed: started.elapsed(), latencies, errors })
+    }
+}
+
+/// The packets sent by one client
+#[derive(Default)]
+struct ClientResult {
+    latencies: Vec<Duration>,
+    errors: u64,
+}
+
+/// Picks a packet factory from the [mix] by weight using the SplitMix64 [state]
+fn pick<'a, G>(mix: &'a [(u32, PacketFactory<G>)], total_weight: u64, state: &mut u64) -> &'a PacketFactory<G> {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    let mut target = (z ^ (z >> 31)) % total_weight;
+    for (weight, factory) in mix {
+        let weight = u64::from(*weight);
+        if target < weight {
+            return factory;
+        }
+        target -= weight;
+    }
+    &mix[mix.len() - 1].1
+}
+
+/// ## Load Report
+/// The results of a LoadGen run
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadReport {
+    /// The time the whole run took
+    pub elapsed: Duration,
+    /// The latency of every successful round trip sorted from fastest to slowest
+    pub latencies: Vec<Duration>,
+    /// The number of packets that failed to encode or send
+    pub errors: u64,
+}
+
+impl LoadReport {
+    /// The number of packets that were handled successfully
+    pub fn handled(&self) -> usize { self.latencies.len() }
+
+    /// The number of packets handled per second
+    pub fn throughput(&self) -> f64 {
+        self.latencies.len() as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+
+    /// The latency that [percentile] percent of the round trips were faster than or
+    /// equal to (e.g. 99.0 for the p99 latency)
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        Some(self.latencies[rank.clamp(1, self.latencies.len()) - 1])
+    }
+}
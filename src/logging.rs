@@ -0,0 +1,21 @@
+use core::fmt::Debug;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// ## Log Fields
+/// Trait for extracting the fields of a packet that should be attached to log lines,
+/// tracing spans and audit records (e.g. user_id or room_id) so that handlers don't
+/// have to repeat the extraction. This is implemented for the generated structs and
+/// groups providing the fields marked with the #[log] attribute
+pub trait LogFields {
+    /// Calls [visit] with the name and value of each field marked with #[log]
+    fn visit_log_fields(&self, visit: &mut dyn FnMut(&'static str, &dyn Debug));
+}
+
+/// The name and Debug output of each of the fields of the [value] marked with #[log]
+pub fn log_fields<T: LogFields + ?Sized>(value: &T) -> Vec<(&'static str, String)> {
+    let mut fields = Vec::new();
+    value.visit_log_fields(&mut |name, value| fields.push((name, format!("{:?}", value))));
+    fields
+}
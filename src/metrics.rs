@@ -0,0 +1,142 @@
+/// ## Metric ID
+/// Creates a stable numeric ID for the provided metric key using the 64-bit
+/// FNV-1a hash. The same key will always produce the same ID regardless of
+/// the build or platform so these IDs can be stored by external systems.
+pub const fn metric_id(key: &str) -> u64 {
+    let bytes = key.as_bytes();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash ^= bytes[index] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        index += 1;
+    }
+    hash
+}
+
+/// The counters of a packet type in PacketMetrics
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacketCounters {
+    /// The number of packets received
+    pub received: u64,
+    /// The number of bytes received
+    pub received_bytes: u64,
+    /// The number of packets sent
+    pub sent: u64,
+    /// The number of bytes sent
+    pub sent_bytes: u64,
+}
+
+/// ## Packet Metrics
+/// Collects packet and connection metrics labeled with the packet metric keys so
+/// small servers can expose them to Prometheus with metrics_text without wiring up
+/// a full metrics stack. Servers handling connections on multiple threads can share
+/// it in a Mutex
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PacketMetrics {
+    packets: alloc::collections::BTreeMap<&'static str, PacketCounters>,
+    decode_errors: u64,
+    connections_open: u64,
+    connections_total: u64,
+}
+
+#[cfg(feature = "metrics")]
+impl PacketMetrics {
+    /// Creates empty metrics
+    pub fn new() -> Self {
+        PacketMetrics::default()
+    }
+
+    /// Adds the packet metric [keys] (e.g. ServerPackets::METRIC_KEYS) so their
+    /// counters are exposed as zero before any packets are handled
+    pub fn register(&mut self, keys: &[&'static str]) -> &mut Self {
+        for key in keys {
+            self.packets.entry(key).or_default();
+        }
+        self
+    }
+
+    /// Records a received packet with the metric [key] (e.g. packet.metric_key())
+    /// that was [bytes] long
+    pub fn record_received(&mut self, key: &'static str, bytes: usize) {
+        let counters = self.packets.entry(key).or_default();
+        counters.received += 1;
+        counters.received_bytes += bytes as u64;
+    }
+
+    /// Records a sent packet with the metric [key] that was [bytes] long
+    pub fn record_sent(&mut self, key: &'static str, bytes: usize) {
+        let counters = self.packets.entry(key).or_default();
+        counters.sent += 1;
+        counters.sent_bytes += bytes as u64;
+    }
+
+    /// Records a packet that failed to decode
+    pub fn record_decode_error(&mut self) {
+        self.decode_errors += 1;
+    }
+
+    /// Records a new connection
+    pub fn connection_opened(&mut self) {
+        self.connections_open += 1;
+        self.connections_total += 1;
+    }
+
+    /// Records a connection closing
+    pub fn connection_closed(&mut self) {
+        self.connections_open = self.connections_open.saturating_sub(1);
+    }
+
+    /// The counters of the packet type with the metric [key]
+    pub fn packet(&self, key: &str) -> PacketCounters {
+        self.packets.get(key).copied().unwrap_or_default()
+    }
+
+    /// The number of packets that failed to decode
+    pub fn decode_errors(&self) -> u64 { self.decode_errors }
+
+    /// The number of connections currently open
+    pub fn connections_open(&self) -> u64 { self.connections_open }
+
+    /// The number of connections opened in total
+    pub fn connections_total(&self) -> u64 { self.connections_total }
+
+    /// ## Metrics Text
+    /// The metrics in the Prometheus text exposition format to be served as the
+    /// response of the metrics endpoint (Content-Type `text/plain; version=0.0.4`)
+    pub fn metrics_text(&self) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let mut out = alloc::string::String::new();
+        let packet_metrics = [
+            ("wsbps_packets_received_total", "Packets received by type"),
+            ("wsbps_packet_bytes_received_total", "Bytes of packets received by type"),
+            ("wsbps_packets_sent_total", "Packets sent by type"),
+            ("wsbps_packet_bytes_sent_total", "Bytes of packets sent by type"),
+        ];
+        for (index, (name, help)) in packet_metrics.into_iter().enumerate() {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+            for (key, counters) in &self.packets {
+                let values = [counters.received, counters.received_bytes, counters.sent, counters.sent_bytes];
+                let _ = writeln!(out, "{}{{packet=\"{}\"}} {}", name, escape_label(key), values[index]);
+            }
+        }
+        let totals = [
+            ("wsbps_decode_errors_total", "Packets that failed to decode", "counter", self.decode_errors),
+            ("wsbps_connections_open", "Connections currently open", "gauge", self.connections_open),
+            ("wsbps_connections_total", "Connections opened", "counter", self.connections_total),
+        ];
+        for (name, help, kind, value) in totals {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} {}\n{} {}", name, help, name, kind, name, value);
+        }
+        out
+    }
+}
+
+/// Escapes the backslashes, quotes and new lines in a Prometheus label [value]
+#[cfg(feature = "metrics")]
+fn escape_label(value: &str) -> alloc::string::String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,556 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::codegen::{is_literal, strip_literal_suffix};
+use crate::error::PacketError;
+use crate::io::{Readable, ReadResult, VarInt, VarLong, Writable};
+use crate::limits::{check_collection_length, check_string_length, ReadContext};
+use crate::schema::{Definition, DefinitionKind, FieldSchema, GroupSchema, PacketSchema, TypeSchema};
+
+/// Where the value of a field of the new version of a packet comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldSource {
+    /// The field of the old version with the same name which is encoded the same
+    Old,
+    /// The default value of the field (`field: u32 = 5`) or the default of its type
+    Default,
+    /// The field isn't in the old version (or is encoded differently) and its type
+    /// has no default value (structs, enums, results and custom codecs)
+    Missing,
+}
+
+/// A field of the new version of a packet and where its value comes from
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMigration {
+    /// The field in the new version
+    pub field: FieldSchema,
+    /// Where the value of the field comes from
+    pub source: FieldSource,
+}
+
+/// ## Packet Migration
+/// How a packet in both versions of a group is translated. The packet is matched by
+/// name so its ID can change between the versions
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketMigration {
+    /// The name of the packet
+    pub name: &'static str,
+    /// The ID of the packet in the old version
+    pub old_id: u32,
+    /// The ID of the packet in the new version
+    pub new_id: u32,
+    /// The fields of the old version which are read from the old frames
+    pub old_fields: Vec<FieldSchema>,
+    /// The fields of the new version in order
+    pub fields: Vec<FieldMigration>,
+}
+
+impl PacketMigration {
+    /// Whether the packet can be translated which requires every new field to have a
+    /// value and every old field to be readable (no custom codecs)
+    pub fn is_supported(&self, old_definitions: &[Definition]) -> bool {
+        self.fields.iter().all(|field| field.source != FieldSource::Missing)
+            && !self.old_fields.iter().any(|field| is_custom(&field.ty, old_definitions, &mut Vec::new()))
+    }
+}
+
+/// ## Schema Migration
+/// The differences between an old and a new version of a packet group used to
+/// translate frames encoded using the old version into the new version so clients
+/// can be updated after the server without the server handling both versions.
+/// Fields are copied when the old version has a field with the same name and
+/// encoding, new fields use their default value and removed fields are dropped.
+/// Packets removed from the new version translate to nothing. Swapping the versions
+/// gives the migration for sending packets to old clients. This is used by
+/// migrate in Rust and by js_shim and kotlin_shim to generate the same translation
+/// for other languages
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaMigration {
+    /// The name of the group
+    pub name: &'static str,
+    /// The packets which are in both versions
+    pub packets: Vec<PacketMigration>,
+    /// The packets of the old version which aren't in the new version
+    pub removed: Vec<PacketSchema>,
+    /// The structs and enums used by the old version
+    pub old_definitions: Vec<Definition>,
+    /// The structs and enums used by the new version
+    pub new_definitions: Vec<Definition>,
+}
+
+impl SchemaMigration {
+    /// Creates the migration from the [old] version of a group to the [new] version
+    pub fn new(old: &GroupSchema, new: &GroupSchema) -> Self {
+        let mut packets = Vec::new();
+        for packet in &new.packets {
+            let Some(old_packet) = old.packets.iter().find(|old_packet| old_packet.name == packet.name) else {
+                continue;
+            };
+            let mut fields: Vec<FieldMigration> = packet.fields.iter()
+                .map(|field| {
+                    let copied = old_packet.fields.iter().any(|old_field| {
+                        old_field.name == field.name
+                            && old_field.length_from == field.length_from
+                            && old_field.when == field.when
+                            && old_field.default.is_some() == field.default.is_some()
+                            && same_encoding(&old_field.ty, &old.definitions, &field.ty, &new.definitions, &mut Vec::new())
+                    });
+                    let source = if copied {
+                        FieldSource::Old
+                    } else if has_default(&field.ty) {
+                        FieldSource::Default
+                    } else {
+                        FieldSource::Missing
+                    };
+                    FieldMigration { field: field.clone(), source }
+                })
+                .collect();
+            // Fields and their length fields have to both be copied so the length
+            // matches the value
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for index in 0..fields.len() {
+                    let Some(length_from) = fields[index].field.length_from else { continue };
+                    let Some(length) = fields.iter().position(|field| field.field.name == length_from) else { continue };
+                    let copied = fields[index].source == FieldSource::Old;
+                    if copied != (fields[length].source == FieldSource::Old) {
+                        let target = if copied { index } else { length };
+                        fields[target].source = if has_default(&fields[target].field.ty) {
+                            FieldSource::Default
+                        } else {
+                            FieldSource::Missing
+                        };
+                        changed = true;
+                    }
+                }
+            }
+            packets.push(PacketMigration {
+                name: packet.name,
+                old_id: old_packet.id,
+                new_id: packet.id,
+                old_fields: old_packet.fields.clone(),
+                fields,
+            });
+        }
+        let removed = old.packets.iter()
+            .filter(|packet| !new.packets.iter().any(|new_packet| new_packet.name == packet.name))
+            .cloned()
+            .collect();
+        SchemaMigration {
+            name: new.name,
+            packets,
+            removed,
+            old_definitions: old.definitions.clone(),
+            new_definitions: new.definitions.clone(),
+        }
+    }
+
+    /// The names of the packets which can't be translated
+    pub fn unsupported(&self) -> Vec<&'static str> {
+        self.packets.iter()
+            .filter(|packet| !packet.is_supported(&self.old_definitions))
+            .map(|packet| packet.name)
+            .collect()
+    }
+
+    /// Translates the [frame] of a packet encoded using the old version into the new
+    /// version returning None if the packet was removed. Fails with
+    /// PacketError::UnsupportedMigration for packets which can't be translated
+    pub fn migrate(&self, frame: &[u8]) -> ReadResult<Option<Vec<u8>>> {
+        let mut i = frame;
+        let id = VarInt::read(&mut i)?.0;
+        if self.removed.iter().any(|packet| packet.id == id) {
+            return Ok(None);
+        }
+        let packet = self.packets.iter()
+            .find(|packet| packet.old_id == id)
+            .ok_or(PacketError::UnknownPacket(id))?;
+        if !packet.is_supported(&self.old_definitions) {
+            return Err(PacketError::UnsupportedMigration(packet.name));
+        }
+
+        let mut ctx = ReadContext::default();
+        let old_values = read_fields(&mut ctx, &packet.old_fields, &self.old_definitions, &mut i)?;
+        let old_value = |name: &str| {
+            packet.old_fields.iter().position(|field| field.name == name).and_then(|index| old_values.get(index))
+        };
+
+        let mut out = Vec::with_capacity(frame.len());
+        write_value(VarInt(packet.new_id), &mut out);
+        let mut scalars: Vec<Option<u64>> = Vec::with_capacity(packet.fields.len());
+        for migration in &packet.fields {
+            let field = &migration.field;
+            if let Some(condition) = field.when {
+                if !is_set(&packet.fields, &scalars, condition) {
+                    scalars.push(None);
+                    continue;
+                }
+            }
+            let scalar = match old_value(field.name) {
+                Some(FieldValue { bytes: Some(bytes), scalar }) if migration.source == FieldSource::Old => {
+                    out.extend_from_slice(bytes);
+                    *scalar
+                }
+                _ => write_default(field, &mut out).ok_or(PacketError::UnsupportedMigration(packet.name))?,
+            };
+            scalars.push(scalar);
+        }
+        Ok(Some(out))
+    }
+}
+
+/// The bytes of a field read from an old frame along with its value when it is an
+/// integer or bool (which is needed for length and condition fields)
+#[derive(Debug, Clone, Copy)]
+struct FieldValue<'a> {
+    /// The encoded field which is None when the field wasn't present
+    bytes: Option<&'a [u8]>,
+    scalar: Option<u64>,
+}
+
+/// Whether the bool field with the [name] in [fields] has been given a true value
+fn is_set(fields: &[FieldMigration], scalars: &[Option<u64>], name: &str) -> bool {
+    fields.iter()
+        .position(|field| field.field.name == name)
+        .and_then(|index| scalars.get(index).copied().flatten())
+        .is_some_and(|value| value != 0)
+}
+
+/// Writes the [value] to the vec [out]
+fn write_value<T: Writable>(mut value: T, out: &mut Vec<u8>) {
+    value.write(out).expect("writing to a vec can't fail");
+}
+
+/// Reads past the [fields] in the source [i] returning the bytes and values of each
+fn read_fields<'a>(
+    ctx: &mut ReadContext,
+    fields: &[FieldSchema],
+    definitions: &[Definition],
+    i: &mut &'a [u8],
+) -> ReadResult<Vec<FieldValue<'a>>> {
+    let mut values: Vec<FieldValue<'a>> = Vec::with_capacity(fields.len());
+    let scalar = |values: &[FieldValue], name: &str| {
+        fields.iter().position(|field| field.name == name).and_then(|index| values[index].scalar)
+    };
+    for field in fields {
+        let absent = FieldValue { bytes: None, scalar: None };
+        if let Some(condition) = field.when {
+            if scalar(&values, condition).unwrap_or(0) == 0 {
+                values.push(absent);
+                continue;
+            }
+        }
+        if field.default.is_some() && i.is_empty() {
+            values.push(absent);
+            continue;
+        }
+        let length = field.length_from.map(|length_from| scalar(&values, length_from).unwrap_or(0) as usize);
+        let start = *i;
+        let value = skip(ctx, &field.ty, definitions, length, i)?;
+        values.push(FieldValue { bytes: Some(&start[..start.len() - i.len()]), scalar: value });
+    }
+    Ok(values)
+}
+
+/// Reads past a value of the type [ty] in the source [i] returning the value when it
+/// is an integer or bool. Strings and lists use the [length] rather than a length
+/// prefix when it is provided
+fn skip(
+    ctx: &mut ReadContext,
+    ty: &TypeSchema,
+    definitions: &[Definition],
+    length: Option<usize>,
+    i: &mut &[u8],
+) -> ReadResult<Option<u64>> {
+    let prefixed = |i: &mut &[u8]| -> ReadResult<usize> {
+        match length {
+            Some(length) => Ok(length),
+            None => Ok(VarInt::read(i)?.0 as usize),
+        }
+    };
+    Ok(match ty {
+        TypeSchema::Bool => Some(bool::read(i)? as u64),
+        TypeSchema::U8 => Some(u8::read(i)? as u64),
+        TypeSchema::U16 => Some(u16::read(i)? as u64),
+        TypeSchema::U32 => Some(u32::read(i)? as u64),
+        TypeSchema::U64 => Some(u64::read(i)?),
+        TypeSchema::I8 => Some(i8::read(i)? as u64),
+        TypeSchema::I16 => Some(i16::read(i)? as u64),
+        TypeSchema::I32 => Some(i32::read(i)? as u64),
+        TypeSchema::I64 => Some(i64::read(i)? as u64),
+        TypeSchema::F32 => {
+            f32::read(i)?;
+            None
+        }
+        TypeSchema::F64 => {
+            f64::read(i)?;
+            None
+        }
+        TypeSchema::VarInt => Some(VarInt::read(i)?.0 as u64),
+        TypeSchema::VarLong => Some(VarLong::read(i)?.0),
+        TypeSchema::String => {
+            let length = prefixed(i)?;
+            check_string_length(ctx, length)?;
+            advance(i, length)?;
+            None
+        }
+        TypeSchema::List(item) => {
+            let length = prefixed(i)?;
+            check_collection_length(ctx, length)?;
+            if **item == TypeSchema::U8 {
+                advance(i, length)?;
+            } else {
+                ctx.enter(|ctx| (0..length).try_for_each(|_| skip(ctx, item, definitions, None, i).map(|_| ())))?;
+            }
+            None
+        }
+        TypeSchema::Map(key, value) => {
+            let length = prefixed(i)?;
+            check_collection_length(ctx, length)?;
+            ctx.enter(|ctx| (0..length).try_for_each(|_| {
+                skip(ctx, key, definitions, None, i)?;
+                skip(ctx, value, definitions, None, i).map(|_| ())
+            }))?;
+            None
+        }
+        TypeSchema::Option(value) => {
+            if bool::read(i)? {
+                ctx.enter(|ctx| skip(ctx, value, definitions, None, i))?;
+            }
+            None
+        }
+        TypeSchema::Result(ok, err) => {
+            let value = match u8::read(i)? {
+                0 => ok,
+                1 => err,
+                _ => return Err(PacketError::UnexpectedValue("0 or 1 for result tag")),
+            };
+            ctx.enter(|ctx| skip(ctx, value, definitions, None, i))?;
+            None
+        }
+        TypeSchema::Unit => None,
+        TypeSchema::Named(name) => {
+            match find_definition(definitions, name).ok_or(PacketError::UnsupportedMigration(name))? {
+                DefinitionKind::Struct(fields) => {
+                    ctx.enter(|ctx| read_fields(ctx, fields, definitions, i))?;
+                }
+                DefinitionKind::Enum { repr, .. } => {
+                    skip(ctx, repr, definitions, None, i)?;
+                }
+            }
+            None
+        }
+        TypeSchema::Custom(codec) => return Err(PacketError::UnsupportedMigration(codec)),
+    })
+}
+
+/// Moves the source [i] forward by [length] bytes
+fn advance(i: &mut &[u8], length: usize) -> ReadResult<()> {
+    if length > i.len() {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    *i = &i[length..];
+    Ok(())
+}
+
+/// Writes the default value of the [field] to [out] returning the value when it is
+/// an integer or bool. Returns None if the type of the field has no default value
+fn write_default(field: &FieldSchema, out: &mut Vec<u8>) -> Option<Option<u64>> {
+    let literal = field.default
+        .map(str::trim)
+        .filter(|source| is_literal(source))
+        .map(strip_literal_suffix);
+    let integer = literal.and_then(parse_integer).unwrap_or(0);
+    let float = literal.and_then(|literal| literal.replace('_', "").parse::<f64>().ok()).unwrap_or(0.0);
+    Some(match &field.ty {
+        TypeSchema::Bool => {
+            let value = literal == Some("true");
+            write_value(value, out);
+            Some(value as u64)
+        }
+        TypeSchema::U8 => {
+            write_value(integer as u8, out);
+            Some(integer as u8 as u64)
+        }
+        TypeSchema::U16 => {
+            write_value(integer as u16, out);
+            Some(integer as u16 as u64)
+        }
+        TypeSchema::U32 => {
+            write_value(integer as u32, out);
+            Some(integer as u32 as u64)
+        }
+        TypeSchema::U64 => {
+            write_value(integer as u64, out);
+            Some(integer as u64)
+        }
+        TypeSchema::I8 => {
+            write_value(integer as i8, out);
+            Some(integer as i8 as u64)
+        }
+        TypeSchema::I16 => {
+            write_value(integer as i16, out);
+            Some(integer as i16 as u64)
+        }
+        TypeSchema::I32 => {
+            write_value(integer as i32, out);
+            Some(integer as i32 as u64)
+        }
+        TypeSchema::I64 => {
+            write_value(integer as i64, out);
+            Some(integer as i64 as u64)
+        }
+        TypeSchema::F32 => {
+            write_value(float as f32, out);
+            None
+        }
+        TypeSchema::F64 => {
+            write_value(float, out);
+            None
+        }
+        TypeSchema::VarInt => {
+            write_value(VarInt(integer as u32), out);
+            Some(integer as u32 as u64)
+        }
+        TypeSchema::VarLong => {
+            write_value(VarLong(integer as u64), out);
+            Some(integer as u64)
+        }
+        TypeSchema::String => {
+            let value = literal
+                .and_then(|literal| literal.strip_prefix('"')?.strip_suffix('"'))
+                .map(unescape)
+                .unwrap_or_default();
+            if field.length_from.is_none() {
+                write_value(VarInt(value.len() as u32), out);
+            }
+            out.extend_from_slice(value.as_bytes());
+            None
+        }
+        // Empty collections are a zero length and None is a false bool
+        TypeSchema::List(_) | TypeSchema::Map(_, _) | TypeSchema::Option(_) => {
+            if field.length_from.is_none() {
+                out.push(0);
+            }
+            None
+        }
+        TypeSchema::Unit => None,
+        TypeSchema::Result(_, _) | TypeSchema::Named(_) | TypeSchema::Custom(_) => return None,
+    })
+}
+
+/// Whether the type [ty] has a default value which can be written by write_default
+fn has_default(ty: &TypeSchema) -> bool {
+    !matches!(ty, TypeSchema::Result(_, _) | TypeSchema::Named(_) | TypeSchema::Custom(_))
+}
+
+/// Parses an integer literal in decimal, hex, octal or binary
+fn parse_integer(literal: &str) -> Option<i128> {
+    let literal = literal.replace('_', "");
+    let (negative, digits) = match literal.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, literal.as_str()),
+    };
+    let value = if let Some(digits) = digits.strip_prefix("0x") {
+        i128::from_str_radix(digits, 16)
+    } else if let Some(digits) = digits.strip_prefix("0o") {
+        i128::from_str_radix(digits, 8)
+    } else if let Some(digits) = digits.strip_prefix("0b") {
+        i128::from_str_radix(digits, 2)
+    } else {
+        digits.parse()
+    }.ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Replaces the common escapes in the contents of a string literal
+fn unescape(literal: &str) -> String {
+    let mut value = String::with_capacity(literal.len());
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some('0') => value.push('\0'),
+            Some(c) => value.push(c),
+            None => {}
+        }
+    }
+    value
+}
+
+/// The fields or variants of the struct or enum with the [name]
+fn find_definition<'a>(definitions: &'a [Definition], name: &str) -> Option<&'a DefinitionKind> {
+    definitions.iter().find(|definition| definition.name == name).map(|definition| &definition.kind)
+}
+
+/// Whether the type [ty] is or contains a field with a custom codec
+fn is_custom(ty: &TypeSchema, definitions: &[Definition], visited: &mut Vec<&'static str>) -> bool {
+    match ty {
+        TypeSchema::Custom(_) => true,
+        TypeSchema::List(value) | TypeSchema::Option(value) => is_custom(value, definitions, visited),
+        TypeSchema::Map(first, second) | TypeSchema::Result(first, second) => {
+            is_custom(first, definitions, visited) || is_custom(second, definitions, visited)
+        }
+        TypeSchema::Named(name) => {
+            if visited.contains(name) {
+                return false;
+            }
+            visited.push(name);
+            match find_definition(definitions, name) {
+                Some(DefinitionKind::Struct(fields)) => fields.iter().any(|field| is_custom(&field.ty, definitions, visited)),
+                Some(DefinitionKind::Enum { .. }) => false,
+                None => true,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Whether the [old] type using the [old_definitions] is encoded the same as the [new]
+/// type using the [new_definitions]. Structs and enums must have the same name
+fn same_encoding(
+    old: &TypeSchema,
+    old_definitions: &[Definition],
+    new: &TypeSchema,
+    new_definitions: &[Definition],
+    visited: &mut Vec<&'static str>,
+) -> bool {
+    match (old, new) {
+        (TypeSchema::List(old), TypeSchema::List(new)) | (TypeSchema::Option(old), TypeSchema::Option(new)) => {
+            same_encoding(old, old_definitions, new, new_definitions, visited)
+        }
+        (TypeSchema::Map(old_key, old_value), TypeSchema::Map(new_key, new_value))
+        | (TypeSchema::Result(old_key, old_value), TypeSchema::Result(new_key, new_value)) => {
+            same_encoding(old_key, old_definitions, new_key, new_definitions, visited)
+                && same_encoding(old_value, old_definitions, new_value, new_definitions, visited)
+        }
+        (TypeSchema::Named(old), TypeSchema::Named(name)) if old == name => {
+            // Recursive types are assumed to match while their fields are compared
+            if visited.contains(name) {
+                return true;
+            }
+            visited.push(name);
+            match (find_definition(old_definitions, name), find_definition(new_definitions, name)) {
+                (Some(DefinitionKind::Struct(old)), Some(DefinitionKind::Struct(new))) => {
+                    old.len() == new.len() && old.iter().zip(new).all(|(old, new)| {
+                        old.name == new.name
+                            && old.length_from == new.length_from
+                            && old.when == new.when
+                            && old.default == new.default
+                            && same_encoding(&old.ty, old_definitions, &new.ty, new_definitions, visited)
+                    })
+                }
+                (Some(old @ DefinitionKind::Enum { .. }), Some(new)) => old == new,
+                _ => false,
+            }
+        }
+        (old, new) => old == new && !matches!(new, TypeSchema::Named(_) | TypeSchema::Custom(_)),
+    }
+}
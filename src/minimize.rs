@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+
+use crate::io::Readable;
+
+/// ## Minimize
+/// Reduces the provided [items] to a smaller list which still makes [fails] return
+/// true using delta debugging (ddmin). Chunks of the list are removed while the
+/// failure still reproduces, halving the chunk size each time no chunk can be
+/// removed, until no single item can be removed. The [items] must make [fails]
+/// return true to begin with otherwise they are returned unchanged
+pub fn minimize<T, F>(items: Vec<T>, mut fails: F) -> Vec<T>
+    where T: Clone,
+          F: FnMut(&[T]) -> bool {
+    if !fails(&items) {
+        return items;
+    }
+    let mut items = items;
+    let mut chunks = 2;
+    while items.len() >= 2 {
+        let chunk_size = items.len().div_ceil(chunks);
+        let mut removed = false;
+        let mut start = 0;
+        while start < items.len() {
+            let end = (start + chunk_size).min(items.len());
+            let candidate: Vec<T> = items[..start].iter()
+                .chain(&items[end..])
+                .cloned()
+                .collect();
+            if fails(&candidate) {
+                items = candidate;
+                removed = true;
+                chunks = (chunks - 1).max(2);
+            } else {
+                start = end;
+            }
+        }
+        if !removed {
+            if chunk_size == 1 {
+                break;
+            }
+            chunks = (chunks * 2).min(items.len());
+        }
+    }
+    items
+}
+
+/// Minimizes a failing capture of [frames] (e.g. websocket messages) to the shortest
+/// sequence of frames which still makes [fails] return true
+pub fn minimize_frames<F>(frames: Vec<Vec<u8>>, fails: F) -> Vec<Vec<u8>>
+    where F: FnMut(&[Vec<u8>]) -> bool {
+    minimize(frames, fails)
+}
+
+/// Minimizes a single failing [frame] to the fewest bytes which still make [fails]
+/// return true
+pub fn minimize_bytes<F>(frame: Vec<u8>, fails: F) -> Vec<u8>
+    where F: FnMut(&[u8]) -> bool {
+    minimize(frame, fails)
+}
+
+/// Whether decoding any of the provided [frames] as a packet of the group G fails.
+/// This is the usual predicate when minimizing a capture that fails to decode
+pub fn decode_fails<G: Readable>(frames: &[Vec<u8>]) -> bool {
+    frames.iter().any(|frame| G::read(&mut frame.as_slice()).is_err())
+}
@@ -0,0 +1,108 @@
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+
+/// ## Narrow
+/// A value that is encoded on the wire as the type W but is used as the smaller
+/// type L (e.g. a u32 on the wire that is used as a u16). Reading fails with
+/// PacketError::NarrowingOverflow when the decoded value doesn't fit in L rather
+/// than being silently truncated by an `as` cast.
+///
+/// ```
+/// use wsbps::Narrow;
+/// let port: Narrow<u32, u16> = Narrow::new(8080);
+/// assert_eq!(*port, 8080u16);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Narrow<W, L>(pub L, PhantomData<W>);
+
+impl<W, L> Narrow<W, L> {
+    /// Creates a new narrowed value
+    pub fn new(value: L) -> Self {
+        Narrow(value, PhantomData)
+    }
+
+    /// Consumes self returning the underlying value
+    pub fn into_inner(self) -> L {
+        self.0
+    }
+}
+
+impl<W, L> Deref for Narrow<W, L> {
+    type Target = L;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<W, L> From<L> for Narrow<W, L> { fn from(v: L) -> Self { Narrow::new(v) } }
+
+impl<W: Writable + TryFrom<L> + Send + Sync, L: Copy + Send + Sync> Writable for Narrow<W, L> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        W::try_from(self.0)
+            .map_err(|_| PacketError::NarrowingOverflow(core::any::type_name::<W>()))?
+            .write_with(ctx, o)
+    }
+}
+
+impl<W: Readable + Send + Sync, L: TryFrom<W> + Send + Sync> Readable for Narrow<W, L> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let value = W::read_with(ctx, i)?;
+        L::try_from(value)
+            .map(Narrow::new)
+            .map_err(|_| PacketError::NarrowingOverflow(core::any::type_name::<L>()))
+    }
+}
+
+impl<W, L: crate::OrderedRepr> crate::OrderedRepr for Narrow<W, L> {
+    fn write_ordered(&self, style: crate::OrderedStyle, out: &mut alloc::string::String) {
+        self.0.write_ordered(style, out)
+    }
+}
+
+impl<W, L: crate::Summary> crate::Summary for Narrow<W, L> {
+    fn write_summary(&self, out: &mut alloc::string::String) {
+        self.0.write_summary(out)
+    }
+}
+
+/// Narrowed values are described as the wire type W
+#[cfg(feature = "codegen")]
+impl<W: crate::Schema, L> crate::Schema for Narrow<W, L> {
+    fn type_schema() -> crate::TypeSchema { W::type_schema() }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::Definition>) { W::definitions(definitions) }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, W, L: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Narrow<W, L> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Narrow::new(L::arbitrary(u)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<W, L: serde::Serialize> serde::Serialize for Narrow<W, L> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, W, L: serde::Deserialize<'de>> serde::Deserialize<'de> for Narrow<W, L> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        L::deserialize(deserializer).map(Narrow::new)
+    }
+}
@@ -0,0 +1,415 @@
+use core::fmt::Write;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::boxed::Box;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::string::ToString;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::io::{VarInt, VarLong};
+
+/// The style of output produced by an ordered representation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderedStyle {
+    /// Output that looks like the derived Debug output
+    Debug,
+    /// Output that is valid JSON
+    Json,
+}
+
+/// ## Ordered Representation
+/// Trait for producing a representation of a value which is stable between runs
+/// and machines. Fields are written in the order they were declared and the entries
+/// of maps are sorted by their keys so that HashMap iteration order doesn't change
+/// the output. This makes diffing logs between runs meaningful.
+///
+/// This is implemented for all the generated packets, structs and enums when the
+/// "ordered" feature is enabled.
+pub trait OrderedRepr {
+    /// Appends the representation of self in the provided style to [out]
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String);
+}
+
+/// Creates the stable Debug style representation of the provided value
+pub fn to_ordered_debug<T: OrderedRepr + ?Sized>(value: &T) -> String {
+    let mut out = String::new();
+    value.write_ordered(OrderedStyle::Debug, &mut out);
+    out
+}
+
+/// Creates the stable JSON representation of the provided value
+pub fn to_ordered_json<T: OrderedRepr + ?Sized>(value: &T) -> String {
+    let mut out = String::new();
+    value.write_ordered(OrderedStyle::Json, &mut out);
+    out
+}
+
+/// Writes a struct with the provided fields in order. Debug style output looks like
+/// `Name { a: 1, b: 2 }` and JSON style output looks like `{"a":1,"b":2}`
+pub fn write_ordered_struct(style: OrderedStyle, out: &mut String, name: &str, fields: &[(&str, &dyn OrderedRepr)]) {
+    match style {
+        OrderedStyle::Debug => {
+            out.push_str(name);
+            if !fields.is_empty() {
+                out.push_str(" { ");
+                for (index, (field, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(field);
+                    out.push_str(": ");
+                    value.write_ordered(style, out);
+                }
+                out.push_str(" }");
+            }
+        }
+        OrderedStyle::Json => {
+            out.push('{');
+            for (index, (field, value)) in fields.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_json_string(out, field);
+                out.push(':');
+                value.write_ordered(style, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Writes a packet variant with the provided fields in order. Debug style output is
+/// the same as structs and JSON style output is tagged with the variant name
+/// `{"Name":{"a":1,"b":2}}`
+pub fn write_ordered_variant(style: OrderedStyle, out: &mut String, name: &str, fields: &[(&str, &dyn OrderedRepr)]) {
+    match style {
+        OrderedStyle::Debug => write_ordered_struct(style, out, name, fields),
+        OrderedStyle::Json => {
+            out.push('{');
+            write_json_string(out, name);
+            out.push(':');
+            write_ordered_struct(style, out, name, fields);
+            out.push('}');
+        }
+    }
+}
+
+/// Writes an enum variant wrapping a single value. Debug style output is the name of
+/// the variant followed by the value in parentheses and JSON style output is an object
+/// with the variant name as the only key
+pub fn write_ordered_newtype_variant(style: OrderedStyle, out: &mut String, name: &str, value: &dyn OrderedRepr) {
+    match style {
+        OrderedStyle::Debug => {
+            out.push_str(name);
+            out.push('(');
+            value.write_ordered(style, out);
+            out.push(')');
+        }
+        OrderedStyle::Json => {
+            out.push('{');
+            write_json_string(out, name);
+            out.push(':');
+            value.write_ordered(style, out);
+            out.push('}');
+        }
+    }
+}
+
+/// Writes a unit enum value. Debug style output is the name of the value and JSON
+/// style output is the name of the value as a string
+pub fn write_ordered_unit(style: OrderedStyle, out: &mut String, name: &str) {
+    match style {
+        OrderedStyle::Debug => out.push_str(name),
+        OrderedStyle::Json => write_json_string(out, name),
+    }
+}
+
+/// Writes the provided string as an escaped JSON string
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Macro for implementing the ordered representation for types whose Debug
+/// output is already valid JSON (numbers and booleans)
+macro_rules! impl_ordered_display {
+    ($($type:ty),*) => {
+        $(
+            impl OrderedRepr for $type {
+                fn write_ordered(&self, _style: OrderedStyle, out: &mut String) {
+                    let _ = write!(out, "{:?}", self);
+                }
+            }
+        )*
+    };
+}
+
+impl_ordered_display!(bool, u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// Floats that aren't finite can't be represented in JSON so they are written as null
+macro_rules! impl_ordered_float {
+    ($($type:ty),*) => {
+        $(
+            impl OrderedRepr for $type {
+                fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+                    if style == OrderedStyle::Json && !self.is_finite() {
+                        out.push_str("null");
+                    } else {
+                        let _ = write!(out, "{:?}", self);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_ordered_float!(f32, f64);
+
+impl OrderedRepr for VarInt {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        match style {
+            OrderedStyle::Debug => { let _ = write!(out, "{:?}", self); }
+            OrderedStyle::Json => self.0.write_ordered(style, out),
+        }
+    }
+}
+
+impl OrderedRepr for VarLong {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        match style {
+            OrderedStyle::Debug => { let _ = write!(out, "{:?}", self); }
+            OrderedStyle::Json => self.0.write_ordered(style, out),
+        }
+    }
+}
+
+impl OrderedRepr for str {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        match style {
+            OrderedStyle::Debug => { let _ = write!(out, "{:?}", self); }
+            OrderedStyle::Json => write_json_string(out, self),
+        }
+    }
+}
+
+impl OrderedRepr for String {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.as_str().write_ordered(style, out)
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for Vec<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.as_slice().write_ordered(style, out)
+    }
+}
+
+impl<T: OrderedRepr + ?Sized> OrderedRepr for Box<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        (**self).write_ordered(style, out)
+    }
+}
+
+impl<T: OrderedRepr + ?Sized> OrderedRepr for Arc<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        (**self).write_ordered(style, out)
+    }
+}
+
+impl<T: OrderedRepr + ToOwned + ?Sized> OrderedRepr for Cow<'_, T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        (**self).write_ordered(style, out)
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for [T] {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_list(style, out, self.iter())
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for Option<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        match (self, style) {
+            (Some(value), OrderedStyle::Debug) => {
+                out.push_str("Some(");
+                value.write_ordered(style, out);
+                out.push(')');
+            }
+            (Some(value), OrderedStyle::Json) => value.write_ordered(style, out),
+            (None, OrderedStyle::Debug) => out.push_str("None"),
+            (None, OrderedStyle::Json) => out.push_str("null"),
+        }
+    }
+}
+
+impl<T: OrderedRepr, E: OrderedRepr> OrderedRepr for Result<T, E> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        let (name, value): (&str, &dyn OrderedRepr) = match self {
+            Ok(value) => ("Ok", value),
+            Err(error) => ("Err", error),
+        };
+        match style {
+            OrderedStyle::Debug => {
+                out.push_str(name);
+                out.push('(');
+                value.write_ordered(style, out);
+                out.push(')');
+            }
+            OrderedStyle::Json => {
+                out.push_str("{\"");
+                out.push_str(name);
+                out.push_str("\":");
+                value.write_ordered(style, out);
+                out.push('}');
+            }
+        }
+    }
+}
+
+/// Macro for implementing OrderedRepr for the address types which are written
+/// using their Display implementation and as strings in JSON
+macro_rules! impl_ordered_address {
+    ($($type:ty),*) => {
+        $(
+            impl OrderedRepr for $type {
+                fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+                    match style {
+                        OrderedStyle::Debug => { let _ = write!(out, "{}", self); }
+                        OrderedStyle::Json => write_json_string(out, &self.to_string()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_ordered_address!(Ipv4Addr, Ipv6Addr, IpAddr, SocketAddr);
+
+impl OrderedRepr for () {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        match style {
+            OrderedStyle::Debug => out.push_str("()"),
+            OrderedStyle::Json => out.push_str("null"),
+        }
+    }
+}
+
+/// Writes the provided values as a list. Debug style output looks like `[1, 2]`
+/// and JSON style output looks like `[1,2]`
+fn write_ordered_list<'a, T: OrderedRepr + ?Sized + 'a>(style: OrderedStyle, out: &mut String, values: impl Iterator<Item=&'a T>) {
+    out.push('[');
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            out.push_str(if style == OrderedStyle::Json { "," } else { ", " });
+        }
+        value.write_ordered(style, out);
+    }
+    out.push(']');
+}
+
+/// Writes the values of a set as a list sorted by their representation
+fn write_ordered_set<'a, T: OrderedRepr + 'a>(style: OrderedStyle, out: &mut String, values: impl Iterator<Item=&'a T>) {
+    let mut values: Vec<String> = values
+        .map(|value| {
+            let mut value_out = String::new();
+            value.write_ordered(style, &mut value_out);
+            value_out
+        })
+        .collect();
+    values.sort();
+    out.push('[');
+    for (index, value) in values.into_iter().enumerate() {
+        if index > 0 {
+            out.push_str(if style == OrderedStyle::Json { "," } else { ", " });
+        }
+        out.push_str(&value);
+    }
+    out.push(']');
+}
+
+/// Map entries are sorted by the representation of their key. JSON object keys
+/// must be strings so keys which aren't represented as strings are quoted.
+fn write_ordered_map<'a, K: OrderedRepr + 'a, V: OrderedRepr + 'a>(style: OrderedStyle, out: &mut String, entries: impl Iterator<Item=(&'a K, &'a V)>) {
+    let mut entries: Vec<(String, &V)> = entries
+        .map(|(key, value)| {
+            let mut key_out = String::new();
+            key.write_ordered(style, &mut key_out);
+            (key_out, value)
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    out.push('{');
+    for (index, (key, value)) in entries.into_iter().enumerate() {
+        match style {
+            OrderedStyle::Debug => {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&key);
+                out.push_str(": ");
+            }
+            OrderedStyle::Json => {
+                if index > 0 {
+                    out.push(',');
+                }
+                if key.starts_with('"') {
+                    out.push_str(&key);
+                } else {
+                    write_json_string(out, &key);
+                }
+                out.push(':');
+            }
+        }
+        value.write_ordered(style, out);
+    }
+    out.push('}');
+}
+
+#[cfg(feature = "std")]
+impl<K: OrderedRepr, V: OrderedRepr> OrderedRepr for HashMap<K, V> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_map(style, out, self.iter())
+    }
+}
+
+impl<K: OrderedRepr, V: OrderedRepr> OrderedRepr for BTreeMap<K, V> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_map(style, out, self.iter())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: OrderedRepr> OrderedRepr for HashSet<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_set(style, out, self.iter())
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for BTreeSet<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_set(style, out, self.iter())
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for VecDeque<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_list(style, out, self.iter())
+    }
+}
@@ -0,0 +1,185 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::io::{PacketResult, Write, Writable};
+use crate::tick::OverflowPolicy;
+
+/// ## Queue Class
+/// A priority class of an OutboundQueue along with the limits on the packets
+/// waiting in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueClass {
+    /// The maximum number of packets waiting in the class
+    pub max_packets: usize,
+    /// The maximum number of encoded bytes waiting in the class
+    pub max_bytes: usize,
+    /// What happens to packets pushed when the class is full
+    pub overflow: OverflowPolicy,
+}
+
+impl QueueClass {
+    /// Creates a new class
+    pub fn new(max_packets: usize, max_bytes: usize, overflow: OverflowPolicy) -> Self {
+        QueueClass { max_packets, max_bytes, overflow }
+    }
+}
+
+/// A packet waiting in an OutboundQueue
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Queued {
+    key: Option<u32>,
+    frame: Vec<u8>,
+}
+
+/// A class and the packets waiting in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ClassQueue {
+    class: QueueClass,
+    frames: VecDeque<Queued>,
+    bytes: usize,
+}
+
+/// ## Outbound Queue
+/// Bounded queue for the packets waiting to be written to a peer so a slow peer
+/// can't cause unbounded memory growth. Packets are encoded when pushed and sorted
+/// into priority classes (the first class has the highest priority) each with its
+/// own size caps. Packets which supersede an earlier queued packet (e.g. the latest
+/// state of an entity) can be coalesced so only the latest is sent. The writer
+/// drains the queue with flush or pop when the peer is ready for more data
+#[derive(Debug, Clone)]
+pub struct OutboundQueue<G> {
+    classes: Vec<ClassQueue>,
+    classify: fn(&G) -> usize,
+    coalesce: Option<fn(&G) -> Option<u32>>,
+    dropped: u64,
+}
+
+impl<G: Writable> OutboundQueue<G> {
+    /// Creates a queue with the provided [classes] using [classify] to get the index
+    /// of the class of each packet. Indexes past the last class use the last class
+    pub fn new(classes: &[QueueClass], classify: fn(&G) -> usize) -> Self {
+        assert!(!classes.is_empty(), "outbound queue must have at least one class");
+        let classes = classes.iter()
+            .map(|class| ClassQueue { class: *class, frames: VecDeque::new(), bytes: 0 })
+            .collect();
+        OutboundQueue { classes, classify, coalesce: None, dropped: 0 }
+    }
+
+    /// Sets the [coalesce] function which returns the key of packets that supersede
+    /// earlier packets. A pushed packet with the same key as a packet waiting in
+    /// the same class replaces it keeping its place in the queue
+    pub fn coalesce(mut self, coalesce: fn(&G) -> Option<u32>) -> Self {
+        self.coalesce = Some(coalesce);
+        self
+    }
+
+    /// Encodes and queues the [packet] returning false if it was dropped because its
+    /// class was full
+    pub fn push(&mut self, packet: &mut G) -> PacketResult<bool> {
+        let index = (self.classify)(packet).min(self.classes.len() - 1);
+        let key = self.coalesce.and_then(|coalesce| coalesce(packet));
+        let mut frame = Vec::new();
+        packet.write(&mut frame)?;
+        let queue = &mut self.classes[index];
+        let class = queue.class;
+
+        if let Some(queued) = key.and_then(|key| queue.frames.iter_mut().find(|queued| queued.key == Some(key))) {
+            let bytes = queue.bytes - queued.frame.len() + frame.len();
+            if bytes <= class.max_bytes {
+                queued.frame = frame;
+                queue.bytes = bytes;
+                return Ok(true);
+            }
+            // The replacement doesn't fit in place so the superseded packet is
+            // removed and the replacement is queued like any other packet
+            let position = queue.frames.iter().position(|queued| queued.key == key).unwrap_or(0);
+            if let Some(queued) = queue.frames.remove(position) {
+                queue.bytes -= queued.frame.len();
+            }
+        }
+
+        let fits = |queue: &ClassQueue| {
+            queue.frames.len() < class.max_packets && queue.bytes + frame.len() <= class.max_bytes
+        };
+        if frame.len() > class.max_bytes || class.max_packets == 0 {
+            self.dropped += 1;
+            return Ok(false);
+        }
+        while !fits(queue) {
+            match class.overflow {
+                OverflowPolicy::DropNewest => {
+                    self.dropped += 1;
+                    return Ok(false);
+                }
+                OverflowPolicy::DropOldest => {
+                    if let Some(queued) = queue.frames.pop_front() {
+                        queue.bytes -= queued.frame.len();
+                        self.dropped += 1;
+                    }
+                }
+            }
+        }
+        queue.bytes += frame.len();
+        queue.frames.push_back(Queued { key, frame });
+        Ok(true)
+    }
+
+    /// Removes the next encoded packet to send which is the oldest packet of the
+    /// highest priority class with packets waiting
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        let queue = self.classes.iter_mut().find(|queue| !queue.frames.is_empty())?;
+        let queued = queue.frames.pop_front()?;
+        queue.bytes -= queued.frame.len();
+        Some(queued.frame)
+    }
+
+    /// Writes the waiting packets to [o] in priority order until writing the next
+    /// packet would go over [max_bytes] returning the number of bytes written. At
+    /// least one packet is written if any are waiting
+    pub fn flush<B: Write>(&mut self, o: &mut B, max_bytes: usize) -> PacketResult<usize> {
+        let mut written = 0;
+        while let Some(queue) = self.classes.iter_mut().find(|queue| !queue.frames.is_empty()) {
+            let next = queue.frames.front().map_or(0, |queued| queued.frame.len());
+            if written > 0 && written + next > max_bytes {
+                break;
+            }
+            if let Some(queued) = queue.frames.pop_front() {
+                queue.bytes -= queued.frame.len();
+                o.write_all(&queued.frame)?;
+                written += queued.frame.len();
+            }
+        }
+        Ok(written)
+    }
+
+    /// The number of packets waiting in the queue
+    pub fn len(&self) -> usize {
+        self.classes.iter().map(|queue| queue.frames.len()).sum()
+    }
+
+    /// Whether there are no packets waiting
+    pub fn is_empty(&self) -> bool {
+        self.classes.iter().all(|queue| queue.frames.is_empty())
+    }
+
+    /// The number of encoded bytes waiting in the queue
+    pub fn queued_bytes(&self) -> usize {
+        self.classes.iter().map(|queue| queue.bytes).sum()
+    }
+
+    /// The number of packets waiting in the class at [index]
+    pub fn class_len(&self, index: usize) -> usize {
+        self.classes.get(index).map_or(0, |queue| queue.frames.len())
+    }
+
+    /// The number of packets dropped because their class was full
+    pub fn dropped(&self) -> u64 { self.dropped }
+
+    /// Removes all the waiting packets
+    pub fn clear(&mut self) {
+        for queue in &mut self.classes {
+            queue.frames.clear();
+            queue.bytes = 0;
+        }
+    }
+}
@@ -1,16 +1,284 @@
-/// ## Writable Type Macro
-/// A macro used internally to convert struct and packet field types
-/// into writable types
+/// ## Read Field Macro
+/// A macro used internally to read a single struct or packet field from the reader [i] with
+/// the read context [ctx] handling any of the field attributes. Fields are read into local variables in order
+/// so attributes can refer to fields that were read before them
+///
+/// ## Attributes
+/// #[len_from(field)] The length of this collection or string is the value of the earlier
+/// field rather than a VarInt prefix
+///
+/// #[default(value)] The value used when the source ends before this field. This is the
+/// attribute produced by the `field: Type = value` syntax
+///
+/// #[when(field)] This field is only present when the earlier bool field is true otherwise
+/// it is skipped and is the default value of its type
+///
+/// #[with(Codec)] This field is read and written using the FieldCodec implementation of
+/// the provided type rather than the Readable implementation of the field type
+///
+/// #[log] This field is included in the LogFields of the struct or packet. It doesn't
+/// change how the field is encoded
+///
+/// #[limits(limits)] This field is read with the provided ReadLimits rather than the
+/// limits of the connection. It doesn't change how the field is encoded
+///
+/// #[tag(id)] The ID of this field in a tagged struct. It doesn't change how the field
+/// is encoded in the other structs
+///
+/// #[canonical] This field must be canonically encoded (see write_canonical) which is
+/// checked when it is written and read. It doesn't change how the field is encoded
+///
+/// #[range(range)] and #[max_len(max)] The value or length of this field is checked by
+/// validate_field after it is read. They don't change how the field is encoded
+#[macro_export]
+macro_rules! read_field {
+    ($ctx:ident, $i:ident, $Type:ty, []) => {
+        <$Type as $crate::Readable>::read_with($ctx, $i)?.into()
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[default($Default:expr)]]) => {
+        $crate::read_or_default::<$Type, _, _>($ctx, $i, || $crate::IntoField::<$Type>::into_field($Default))?
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[with($Codec:path)]]) => {
+        <$Codec as $crate::FieldCodec<$Type>>::read_field($ctx, $i)?
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        if $Condition {
+            $crate::read_field!($ctx, $i, $Type, [$($Rest)*])
+        } else {
+            <$Type as Default>::default()
+        }
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[len_from($Length:ident)]]) => {
+        <$Type as $crate::ReadUnprefixed>::read_with_length($ctx, $i, $crate::LengthField::to_length(&$Length))?
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[log] $($Rest:tt)*]) => {
+        $crate::read_field!($ctx, $i, $Type, [$($Rest)*])
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[limits($Limits:expr)] $($Rest:tt)*]) => {
+        $ctx.with_field_limits($Limits, |$ctx| Ok($crate::read_field!($ctx, $i, $Type, [$($Rest)*])))?
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[tag($Id:literal)] $($Rest:tt)*]) => {
+        $crate::read_field!($ctx, $i, $Type, [$($Rest)*])
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[canonical] $($Rest:tt)*]) => {
+        $ctx.with_field_canonical(|$ctx| Ok($crate::read_field!($ctx, $i, $Type, [$($Rest)*])))?
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[range($Range:expr)] $($Rest:tt)*]) => {
+        $crate::read_field!($ctx, $i, $Type, [$($Rest)*])
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[max_len($Max:expr)] $($Rest:tt)*]) => {
+        $crate::read_field!($ctx, $i, $Type, [$($Rest)*])
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[$Attr:ident $($Args:tt)?] #[log] $($Rest:tt)*]) => {
+        $crate::read_field!($ctx, $i, $Type, [#[$Attr $($Args)?] $($Rest)*])
+    };
+    ($ctx:ident, $i:ident, $Type:ty, [#[$($Unknown:tt)*] $($Rest:tt)*]) => {
+        compile_error!(concat!("unknown or unsupported field attribute: #[", stringify!($($Unknown)*), "]"))
+    };
+}
+
+/// ## Write Field Macro
+/// A macro used internally to write a single struct or packet field to the output [o]
+/// with the write context [ctx] handling any of the field attributes. [Field] is a
+/// mutable reference to the field value and any other fields referred to by attributes
+#[macro_export]
+macro_rules! write_field {
+    // Canonical fields are encoded as their other attributes describe and only written
+    // if the encoding is read back canonically. Fields with defaults are always written
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [$(#[default($Default:expr)])?]) => {
+        $crate::write_canonical_with::<$Type, _>($ctx, $Field, $o)?;
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[with($Codec:path)]]) => {
+        $crate::write_canonical_field(
+            $ctx,
+            $o,
+            |_, o| <$Codec as $crate::FieldCodec<$Type>>::write_field($Field, o),
+            |ctx, i| <$Codec as $crate::FieldCodec<$Type>>::read_field(ctx, i).map(|_| ()),
+        )?;
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        if *$Condition {
+            $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [$($Rest)*]);
+        }
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[len_from($Length:ident)]]) => {
+        let expected = $crate::LengthField::to_length(&*$Length);
+        let actual = $crate::WriteUnprefixed::length(&*$Field);
+        if expected != actual {
+            return Err($crate::PacketError::LengthMismatch(stringify!($Field), actual, stringify!($Length), expected));
+        }
+        $crate::write_canonical_field(
+            $ctx,
+            $o,
+            |ctx, o| $crate::WriteUnprefixed::write_without_length($Field, ctx, o),
+            |ctx, i| <$Type as $crate::ReadUnprefixed>::read_with_length(ctx, i, expected).map(|_| ()),
+        )?;
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[log] $($Rest:tt)*]) => {
+        $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[limits($Limits:expr)] $($Rest:tt)*]) => {
+        $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[tag($Id:literal)] $($Rest:tt)*]) => {
+        $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[range($Range:expr)] $($Rest:tt)*]) => {
+        $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[max_len($Max:expr)] $($Rest:tt)*]) => {
+        $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[$Attr:ident $($Args:tt)?] #[log] $($Rest:tt)*]) => {
+        $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [#[$Attr $($Args)?] $($Rest)*]);
+    };
+    (@canonical $ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[$($Unknown:tt)*] $($Rest:tt)*]) => {
+        compile_error!(concat!("unknown or unsupported field attribute: #[", stringify!($($Unknown)*), "]"));
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, []) => {
+        <$Type as $crate::Writable>::write_with($Field, $ctx, $o)?;
+    };
+    // Fields with defaults are always written
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[default($Default:expr)]]) => {
+        $crate::write_field!($ctx, $o, $Field, $Type, []);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[with($Codec:path)]]) => {
+        <$Codec as $crate::FieldCodec<$Type>>::write_field($Field, $o)?;
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        if *$Condition {
+            $crate::write_field!($ctx, $o, $Field, $Type, [$($Rest)*]);
+        }
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[len_from($Length:ident)]]) => {
+        // The length field must match the actual length as it is what the reader uses
+        let expected = $crate::LengthField::to_length(&*$Length);
+        let actual = $crate::WriteUnprefixed::length(&*$Field);
+        if expected != actual {
+            return Err($crate::PacketError::LengthMismatch(stringify!($Field), actual, stringify!($Length), expected));
+        }
+        $crate::WriteUnprefixed::write_without_length($Field, $ctx, $o)?;
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[log] $($Rest:tt)*]) => {
+        $crate::write_field!($ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[limits($Limits:expr)] $($Rest:tt)*]) => {
+        $crate::write_field!($ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[tag($Id:literal)] $($Rest:tt)*]) => {
+        $crate::write_field!($ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[range($Range:expr)] $($Rest:tt)*]) => {
+        $crate::write_field!($ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[max_len($Max:expr)] $($Rest:tt)*]) => {
+        $crate::write_field!($ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[$Attr:ident $($Args:tt)?] #[log] $($Rest:tt)*]) => {
+        $crate::write_field!($ctx, $o, $Field, $Type, [#[$Attr $($Args)?] $($Rest)*]);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[canonical] $($Rest:tt)*]) => {
+        $crate::write_field!(@canonical $ctx, $o, $Field, $Type, [$($Rest)*]);
+    };
+    ($ctx:ident, $o:ident, $Field:ident, $Type:ty, [#[$($Unknown:tt)*] $($Rest:tt)*]) => {
+        compile_error!(concat!("unknown or unsupported field attribute: #[", stringify!($($Unknown)*), "]"));
+    };
+}
+
+/// ## Validate Field Macro
+/// A macro used internally to check the value of a field of the struct or packet [Name]
+/// after it has been read against its #[range(range)] and #[max_len(max)] attributes.
+/// Conditional fields are only checked when they are present
+#[macro_export]
+macro_rules! validate_field {
+    ($Name:ident, $Field:ident, []) => {};
+    ($Name:ident, $Field:ident, [#[range($Range:expr)] $($Rest:tt)*]) => {
+        $crate::check_range(stringify!($Name), stringify!($Field), &$Field, &($Range))?;
+        $crate::validate_field!($Name, $Field, [$($Rest)*]);
+    };
+    ($Name:ident, $Field:ident, [#[max_len($Max:expr)] $($Rest:tt)*]) => {
+        $crate::check_max_len(stringify!($Name), stringify!($Field), &$Field, $Max)?;
+        $crate::validate_field!($Name, $Field, [$($Rest)*]);
+    };
+    ($Name:ident, $Field:ident, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        if $Condition {
+            $crate::validate_field!($Name, $Field, [$($Rest)*]);
+        }
+    };
+    ($Name:ident, $Field:ident, [#[$($Attr:tt)*] $($Rest:tt)*]) => {
+        $crate::validate_field!($Name, $Field, [$($Rest)*]);
+    };
+}
+
+/// ## Field Doc Macro
+/// A macro used internally to create the wire layout documentation row for a field
+#[macro_export]
+macro_rules! field_doc {
+    ($Field:ident, $Type:ty, [#[log] $($Rest:tt)*]) => {
+        $crate::field_doc!($Field, $Type, [$($Rest)*])
+    };
+    ($Field:ident, $Type:ty, [#[limits($Limits:expr)] $($Rest:tt)*]) => {
+        $crate::field_doc!($Field, $Type, [$($Rest)*])
+    };
+    ($Field:ident, $Type:ty, [#[tag($Id:literal)] $($Rest:tt)*]) => {
+        $crate::field_doc!($Field, $Type, [$($Rest)*])
+    };
+    ($Field:ident, $Type:ty, [#[canonical] $($Rest:tt)*]) => {
+        $crate::field_doc!($Field, $Type, [$($Rest)*])
+    };
+    ($Field:ident, $Type:ty, [#[range($Range:expr)] $($Rest:tt)*]) => {
+        concat!("| `", stringify!($Field), "` | `", stringify!($Type), "` within `", stringify!($Range), "` |")
+    };
+    ($Field:ident, $Type:ty, [#[max_len($Max:expr)] $($Rest:tt)*]) => {
+        concat!("| `", stringify!($Field), "` | `", stringify!($Type), "` with a length of at most `", stringify!($Max), "` |")
+    };
+    ($Field:ident, $Type:ty, [#[len_from($Length:ident)] $($Rest:tt)*]) => {
+        concat!("| `", stringify!($Field), "` | `", stringify!($Type), "` without a length prefix, the length is `", stringify!($Length), "` |")
+    };
+    ($Field:ident, $Type:ty, [#[with($Codec:path)] $($Rest:tt)*]) => {
+        concat!("| `", stringify!($Field), "` | `", stringify!($Type), "` encoded using `", stringify!($Codec), "` |")
+    };
+    ($Field:ident, $Type:ty, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        concat!("| `", stringify!($Field), "` | `", stringify!($Type), "` only present when `", stringify!($Condition), "` is true |")
+    };
+    ($Field:ident, $Type:ty, [#[default($Default:expr)] $($Rest:tt)*]) => {
+        concat!("| `", stringify!($Field), "` | `", stringify!($Type), "` or `", stringify!($Default), "` if the packet ends before it |")
+    };
+    ($Field:ident, $Type:ty, $Attrs:tt) => {
+        concat!("| `", stringify!($Field), "` | `", stringify!($Type), "` |")
+    };
+}
+
+/// ## Fixed Size Type Macro
+/// A macro used internally to provide the type used for a field in the FixedSize bounds of
+/// a struct. Conditional fields are never fixed size as they aren't always written and
+/// fields with a custom codec aren't as their size isn't known
+#[macro_export]
+macro_rules! fixed_size_type {
+    ($Type:ty, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        $crate::NotFixedSize<$Type>
+    };
+    ($Type:ty, [#[with($Codec:path)] $($Rest:tt)*]) => {
+        $crate::NotFixedSize<$Type>
+    };
+    ($Type:ty, [#[$($Attr:tt)*] $($Rest:tt)*]) => {
+        $crate::fixed_size_type!($Type, [$($Rest)*])
+    };
+    ($Type:ty, []) => {
+        $Type
+    };
+}
+
+/// ## Wire Layout Legend Macro
+/// A macro used internally to provide the description of the length and presence prefixes
+/// that is appended to the generated wire layout documentation
 #[macro_export]
-macro_rules! writable_type {
-    // Match VarInts
-    (VarInt, $e:expr) => { *$e };
-    // Match VarLongs
-    (VarLong, $e:expr) => { *$e } ;
-    // Match vectors
-    (Vec<$inner:ident>, $e:expr) => { *$e };
-    // Match all other types
-    ($typ:ty, $e:expr) => { $e };
+macro_rules! wire_layout_legend {
+    () => {
+        "Numbers are Big-Endian, `VarInt`/`VarLong` use 1-5/1-10 bytes, `bool` is a single byte, \
+        `String`, `Vec` and `HashMap` are prefixed with their length as a VarInt and `Option` is \
+        prefixed with a `bool` for whether the value is present"
+    };
 }
 
 /// ## Impl Struct Mode Macro
@@ -20,57 +288,236 @@ macro_rules! writable_type {
 macro_rules! impl_struct_mode {
     (
         (<-) $Name:ident {
-            $($Field:ident, $FieldType:ty),*
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
         }
     ) => {
         // Implement the io::Readable trait so this struct can be read
         impl $crate::Readable for $Name {
-            fn read<_ReadX: std::io::Read>(i: &mut _ReadX) -> $crate::ReadResult<Self> where Self: Sized {
-                // Provide all the fields to a new struct of self
-                Ok(Self {
-                    // Read all the fields for the struct
+            fn read<_ReadX: $crate::io::Read>(i: &mut _ReadX) -> $crate::ReadResult<Self> where Self: Sized {
+                $crate::read_counted(&mut $crate::ReadContext::default(), i, |ctx, i| Self::read_with(ctx, i))
+            }
+
+            #[allow(unused_variables, unused_assignments, unused_mut)]
+            fn read_with<_ReadX: $crate::io::Read>(ctx: &mut $crate::ReadContext, i: &mut _ReadX) -> $crate::ReadResult<Self> where Self: Sized {
+                ctx.enter(|ctx| {
+                    // The fields are read from the reader of the enclosing packet (which
+                    // sets the offsets of the errors) rather than wrapping it again so
+                    // structs which contain themselves don't create a reader type for
+                    // every level
+                    let mut index = 0;
+                    // Read all the fields in order so that fields can depend on earlier fields
                     $(
-                        $Field: <$FieldType>::read(i)?.into(),
+                        let $Field: $FieldType = $crate::read_field_with_context(ctx, i, stringify!($Name), stringify!($Field), index, |ctx, i| {
+                            Ok($crate::read_field!(ctx, i, $FieldType, $Attrs))
+                        })?;
+                        $crate::validate_field!($Name, $Field, $Attrs);
+                        index += 1;
                     )*
+                    // Provide all the fields to a new struct of self
+                    Ok(Self { $($Field),* })
                 })
             }
         }
     };
     (
         (->) $Name:ident {
-            $($Field:ident, $FieldType:ty),*
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
         }
     ) => {
         // Implement the io::Writable trait so the enum can be written
         #[allow(unused_imports, unused_variables)]
         impl $crate::Writable for $Name {
-            fn write<_ReadX: std::io::Write>(&mut self, o: &mut _ReadX) -> $crate::WriteResult {
+            fn write<_ReadX: $crate::io::Write>(&mut self, o: &mut _ReadX) -> $crate::WriteResult {
+                self.write_with(&mut $crate::WriteContext::default(), o)
+            }
+
+            fn write_with<_ReadX: $crate::io::Write>(&mut self, ctx: &mut $crate::WriteContext, o: &mut _ReadX) -> $crate::WriteResult {
+                let $Name { $($Field),* } = self;
                 // Create a write call for all of the fields using their type
-                $($crate::writable_type!($FieldType, &mut self.$Field).write(o)?;)*
+                $($crate::write_field!(ctx, o, $Field, $FieldType, $Attrs);)*
                 Ok(())
             }
         }
+
+        // Structs are fixed size when all of their fields are. The bounds are higher-ranked so
+        // that they are allowed when a field type isn't fixed size which leaves this unimplemented
+        impl $crate::FixedSize for $Name where $(for<'a> $crate::fixed_size_type!($FieldType, $Attrs): $crate::FixedSize),* {
+            const ENCODED_SIZE: usize = 0 $(+ <$crate::fixed_size_type!($FieldType, $Attrs) as $crate::FixedSize>::ENCODED_SIZE)*;
+        }
+
+        #[allow(dead_code)]
+        impl $Name where $(for<'a> $crate::fixed_size_type!($FieldType, $Attrs): $crate::FixedSize),* {
+            /// Writes this struct to an array of ENCODED_SIZE bytes on the stack without
+            /// any allocation. The length of the array is usually inferred from the type
+            /// `[u8; Name::ENCODED_SIZE]`
+            pub fn write_to_array<const N: usize>(&self) -> [u8; N] {
+                const { assert!(N == <Self as $crate::FixedSize>::ENCODED_SIZE, "the array length must be the ENCODED_SIZE") };
+                let mut bytes = [0u8; N];
+                let mut o: &mut [u8] = &mut bytes;
+                $crate::Writable::write(&mut self.clone(), &mut o)
+                    .expect("fixed size structs always fit the array");
+                bytes
+            }
+        }
     };
    (
        (<->) $Name:ident {
-           $($Field:ident, $FieldType:ty),*
+           $($Field:ident, $FieldType:ty, $Attrs:tt),*
        }
    ) => {
         // Pass the parameters onto the read implementation
         $crate::impl_struct_mode!(
             (<-) $Name {
-                $($Field, $FieldType),*
+                $($Field, $FieldType, $Attrs),*
             }
         );
         // Pass the parameters onto the write implementation
         $crate::impl_struct_mode!(
             (->) $Name {
-                $($Field, $FieldType),*
+                $($Field, $FieldType, $Attrs),*
             }
         );
     };
 }
 
+/// ## Tagged Field ID Macro
+/// A macro used internally to find the ID of a tagged struct field from its #[tag(id)]
+/// attribute. Only the #[log] and #[default] attributes can be used along with it
+#[macro_export]
+macro_rules! tagged_field_id {
+    ([#[tag($Id:literal)] $($Rest:tt)*]) => {
+        $crate::tagged_field_id!(@found $Id, [$($Rest)*])
+    };
+    ([#[log] $($Rest:tt)*]) => {
+        $crate::tagged_field_id!([$($Rest)*])
+    };
+    ([#[default($Default:expr)] $($Rest:tt)*]) => {
+        $crate::tagged_field_id!([$($Rest)*])
+    };
+    ([#[$($Unknown:tt)*] $($Rest:tt)*]) => {
+        compile_error!(concat!("field attribute isn't supported in tagged structs: #[", stringify!($($Unknown)*), "]"))
+    };
+    ([]) => {
+        compile_error!("fields of tagged structs need a #[tag(id)] attribute")
+    };
+    (@found $Id:literal, []) => {
+        $Id
+    };
+    (@found $Id:literal, [#[log] $($Rest:tt)*]) => {
+        $crate::tagged_field_id!(@found $Id, [$($Rest)*])
+    };
+    (@found $Id:literal, [#[default($Default:expr)] $($Rest:tt)*]) => {
+        $crate::tagged_field_id!(@found $Id, [$($Rest)*])
+    };
+    (@found $Id:literal, [#[$($Unknown:tt)*] $($Rest:tt)*]) => {
+        compile_error!(concat!("field attribute isn't supported in tagged structs: #[", stringify!($($Unknown)*), "]"))
+    };
+}
+
+/// ## Tagged Wire Type Macro
+/// A macro used internally to choose the wire type of a tagged struct field from
+/// its type which is fixed for FixedSize types of 1, 2, 4 or 8 bytes and delimited
+/// for all other types
+#[macro_export]
+macro_rules! tagged_wire_type {
+    ($Type:ty) => {{
+        #[allow(unused_imports)]
+        use $crate::tagged::{DelimitedWireType as _, FixedWireType as _};
+        (&$crate::tagged::TaggedWireType::<$Type>(::core::marker::PhantomData)).wire_type()
+    }};
+}
+
+/// ## Tagged Field Default Macro
+/// A macro used internally to provide the value of a tagged struct field that wasn't
+/// present which is its #[default] value or the Default of its type
+#[macro_export]
+macro_rules! tagged_field_default {
+    ($Type:ty, [#[default($Default:expr)] $($Rest:tt)*]) => {
+        $crate::IntoField::<$Type>::into_field($Default)
+    };
+    ($Type:ty, [#[$($Attr:tt)*] $($Rest:tt)*]) => {
+        $crate::tagged_field_default!($Type, [$($Rest)*])
+    };
+    ($Type:ty, []) => {
+        <$Type as Default>::default()
+    };
+}
+
+/// ## Impl Tagged Struct Mode Macro
+/// The tagged counterpart of impl_struct_mode which is used for the structs with the
+/// `#[wsbps(tagged)]` option. Each field is written as its key (the tag and wire type)
+/// and value so the fields can be read in any order and unknown fields can be skipped
+#[macro_export]
+macro_rules! impl_tagged_struct_mode {
+    (
+        (<-) $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        impl $crate::Readable for $Name {
+            fn read<_ReadX: $crate::io::Read>(i: &mut _ReadX) -> $crate::ReadResult<Self> where Self: Sized {
+                Self::read_with(&mut $crate::ReadContext::default(), i)
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn read_with<_ReadX: $crate::io::Read>(ctx: &mut $crate::ReadContext, i: &mut _ReadX) -> $crate::ReadResult<Self> where Self: Sized {
+                ctx.enter(|ctx| {
+                    $(let mut $Field: Option<$FieldType> = None;)*
+                    for _ in 0..$crate::read_tagged_count(ctx, i)? {
+                        let (tag, wire_type, payload) = $crate::read_tagged_field(ctx, i)?;
+                        // Fields with tags that aren't known are skipped
+                        $(
+                            if tag == $crate::tagged_field_id!($Attrs) {
+                                if $Field.is_some() {
+                                    Err($crate::PacketError::UnexpectedValue("each field of a tagged struct at most once"))?;
+                                }
+                                $Field = Some($crate::read_tagged_value::<$FieldType>(ctx, $crate::tagged_wire_type!($FieldType), wire_type, &payload)?);
+                            }
+                        )*
+                    }
+                    // Fields that weren't present use their default
+                    Ok(Self { $($Field: $Field.unwrap_or_else(|| $crate::tagged_field_default!($FieldType, $Attrs))),* })
+                })
+            }
+        }
+    };
+    (
+        (->) $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        #[allow(unused_variables)]
+        impl $crate::Writable for $Name {
+            fn write<_ReadX: $crate::io::Write>(&mut self, o: &mut _ReadX) -> $crate::WriteResult {
+                self.write_with(&mut $crate::WriteContext::default(), o)
+            }
+
+            fn write_with<_ReadX: $crate::io::Write>(&mut self, ctx: &mut $crate::WriteContext, o: &mut _ReadX) -> $crate::WriteResult {
+                let $Name { $($Field),* } = self;
+                let count: &[&str] = &[$(stringify!($Field)),*];
+                $crate::Writable::write(&mut $crate::VarInt(count.len() as u32), o)?;
+                $($crate::write_tagged_field(ctx, o, $crate::tagged_field_id!($Attrs), $crate::tagged_wire_type!($FieldType), $Field)?;)*
+                Ok(())
+            }
+        }
+    };
+    (
+        (<->) $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        $crate::impl_tagged_struct_mode!(
+            (<-) $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+        $crate::impl_tagged_struct_mode!(
+            (->) $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+    };
+}
 
 #[macro_export]
 macro_rules! discriminant_to_literal {
@@ -84,17 +531,51 @@ macro_rules! discriminant_to_literal {
 
 /// ## Impl Enum Mode Macro
 /// This is the underlying backing macro which is used by the impl_packet_data macro which is used by the
-/// packet_data macro to generate the specific enum trait implementations for the desired packet mode
+/// packet_data macro to generate the specific enum trait implementations for the desired packet mode.
+/// String enums are read and written through their StringEnum implementation so their values can
+/// be sent as negotiated IDs
 #[macro_export]
 macro_rules! impl_enum_mode {
     (
-        (<-) $Name:ident $Type:ty {
+        (<-) $Name:ident (String) {
+            $($Field:ident, $Value:expr),*
+        }
+    ) => {
+        // Implement the io::Readable trait so this enum can be read
+        impl $crate::Readable for $Name {
+            fn read<B: $crate::io::Read>(i: &mut B) -> $crate::ReadResult<Self> where Self: Sized {
+                Self::read_with(&mut $crate::ReadContext::default(), i)
+            }
+
+            fn read_with<B: $crate::io::Read>(ctx: &mut $crate::ReadContext, i: &mut B) -> $crate::ReadResult<Self> where Self: Sized {
+                $crate::read_string_enum(ctx, i)
+            }
+        }
+    };
+    (
+        (->) $Name:ident (String) {
+            $($Field:ident, $Value:expr),*
+        }
+    ) => {
+        // Implement the io::Writable trait so the enum can be written
+        impl $crate::Writable for $Name {
+            fn write<B: $crate::io::Write>(&mut self, o: &mut B) -> $crate::WriteResult {
+                self.write_with(&mut $crate::WriteContext::default(), o)
+            }
+
+            fn write_with<B: $crate::io::Write>(&mut self, ctx: &mut $crate::WriteContext, o: &mut B) -> $crate::WriteResult {
+                $crate::write_string_enum(ctx, self, o)
+            }
+        }
+    };
+    (
+        (<-) $Name:ident ($Type:ty) {
             $($Field:ident, $Value:expr),*
         }
     ) => {
         // Implement the io::Readable trait so this enum can be read
         impl $crate::Readable for $Name {
-            fn read<B: std::io::Read>(i: &mut B) -> $crate::ReadResult<Self> where Self: Sized {
+            fn read<B: $crate::io::Read>(i: &mut B) -> $crate::ReadResult<Self> where Self: Sized {
                 // Use the io::Readable for the type parameter to encode it
                 let value = $crate::discriminant_to_literal!($Type, <$Type>::read(i)?);
                 match value { // Match the value that was read
@@ -107,13 +588,13 @@ macro_rules! impl_enum_mode {
         }
     };
     (
-        (->) $Name:ident $Type:ty {
+        (->) $Name:ident ($Type:ty) {
             $($Field:ident, $Value:expr),*
         }
     ) => {
         // Implement the io::Writable trait so the enum can be written
         impl $crate::Writable for $Name {
-            fn write<B: std::io::Write>(&mut self, o: &mut B) -> $crate::WriteResult {
+            fn write<B: $crate::io::Write>(&mut self, o: &mut B) -> $crate::WriteResult {
                 match self { // Match self
                     // For each of the fields map them to a write call for the type
                     // and the value for that type
@@ -122,9 +603,14 @@ macro_rules! impl_enum_mode {
                 Ok(())
             }
         }
+
+        // Enums are fixed size when their value type is
+        impl $crate::FixedSize for $Name where for<'a> $Type: $crate::FixedSize {
+            const ENCODED_SIZE: usize = <$Type as $crate::FixedSize>::ENCODED_SIZE;
+        }
     };
     (
-        (<->) $Name:ident $Type:ty {
+        (<->) $Name:ident $Type:tt {
             $($Field:ident, $Value:expr),*
         }
     ) => {
@@ -143,58 +629,1223 @@ macro_rules! impl_enum_mode {
     };
 }
 
-/// ## Impl Packet Data
-/// This is the underlying backing macro for packet_data which handles which type should be
-/// implemented and for which mode (enum / struct) this is used to speed up parsing and reduce
-/// the complexity of the packet_data macro
+/// ## Enum Value Matches Macro
+/// A macro used internally to check if a string [value] is the value of a string enum variant
+/// or one of its #[alias(..)] values. [Matching] is `exact` or `case_insensitive` which only
+/// ignores the case of ASCII letters
 #[macro_export]
-macro_rules! impl_packet_data {
-    // Matching enums
+macro_rules! enum_value_matches {
+    (exact, $value:ident, $Expected:expr) => {
+        $value == $Expected
+    };
+    (case_insensitive, $value:ident, $Expected:expr) => {
+        $value.eq_ignore_ascii_case($Expected)
+    };
+    ($Matching:ident, $value:ident, $Value:expr, [$($Attrs:tt)*]) => {
+        $crate::enum_value_matches!(@aliases $Matching, $value, [$($Attrs)*], $crate::enum_value_matches!($Matching, $value, $Value))
+    };
+    (@aliases $Matching:ident, $value:ident, [#[alias($($Alias:literal),+)] $($Rest:tt)*], $Matches:expr) => {
+        $crate::enum_value_matches!(@aliases $Matching, $value, [$($Rest)*], $Matches $(|| $crate::enum_value_matches!($Matching, $value, $Alias))+)
+    };
+    (@aliases $Matching:ident, $value:ident, [#[$($Other:tt)*] $($Rest:tt)*], $Matches:expr) => {
+        $crate::enum_value_matches!(@aliases $Matching, $value, [$($Rest)*], $Matches)
+    };
+    (@aliases $Matching:ident, $value:ident, [], $Matches:expr) => {
+        $Matches
+    };
+    // Enums that don't have String values can't have aliases
+    (@reject [#[alias($($Alias:tt)*)] $($Rest:tt)*]) => {
+        compile_error!("only enums with String values can have aliases");
+    };
+    (@reject [#[$($Other:tt)*] $($Rest:tt)*]) => {
+        $crate::enum_value_matches!(@reject [$($Rest)*]);
+    };
+    (@reject []) => {};
+}
+
+/// ## Impl String Enum Macro
+/// This macro implements StringEnum for the enums whose values are strings and expands to
+/// nothing for enums of any other type. Values are matched using [Matching] (see
+/// enum_value_matches) which also accepts the #[alias(..)] values of the variants
+#[macro_export]
+macro_rules! impl_string_enum {
     (
-        enum $Name:ident $Mode:tt $Type:ty {
-            $($Field:ident, $Value:expr),*
+        (String) [$Matching:ident] $Name:ident {
+            $($Field:ident, $Value:expr, $Attrs:tt),*
         }
     ) => {
-        // Create the backing enum
-        #[derive(Debug, Clone, PartialEq)]
-        #[allow(dead_code)]
-        pub enum $Name {
-            $($Field),*
+        // An empty string is written as a zero length which marks a compressed value
+        const _: () = {
+            $(assert!(!$Value.is_empty(), concat!("the values of the string enum ", stringify!($Name), " must not be empty"));)*
+        };
+
+        impl $crate::StringEnum for $Name {
+            const NAME: &'static str = stringify!($Name);
+            const VALUES: &'static [&'static str] = &[$($Value),*];
+
+            fn as_str(&self) -> &'static str {
+                match self {
+                    $($Name::$Field => $Value,)*
+                }
+            }
+
+            fn from_value(value: &str) -> Option<Self> {
+                $(
+                    if $crate::enum_value_matches!($Matching, value, $Value, $Attrs) {
+                        return Some($Name::$Field);
+                    }
+                )*
+                None
+            }
+        }
+    };
+    (
+        $Type:tt [exact] $Name:ident {
+            $($Field:ident, $Value:expr, $Attrs:tt),*
         }
+    ) => {
+        $($crate::enum_value_matches!(@reject $Attrs);)*
+    };
+}
 
-        // Implement the traits for the provided mode
-        $crate::impl_enum_mode!(
-            $Mode $Name $Type {
-                $($Field, $Value),*
+/// ## Impl Enum Values Macro
+/// This macro implements the conversions between the generated enums and their values
+/// (TryFrom and From), FromStr which parses the variant names and the ALL constant of
+/// the variants so the mapping doesn't need to be repeated outside of the macro
+#[macro_export]
+macro_rules! impl_enum_values {
+    (@common $Name:ident { $($Field:ident),* }) => {
+        impl $Name {
+            /// All the variants in declaration order
+            pub const ALL: &'static [$Name] = &[$($Name::$Field),*];
+
+            /// Iterates over all the variants in declaration order
+            pub fn iter() -> impl Iterator<Item=$Name> {
+                Self::ALL.iter().cloned()
             }
-        );
+
+            /// The name of the variant
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($Name::$Field => stringify!($Field),)*
+                }
+            }
+        }
+
+        /// Parses the name of a variant
+        impl core::str::FromStr for $Name {
+            type Err = $crate::PacketError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    $(stringify!($Field) => Ok($Name::$Field),)*
+                    _ => Err($crate::PacketError::UnknownEnumValue),
+                }
+            }
+        }
     };
-    // Matching structs
     (
-        struct $Name:ident $Mode:tt {
-            $($Field:ident, $FieldType:ty),*
+        (String) $Name:ident {
+            $($Field:ident, $Value:expr),*
         }
     ) => {
-        // Create the backing struct
-        #[derive(Debug, Clone, PartialEq)]
-        pub struct $Name {
-            $(pub $Field: $FieldType),*
+        $crate::impl_enum_values!(@common $Name { $($Field),* });
+
+        impl TryFrom<&str> for $Name {
+            type Error = $crate::PacketError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                <$Name as $crate::StringEnum>::from_value(value).ok_or($crate::PacketError::UnknownEnumValue)
+            }
         }
 
-        // Implement the traits for the provided mode
-        $crate::impl_struct_mode!(
-            $Mode $Name {
-                $($Field, $FieldType),*
+        impl TryFrom<$crate::alloc::string::String> for $Name {
+            type Error = $crate::PacketError;
+
+            fn try_from(value: $crate::alloc::string::String) -> Result<Self, Self::Error> {
+                Self::try_from(&*value)
             }
-        );
+        }
+
+        impl From<$Name> for $crate::alloc::string::String {
+            fn from(value: $Name) -> Self {
+                match value {
+                    $($Name::$Field => $crate::alloc::string::String::from($Value),)*
+                }
+            }
+        }
     };
-}
+    (
+        ($Type:ty) $Name:ident {
+            $($Field:ident, $Value:expr),*
+        }
+    ) => {
+        $crate::impl_enum_values!(@common $Name { $($Field),* });
 
-/// ## Packet Data
-/// This macro is used to implement read and write traits for enums so they can be used within
-/// packets as packet fields. This is a block and you should use it to implement all of your
-/// structs and enums at once.
-///
+        impl TryFrom<$Type> for $Name {
+            type Error = $crate::PacketError;
+
+            fn try_from(value: $Type) -> Result<Self, Self::Error> {
+                match $crate::discriminant_to_literal!($Type, value) {
+                    $($Value => Ok($Name::$Field),)*
+                    _ => Err($crate::PacketError::UnknownEnumValue),
+                }
+            }
+        }
+
+        impl From<$Name> for $Type {
+            fn from(value: $Name) -> Self {
+                match value {
+                    $($Name::$Field => <$Type>::from($Value),)*
+                }
+            }
+        }
+    };
+}
+
+/// ## Impl Arbitrary Macro
+/// This macro implements arbitrary::Arbitrary for the generated structs, enums and packet groups
+/// so they can be generated by fuzzers. When the "arbitrary" feature is disabled this macro
+/// expands to nothing.
+#[cfg(feature = "arbitrary")]
+#[macro_export]
+macro_rules! impl_arbitrary {
+    (
+        struct $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        impl<'a> $crate::arbitrary::Arbitrary<'a> for $Name {
+            fn arbitrary(u: &mut $crate::arbitrary::Unstructured<'a>) -> $crate::arbitrary::Result<Self> {
+                Ok($crate::arbitrary_fields!(u, Self {
+                    $($Field, $FieldType, $Attrs),*
+                }))
+            }
+        }
+    };
+    (
+        enum $Name:ident {
+            $($Field:ident),*
+        }
+    ) => {
+        impl<'a> $crate::arbitrary::Arbitrary<'a> for $Name {
+            fn arbitrary(u: &mut $crate::arbitrary::Unstructured<'a>) -> $crate::arbitrary::Result<Self> {
+                // Pick one of the enum values at random
+                u.choose(&[$($Name::$Field),*]).cloned()
+            }
+        }
+    };
+    (
+        newtype $Name:ident($Type:ty)
+    ) => {
+        impl<'a> $crate::arbitrary::Arbitrary<'a> for $Name {
+            fn arbitrary(u: &mut $crate::arbitrary::Unstructured<'a>) -> $crate::arbitrary::Result<Self> {
+                Ok($Name(<$Type as $crate::arbitrary::Arbitrary>::arbitrary(u)?))
+            }
+        }
+    };
+    (
+        group $Group:ident {
+            $(
+                $Name:ident {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
+                }
+            );*
+        }
+    ) => {
+        impl<'a> $crate::arbitrary::Arbitrary<'a> for $Group {
+            #[allow(unused_variables)]
+            fn arbitrary(u: &mut $crate::arbitrary::Unstructured<'a>) -> $crate::arbitrary::Result<Self> {
+                // A function for creating each of the packets, one is picked at random
+                let packets: &[fn(&mut $crate::arbitrary::Unstructured<'a>) -> $crate::arbitrary::Result<$Group>] = &[
+                    $(
+                        |u| Ok($crate::arbitrary_fields!(u, $Group::$Name {
+                            $($Field, $Type, $Attrs),*
+                        })),
+                    )*
+                ];
+                (u.choose(packets)?)(u)
+            }
+        }
+    };
+    (
+        structs $Group:ident {
+            $($Name:ident),*
+        }
+    ) => {
+        impl<'a> $crate::arbitrary::Arbitrary<'a> for $Group {
+            fn arbitrary(u: &mut $crate::arbitrary::Unstructured<'a>) -> $crate::arbitrary::Result<Self> {
+                // A function for creating each of the packets, one is picked at random
+                let packets: &[fn(&mut $crate::arbitrary::Unstructured<'a>) -> $crate::arbitrary::Result<$Group>] = &[
+                    $(
+                        |u| Ok($Group::$Name(<$Name as $crate::arbitrary::Arbitrary>::arbitrary(u)?)),
+                    )*
+                ];
+                (u.choose(packets)?)(u)
+            }
+        }
+    };
+}
+
+/// ## Arbitrary Fields Macro
+/// Creates a struct or packet from arbitrary field values. Fields whose length comes from
+/// another field have that field updated to match the generated length
+#[cfg(feature = "arbitrary")]
+#[macro_export]
+macro_rules! arbitrary_fields {
+    (
+        $u:ident, $($Path:ident)::+ {
+            $($Field:ident, $Type:ty, $Attrs:tt),*
+        }
+    ) => {{
+        $(
+            #[allow(unused_mut, unused_assignments)]
+            let mut $Field = <$Type as $crate::arbitrary::Arbitrary>::arbitrary($u)?;
+        )*
+        $($crate::arbitrary_field_fixup!($Field, $Attrs);)*
+        $($Path)::+ { $($Field),* }
+    }};
+}
+
+/// ## Arbitrary Packet Macro
+/// Creates a packet of a group from arbitrary field values for the layout of the group
+#[cfg(feature = "arbitrary")]
+#[macro_export]
+macro_rules! arbitrary_packet {
+    (variants $u:ident, $Group:ident::$Name:ident $Fields:tt) => {
+        $crate::arbitrary_fields!($u, $Group::$Name $Fields)
+    };
+    // The struct type is inferred from the variant as the roundtrip test modules are named
+    // after the packets which shadows the packet structs
+    (structs $u:ident, $Group:ident::$Name:ident $Fields:tt) => {
+        $Group::$Name($crate::arbitrary::Arbitrary::arbitrary($u)?)
+    };
+}
+
+/// ## Arbitrary Field Fixup Macro
+/// Updates the fields that the provided field depends on so that generated values
+/// are consistent
+#[cfg(feature = "arbitrary")]
+#[macro_export]
+macro_rules! arbitrary_field_fixup {
+    ($Field:ident, []) => {};
+    ($Field:ident, [#[len_from($Length:ident)] $($Rest:tt)*]) => {
+        $Length = $crate::LengthField::from_length($crate::WriteUnprefixed::length(&$Field))
+            .ok_or($crate::arbitrary::Error::IncorrectFormat)?;
+        $crate::arbitrary_field_fixup!($Field, [$($Rest)*]);
+    };
+    // Values that wouldn't pass validation can't be read back
+    ($Field:ident, [#[range($Range:expr)] $($Rest:tt)*]) => {
+        if !core::ops::RangeBounds::contains(&($Range), &$Field) {
+            return Err($crate::arbitrary::Error::IncorrectFormat);
+        }
+        $crate::arbitrary_field_fixup!($Field, [$($Rest)*]);
+    };
+    ($Field:ident, [#[max_len($Max:expr)] $($Rest:tt)*]) => {
+        if $crate::ValidatedLength::validated_length(&$Field) > $Max {
+            return Err($crate::arbitrary::Error::IncorrectFormat);
+        }
+        $crate::arbitrary_field_fixup!($Field, [$($Rest)*]);
+    };
+    // Conditional fields that aren't present are read as their default value
+    ($Field:ident, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        if !$Condition {
+            $Field = Default::default();
+        }
+        $crate::arbitrary_field_fixup!($Field, [$($Rest)*]);
+    };
+    ($Field:ident, [#[$($Unknown:tt)*] $($Rest:tt)*]) => {
+        $crate::arbitrary_field_fixup!($Field, [$($Rest)*]);
+    };
+}
+
+/// ## Impl Arbitrary Macro
+/// The "arbitrary" feature is disabled so nothing is implemented
+#[cfg(not(feature = "arbitrary"))]
+#[macro_export]
+macro_rules! impl_arbitrary {
+    ($($tokens:tt)*) => {};
+}
+
+/// ## Impl Ordered Macro
+/// This macro implements OrderedRepr for the generated structs, enums and packet groups so
+/// they have a stable Debug and JSON representation. When the "ordered" feature is disabled
+/// this macro expands to nothing.
+#[cfg(feature = "ordered")]
+#[macro_export]
+macro_rules! impl_ordered {
+    (
+        struct $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        impl $crate::OrderedRepr for $Name {
+            fn write_ordered(&self, style: $crate::OrderedStyle, out: &mut $crate::alloc::string::String) {
+                $crate::write_ordered_struct(style, out, stringify!($Name), &[
+                    $((stringify!($Field), &self.$Field as &dyn $crate::OrderedRepr)),*
+                ]);
+            }
+        }
+    };
+    (
+        enum $Name:ident {
+            $($Field:ident),*
+        }
+    ) => {
+        impl $crate::OrderedRepr for $Name {
+            fn write_ordered(&self, style: $crate::OrderedStyle, out: &mut $crate::alloc::string::String) {
+                $crate::write_ordered_unit(style, out, match self {
+                    $($Name::$Field => stringify!($Field),)*
+                });
+            }
+        }
+    };
+    (
+        newtype $Name:ident($Type:ty)
+    ) => {
+        impl $crate::OrderedRepr for $Name {
+            fn write_ordered(&self, style: $crate::OrderedStyle, out: &mut $crate::alloc::string::String) {
+                $crate::OrderedRepr::write_ordered(&self.0, style, out)
+            }
+        }
+    };
+    (
+        group $Group:ident {
+            $(
+                $Name:ident {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
+                }
+            );*
+        }
+    ) => {
+        impl $crate::OrderedRepr for $Group {
+            fn write_ordered(&self, style: $crate::OrderedStyle, out: &mut $crate::alloc::string::String) {
+                match self {
+                    $(
+                        $Group::$Name { $($Field),* } => $crate::write_ordered_variant(style, out, stringify!($Name), &[
+                            $((stringify!($Field), $Field as &dyn $crate::OrderedRepr)),*
+                        ]),
+                    )*
+                }
+            }
+        }
+    };
+    (
+        structs $Group:ident {
+            $($Name:ident),*
+        }
+    ) => {
+        impl $crate::OrderedRepr for $Group {
+            fn write_ordered(&self, style: $crate::OrderedStyle, out: &mut $crate::alloc::string::String) {
+                match self {
+                    $(
+                        $Group::$Name(packet) => $crate::write_ordered_newtype_variant(style, out, stringify!($Name), packet),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+/// ## Impl Ordered Macro
+/// The "ordered" feature is disabled so nothing is implemented
+#[cfg(not(feature = "ordered"))]
+#[macro_export]
+macro_rules! impl_ordered {
+    ($($tokens:tt)*) => {};
+}
+
+/// ## Impl Summary Macro
+/// This macro implements the Summary trait for the generated types and Display for the
+/// generated groups so that packets can be logged as concise one line summaries. When the
+/// "summary" feature is disabled this macro expands to nothing.
+#[cfg(feature = "summary")]
+#[macro_export]
+macro_rules! impl_summary {
+    (
+        struct $Name:ident {
+            $($Field:ident),*
+        }
+    ) => {
+        impl $crate::Summary for $Name {
+            fn write_summary(&self, out: &mut $crate::alloc::string::String) {
+                $crate::write_summary_fields(out, stringify!($Name), None, &[
+                    $((stringify!($Field), &self.$Field as &dyn $crate::Summary)),*
+                ]);
+            }
+        }
+    };
+    (
+        enum $Name:ident {
+            $($Field:ident),*
+        }
+    ) => {
+        impl $crate::Summary for $Name {
+            fn write_summary(&self, out: &mut $crate::alloc::string::String) {
+                out.push_str(match self {
+                    $($Name::$Field => stringify!($Field),)*
+                });
+            }
+        }
+    };
+    (
+        newtype $Name:ident($Type:ty)
+    ) => {
+        impl $crate::Summary for $Name {
+            fn write_summary(&self, out: &mut $crate::alloc::string::String) {
+                $crate::Summary::write_summary(&self.0, out)
+            }
+        }
+    };
+    (
+        group $Group:ident {
+            $(
+                $Name:ident ($ID:expr) {
+                    $($Field:ident),*
+                }
+            );*
+        }
+    ) => {
+        impl $crate::Summary for $Group {
+            fn write_summary(&self, out: &mut $crate::alloc::string::String) {
+                match self {
+                    $(
+                        $Group::$Name { $($Field),* } => $crate::write_summary_fields(out, stringify!($Name), Some($ID as u32), &[
+                            $((stringify!($Field), $Field as &dyn $crate::Summary)),*
+                        ]),
+                    )*
+                }
+            }
+        }
+
+        $crate::impl_summary!(display $Group);
+    };
+    (
+        structs $Group:ident {
+            $(
+                $Name:ident ($ID:expr) {
+                    $($Field:ident),*
+                }
+            );*
+        }
+    ) => {
+        impl $crate::Summary for $Group {
+            fn write_summary(&self, out: &mut $crate::alloc::string::String) {
+                match self {
+                    $(
+                        $Group::$Name(packet) => $crate::write_summary_fields(out, stringify!($Name), Some($ID as u32), &[
+                            $((stringify!($Field), &packet.$Field as &dyn $crate::Summary)),*
+                        ]),
+                    )*
+                }
+            }
+        }
+
+        $crate::impl_summary!(display $Group);
+    };
+    (
+        display $Group:ident
+    ) => {
+        impl core::fmt::Display for $Group {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(&$crate::to_summary(self))
+            }
+        }
+    };
+}
+
+/// ## Impl Summary Macro
+/// The "summary" feature is disabled so nothing is implemented
+#[cfg(not(feature = "summary"))]
+#[macro_export]
+macro_rules! impl_summary {
+    ($($tokens:tt)*) => {};
+}
+
+/// ## Log Field Macro
+/// A macro used internally to pass the [value] of a field to the LogFields [visit] function
+/// when the field has the #[log] attribute
+#[macro_export]
+macro_rules! log_field {
+    ($visit:ident, $Field:ident, $value:expr, []) => {};
+    ($visit:ident, $Field:ident, $value:expr, [#[log] $($Rest:tt)*]) => {
+        $visit(stringify!($Field), $value);
+    };
+    ($visit:ident, $Field:ident, $value:expr, [#[$($Attr:tt)*] $($Rest:tt)*]) => {
+        $crate::log_field!($visit, $Field, $value, [$($Rest)*]);
+    };
+}
+
+/// ## Impl Log Fields Macro
+/// This macro implements LogFields for the generated structs and packet groups providing
+/// the fields marked with the #[log] attribute
+#[macro_export]
+macro_rules! impl_log_fields {
+    (
+        struct $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        impl $crate::LogFields for $Name {
+            #[allow(unused_variables)]
+            fn visit_log_fields(&self, visit: &mut dyn FnMut(&'static str, &dyn core::fmt::Debug)) {
+                $($crate::log_field!(visit, $Field, &self.$Field, $Attrs);)*
+            }
+        }
+    };
+    (
+        group $Group:ident {
+            $(
+                $Name:ident {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
+                }
+            );*
+        }
+    ) => {
+        impl $crate::LogFields for $Group {
+            #[allow(unused_variables)]
+            fn visit_log_fields(&self, visit: &mut dyn FnMut(&'static str, &dyn core::fmt::Debug)) {
+                match self {
+                    $(
+                        $Group::$Name { $($Field),* } => {
+                            $($crate::log_field!(visit, $Field, $Field, $Attrs);)*
+                        }
+                    )*
+                }
+            }
+        }
+    };
+    (
+        structs $Group:ident {
+            $($Name:ident),*
+        }
+    ) => {
+        impl $crate::LogFields for $Group {
+            fn visit_log_fields(&self, visit: &mut dyn FnMut(&'static str, &dyn core::fmt::Debug)) {
+                match self {
+                    $($Group::$Name(packet) => $crate::LogFields::visit_log_fields(packet, visit),)*
+                }
+            }
+        }
+    };
+}
+
+/// ## Impl Diffable Macro
+/// This macro implements Diffable and DeltaReadable for the generated structs based on
+/// their direction so consecutive values can be encoded as field level deltas. When the
+/// "delta" feature is disabled this macro expands to nothing.
+#[cfg(feature = "delta")]
+#[macro_export]
+macro_rules! impl_diffable {
+    (
+        (<-) $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        impl $crate::DeltaReadable for $Name {
+            #[allow(unused_variables, unused_assignments, unused_mut)]
+            fn read_delta_with<_ReadX: $crate::io::Read>(ctx: &mut $crate::ReadContext, base: &Self, i: &mut _ReadX) -> $crate::ReadResult<Self> {
+                const FIELDS: &[&str] = &[$(stringify!($Field)),*];
+                ctx.enter(|ctx| {
+                    let mut mask = [0u8; FIELDS.len().div_ceil(8)];
+                    i.read_exact(&mut mask)?;
+                    let mut index = 0;
+                    // Unchanged fields are taken from the base so later fields can still
+                    // refer to them
+                    $(
+                        let $Field: $FieldType = if mask[index / 8] & (1 << (index % 8)) != 0 {
+                            let $Field: $FieldType = $crate::read_field!(ctx, i, $FieldType, $Attrs);
+                            $crate::validate_field!($Name, $Field, $Attrs);
+                            $Field
+                        } else {
+                            base.$Field.clone()
+                        };
+                        index += 1;
+                    )*
+                    Ok(Self { $($Field),* })
+                })
+            }
+        }
+    };
+    (
+        (->) $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        impl $crate::Diffable for $Name {
+            #[allow(unused_variables, unused_assignments, unused_mut)]
+            fn write_delta<_WriteX: $crate::io::Write>(&mut self, base: &Self, o: &mut _WriteX) -> $crate::WriteResult {
+                const FIELDS: &[&str] = &[$(stringify!($Field)),*];
+                let mut mask = [0u8; FIELDS.len().div_ceil(8)];
+                let mut index = 0;
+                $(
+                    if self.$Field != base.$Field {
+                        mask[index / 8] |= 1 << (index % 8);
+                    }
+                    index += 1;
+                )*
+                o.write_all(&mask)?;
+                let $Name { $($Field),* } = self;
+                let ctx = &mut $crate::WriteContext::default();
+                let mut index = 0;
+                $(
+                    if mask[index / 8] & (1 << (index % 8)) != 0 {
+                        $crate::write_field!(ctx, o, $Field, $FieldType, $Attrs);
+                    }
+                    index += 1;
+                )*
+                Ok(())
+            }
+        }
+    };
+    (
+        (<->) $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        $crate::impl_diffable!(
+            (<-) $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+        $crate::impl_diffable!(
+            (->) $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+    };
+}
+
+/// ## Impl Diffable Macro
+/// The "delta" feature is disabled so nothing is implemented
+#[cfg(not(feature = "delta"))]
+#[macro_export]
+macro_rules! impl_diffable {
+    ($($tokens:tt)*) => {};
+}
+
+/// ## Impl Schema Macro
+/// This macro implements Schema for the generated structs and enums and PacketGroupSchema
+/// for the generated groups so the code generators can produce readers and writers for
+/// other languages. When the "codegen" feature is disabled this macro expands to nothing.
+#[cfg(feature = "codegen")]
+#[macro_export]
+macro_rules! impl_schema {
+    // The generators don't support the tagged encoding so these are custom types
+    (
+        tagged struct $Name:ident
+    ) => {
+        impl $crate::Schema for $Name {
+            fn type_schema() -> $crate::TypeSchema {
+                $crate::TypeSchema::Custom(concat!("tagged struct ", stringify!($Name)))
+            }
+        }
+    };
+    (
+        struct $Name:ident {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        impl $crate::Schema for $Name {
+            fn type_schema() -> $crate::TypeSchema {
+                $crate::TypeSchema::Named(stringify!($Name))
+            }
+
+            #[allow(unused_variables)]
+            fn definitions(definitions: &mut $crate::alloc::vec::Vec<$crate::Definition>) {
+                let definition = $crate::Definition {
+                    name: stringify!($Name),
+                    kind: $crate::DefinitionKind::Struct($crate::alloc::vec![
+                        $($crate::field_schema!($Field, $FieldType, $Attrs)),*
+                    ]),
+                };
+                if $crate::add_definition(definitions, definition) {
+                    $($crate::field_schema!(@definitions definitions, $FieldType, $Attrs);)*
+                }
+            }
+        }
+    };
+    (
+        enum $Name:ident $Type:ty {
+            $($Field:ident, $Value:expr),*
+        }
+    ) => {
+        impl $crate::Schema for $Name {
+            fn type_schema() -> $crate::TypeSchema {
+                $crate::TypeSchema::Named(stringify!($Name))
+            }
+
+            fn definitions(definitions: &mut $crate::alloc::vec::Vec<$crate::Definition>) {
+                $crate::add_definition(definitions, $crate::Definition {
+                    name: stringify!($Name),
+                    kind: $crate::DefinitionKind::Enum {
+                        repr: <$Type as $crate::Schema>::type_schema(),
+                        variants: $crate::alloc::vec![$((stringify!($Field), stringify!($Value))),*],
+                    },
+                });
+            }
+        }
+    };
+    (
+        newtype $Name:ident($Type:ty)
+    ) => {
+        impl $crate::Schema for $Name {
+            fn type_schema() -> $crate::TypeSchema {
+                <$Type as $crate::Schema>::type_schema()
+            }
+
+            fn definitions(definitions: &mut $crate::alloc::vec::Vec<$crate::Definition>) {
+                <$Type as $crate::Schema>::definitions(definitions)
+            }
+        }
+    };
+    (
+        group $Group:ident {
+            $(
+                $Name:ident ($ID:expr) {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
+                }
+            )*
+        }
+    ) => {
+        impl $crate::PacketGroupSchema for $Group {
+            fn group_schema() -> $crate::GroupSchema {
+                #[allow(unused_mut)]
+                let mut definitions = $crate::alloc::vec::Vec::new();
+                $($($crate::field_schema!(@definitions &mut definitions, $Type, $Attrs);)*)*
+                $crate::GroupSchema {
+                    name: stringify!($Group),
+                    packets: $crate::alloc::vec![
+                        $(
+                            $crate::PacketSchema {
+                                name: stringify!($Name),
+                                id: $ID as u32,
+                                fields: $crate::alloc::vec![
+                                    $($crate::field_schema!($Field, $Type, $Attrs)),*
+                                ],
+                            }
+                        ),*
+                    ],
+                    definitions,
+                }
+            }
+        }
+    };
+}
+
+/// ## Impl Schema Macro
+/// The "codegen" feature is disabled so nothing is implemented
+#[cfg(not(feature = "codegen"))]
+#[macro_export]
+macro_rules! impl_schema {
+    ($($tokens:tt)*) => {};
+}
+
+/// ## Field Schema Macro
+/// A macro used internally to create the FieldSchema for a struct or packet field from its
+/// field attributes. `@definitions` adds the definitions used by the field type, fields with
+/// a custom codec don't use their type so they don't add any
+#[macro_export]
+macro_rules! field_schema {
+    (@type $Type:ty, [#[with($Codec:path)] $($Rest:tt)*]) => {
+        $crate::TypeSchema::Custom(stringify!($Codec))
+    };
+    (@type $Type:ty, [#[$($Attr:tt)*] $($Rest:tt)*]) => {
+        $crate::field_schema!(@type $Type, [$($Rest)*])
+    };
+    (@type $Type:ty, []) => {
+        <$Type as $crate::Schema>::type_schema()
+    };
+    (@definitions $definitions:expr, $Type:ty, [#[with($Codec:path)] $($Rest:tt)*]) => {};
+    (@definitions $definitions:expr, $Type:ty, [#[$($Attr:tt)*] $($Rest:tt)*]) => {
+        $crate::field_schema!(@definitions $definitions, $Type, [$($Rest)*]);
+    };
+    (@definitions $definitions:expr, $Type:ty, []) => {
+        <$Type as $crate::Schema>::definitions($definitions);
+    };
+    (@attrs $field:ident, []) => {};
+    (@attrs $field:ident, [#[len_from($Length:ident)] $($Rest:tt)*]) => {
+        $field.length_from = Some(stringify!($Length));
+        $crate::field_schema!(@attrs $field, [$($Rest)*]);
+    };
+    (@attrs $field:ident, [#[when($Condition:ident)] $($Rest:tt)*]) => {
+        $field.when = Some(stringify!($Condition));
+        $crate::field_schema!(@attrs $field, [$($Rest)*]);
+    };
+    (@attrs $field:ident, [#[default($Default:expr)] $($Rest:tt)*]) => {
+        $field.default = Some(stringify!($Default));
+        $crate::field_schema!(@attrs $field, [$($Rest)*]);
+    };
+    (@attrs $field:ident, [#[$($Attr:tt)*] $($Rest:tt)*]) => {
+        $crate::field_schema!(@attrs $field, [$($Rest)*]);
+    };
+    ($Field:ident, $Type:ty, $Attrs:tt) => {{
+        #[allow(unused_mut)]
+        let mut field = $crate::FieldSchema::new(stringify!($Field), $crate::field_schema!(@type $Type, $Attrs));
+        $crate::field_schema!(@attrs field, $Attrs);
+        field
+    }};
+}
+
+/// ## Serde Item Macro
+/// This macro adds the serde::Serialize and serde::Deserialize derives to the generated structs,
+/// enums and packet groups. When the "serde" feature is disabled the item is left unchanged.
+/// The derives refer to serde through the glob import of serde_crate as the crate attribute
+/// only takes a string (which can't be made from $crate) so this works under any crate name
+#[cfg(feature = "serde")]
+#[macro_export]
+macro_rules! serde_item {
+    (transparent $Item:item) => {
+        #[allow(unused_imports)]
+        use $crate::serde_crate::*;
+        #[derive($crate::serde::Serialize, $crate::serde::Deserialize)]
+        #[serde(crate = "__wsbps_serde", transparent)]
+        $Item
+    };
+    ($Item:item) => {
+        #[allow(unused_imports)]
+        use $crate::serde_crate::*;
+        #[derive($crate::serde::Serialize, $crate::serde::Deserialize)]
+        #[serde(crate = "__wsbps_serde")]
+        $Item
+    };
+}
+
+/// ## Serde Item Macro
+/// The "serde" feature is disabled so the item is left unchanged
+#[cfg(not(feature = "serde"))]
+#[macro_export]
+macro_rules! serde_item {
+    (transparent $Item:item) => {
+        $Item
+    };
+    ($Item:item) => {
+        $Item
+    };
+}
+
+/// ## Impl Codec Macro
+/// This macro implements CodecWrite and CodecRead for the generated types depending on
+/// their mode so they can be encoded and decoded using any of the codecs
+#[macro_export]
+macro_rules! impl_codec {
+    ((<->) $Name:ident) => {
+        $crate::impl_codec!((->) $Name);
+        $crate::impl_codec!((<-) $Name);
+    };
+    ((->) $Name:ident) => {
+        impl $crate::CodecWrite for $Name {
+            fn encode_with(&mut self, codec: $crate::Codec) -> $crate::PacketResult<$crate::alloc::vec::Vec<u8>> {
+                $crate::codec::encode_with_codec(codec, self)
+            }
+        }
+    };
+    ((<-) $Name:ident) => {
+        impl $crate::CodecRead for $Name {
+            fn decode_with(codec: $crate::Codec, data: &[u8]) -> $crate::PacketResult<Self> {
+                $crate::codec::decode_with_codec(codec, data)
+            }
+        }
+    };
+}
+
+/// ## Impl Batch Macro
+/// This macro implements the read_batch and write_batch functions for packet groups
+/// depending on their mode for sending multiple packets in a single frame
+#[macro_export]
+macro_rules! impl_batch {
+    ((<->) $Group:ident) => {
+        $crate::impl_batch!((->) $Group);
+        $crate::impl_batch!((<-) $Group);
+    };
+    ((->) $Group:ident) => {
+        #[allow(dead_code)]
+        impl $Group {
+            /// Writes the [packets] to [o] as a batch sent in a single frame
+            pub fn write_batch<B: $crate::io::Write>(packets: &mut [Self], o: &mut B) -> $crate::WriteResult {
+                $crate::write_batch(packets, o)
+            }
+        }
+    };
+    ((<-) $Group:ident) => {
+        #[allow(dead_code)]
+        impl $Group {
+            /// Reads a batch of packets of this group sent in a single frame from [i]
+            pub fn read_batch<B: $crate::io::Read>(i: &mut B) -> $crate::ReadResult<$crate::alloc::vec::Vec<Self>> {
+                $crate::read_batch(i)
+            }
+        }
+    };
+}
+
+/// ## Impl Peek ID Macro
+/// This macro implements the peek_id functions for packet groups which read the ID of the
+/// next packet without consuming it. These require std::io so when the "std" feature is
+/// disabled this macro expands to nothing.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! impl_peek_id {
+    ($Group:ident) => {
+        #[allow(dead_code)]
+        impl $Group {
+            /// Reads the ID of the next packet from [r] without consuming it by seeking back
+            /// to where it started so routers can forward the raw bytes without decoding
+            pub fn peek_id<R: $crate::io::Read + $crate::io::Seek>(r: &mut R) -> $crate::ReadResult<u32> {
+                $crate::peek_id(r)
+            }
+
+            /// Reads the ID of the next packet from the buffered bytes of [r] without
+            /// consuming them for streams that can't seek
+            pub fn peek_id_buffered<R: $crate::io::BufRead>(r: &mut R) -> $crate::ReadResult<u32> {
+                $crate::peek_id_buffered(r)
+            }
+        }
+    };
+}
+
+/// ## Impl Peek ID Macro
+/// The "std" feature is disabled so nothing is implemented
+#[cfg(not(feature = "std"))]
+#[macro_export]
+macro_rules! impl_peek_id {
+    ($($tokens:tt)*) => {};
+}
+
+/// ## Impl Packet Data
+/// This is the underlying backing macro for packet_data which handles which type should be
+/// implemented and for which mode (enum / struct) this is used to speed up parsing and reduce
+/// the complexity of the packet_data macro
+#[macro_export]
+macro_rules! impl_packet_data {
+    // Matching enums
+    (
+        enum $Name:ident $Mode:tt ($($Type:tt)+) {
+            $($Field:ident, $Value:expr, $Attrs:tt),*
+        }
+    ) => {
+        $crate::impl_packet_data!(
+            @enum [exact] $Name $Mode ($($Type)+) {
+                $($Field, $Value, $Attrs),*
+            }
+        );
+    };
+    // Matching string enums which ignore the case of the values they read
+    (
+        case_insensitive enum $Name:ident $Mode:tt (String) {
+            $($Field:ident, $Value:expr, $Attrs:tt),*
+        }
+    ) => {
+        $crate::impl_packet_data!(
+            @enum [case_insensitive] $Name $Mode (String) {
+                $($Field, $Value, $Attrs),*
+            }
+        );
+    };
+    (
+        @enum [$Matching:ident] $Name:ident $Mode:tt ($($Type:tt)+) {
+            $($Field:ident, $Value:expr, $Attrs:tt),*
+        }
+    ) => {
+        // Create the backing enum with its wire layout documented
+        $crate::serde_item! {
+            #[doc = concat!("Encoded as a single `", stringify!($($Type)+), "` holding the value for the variant")]
+            #[doc = ""]
+            #[doc = "| Variant | Value |"]
+            #[doc = "|---------|-------|"]
+            $(#[doc = concat!("| `", stringify!($Field), "` | `", stringify!($Value), "` |")])*
+            #[derive(Debug, Clone, PartialEq)]
+            #[allow(dead_code)]
+            pub enum $Name {
+                $($Field),*
+            }
+        }
+
+        // Implement the traits for the provided mode
+        $crate::impl_enum_mode!(
+            $Mode $Name ($($Type)+) {
+                $($Field, $Value),*
+            }
+        );
+
+        $crate::impl_string_enum!(
+            ($($Type)+) [$Matching] $Name {
+                $($Field, $Value, $Attrs),*
+            }
+        );
+
+        $crate::impl_enum_values!(
+            ($($Type)+) $Name {
+                $($Field, $Value),*
+            }
+        );
+
+        $crate::impl_arbitrary!(
+            enum $Name {
+                $($Field),*
+            }
+        );
+
+        $crate::impl_ordered!(
+            enum $Name {
+                $($Field),*
+            }
+        );
+
+        $crate::impl_summary!(
+            enum $Name {
+                $($Field),*
+            }
+        );
+
+        $crate::impl_schema!(
+            enum $Name $($Type)+ {
+                $($Field, $Value),*
+            }
+        );
+
+        $crate::impl_codec!($Mode $Name);
+    };
+    // Matching structs
+    (
+        struct $Name:ident $Mode:tt {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        // Create the backing struct with its wire layout documented
+        $crate::serde_item! {
+            #[doc = "Fields are encoded in the following order with no padding between them"]
+            #[doc = ""]
+            #[doc = "| Field | Type |"]
+            #[doc = "|-------|------|"]
+            $(#[doc = $crate::field_doc!($Field, $FieldType, $Attrs)])*
+            #[doc = ""]
+            #[doc = $crate::wire_layout_legend!()]
+            #[derive(Debug, Clone, PartialEq)]
+            #[allow(dead_code)]
+            pub struct $Name {
+                $(pub $Field: $FieldType),*
+            }
+        }
+
+        // Implement the traits for the provided mode
+        $crate::impl_struct_mode!(
+            $Mode $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_arbitrary!(
+            struct $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_ordered!(
+            struct $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_summary!(
+            struct $Name {
+                $($Field),*
+            }
+        );
+
+        $crate::impl_schema!(
+            struct $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_diffable!(
+            $Mode $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_log_fields!(
+            struct $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_codec!($Mode $Name);
+    };
+    // Matching tagged structs
+    (
+        tagged struct $Name:ident $Mode:tt {
+            $($Field:ident, $FieldType:ty, $Attrs:tt),*
+        }
+    ) => {
+        // Create the backing struct with its wire layout documented
+        $crate::serde_item! {
+            #[doc = "Encoded as the number of fields (VarInt) followed by each field as its key (the tag shifted left"]
+            #[doc = "3 bits with the wire type in the low bits as a VarInt) and value. Fields can be in any order and"]
+            #[doc = "fields with unknown tags are skipped"]
+            #[doc = ""]
+            #[doc = "| Field | Tag | Type |"]
+            #[doc = "|-------|-----|------|"]
+            $(#[doc = concat!("| `", stringify!($Field), "` | ", $crate::tagged_field_id!($Attrs), " | `", stringify!($FieldType), "` |")])*
+            #[derive(Debug, Clone, PartialEq)]
+            #[allow(dead_code)]
+            pub struct $Name {
+                $(pub $Field: $FieldType),*
+            }
+        }
+
+        const _: () = assert!(
+            $crate::check_field_tags(&[$($crate::tagged_field_id!($Attrs)),*]),
+            concat!("the field tags of ", stringify!($Name), " must be unique and at most MAX_FIELD_TAG")
+        );
+
+        // Implement the traits for the provided mode
+        $crate::impl_tagged_struct_mode!(
+            $Mode $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_arbitrary!(
+            struct $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_ordered!(
+            struct $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_summary!(
+            struct $Name {
+                $($Field),*
+            }
+        );
+
+        $crate::impl_schema!(tagged struct $Name);
+
+        $crate::impl_log_fields!(
+            struct $Name {
+                $($Field, $FieldType, $Attrs),*
+            }
+        );
+
+        $crate::impl_codec!($Mode $Name);
+    };
+    (
+        $Option:ident $Keyword:ident $Name:ident $($Rest:tt)*
+    ) => {
+        compile_error!(concat!("the ", stringify!($Option), " option isn't supported for ", stringify!($Keyword), " ", stringify!($Name)));
+    };
+}
+
+/// ## Packet Data
+/// This macro is used to implement read and write traits for enums so they can be used within
+/// packets as packet fields. This is a block and you should use it to implement all of your
+/// structs and enums at once.
+///
 /// ## Directions
 /// (<->) Bi-Direction: This implements both readers and writers for this data. This should
 /// be used in structs and enums that are shared between readable and writable packets.
@@ -205,6 +1856,15 @@ macro_rules! impl_packet_data {
 /// (<-) Read-Only: This implements only the readers for this data. This should be used if
 /// the struct/enum is only going to be received and not send.
 ///
+/// ## Options
+/// #[wsbps(tagged)] Structs with this option encode each field with the ID from its
+/// #[tag(id)] attribute so fields can be added and removed while older peers skip the
+/// fields they don't know
+///
+/// #[wsbps(case_insensitive)] Enums with String values and this option ignore the case
+/// of ASCII letters when reading values. Variants can also accept other values when
+/// reading by listing them after the value (`Red: "red" | "RED"`)
+///
 /// ## Example
 ///
 /// ```
@@ -226,9 +1886,11 @@ macro_rules! impl_packet_data {
 macro_rules! packet_data {
     (
         $(
-            $Keyword:ident $Name:ident $Mode:tt $(($Type:ty))? {
+            $(#[wsbps($Option:ident)])?
+            $Keyword:ident $Name:ident $Mode:tt $(($($Type:tt)+))? {
                 $(
-                    $Field:ident:$($EnumValue:literal)?$($FieldType:ty)?
+                    $(#[$($FieldAttr:tt)*])*
+                    $Field:ident:$($EnumValue:literal $(| $Alias:literal)*)?$($FieldType:ty)? $(= $Default:expr)?
                 ),* $(,)?
             }
         )*
@@ -236,8 +1898,8 @@ macro_rules! packet_data {
         $(
             // Implement the underlying types for each matched value
             $crate::impl_packet_data!(
-                $Keyword $Name $Mode $($Type)? {
-                    $($Field, $($EnumValue)? $($FieldType)?),*
+                $($Option)? $Keyword $Name $Mode $(($($Type)+))? {
+                    $($Field, $($EnumValue)? $($FieldType)?, [$(#[$($FieldAttr)*])* $($(#[alias($Alias)])*)? $(#[default($Default)])?]),*
                 }
             );
         )*
@@ -252,8 +1914,8 @@ macro_rules! impl_group_mode {
     (
         (<-) $Group:ident {
             $(
-                $Name:ident, $ID:literal {
-                    $($Field:ident, $Type:ty),*
+                $Name:ident, $ID:tt {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
                 }
             );*
         }
@@ -262,17 +1924,35 @@ macro_rules! impl_group_mode {
         // implemented here so we can read the packet ID first then read the
         // respective packet
         impl $crate::Readable for $Group {
-            fn read<_ReadX: std::io::Read>(i: &mut _ReadX) -> $crate::ReadResult<Self> {
-                let p_id = $crate::VarInt::read(i)?.0;
+            fn read<_ReadX: $crate::io::Read>(i: &mut _ReadX) -> $crate::ReadResult<Self> {
+                Self::read_with(&mut $crate::ReadContext::default(), i)
+            }
+
+            fn read_with<_ReadX: $crate::io::Read>(ctx: &mut $crate::ReadContext, i: &mut _ReadX) -> $crate::ReadResult<Self> {
+                let p_id = <$crate::VarInt as $crate::Readable>::read_with(ctx, i)?.0;
+                <Self as $crate::ReadPacket>::read_packet(ctx, p_id, i)
+            }
+        }
+
+        impl $crate::ReadPacket for $Group {
+            #[allow(unused_variables, unused_assignments, unused_mut)]
+            fn read_packet<_ReadX: $crate::io::Read>(ctx: &mut $crate::ReadContext, p_id: u32, i: &mut _ReadX) -> $crate::ReadResult<Self> {
                 match p_id {
                     // Match for all the packet IDS and read the packet struct and return
                     // the enum value with the struct as the value
                     $(
-                        $ID => Ok($Group::$Name {
+                        _ if p_id == $ID as u32 => ctx.enter(|ctx| $crate::read_counted(ctx, i, |ctx, i| {
+                            let mut index = 0;
+                            // Read all the fields in order so that fields can depend on earlier fields
                             $(
-                                $Field: <$Type>::read(i)?.into(),
+                                let $Field: $Type = $crate::read_field_with_context(ctx, i, stringify!($Name), stringify!($Field), index, |ctx, i| {
+                                    Ok($crate::read_field!(ctx, i, $Type, $Attrs))
+                                })?;
+                                $crate::validate_field!($Name, $Field, $Attrs);
+                                index += 1;
                             )*
-                        }),
+                            Ok($Group::$Name { $($Field),* })
+                        })),
                     )*
                     _ => Err($crate::PacketError::UnknownPacket(p_id))
                 }
@@ -282,21 +1962,25 @@ macro_rules! impl_group_mode {
     (
         (->) $Group:ident {
             $(
-                $Name:ident, $ID:literal {
-                    $($Field:ident, $Type:ty),*
+                $Name:ident, $ID:tt {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
                 }
             );*
         }
     ) => {
         impl $crate::Writable for $Group {
-            fn write<_WriteX: std::io::Write>(&mut self, o: &mut _WriteX) -> $crate::WriteResult {
+            fn write<_WriteX: $crate::io::Write>(&mut self, o: &mut _WriteX) -> $crate::WriteResult {
+                self.write_with(&mut $crate::WriteContext::default(), o)
+            }
+
+            fn write_with<_WriteX: $crate::io::Write>(&mut self, ctx: &mut $crate::WriteContext, o: &mut _WriteX) -> $crate::WriteResult {
                 match self {
                     $(
                         $Group::$Name {
                             $($Field),*
                         } => {
                             $crate::VarInt($ID as u32).write(o)?;
-                            $($crate::writable_type!($Type, $Field).write(o)?;)*
+                            $($crate::write_field!(ctx, o, $Field, $Type, $Attrs);)*
                         },
                     )*
                 }
@@ -307,8 +1991,8 @@ macro_rules! impl_group_mode {
     (
         (<->) $Group:ident {
             $(
-                $Name:ident, $ID:literal {
-                    $($Field:ident, $Type:ty),*
+                $Name:ident, $ID:tt {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
                 }
             );*
         }
@@ -317,7 +2001,7 @@ macro_rules! impl_group_mode {
             (<-) $Group {
                 $(
                     $Name, $ID {
-                        $($Field, $Type),*
+                        $($Field, $Type, $Attrs),*
                     }
                 );*
             }
@@ -326,12 +2010,148 @@ macro_rules! impl_group_mode {
            (->) $Group {
                 $(
                     $Name, $ID {
-                        $($Field, $Type),*
+                        $($Field, $Type, $Attrs),*
                     }
                 );*
             }
         );
     };
+    (
+        structs (<-) $Group:ident {
+            $($Name:ident, $ID:tt);*
+        }
+    ) => {
+        impl $crate::Readable for $Group {
+            fn read<_ReadX: $crate::io::Read>(i: &mut _ReadX) -> $crate::ReadResult<Self> {
+                Self::read_with(&mut $crate::ReadContext::default(), i)
+            }
+
+            fn read_with<_ReadX: $crate::io::Read>(ctx: &mut $crate::ReadContext, i: &mut _ReadX) -> $crate::ReadResult<Self> {
+                let p_id = <$crate::VarInt as $crate::Readable>::read_with(ctx, i)?.0;
+                <Self as $crate::ReadPacket>::read_packet(ctx, p_id, i)
+            }
+        }
+
+        impl $crate::ReadPacket for $Group {
+            #[allow(unused_variables)]
+            fn read_packet<_ReadX: $crate::io::Read>(ctx: &mut $crate::ReadContext, p_id: u32, i: &mut _ReadX) -> $crate::ReadResult<Self> {
+                match p_id {
+                    // Read the packet struct for the ID and wrap it in the group
+                    $(
+                        _ if p_id == $ID as u32 => Ok($Group::$Name(<$Name as $crate::Readable>::read_with(ctx, i)?)),
+                    )*
+                    _ => Err($crate::PacketError::UnknownPacket(p_id))
+                }
+            }
+        }
+    };
+    (
+        structs (->) $Group:ident {
+            $($Name:ident, $ID:tt);*
+        }
+    ) => {
+        impl $crate::Writable for $Group {
+            fn write<_WriteX: $crate::io::Write>(&mut self, o: &mut _WriteX) -> $crate::WriteResult {
+                self.write_with(&mut $crate::WriteContext::default(), o)
+            }
+
+            fn write_with<_WriteX: $crate::io::Write>(&mut self, ctx: &mut $crate::WriteContext, o: &mut _WriteX) -> $crate::WriteResult {
+                match self {
+                    $(
+                        $Group::$Name(packet) => {
+                            $crate::Writable::write(&mut $crate::VarInt($ID as u32), o)?;
+                            $crate::Writable::write_with(packet, ctx, o)?;
+                        },
+                    )*
+                }
+                Ok(())
+            }
+        }
+    };
+    (
+        structs (<->) $Group:ident {
+            $($Name:ident, $ID:tt);*
+        }
+    ) => {
+        $crate::impl_group_mode!(structs (<-) $Group { $($Name, $ID);* });
+        $crate::impl_group_mode!(structs (->) $Group { $($Name, $ID);* });
+    };
+}
+
+/// # Impl Group Options Macro
+/// This macro handles the options provided to a group using the `#[wsbps(...)]` attribute
+///
+/// ## Options
+/// tests: Generates a roundtrip_tests module containing a property test for every packet
+/// in the group. tests = name can be used to change the name of the generated module
+///
+/// structs: Generates a separate struct for each packet which the group enum wraps
+/// (`Group::TestA(TestA)`) rather than using struct variants. This option is handled
+/// by impl_packets_group and selects the layout passed to this macro
+#[macro_export]
+macro_rules! impl_group_options {
+    ($Layout:ident $Mode:tt $Group:ident [] $Packets:tt) => {};
+    ($Layout:ident $Mode:tt $Group:ident [tests = $Module:ident $(, $($Rest:tt)*)?] $Packets:tt) => {
+        $crate::impl_roundtrip_tests!($Layout $Mode $Group $Module $Packets);
+        $crate::impl_group_options!($Layout $Mode $Group [$($($Rest)*)?] $Packets);
+    };
+    ($Layout:ident $Mode:tt $Group:ident [tests $(, $($Rest:tt)*)?] $Packets:tt) => {
+        $crate::impl_roundtrip_tests!($Layout $Mode $Group roundtrip_tests $Packets);
+        $crate::impl_group_options!($Layout $Mode $Group [$($($Rest)*)?] $Packets);
+    };
+    ($Layout:ident $Mode:tt $Group:ident [$Unknown:tt $($Rest:tt)*] $Packets:tt) => {
+        compile_error!(concat!("unknown wsbps group option: ", stringify!($Unknown)));
+    };
+}
+
+/// # Impl Roundtrip Tests Macro
+/// Generates a test module with a roundtrip property test for each packet in a bi-directional
+/// group. Each test generates packets from pseudo-random data using arbitrary and checks that
+/// reading the written packet produces the same packet. When the "arbitrary" feature is
+/// disabled no tests are generated.
+#[cfg(feature = "arbitrary")]
+#[macro_export]
+macro_rules! impl_roundtrip_tests {
+    (
+        $Layout:ident (<->) $Group:ident $Module:ident {
+            $(
+                $Name:ident {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
+                }
+            );*
+        }
+    ) => {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod $Module {
+            use super::*;
+
+            $(
+                mod $Name {
+                    use super::*;
+
+                    #[test]
+                    #[allow(unused_variables)]
+                    fn roundtrip() {
+                        $crate::roundtrip_property(256, |u| Ok($crate::arbitrary_packet!($Layout u, $Group::$Name {
+                            $($Field, $Type, $Attrs),*
+                        })));
+                    }
+                }
+            )*
+        }
+    };
+    ($Layout:ident $Mode:tt $Group:ident $Module:ident $Packets:tt) => {
+        compile_error!(concat!("roundtrip tests can only be generated for bi-directional (<->) groups not ", stringify!($Group)));
+    };
+}
+
+/// # Impl Roundtrip Tests Macro
+/// The "arbitrary" feature is disabled so no tests are generated
+#[cfg(not(feature = "arbitrary"))]
+#[macro_export]
+macro_rules! impl_roundtrip_tests {
+    ($($tokens:tt)*) => {};
 }
 
 /// # Packets Macro
@@ -339,6 +2159,21 @@ macro_rules! impl_group_mode {
 /// with their readers and writers (if they require them) and an enum for the packet group to
 /// read packets.
 ///
+/// Each packet also gets a constructor named after it in snake case which takes the fields in
+/// order. The arguments accept anything implementing IntoField for the field type so VarInts
+/// can be passed as u32 values and Strings as &str values. The `as_<packet>` and
+/// `try_into_<packet>` accessors extract the fields of a specific packet without a match.
+///
+/// ## Options
+/// Options are provided with the `#[wsbps(...)]` attribute on the group
+///
+/// tests: Generates a roundtrip property test for every packet (see impl_group_options)
+///
+/// structs: Each packet is generated as a separate struct which the group enum wraps
+/// (`Group::TestA(TestA)`) so packets can be passed around by type and have traits
+/// implemented on them. The accessors then return the packet struct and From is
+/// implemented for the group from each packet struct.
+///
 /// ## Directions
 /// (<->) Bi-Direction: This implements both readers and writers for this data. This should
 /// be used in structs and enums that are shared between readable and writable packets.
@@ -375,26 +2210,62 @@ macro_rules! impl_group_mode {
 ///         }
 ///     }
 /// }
+///
+/// let packet = BiPackets::a_packet(1, "Jacob");
+/// assert_eq!(packet, BiPackets::APacket { User: 1, Name: "Jacob".to_string() });
+/// assert_eq!(packet.as_a_packet(), Some((&1, &"Jacob".to_string())));
+/// assert_eq!(packet.as_b_packet(), None);
 /// ```
 #[macro_export]
 macro_rules! packets {
     (
         $(
+            $(#[wsbps($($Option:tt)*)])?
             $Group:ident $Mode:tt {
-                 $(
-                     $Name:ident ($ID:literal) {
-                            $($Field:ident: $Type:ty),* $(,)?
-                     }
-                 )*
+                $($Body:tt)*
             }
         )*
     ) => {
         $(
-            // Implement the group enum
+            $crate::impl_packets_group!([$($($Option)*)?] $Group $Mode [] { $($Body)* });
+        )*
+    };
+}
+
+/// # Impl Packets Group Macro
+/// This is the underlying backing macro for packets which expands any of the packet sets
+/// included at the start of the group before implementing the group. Each packet set
+/// macro appends its packets to the included packets and passes them back to this macro
+#[macro_export]
+macro_rules! impl_packets_group {
+    // Packets are variants of the group enum
+    (
+        @generate variants [$($Option:tt)*] $Group:ident $Mode:tt {
+            $(
+                $Name:ident ($ID:expr) {
+                    $(
+                        $(#[$($FieldAttr:tt)*])*
+                        $Field:ident: $Type:ty $(= $Default:expr)?
+                    ),* $(,)?
+                }
+            )*
+        }
+    ) => {
+        // Implement the group enum with the wire layout of each packet documented
+        $crate::serde_item! {
+            #[doc = concat!("Packet group with the direction `", stringify!($Mode), "`. Each packet is encoded ")]
+            #[doc = "as its ID (VarInt) followed by its fields in order."]
             #[derive(Debug, Clone, PartialEq)]
             #[allow(dead_code)]
             pub enum $Group {
                 $(
+                    #[doc = concat!("Packet ID: `", stringify!($ID), "` encoded as a VarInt followed by the fields in order")]
+                    #[doc = ""]
+                    #[doc = "| Field | Type |"]
+                    #[doc = "|-------|------|"]
+                    $(#[doc = $crate::field_doc!($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?])])*
+                    #[doc = ""]
+                    #[doc = $crate::wire_layout_legend!()]
                     $Name {
                         $(
                             $Field: $Type,
@@ -402,27 +2273,524 @@ macro_rules! packets {
                     }
                 ),*
             }
+        }
+
+        // Implement the specified group mode
+        $crate::impl_group_mode!(
+            $Mode $Group {
+                $(
+                    $Name, $ID {
+                        $($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?]),*
+                    }
+                );*
+            }
+        );
+
+        $crate::impl_arbitrary!(
+            group $Group {
+                $(
+                    $Name {
+                        $($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?]),*
+                    }
+                );*
+            }
+        );
 
-            // Implement the specified group mode
-            $crate::impl_group_mode!(
-                $Mode $Group {
-                    $(
-                        $Name, $ID {
-                            $($Field, $Type),*
+        $crate::impl_ordered!(
+            group $Group {
+                $(
+                    $Name {
+                        $($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?]),*
+                    }
+                );*
+            }
+        );
+
+        $crate::impl_summary!(
+            group $Group {
+                $(
+                    $Name ($ID) {
+                        $($Field),*
+                    }
+                );*
+            }
+        );
+
+        $crate::impl_log_fields!(
+            group $Group {
+                $(
+                    $Name {
+                        $($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?]),*
+                    }
+                );*
+            }
+        );
+
+        // Implement a constructor and accessors for each packet named after the packet in snake case
+        $crate::pastey::paste! {
+            #[allow(dead_code)]
+            impl $Group {
+                $(
+                    #[doc = concat!("Creates a new `", stringify!($Name), "` packet")]
+                    #[allow(non_snake_case, clippy::too_many_arguments)]
+                    pub fn [< # $Name:snake >]($($Field: impl $crate::IntoField<$Type>),*) -> Self {
+                        $Group::$Name {
+                            $($Field: $crate::IntoField::into_field($Field),)*
                         }
-                    );*
+                    }
+
+                    #[doc = concat!("Returns the fields of this packet in order if it is a `", stringify!($Name), "` packet")]
+                    #[allow(non_snake_case, unused_parens)]
+                    pub fn [<as_ $Name:snake>](&self) -> Option<($(&$Type),*)> {
+                        match self {
+                            $Group::$Name { $($Field),* } => Some(($($Field),*)),
+                            #[allow(unreachable_patterns)]
+                            _ => None
+                        }
+                    }
+
+                    #[doc = concat!("Converts this packet into its fields in order if it is a `", stringify!($Name), "` packet ")]
+                    #[doc = "otherwise the packet is returned as the error"]
+                    #[allow(non_snake_case, unused_parens)]
+                    pub fn [<try_into_ $Name:snake>](self) -> Result<($($Type),*), Self> {
+                        match self {
+                            $Group::$Name { $($Field),* } => Ok(($($Field),*)),
+                            #[allow(unreachable_patterns)]
+                            packet => Err(packet)
+                        }
+                    }
+                )*
+            }
+        }
+
+        $crate::impl_packets_group!(@common variants [$($Option)*] $Group $Mode {
+            $(
+                $Name ($ID) {
+                    $($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?]),*
+                }
+            )*
+        });
+    };
+    // Packets are separate structs wrapped by the group enum
+    (
+        @generate structs [$($Option:tt)*] $Group:ident $Mode:tt {
+            $(
+                $Name:ident ($ID:expr) {
+                    $(
+                        $(#[$($FieldAttr:tt)*])*
+                        $Field:ident: $Type:ty $(= $Default:expr)?
+                    ),* $(,)?
+                }
+            )*
+        }
+    ) => {
+        // Implement the struct for each packet
+        $(
+            $crate::impl_packet_data!(
+                struct $Name $Mode {
+                    $($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?]),*
                 }
             );
+        )*
+
+        // Implement the group enum wrapping each of the packet structs
+        $crate::serde_item! {
+            #[doc = concat!("Packet group with the direction `", stringify!($Mode), "`. Each packet is encoded ")]
+            #[doc = "as its ID (VarInt) followed by the fields of its struct in order."]
+            #[derive(Debug, Clone, PartialEq)]
+            #[allow(dead_code)]
+            pub enum $Group {
+                $(
+                    #[doc = concat!("Packet ID: `", stringify!($ID), "` encoded as a VarInt followed by the fields of [`", stringify!($Name), "`]")]
+                    $Name($Name)
+                ),*
+            }
+        }
+
+        // Implement the specified group mode
+        $crate::impl_group_mode!(
+            structs $Mode $Group {
+                $($Name, $ID);*
+            }
+        );
+
+        $crate::impl_arbitrary!(
+            structs $Group {
+                $($Name),*
+            }
+        );
+
+        $crate::impl_ordered!(
+            structs $Group {
+                $($Name),*
+            }
+        );
 
-            // Implement packet variant ID for each packet enum value
+        $crate::impl_summary!(
+            structs $Group {
+                $(
+                    $Name ($ID) {
+                        $($Field),*
+                    }
+                );*
+            }
+        );
+
+        $crate::impl_log_fields!(
+            structs $Group {
+                $($Name),*
+            }
+        );
+
+        $(
+            impl From<$Name> for $Group { fn from(v: $Name) -> Self { $Group::$Name(v) } }
+        )*
+
+        // Implement a constructor and accessors for each packet named after the packet in snake case
+        $crate::pastey::paste! {
+            #[allow(dead_code)]
             impl $Group {
-                // Packet id function to allow retrieval of the packet ID on the packet
-                fn id(&self) -> $crate::VarInt {
-                    $crate::VarInt(match self {
-                        $($Group::$Name { .. } => $ID as u32,)*
-                    })
+                $(
+                    #[doc = concat!("Creates a new `", stringify!($Name), "` packet")]
+                    #[allow(non_snake_case, clippy::too_many_arguments)]
+                    pub fn [< # $Name:snake >]($($Field: impl $crate::IntoField<$Type>),*) -> Self {
+                        $Group::$Name($Name {
+                            $($Field: $crate::IntoField::into_field($Field),)*
+                        })
+                    }
+
+                    #[doc = concat!("Returns the packet if it is a `", stringify!($Name), "` packet")]
+                    pub fn [<as_ $Name:snake>](&self) -> Option<&$Name> {
+                        match self {
+                            $Group::$Name(packet) => Some(packet),
+                            #[allow(unreachable_patterns)]
+                            _ => None
+                        }
+                    }
+
+                    #[doc = concat!("Converts this into the packet if it is a `", stringify!($Name), "` packet ")]
+                    #[doc = "otherwise the packet is returned as the error"]
+                    pub fn [<try_into_ $Name:snake>](self) -> Result<$Name, Self> {
+                        match self {
+                            $Group::$Name(packet) => Ok(packet),
+                            #[allow(unreachable_patterns)]
+                            packet => Err(packet)
+                        }
+                    }
+                )*
+            }
+        }
+
+        $crate::impl_packets_group!(@common structs [$($Option)*] $Group $Mode {
+            $(
+                $Name ($ID) {
+                    $($Field, $Type, [$(#[$($FieldAttr)*])* $(#[default($Default)])?]),*
+                }
+            )*
+        });
+    };
+    // Implementations shared by both layouts
+    (
+        @common $Layout:ident [$($Option:tt)*] $Group:ident $Mode:tt {
+            $(
+                $Name:ident ($ID:expr) {
+                    $($Field:ident, $Type:ty, $Attrs:tt),*
+                }
+            )*
+        }
+    ) => {
+        $crate::impl_codec!($Mode $Group);
+        $crate::impl_batch!($Mode $Group);
+
+        $crate::impl_peek_id!($Group);
+
+        $crate::impl_schema!(
+            group $Group {
+                $(
+                    $Name ($ID) {
+                        $($Field, $Type, $Attrs),*
+                    }
+                )*
+            }
+        );
+
+        // Implement the options provided in the group attribute
+        $crate::impl_group_options!(
+            $Layout $Mode $Group [$($Option)*] {
+                $(
+                    $Name {
+                        $($Field, $Type, $Attrs),*
+                    }
+                );*
+            }
+        );
+
+        // Implement packet variant ID for each packet enum value
+        #[allow(dead_code)]
+        impl $Group {
+            // Packet id function to allow retrieval of the packet ID on the packet
+            pub fn id(&self) -> $crate::VarInt {
+                $crate::VarInt(match self {
+                    $($Group::$Name { .. } => $ID as u32,)*
+                })
+            }
+
+            /// The metric keys for all the packets in this group in declaration order
+            pub const METRIC_KEYS: &'static [&'static str] = &[
+                $(concat!(stringify!($Group), ".", stringify!($Name)),)*
+            ];
+
+            /// Stable string key for the type of this packet (e.g. "BiPackets.TestA") used
+            /// to label metrics, traces and audit records consistently
+            pub fn metric_key(&self) -> &'static str {
+                match self {
+                    $($Group::$Name { .. } => concat!(stringify!($Group), ".", stringify!($Name)),)*
+                }
+            }
+
+            /// Stable numeric ID for the type of this packet derived from its metric key
+            pub fn metric_id(&self) -> u64 {
+                match self {
+                    $($Group::$Name { .. } => {
+                        const ID: u64 = $crate::metric_id(concat!(stringify!($Group), ".", stringify!($Name)));
+                        ID
+                    },)*
+                }
+            }
+        }
+
+        // Packet IDs are written as VarInts so they must fit in a u32, this fails the build
+        // rather than letting the ID casts wrap negative or wider IDs onto other packets
+        const _: () = {
+            let ids: &[i128] = &[$($ID as i128),*];
+            let mut a = 0;
+            while a < ids.len() {
+                if ids[a] < 0 || ids[a] > u32::MAX as i128 {
+                    panic!(concat!("packet ID outside of the VarInt range (0 to u32::MAX) in the group ", stringify!($Group)));
+                }
+                a += 1;
+            }
+        };
+
+        // Packet IDs must be unique within the group, this also catches collisions
+        // between included packet sets
+        const _: () = {
+            let ids: &[u32] = &[$($ID as u32),*];
+            let mut a = 0;
+            while a < ids.len() {
+                let mut b = a + 1;
+                while b < ids.len() {
+                    if ids[a] == ids[b] {
+                        panic!(concat!("duplicate packet ID in the group ", stringify!($Group)));
+                    }
+                    b += 1;
+                }
+                a += 1;
+            }
+        };
+    };
+    // Select the layout of the group using the structs option which is removed from the
+    // options, the value of any other option is skipped so it isn't mistaken for the option
+    (
+        @layout [$($Seen:tt)*] [structs $(, $($Rest:tt)*)?] $Group:ident $Mode:tt $Packets:tt
+    ) => {
+        $crate::impl_packets_group!(@generate structs [$($Seen)* $($($Rest)*)?] $Group $Mode $Packets);
+    };
+    (
+        @layout [$($Seen:tt)*] [$Option:tt = $Value:tt $($Rest:tt)*] $Group:ident $Mode:tt $Packets:tt
+    ) => {
+        $crate::impl_packets_group!(@layout [$($Seen)* $Option = $Value] [$($Rest)*] $Group $Mode $Packets);
+    };
+    (
+        @layout [$($Seen:tt)*] [$Next:tt $($Rest:tt)*] $Group:ident $Mode:tt $Packets:tt
+    ) => {
+        $crate::impl_packets_group!(@layout [$($Seen)* $Next] [$($Rest)*] $Group $Mode $Packets);
+    };
+    (
+        @layout [$($Seen:tt)*] [] $Group:ident $Mode:tt $Packets:tt
+    ) => {
+        $crate::impl_packets_group!(@generate variants [$($Seen)*] $Group $Mode $Packets);
+    };
+    // Expand the next included packet set
+    (
+        $Options:tt $Group:ident $Mode:tt $Included:tt {
+            include $Set:ident $(+ $Offset:expr)?;
+            $($Rest:tt)*
+        }
+    ) => {
+        $Set! { [$($Offset)?] $Options $Group $Mode $Included { $($Rest)* } }
+    };
+    // All the packet sets have been included
+    (
+        [$($Option:tt)*] $Group:ident $Mode:tt [$($Included:tt)*] {
+            $($Packets:tt)*
+        }
+    ) => {
+        $crate::impl_packets_group!(@layout [] [$($Option)*] $Group $Mode { $($Included)* $($Packets)* });
+    };
+}
+/// # Packet Set Macro
+/// This macro defines sets of packets that can be included at the start of any number of
+/// groups using `include Name;` so that large protocols can be split across modules without
+/// copying packet definitions. `include Name + offset;` adds the offset to the ID of each
+/// of the included packets to avoid collisions with the other packets in the group. Each
+/// set is defined as a macro so it must be defined before it is included (or imported
+/// with `use`)
+///
+/// ## Example
+///
+/// ```
+/// use wsbps::{packet_set, packets};
+///
+/// packet_set! {
+///     Common {
+///         Ping (0x01) {}
+///         Chat (0x02) {
+///             message: String
+///         }
+///     }
+/// }
+///
+/// packets! {
+///     ClientPackets (->) {
+///         include Common + 0x10;
+///         Move (0x01) {
+///             x: u8
+///         }
+///     }
+/// }
+///
+/// assert_eq!(ClientPackets::Ping {}.id().0, 0x11);
+/// ```
+#[macro_export]
+macro_rules! packet_set {
+    (
+        @define ($d:tt)
+        $(#[$Meta:meta])*
+        $Set:ident {
+            $(
+                $Name:ident ($ID:expr) {
+                    $(
+                        $(#[$($FieldAttr:tt)*])*
+                        $Field:ident: $Type:ty $(= $Default:expr)?
+                    ),* $(,)?
+                }
+            )*
+        }
+    ) => {
+        $(#[$Meta])*
+        #[allow(unused_macros)]
+        macro_rules! $Set {
+            ([$d($d Offset:expr)?] $d Options:tt $d Group:ident $d Mode:tt [$d($d Included:tt)*] $d Body:tt) => {
+                $crate::impl_packets_group! {
+                    $d Options $d Group $d Mode [
+                        $d($d Included)*
+                        $(
+                            $Name ($ID $d(+ $d Offset)?) {
+                                $($(#[$($FieldAttr)*])* $Field: $Type $(= $Default)?),*
+                            }
+                        )*
+                    ] $d Body
+                }
+            };
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use $Set;
+    };
+    (
+        $(
+            $(#[$Meta:meta])*
+            $Set:ident $Packets:tt
+        )*
+    ) => {
+        $(
+            $crate::packet_set!(@define ($) $(#[$Meta])* $Set $Packets);
+        )*
+    };
+}
+
+/// # Wire Newtype Macro
+/// This macro creates transparent newtypes around existing wire types so protocols can use
+/// distinct types for values such as IDs and units without any change to the encoding. The
+/// newtype is encoded exactly the same as the type it wraps and implements From in both
+/// directions along with Display
+///
+/// ## Example
+///
+/// ```
+/// use wsbps::{wire_newtype, VarInt};
+/// wire_newtype! {
+///     /// Distance in millimeters
+///     Millimeters(u32);
+///     PlayerId(VarInt);
+/// }
+///
+/// let distance = Millimeters::from(25);
+/// assert_eq!(distance.to_string(), "25");
+/// ```
+#[macro_export]
+macro_rules! wire_newtype {
+    (
+        $(
+            $(#[$Meta:meta])*
+            $Name:ident($Type:ty)
+        );* $(;)?
+    ) => {
+        $(
+            $crate::serde_item! {
+                transparent
+                $(#[$Meta])*
+                #[derive(Debug, Clone, PartialEq)]
+                #[allow(dead_code)]
+                pub struct $Name(pub $Type);
+            }
+
+            impl $crate::Writable for $Name {
+                fn write<B: $crate::io::Write>(&mut self, o: &mut B) -> $crate::WriteResult {
+                    $crate::Writable::write(&mut self.0, o)
+                }
+
+                fn write_with<B: $crate::io::Write>(&mut self, ctx: &mut $crate::WriteContext, o: &mut B) -> $crate::WriteResult {
+                    $crate::Writable::write_with(&mut self.0, ctx, o)
+                }
+            }
+
+            impl $crate::FixedSize for $Name where for<'a> $Type: $crate::FixedSize {
+                const ENCODED_SIZE: usize = <$Type as $crate::FixedSize>::ENCODED_SIZE;
+            }
+
+            impl $crate::Readable for $Name {
+                fn read<B: $crate::io::Read>(i: &mut B) -> $crate::ReadResult<Self> where Self: Sized {
+                    Ok($Name(<$Type as $crate::Readable>::read(i)?))
+                }
+
+                fn read_with<B: $crate::io::Read>(ctx: &mut $crate::ReadContext, i: &mut B) -> $crate::ReadResult<Self> where Self: Sized {
+                    Ok($Name(<$Type as $crate::Readable>::read_with(ctx, i)?))
+                }
+            }
+
+            impl From<$Type> for $Name { fn from(v: $Type) -> Self { $Name(v) } }
+
+            impl From<$Name> for $Type { fn from(v: $Name) -> Self { v.0 } }
+
+            impl $crate::IntoField<$Name> for $Type {
+                fn into_field(self) -> $Name { $Name(self) }
+            }
+
+            impl core::fmt::Display for $Name {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    core::fmt::Display::fmt(&self.0, f)
                 }
             }
+
+            $crate::impl_arbitrary!(newtype $Name($Type));
+
+            $crate::impl_ordered!(newtype $Name($Type));
+
+            $crate::impl_summary!(newtype $Name($Type));
+
+            $crate::impl_schema!(newtype $Name($Type));
         )*
     };
-}
\ No newline at end of file
+}
@@ -0,0 +1,133 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+
+use crate::io::{Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{write_ordered_struct, OrderedRepr, OrderedStyle};
+use crate::summary::{write_summary_fields, Summary};
+
+/// ## Ticked
+/// A value stamped with the simulation tick that it is for. Clients send their
+/// inputs as Ticked values and the server acknowledges them by sending its state
+/// as a Ticked value with the tick of the last input it processed. This is encoded
+/// as the tick (VarInt) followed by the value
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ticked<T> {
+    /// The tick the value is for
+    pub tick: u32,
+    /// The value
+    pub value: T,
+}
+
+impl<T> Ticked<T> {
+    /// Creates a new value stamped with the provided tick
+    pub fn new(tick: u32, value: T) -> Self {
+        Ticked { tick, value }
+    }
+}
+
+impl<T: Writable> Writable for Ticked<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        VarInt(self.tick).write(o)?;
+        self.value.write_with(ctx, o)
+    }
+}
+
+impl<T: Readable> Readable for Ticked<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let tick = VarInt::read_with(ctx, i)?.0;
+        let value = ctx.enter(|ctx| T::read_with(ctx, i))?;
+        Ok(Ticked { tick, value })
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for Ticked<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_struct(style, out, "Ticked", &[("tick", &self.tick), ("value", &self.value)])
+    }
+}
+
+impl<T: Summary> Summary for Ticked<T> {
+    fn write_summary(&self, out: &mut String) {
+        write_summary_fields(out, "Ticked", None, &[("tick", &self.tick), ("value", &self.value)])
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Ticked<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Ticked { tick: u32::arbitrary(u)?, value: T::arbitrary(u)? })
+    }
+}
+
+/// The generators have no generic structs so the tick has to be read by hand
+#[cfg(feature = "codegen")]
+impl<T: crate::Schema> crate::Schema for Ticked<T> {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("Ticked") }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::Definition>) { T::definitions(definitions) }
+}
+
+/// ## Prediction Buffer
+/// Client side buffer of the inputs that have been applied locally (predicted) but
+/// haven't been acknowledged by the server yet. When the server state for a tick
+/// arrives the acknowledged inputs are dropped and the rest are replayed on top of
+/// the server state to reconcile the prediction
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictionBuffer<I> {
+    next_tick: u32,
+    pending: VecDeque<Ticked<I>>,
+}
+
+impl<I> PredictionBuffer<I> {
+    /// Creates a new empty buffer starting at tick 0
+    pub fn new() -> Self {
+        PredictionBuffer { next_tick: 0, pending: VecDeque::new() }
+    }
+
+    /// Stamps the [input] with the next tick keeping a copy until it is acknowledged
+    /// and returns the stamped input to send to the server
+    pub fn push(&mut self, input: I) -> Ticked<I> where I: Clone {
+        let ticked = Ticked::new(self.next_tick, input);
+        self.next_tick = self.next_tick.wrapping_add(1);
+        self.pending.push_back(ticked.clone());
+        ticked
+    }
+
+    /// Drops all the inputs up to and including the acknowledged [tick]
+    pub fn acknowledge(&mut self, tick: u32) {
+        while self.pending.front().is_some_and(|input| input.tick <= tick) {
+            self.pending.pop_front();
+        }
+    }
+
+    /// Reconciles the prediction with the server [state] for the acknowledged
+    /// [tick] by replaying the inputs that haven't been acknowledged yet on top of
+    /// it using [apply] returning the new predicted state
+    pub fn reconcile<S, F>(&mut self, tick: u32, state: S, mut apply: F) -> S
+        where F: FnMut(S, &I) -> S {
+        self.acknowledge(tick);
+        self.pending.iter().fold(state, |state, input| apply(state, &input.value))
+    }
+
+    /// Iterates over the inputs that haven't been acknowledged in tick order
+    pub fn pending(&self) -> impl Iterator<Item=&Ticked<I>> {
+        self.pending.iter()
+    }
+
+    /// The tick the next input will be stamped with
+    pub fn next_tick(&self) -> u32 { self.next_tick }
+}
+
+impl<I> Default for PredictionBuffer<I> {
+    fn default() -> Self { PredictionBuffer::new() }
+}
@@ -0,0 +1,94 @@
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{Read, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::ReadLimits;
+
+/// The size of the chunks that packet bodies are copied in
+const CHUNK_SIZE: usize = 4096;
+
+/// Reads the packet ID (VarInt) from [r] returning the ID along with the bytes it
+/// was encoded as so they can be copied verbatim
+fn read_id<R: Read>(r: &mut R) -> ReadResult<(u32, [u8; VarInt::MAX_SIZE], usize)> {
+    let mut bytes = [0u8; VarInt::MAX_SIZE];
+    for index in 0..VarInt::MAX_SIZE {
+        r.read_exact(&mut bytes[index..index + 1])?;
+        if bytes[index] & 0b1000_0000 /* 0x80 */ == 0 {
+            let (id, size) = VarInt::decode(&bytes[..index + 1])?;
+            return Ok((id.0, bytes, size));
+        }
+    }
+    Err(PacketError::VarOverflow("int", VarInt::MAX_SIZE))
+}
+
+/// ## Copy Packet
+/// Copies a packet from [r] to [w] byte for byte without decoding its body and
+/// returns the packet ID. Everything after the ID is treated as the body so [r]
+/// must only contain the one packet (e.g. a single websocket message). This lets
+/// relays forward packets without defining every downstream packet
+#[cfg(feature = "std")]
+pub fn copy_packet<R: Read, W: Write>(r: &mut R, w: &mut W) -> ReadResult<u32> {
+    let (id, bytes, size) = read_id(r)?;
+    w.write_all(&bytes[..size])?;
+    std::io::copy(r, w)?;
+    Ok(id)
+}
+
+/// ## Copy Packet Sized
+/// Copies a packet which is [length] bytes long in total (ID and body) from [r]
+/// to [w] byte for byte returning the packet ID. This is used when the length is
+/// known from the framing (e.g. a length prefix) and fails with
+/// PacketError::PacketTooLarge if the length is over the global max_packet_size
+pub fn copy_packet_sized<R: Read, W: Write>(r: &mut R, w: &mut W, length: usize) -> ReadResult<u32> {
+    let max_size = ReadLimits::global().max_packet_size;
+    if length > max_size {
+        return Err(PacketError::PacketTooLarge(max_size));
+    }
+    let (id, bytes, size) = read_id(r)?;
+    if size > length {
+        return Err(PacketError::UnexpectedValue("packet ID is longer than the packet length"));
+    }
+    w.write_all(&bytes[..size])?;
+    let mut remaining = length - size;
+    let mut chunk = [0u8; CHUNK_SIZE];
+    while remaining > 0 {
+        let count = remaining.min(CHUNK_SIZE);
+        r.read_exact(&mut chunk[..count])?;
+        w.write_all(&chunk[..count])?;
+        remaining -= count;
+    }
+    Ok(id)
+}
+
+/// ## Raw Packet
+/// A packet which is already encoded as its ID and body bytes. Writing it writes
+/// the ID (VarInt) followed by the body unchanged so proxies and replayers can send
+/// frames they never decoded through the same writers and queues as typed packets
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RawPacket {
+    /// The packet ID
+    pub id: u32,
+    /// The encoded contents of the packet after the ID
+    pub body: Vec<u8>,
+}
+
+impl RawPacket {
+    /// Creates a packet with the [id] and encoded [body]
+    pub fn new(id: u32, body: Vec<u8>) -> Self {
+        RawPacket { id, body }
+    }
+
+    /// Splits the [frame] of a single packet into its ID and body
+    pub fn from_frame(frame: &[u8]) -> ReadResult<Self> {
+        let (id, size) = VarInt::decode(frame)?;
+        Ok(RawPacket { id: id.0, body: frame[size..].to_vec() })
+    }
+}
+
+impl Writable for RawPacket {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        VarInt(self.id).write(o)?;
+        o.write_all(&self.body)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use alloc::collections::BTreeMap;
+
+use crate::error::PacketError;
+use crate::io::{Readable, ReadResult, VarInt};
+
+/// ## Rate Limit
+/// The maximum number of packets and bytes per second. A burst of up to a second's
+/// worth is allowed after a quiet period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The maximum number of packets per second
+    pub max_packets: Option<u32>,
+    /// The maximum number of bytes per second
+    pub max_bytes: Option<u32>,
+}
+
+impl RateLimit {
+    /// A limit which allows everything
+    pub const UNLIMITED: RateLimit = RateLimit { max_packets: None, max_bytes: None };
+
+    /// Limits the number of packets per second to [max_packets]
+    pub const fn packets(self, max_packets: u32) -> Self {
+        RateLimit { max_packets: Some(max_packets), ..self }
+    }
+
+    /// Limits the number of bytes per second to [max_bytes]
+    pub const fn bytes(self, max_bytes: u32) -> Self {
+        RateLimit { max_bytes: Some(max_bytes), ..self }
+    }
+}
+
+/// A token bucket for each of the limits of a RateLimit. Tokens are stored in
+/// thousandths so a rate per second refills by the rate every millisecond
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bucket {
+    packets: u64,
+    bytes: u64,
+    last: u64,
+}
+
+impl Bucket {
+    /// Creates a full bucket for the [limit]
+    fn new(limit: &RateLimit, now: u64) -> Self {
+        Bucket {
+            packets: capacity(limit.max_packets),
+            bytes: capacity(limit.max_bytes),
+            last: now,
+        }
+    }
+
+    /// Refills the bucket for the time passed since it was last used
+    fn refill(&mut self, limit: &RateLimit, now: u64) {
+        let elapsed = now.saturating_sub(self.last);
+        self.last = self.last.max(now);
+        if let Some(rate) = limit.max_packets {
+            self.packets = (self.packets + elapsed * rate as u64).min(capacity(Some(rate)));
+        }
+        if let Some(rate) = limit.max_bytes {
+            self.bytes = (self.bytes + elapsed * rate as u64).min(capacity(Some(rate)));
+        }
+    }
+
+    /// Whether the bucket has enough tokens for a packet of [bytes]
+    fn allows(&self, limit: &RateLimit, bytes: u64) -> bool {
+        (limit.max_packets.is_none() || self.packets >= 1000)
+            && (limit.max_bytes.is_none() || self.bytes >= bytes * 1000)
+    }
+
+    /// Takes the tokens for a packet of [bytes]
+    fn take(&mut self, limit: &RateLimit, bytes: u64) {
+        if limit.max_packets.is_some() {
+            self.packets -= 1000;
+        }
+        if limit.max_bytes.is_some() {
+            self.bytes -= bytes * 1000;
+        }
+    }
+}
+
+/// The capacity in thousandths of a bucket with the [rate]
+fn capacity(rate: Option<u32>) -> u64 {
+    rate.map_or(0, |rate| rate as u64 * 1000)
+}
+
+/// ## Rate Limiter
+/// Flood protection for the packets read from a connection. Limits can be set for
+/// each packet ID, for the IDs without their own limit and for all the packets of
+/// the connection combined. Packets exceeding a limit fail with
+/// PacketError::RateLimited and don't count towards the limits. Times are
+/// milliseconds provided by the caller
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RateLimiter {
+    limits: BTreeMap<u32, RateLimit>,
+    default_limit: Option<RateLimit>,
+    total_limit: Option<RateLimit>,
+    buckets: BTreeMap<u32, Bucket>,
+    total: Option<Bucket>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter without any limits
+    pub fn new() -> Self {
+        RateLimiter::default()
+    }
+
+    /// Sets the [limit] for the packet with the provided [id]
+    pub fn limit(mut self, id: u32, limit: RateLimit) -> Self {
+        self.limits.insert(id, limit);
+        self.buckets.remove(&id);
+        self
+    }
+
+    /// Sets the [limit] used for each of the packet IDs without their own limit
+    pub fn default_limit(mut self, limit: RateLimit) -> Self {
+        self.default_limit = Some(limit);
+        self
+    }
+
+    /// Sets the [limit] for all the packets combined
+    pub fn total_limit(mut self, limit: RateLimit) -> Self {
+        self.total_limit = Some(limit);
+        self.total = None;
+        self
+    }
+
+    /// Checks whether a packet with the [id] that is [bytes] long can be read at the
+    /// time [now] failing with PacketError::RateLimited if it exceeds a limit
+    pub fn check(&mut self, now: u64, id: u32, bytes: usize) -> ReadResult<()> {
+        let bytes = bytes as u64;
+        let limit = self.limits.get(&id).or(self.default_limit.as_ref());
+        let mut bucket = limit.map(|limit| {
+            let mut bucket = *self.buckets.get(&id).unwrap_or(&Bucket::new(limit, now));
+            bucket.refill(limit, now);
+            (limit, bucket)
+        });
+        let mut total = self.total_limit.as_ref().map(|limit| {
+            let mut bucket = self.total.unwrap_or(Bucket::new(limit, now));
+            bucket.refill(limit, now);
+            (limit, bucket)
+        });
+        let allowed = bucket.as_ref().is_none_or(|(limit, bucket)| bucket.allows(limit, bytes))
+            && total.as_ref().is_none_or(|(limit, bucket)| bucket.allows(limit, bytes));
+        if allowed {
+            if let Some((limit, bucket)) = &mut bucket {
+                bucket.take(limit, bytes);
+            }
+            if let Some((limit, bucket)) = &mut total {
+                bucket.take(limit, bytes);
+            }
+        }
+        // The refilled buckets are stored even when the packet is rejected
+        if let Some((_, bucket)) = bucket {
+            self.buckets.insert(id, bucket);
+        }
+        if let Some((_, bucket)) = total {
+            self.total = Some(bucket);
+        }
+        if allowed { Ok(()) } else { Err(PacketError::RateLimited(id)) }
+    }
+
+    /// Reads a packet (ID and contents) of the group G from the [frame] received at
+    /// the time [now] after checking it against the limits
+    pub fn read<G: Readable>(&mut self, now: u64, frame: &[u8]) -> ReadResult<G> {
+        let id = VarInt::read(&mut &frame[..])?.0;
+        self.check(now, id, frame.len())?;
+        G::read(&mut &frame[..])
+    }
+}
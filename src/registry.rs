@@ -0,0 +1,204 @@
+use core::any::{type_name, Any, TypeId};
+use core::fmt::Debug;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, VarInt, Writable, Write, WriteResult};
+use crate::limits::ReadContext;
+
+/// Object safe trait for packets decoded by a PacketRegistry which is implemented
+/// for all writable types
+pub trait AnyPacket: Any + Debug + Send + Sync {
+    /// Writes the packet contents (without the ID) to [o]
+    fn write_dyn(&mut self, o: &mut dyn Write) -> WriteResult;
+
+    /// The name of the packet type
+    fn type_name(&self) -> &'static str;
+
+    /// Provides the packet as Any so it can be downcast
+    fn as_any(&self) -> &dyn Any;
+
+    /// Converts the boxed packet into Any so it can be downcast by value
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Writable + Debug + Any> AnyPacket for T {
+    fn write_dyn(&mut self, mut o: &mut dyn Write) -> WriteResult {
+        self.write(&mut o)
+    }
+
+    fn type_name(&self) -> &'static str { type_name::<T>() }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+}
+
+impl dyn AnyPacket {
+    /// Whether the packet is of the type T
+    pub fn is<T: Any>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Returns the packet if it is of the type T
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Returns the packet by value if it is of the type T otherwise the packet
+    /// is returned unchanged as the error
+    pub fn downcast<T: Any>(self: Box<Self>) -> Result<Box<T>, Box<dyn AnyPacket>> {
+        if self.is::<T>() {
+            Ok(self.into_any().downcast().expect("packet type was checked"))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// A packet type registered in a PacketRegistry
+#[derive(Debug, Clone, Copy)]
+struct Registration {
+    type_id: TypeId,
+    read: fn(&mut ReadContext, &mut dyn Read) -> ReadResult<Box<dyn AnyPacket>>,
+}
+
+/// Reads the packet contents of the type P as a boxed AnyPacket
+fn read_any<P: Readable + AnyPacket>(ctx: &mut ReadContext, mut i: &mut dyn Read) -> ReadResult<Box<dyn AnyPacket>> {
+    Ok(Box::new(P::read_with(ctx, &mut i)?))
+}
+
+/// ## Packet Registry
+/// Runtime alternative to the packet groups for when the full set of packets isn't
+/// known at compile time (e.g. scripting or hot reloading). Packet types are
+/// registered with an ID and are read as a boxed AnyPacket which can be downcast or
+/// handled with a PacketDispatcher. Packets are encoded the same as in a group, the
+/// ID (VarInt) followed by the packet contents
+#[derive(Debug, Clone, Default)]
+pub struct PacketRegistry {
+    packets: BTreeMap<u32, Registration>,
+    ids: BTreeMap<TypeId, u32>,
+}
+
+impl PacketRegistry {
+    /// Creates a new registry without any packets
+    pub fn new() -> Self {
+        PacketRegistry::default()
+    }
+
+    /// Registers the packet type P with the provided [id] returning whether the ID
+    /// was unused. Registering a type that was already registered or an ID that was
+    /// already used replaces the previous registration
+    pub fn register<P: Readable + AnyPacket>(&mut self, id: u32) -> bool {
+        let type_id = TypeId::of::<P>();
+        if let Some(previous) = self.ids.insert(type_id, id) {
+            self.packets.remove(&previous);
+        }
+        let previous = self.packets.insert(id, Registration { type_id, read: read_any::<P> });
+        match previous {
+            Some(previous) if previous.type_id != type_id => {
+                self.ids.remove(&previous.type_id);
+                false
+            }
+            Some(_) => false,
+            None => true
+        }
+    }
+
+    /// Removes the packet type registered with the provided [id] returning whether
+    /// there was one
+    pub fn unregister(&mut self, id: u32) -> bool {
+        match self.packets.remove(&id) {
+            Some(registration) => {
+                self.ids.remove(&registration.type_id);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// The ID the packet type P is registered with
+    pub fn id_of<P: Any>(&self) -> Option<u32> {
+        self.ids.get(&TypeId::of::<P>()).copied()
+    }
+
+    /// The ID the type of the provided [packet] is registered with
+    pub fn id_for(&self, packet: &dyn AnyPacket) -> Option<u32> {
+        self.ids.get(&packet.as_any().type_id()).copied()
+    }
+
+    /// Whether a packet type is registered with the provided [id]
+    pub fn contains(&self, id: u32) -> bool {
+        self.packets.contains_key(&id)
+    }
+
+    /// Reads a packet from the source [i] failing with PacketError::UnknownPacket
+    /// if no packet type is registered with its ID
+    pub fn read<B: Read>(&self, i: &mut B) -> ReadResult<Box<dyn AnyPacket>> {
+        self.read_with(&mut ReadContext::default(), i)
+    }
+
+    /// Reads a packet from the source [i] using the provided context
+    pub fn read_with<B: Read>(&self, ctx: &mut ReadContext, mut i: &mut B) -> ReadResult<Box<dyn AnyPacket>> {
+        let id = VarInt::read_with(ctx, i)?.0;
+        let registration = self.packets.get(&id)
+            .ok_or(PacketError::UnknownPacket(id))?;
+        ctx.enter(|ctx| (registration.read)(ctx, &mut i))
+    }
+
+    /// Writes the [packet] with the ID its type is registered with to [o] failing
+    /// with PacketError::UnregisteredPacket if its type isn't registered
+    pub fn write<B: Write>(&self, packet: &mut dyn AnyPacket, mut o: &mut B) -> WriteResult {
+        let id = self.id_for(packet)
+            .ok_or(PacketError::UnregisteredPacket(packet.type_name()))?;
+        VarInt(id).write(o)?;
+        packet.write_dyn(&mut o)
+    }
+}
+
+/// The handler for a packet type in a PacketDispatcher
+type PacketHandler<S> = Box<dyn FnMut(&mut S, Box<dyn AnyPacket>)>;
+
+/// ## Packet Dispatcher
+/// Dispatches the packets read by a PacketRegistry to the handlers registered for
+/// their types. Handlers are given the state S (e.g. the connection) and the packet
+/// by value
+pub struct PacketDispatcher<S> {
+    handlers: BTreeMap<TypeId, PacketHandler<S>>,
+}
+
+impl<S> PacketDispatcher<S> {
+    /// Creates a new dispatcher without any handlers
+    pub fn new() -> Self {
+        PacketDispatcher { handlers: BTreeMap::new() }
+    }
+
+    /// Sets the [handler] for packets of the type P replacing any previous handler
+    pub fn on<P, F>(&mut self, mut handler: F) -> &mut Self
+        where P: Any,
+              F: FnMut(&mut S, P) + 'static {
+        self.handlers.insert(TypeId::of::<P>(), Box::new(move |state, packet| {
+            if let Ok(packet) = packet.downcast::<P>() {
+                handler(state, *packet)
+            }
+        }));
+        self
+    }
+
+    /// Passes the [packet] to the handler for its type returning it unchanged as
+    /// the error if there is no handler for its type
+    pub fn dispatch(&mut self, state: &mut S, packet: Box<dyn AnyPacket>) -> Result<(), Box<dyn AnyPacket>> {
+        match self.handlers.get_mut(&packet.as_any().type_id()) {
+            Some(handler) => {
+                handler(state, packet);
+                Ok(())
+            }
+            None => Err(packet)
+        }
+    }
+}
+
+impl<S> Default for PacketDispatcher<S> {
+    fn default() -> Self { PacketDispatcher::new() }
+}
@@ -0,0 +1,161 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use crate::error::PacketError;
+use crate::io::{is_eof, PacketResult, Read, Readable, VarInt, Writable, Write, ReadResult, WriteResult};
+
+/// The bytes at the start of every replay file followed by the format version
+pub const REPLAY_MAGIC: [u8; 4] = *b"WSBR";
+
+/// The version of the replay file format written by Recorder
+pub const REPLAY_VERSION: u8 = 1;
+
+/// ## Recorder
+/// Writes packets to a replay file along with the caller provided time in
+/// milliseconds they were sent or received at. The file is the magic and version
+/// followed by a record for each packet which is the time (u64) and the encoded
+/// packet prefixed with its length as a VarInt. Records are written as they are
+/// made so a file from a process that crashed can still be played back
+pub struct Recorder<W> {
+    output: W,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a recorder writing the replay file header to [output]
+    pub fn new(mut output: W) -> PacketResult<Self> {
+        output.write_all(&REPLAY_MAGIC)?;
+        output.write_all(&[REPLAY_VERSION])?;
+        Ok(Recorder { output })
+    }
+
+    /// Records the [packet] at the time [now]
+    pub fn record<P: Writable>(&mut self, now: u64, packet: &mut P) -> WriteResult {
+        let mut frame = Vec::new();
+        packet.write(&mut frame)?;
+        self.record_frame(now, &frame)
+    }
+
+    /// Records the already encoded [frame] at the time [now]
+    pub fn record_frame(&mut self, now: u64, frame: &[u8]) -> WriteResult {
+        self.output.write_all(&now.to_be_bytes())?;
+        VarInt(frame.len() as u32).write(&mut self.output)?;
+        self.output.write_all(frame)?;
+        Ok(())
+    }
+
+    /// The output the replay file is being written to
+    pub fn get_ref(&self) -> &W { &self.output }
+
+    /// Returns the output the replay file was written to
+    pub fn into_inner(self) -> W { self.output }
+}
+
+/// ## Player
+/// Reads the packets back from a replay file written by a Recorder. Packets are
+/// read one at a time with next_packet or with std the player is a Read source of
+/// the recorded bytes so packets can be read straight from it like the original
+/// stream. When playing in realtime (std only) each packet is delayed until the
+/// same amount of time has passed since the first packet as when it was recorded
+pub struct Player<R> {
+    input: R,
+    #[cfg(feature = "std")]
+    frame: Vec<u8>,
+    #[cfg(feature = "std")]
+    position: usize,
+    #[cfg(feature = "std")]
+    realtime: bool,
+    #[cfg(feature = "std")]
+    start: Option<(Instant, u64)>,
+}
+
+impl<R: Read> Player<R> {
+    /// Creates a player reading from the replay file [input] failing if the file
+    /// doesn't start with the replay file header
+    pub fn new(mut input: R) -> ReadResult<Self> {
+        let mut header = [0u8; 5];
+        input.read_exact(&mut header)?;
+        if header[..4] != REPLAY_MAGIC || header[4] != REPLAY_VERSION {
+            return Err(PacketError::UnexpectedValue("replay file header"));
+        }
+        Ok(Player {
+            input,
+            #[cfg(feature = "std")]
+            frame: Vec::new(),
+            #[cfg(feature = "std")]
+            position: 0,
+            #[cfg(feature = "std")]
+            realtime: false,
+            #[cfg(feature = "std")]
+            start: None,
+        })
+    }
+
+    /// Sets whether the packets are delayed to match the times they were recorded
+    #[cfg(feature = "std")]
+    pub fn realtime(mut self, realtime: bool) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    /// Reads the next recorded frame along with the time it was recorded at returning
+    /// None at the end of the file. A record cut off within its time (e.g. by a
+    /// crash) is treated as the end of the file
+    pub fn next_frame(&mut self) -> ReadResult<Option<(u64, Vec<u8>)>> {
+        let mut time = [0u8; 8];
+        if let Err(err) = self.input.read_exact(&mut time) {
+            return if is_eof(&err) { Ok(None) } else { Err(err.into()) };
+        }
+        let time = u64::from_be_bytes(time);
+        let frame = Vec::<u8>::read(&mut self.input)?;
+        #[cfg(feature = "std")]
+        if self.realtime {
+            let (started, first) = *self.start.get_or_insert((Instant::now(), time));
+            let due = started + Duration::from_millis(time.saturating_sub(first));
+            let now = Instant::now();
+            if due > now {
+                std::thread::sleep(due - now);
+            }
+        }
+        Ok(Some((time, frame)))
+    }
+
+    /// Reads the next recorded packet along with the time it was recorded at returning
+    /// None at the end of the file
+    pub fn next_packet<P: Readable>(&mut self) -> ReadResult<Option<(u64, P)>> {
+        match self.next_frame()? {
+            Some((time, frame)) => Ok(Some((time, P::read(&mut frame.as_slice())?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads all the remaining recorded frames
+    pub fn frames(&mut self) -> ReadResult<Vec<(u64, Vec<u8>)>> {
+        let mut frames = Vec::new();
+        while let Some(frame) = self.next_frame()? {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> std::io::Read for Player<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position >= self.frame.len() {
+            match self.next_frame() {
+                Ok(Some((_, frame))) => {
+                    self.frame = frame;
+                    self.position = 0;
+                }
+                Ok(None) => return Ok(0),
+                Err(PacketError::IO(err)) => return Err(err),
+                Err(err) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err)),
+            }
+        }
+        let length = buf.len().min(self.frame.len() - self.position);
+        buf[..length].copy_from_slice(&self.frame[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
@@ -0,0 +1,244 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{write_ordered_variant, OrderedRepr, OrderedStyle};
+use crate::summary::{write_summary_fields, Summary};
+
+/// ## Entity Update
+/// A change to a replicated entity of the type E. This is encoded as a u8 tag
+/// (0 = Create, 1 = Update, 2 = Destroy) followed by the entity ID (VarInt) and
+/// then the entity for Create and Update
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntityUpdate<E> {
+    /// The entity has started being replicated
+    Create { id: u32, entity: E },
+    /// The entity has changed since it was last replicated
+    Update { id: u32, entity: E },
+    /// The entity has stopped being replicated
+    Destroy { id: u32 },
+}
+
+impl<E> EntityUpdate<E> {
+    /// The ID of the entity this update is for
+    pub fn id(&self) -> u32 {
+        match self {
+            EntityUpdate::Create { id, .. }
+            | EntityUpdate::Update { id, .. }
+            | EntityUpdate::Destroy { id } => *id
+        }
+    }
+}
+
+impl<E: Writable> Writable for EntityUpdate<E> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        match self {
+            EntityUpdate::Create { id, entity } => {
+                0u8.write(o)?;
+                VarInt(*id).write(o)?;
+                entity.write_with(ctx, o)
+            }
+            EntityUpdate::Update { id, entity } => {
+                1u8.write(o)?;
+                VarInt(*id).write(o)?;
+                entity.write_with(ctx, o)
+            }
+            EntityUpdate::Destroy { id } => {
+                2u8.write(o)?;
+                VarInt(*id).write(o)
+            }
+        }
+    }
+}
+
+impl<E: Readable> Readable for EntityUpdate<E> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let tag = u8::read(i)?;
+        let id = VarInt::read_with(ctx, i)?.0;
+        match tag {
+            0 => Ok(EntityUpdate::Create { id, entity: ctx.enter(|ctx| E::read_with(ctx, i))? }),
+            1 => Ok(EntityUpdate::Update { id, entity: ctx.enter(|ctx| E::read_with(ctx, i))? }),
+            2 => Ok(EntityUpdate::Destroy { id }),
+            _ => Err(PacketError::UnknownEnumValue)
+        }
+    }
+}
+
+impl<E: OrderedRepr> OrderedRepr for EntityUpdate<E> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        match self {
+            EntityUpdate::Create { id, entity } => write_ordered_variant(style, out, "Create", &[("id", id), ("entity", entity)]),
+            EntityUpdate::Update { id, entity } => write_ordered_variant(style, out, "Update", &[("id", id), ("entity", entity)]),
+            EntityUpdate::Destroy { id } => write_ordered_variant(style, out, "Destroy", &[("id", id)]),
+        }
+    }
+}
+
+impl<E: Summary> Summary for EntityUpdate<E> {
+    fn write_summary(&self, out: &mut String) {
+        match self {
+            EntityUpdate::Create { id, entity } => write_summary_fields(out, "Create", None, &[("id", id), ("entity", entity)]),
+            EntityUpdate::Update { id, entity } => write_summary_fields(out, "Update", None, &[("id", id), ("entity", entity)]),
+            EntityUpdate::Destroy { id } => write_summary_fields(out, "Destroy", None, &[("id", id)]),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, E: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for EntityUpdate<E> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let id = u32::arbitrary(u)?;
+        Ok(match u.int_in_range(0..=2u8)? {
+            0 => EntityUpdate::Create { id, entity: E::arbitrary(u)? },
+            1 => EntityUpdate::Update { id, entity: E::arbitrary(u)? },
+            _ => EntityUpdate::Destroy { id },
+        })
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl<E: crate::Schema> crate::Schema for EntityUpdate<E> {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("EntityUpdate") }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::Definition>) { E::definitions(definitions) }
+}
+
+/// ## Replicator
+/// Tracks the state of the entities that have been replicated to a peer so that
+/// only the changes are sent. Each call to diff compares the current entities with
+/// those last replicated and returns the updates the peer needs to match them.
+/// Entities are compared as a whole so any changed entity is sent in full
+#[derive(Debug, Clone)]
+pub struct Replicator<E> {
+    replicated: BTreeMap<u32, E>,
+}
+
+impl<E> Replicator<E> {
+    /// Creates a new replicator with no replicated entities
+    pub fn new() -> Self {
+        Replicator { replicated: BTreeMap::new() }
+    }
+
+    /// The number of entities currently replicated
+    pub fn len(&self) -> usize { self.replicated.len() }
+
+    /// Whether there are no entities currently replicated
+    pub fn is_empty(&self) -> bool { self.replicated.is_empty() }
+
+    /// Whether the entity with the provided ID is currently replicated
+    pub fn contains(&self, id: u32) -> bool { self.replicated.contains_key(&id) }
+
+    /// Forgets all the replicated entities so they are all created again by the
+    /// next diff (e.g. when the peer reconnects)
+    pub fn reset(&mut self) { self.replicated.clear() }
+}
+
+impl<E: Clone + PartialEq> Replicator<E> {
+    /// Compares the provided [entities] with the entities last replicated returning
+    /// Create for new entities, Update for changed entities and Destroy for entities
+    /// that are no longer present
+    pub fn diff<'a, I>(&mut self, entities: I) -> Vec<EntityUpdate<E>>
+        where I: IntoIterator<Item=(u32, &'a E)>,
+              E: 'a {
+        self.diff_interest(entities, |_, _| true)
+    }
+
+    /// Diffs only the provided [entities] that the peer is interested in according to
+    /// [interest] (e.g. entities within a distance of the player or with a tag the peer
+    /// is subscribed to). Entities the peer stops being interested in are destroyed and
+    /// entities the peer becomes interested in are created. Each peer should have its
+    /// own replicator so that each can have its own interest
+    pub fn diff_interest<'a, I, F>(&mut self, entities: I, mut interest: F) -> Vec<EntityUpdate<E>>
+        where I: IntoIterator<Item=(u32, &'a E)>,
+              F: FnMut(u32, &E) -> bool,
+              E: 'a {
+        let mut updates = Vec::new();
+        let mut present = BTreeSet::new();
+        for (id, entity) in entities {
+            if !interest(id, entity) {
+                continue;
+            }
+            present.insert(id);
+            match self.replicated.get_mut(&id) {
+                None => {
+                    self.replicated.insert(id, entity.clone());
+                    updates.push(EntityUpdate::Create { id, entity: entity.clone() });
+                }
+                Some(replicated) if replicated != entity => {
+                    *replicated = entity.clone();
+                    updates.push(EntityUpdate::Update { id, entity: entity.clone() });
+                }
+                Some(_) => {}
+            }
+        }
+        self.replicated.retain(|id, _| {
+            let keep = present.contains(id);
+            if !keep {
+                updates.push(EntityUpdate::Destroy { id: *id });
+            }
+            keep
+        });
+        updates
+    }
+}
+
+impl<E> Default for Replicator<E> {
+    fn default() -> Self { Replicator::new() }
+}
+
+/// ## Entity Store
+/// The local copy of the replicated entities on the receiving side which the
+/// updates produced by a Replicator are applied to
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityStore<E> {
+    entities: BTreeMap<u32, E>,
+}
+
+impl<E> EntityStore<E> {
+    /// Creates a new empty store
+    pub fn new() -> Self {
+        EntityStore { entities: BTreeMap::new() }
+    }
+
+    /// Applies the provided update to the store
+    pub fn apply(&mut self, update: EntityUpdate<E>) {
+        match update {
+            EntityUpdate::Create { id, entity } | EntityUpdate::Update { id, entity } => {
+                self.entities.insert(id, entity);
+            }
+            EntityUpdate::Destroy { id } => {
+                self.entities.remove(&id);
+            }
+        }
+    }
+
+    /// The entity with the provided ID if it exists
+    pub fn get(&self, id: u32) -> Option<&E> { self.entities.get(&id) }
+
+    /// Iterates over the entities and their IDs in order of ID
+    pub fn iter(&self) -> impl Iterator<Item=(u32, &E)> {
+        self.entities.iter().map(|(id, entity)| (*id, entity))
+    }
+
+    /// The number of entities in the store
+    pub fn len(&self) -> usize { self.entities.len() }
+
+    /// Whether the store has no entities
+    pub fn is_empty(&self) -> bool { self.entities.is_empty() }
+}
+
+impl<E> Default for EntityStore<E> {
+    fn default() -> Self { EntityStore::new() }
+}
@@ -0,0 +1,58 @@
+use crate::io::{Read, Readable, Writable, Write, WriteResult};
+
+/// ## Request
+/// Packets which are requests with a declared response type. Handlers for the
+/// request must return the response type which is then written automatically by
+/// respond. This is implemented using the requests macro
+pub trait Request: Readable {
+    /// The type of the response to this request
+    type Response: Writable;
+}
+
+/// ## Respond
+/// Reads a request of type R from [i] and passes it to the [handler] then
+/// writes the response returned by the handler to [o]
+pub fn respond<R, I, O, H>(i: &mut I, o: &mut O, handler: H) -> WriteResult
+    where R: Request,
+          I: Read,
+          O: Write,
+          H: FnOnce(R) -> R::Response {
+    let request = R::read(i)?;
+    let mut response = handler(request);
+    response.write(o)
+}
+
+/// # Requests Macro
+/// This macro declares the response types of request packets by implementing the
+/// Request trait so handlers passed to respond must return the declared response
+///
+/// ## Example
+///
+/// ```
+/// use wsbps::{packet_data, requests};
+/// packet_data! {
+///     struct GetUser (<->) {
+///         id: u32
+///     }
+///
+///     struct GetUserResult (<->) {
+///         name: String
+///     }
+/// }
+///
+/// requests! {
+///     GetUser => GetUserResult;
+/// }
+/// ```
+#[macro_export]
+macro_rules! requests {
+    (
+        $($Request:ty => $Response:ty);* $(;)?
+    ) => {
+        $(
+            impl $crate::Request for $Request {
+                type Response = $Response;
+            }
+        )*
+    };
+}
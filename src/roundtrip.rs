@@ -0,0 +1,73 @@
+use alloc::vec::Vec;
+
+use crate::io::{PacketResult, Readable, Writable};
+
+/// Writes the provided value and then reads it back from the written bytes
+/// returning the value that was read
+pub fn roundtrip<T: Readable + Writable + Clone>(value: &T) -> PacketResult<T> {
+    let mut o = Vec::new();
+    value.clone().write(&mut o)?;
+    T::read(&mut o.as_slice())
+}
+
+/// ## Roundtrip Check
+/// Generates a value of T from the provided fuzzer data and checks that reading
+/// the written value produces the exact same value. This is intended to be used
+/// as the body of a cargo-fuzz target:
+///
+/// ```ignore
+/// fuzz_target!(|data: &[u8]| {
+///     wsbps::roundtrip_check::<BiPackets>(data);
+/// });
+/// ```
+///
+/// Panics if the value fails to be written, read or doesn't equal itself after
+/// being read. Values containing NaN floats will never be equal to themselves.
+#[cfg(feature = "arbitrary")]
+pub fn roundtrip_check<T>(data: &[u8])
+    where T: for<'a> arbitrary::Arbitrary<'a> + Readable + Writable + Clone + PartialEq + core::fmt::Debug {
+    let mut u = arbitrary::Unstructured::new(data);
+    // Not having enough data to generate a value isn't a failure
+    let value = match T::arbitrary(&mut u) {
+        Ok(value) => value,
+        Err(_) => return
+    };
+    match roundtrip(&value) {
+        Ok(read) => assert_eq!(read, value, "value changed after being written and read"),
+        Err(err) => panic!("failed to roundtrip {:?}: {}", value, err)
+    }
+}
+
+/// ## Roundtrip Property
+/// Checks the roundtrip of the specified number of values created by [generate] from
+/// pseudo-random data. The data is produced from a fixed seed so failures can be
+/// reproduced. This is used by the tests generated with the `#[wsbps(tests)]` option.
+///
+/// Panics if any of the values fail to roundtrip
+#[cfg(feature = "arbitrary")]
+pub fn roundtrip_property<T, F>(cases: usize, generate: F)
+    where T: Readable + Writable + Clone + PartialEq + core::fmt::Debug,
+          F: Fn(&mut arbitrary::Unstructured) -> arbitrary::Result<T> {
+    // xorshift state used to produce the data for each case
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut data = Vec::new();
+    for case in 0..cases {
+        data.clear();
+        let length = 16 + (case * 8) % 1024;
+        for _ in 0..length {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            data.push(state as u8);
+        }
+        let mut u = arbitrary::Unstructured::new(&data);
+        let value = match generate(&mut u) {
+            Ok(value) => value,
+            Err(_) => continue
+        };
+        match roundtrip(&value) {
+            Ok(read) => assert_eq!(read, value, "value changed after being written and read (case {})", case),
+            Err(err) => panic!("failed to roundtrip {:?} (case {}): {}", value, case, err)
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::io::{Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{write_ordered_struct, OrderedRepr, OrderedStyle};
+use crate::summary::{write_summary_fields, Summary};
+
+/// ## Correlated
+/// A request or response with the correlation ID used to match responses to the
+/// requests they answer. This is encoded as the value followed by the ID (VarInt)
+/// so the ID is appended to the end of the packet
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Correlated<T> {
+    /// The correlation ID
+    pub id: u32,
+    /// The request or response
+    pub value: T,
+}
+
+impl<T> Correlated<T> {
+    /// Creates a new value with the provided correlation ID
+    pub fn new(id: u32, value: T) -> Self {
+        Correlated { id, value }
+    }
+
+    /// Creates the response to this request which has the same correlation ID
+    pub fn reply<R>(&self, value: R) -> Correlated<R> {
+        Correlated::new(self.id, value)
+    }
+}
+
+impl<T: Writable> Writable for Correlated<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        self.value.write_with(ctx, o)?;
+        VarInt(self.id).write(o)
+    }
+}
+
+impl<T: Readable> Readable for Correlated<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let value = ctx.enter(|ctx| T::read_with(ctx, i))?;
+        let id = VarInt::read_with(ctx, i)?.0;
+        Ok(Correlated { id, value })
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for Correlated<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_struct(style, out, "Correlated", &[("id", &self.id), ("value", &self.value)])
+    }
+}
+
+impl<T: Summary> Summary for Correlated<T> {
+    fn write_summary(&self, out: &mut String) {
+        write_summary_fields(out, "Correlated", None, &[("id", &self.id), ("value", &self.value)])
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Correlated<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Correlated { id: u32::arbitrary(u)?, value: T::arbitrary(u)? })
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl<T: crate::Schema> crate::Schema for Correlated<T> {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("Correlated") }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::Definition>) { T::definitions(definitions) }
+}
+
+/// The callback for a pending request which is called with the response or None
+/// if the request timed out
+type ResponseCallback<T> = Box<dyn FnOnce(Option<T>)>;
+
+/// A request which is awaiting a response
+struct PendingRequest<T> {
+    deadline: u64,
+    callback: ResponseCallback<T>,
+}
+
+/// ## Request Tracker
+/// Assigns correlation IDs to outgoing requests and matches the incoming responses
+/// of type T to the callbacks of the requests they answer. Requests which aren't
+/// answered within the timeout have their callback called with None.
+///
+/// Times are milliseconds from any monotonic clock provided by the caller so this
+/// can be used without std and without an async runtime
+pub struct RequestTracker<T> {
+    timeout: u64,
+    next_id: u32,
+    pending: BTreeMap<u32, PendingRequest<T>>,
+}
+
+impl<T> RequestTracker<T> {
+    /// Creates a new tracker which times out requests after [timeout] milliseconds
+    pub fn new(timeout: u64) -> Self {
+        RequestTracker { timeout, next_id: 0, pending: BTreeMap::new() }
+    }
+
+    /// Assigns a correlation ID to the [request] and registers the [callback] which
+    /// is called with the response returning the request to send
+    pub fn request<Q, F>(&mut self, request: Q, now: u64, callback: F) -> Correlated<Q>
+        where F: FnOnce(Option<T>) + 'static {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.pending.insert(id, PendingRequest {
+            deadline: now.saturating_add(self.timeout),
+            callback: Box::new(callback),
+        });
+        Correlated::new(id, request)
+    }
+
+    /// Calls the callback of the request that the [response] answers returning
+    /// whether there was a pending request for it. Responses to requests which
+    /// have timed out or were never sent are ignored
+    pub fn receive(&mut self, response: Correlated<T>) -> bool {
+        match self.pending.remove(&response.id) {
+            Some(pending) => {
+                (pending.callback)(Some(response.value));
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Times out all the requests whose timeout has passed at [now] calling their
+    /// callbacks with None and returns the number of requests that timed out
+    pub fn poll(&mut self, now: u64) -> usize {
+        let expired: Vec<u32> = self.pending.iter()
+            .filter(|(_, pending)| now >= pending.deadline)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &expired {
+            if let Some(pending) = self.pending.remove(id) {
+                (pending.callback)(None);
+            }
+        }
+        expired.len()
+    }
+
+    /// The number of requests awaiting a response
+    pub fn len(&self) -> usize { self.pending.len() }
+
+    /// Whether there are no requests awaiting a response
+    pub fn is_empty(&self) -> bool { self.pending.is_empty() }
+}
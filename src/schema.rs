@@ -0,0 +1,258 @@
+use alloc::boxed::Box;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::io::{VarInt, VarLong};
+
+/// ## Type Schema
+/// Description of how a type is encoded used by the code generators to produce
+/// readers and writers for other languages
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeSchema {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    VarInt,
+    VarLong,
+    /// UTF-8 bytes prefixed with their length as a VarInt
+    String,
+    /// The values prefixed with the number of values as a VarInt
+    List(Box<TypeSchema>),
+    /// The entries (key then value) prefixed with the number of entries as a VarInt
+    Map(Box<TypeSchema>, Box<TypeSchema>),
+    /// A bool for whether the value is present followed by the value
+    Option(Box<TypeSchema>),
+    /// A u8 tag (0 for Ok and 1 for Err) followed by the ok or error value
+    Result(Box<TypeSchema>, Box<TypeSchema>),
+    /// The unit type which takes no bytes
+    Unit,
+    /// A struct or enum with the provided name which is described by a Definition
+    Named(&'static str),
+    /// A field encoded using the FieldCodec with the provided name which can't be
+    /// described so it has to be implemented by hand
+    Custom(&'static str),
+}
+
+/// ## Field Schema
+/// Description of a struct or packet field along with its field attributes
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    /// The name of the field
+    pub name: &'static str,
+    /// How the field value is encoded
+    pub ty: TypeSchema,
+    /// The earlier field whose value is the length of this field (#[len_from])
+    pub length_from: Option<&'static str>,
+    /// The earlier bool field which decides if this field is present (#[when])
+    pub when: Option<&'static str>,
+    /// The Rust source of the value used when the packet ends before this field
+    pub default: Option<&'static str>,
+}
+
+impl FieldSchema {
+    /// Creates the schema for a field without any attributes
+    pub fn new(name: &'static str, ty: TypeSchema) -> Self {
+        FieldSchema { name, ty, length_from: None, when: None, default: None }
+    }
+}
+
+/// What a Definition describes
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefinitionKind {
+    /// A struct whose fields are encoded in order
+    Struct(Vec<FieldSchema>),
+    /// An enum encoded as the value of the variant using the repr type. The values
+    /// are the Rust source of the literals (e.g. `1`, `0x02` or `"name"`)
+    Enum {
+        repr: TypeSchema,
+        variants: Vec<(&'static str, &'static str)>,
+    },
+}
+
+/// The description of a named struct or enum
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    /// The name of the struct or enum
+    pub name: &'static str,
+    /// The fields or variants
+    pub kind: DefinitionKind,
+}
+
+/// The description of a packet in a group
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketSchema {
+    /// The name of the packet
+    pub name: &'static str,
+    /// The packet ID
+    pub id: u32,
+    /// The fields of the packet in order
+    pub fields: Vec<FieldSchema>,
+}
+
+/// ## Group Schema
+/// The description of a packet group along with all the structs and enums used by
+/// its packets which is what the code generators are given
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupSchema {
+    /// The name of the group
+    pub name: &'static str,
+    /// The packets in declaration order
+    pub packets: Vec<PacketSchema>,
+    /// The structs and enums used by the packets
+    pub definitions: Vec<Definition>,
+}
+
+/// ## Schema
+/// Trait for describing how a type is encoded. This is implemented for all the
+/// encodable types and for the generated structs and enums when the "codegen"
+/// feature is enabled
+pub trait Schema {
+    /// The description of how this type is encoded
+    fn type_schema() -> TypeSchema;
+
+    /// Adds the definitions of the named types used by this type to [definitions].
+    /// Definitions that are already present aren't added again
+    fn definitions(definitions: &mut Vec<Definition>) {
+        let _ = definitions;
+    }
+}
+
+/// ## Packet Group Schema
+/// Implemented by the generated packet groups when the "codegen" feature is enabled
+pub trait PacketGroupSchema {
+    /// The description of the group and the types its packets use
+    fn group_schema() -> GroupSchema;
+}
+
+/// Adds the [definition] to [definitions] returning false if a definition with the
+/// same name was already present so recursive types aren't visited forever
+pub fn add_definition(definitions: &mut Vec<Definition>, definition: Definition) -> bool {
+    if definitions.iter().any(|existing| existing.name == definition.name) {
+        return false;
+    }
+    definitions.push(definition);
+    true
+}
+
+/// Macro for implementing the schema of the primitive types
+macro_rules! impl_schema_primitive {
+    ($($type:ty => $schema:ident),*) => {
+        $(
+            impl Schema for $type {
+                fn type_schema() -> TypeSchema { TypeSchema::$schema }
+            }
+        )*
+    };
+}
+
+impl_schema_primitive!(
+    bool => Bool, u8 => U8, u16 => U16, u32 => U32, u64 => U64,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, f32 => F32, f64 => F64,
+    VarInt => VarInt, VarLong => VarLong, String => String, Cow<'_, str> => String
+);
+
+impl Schema for Cow<'_, [u8]> {
+    fn type_schema() -> TypeSchema { TypeSchema::List(Box::new(TypeSchema::U8)) }
+}
+
+/// Macro for implementing the schema of the types encoded the same as the type
+/// they contain
+macro_rules! impl_schema_wrapper {
+    ($($wrapper:ident),*) => {
+        $(
+            impl<T: Schema> Schema for $wrapper<T> {
+                fn type_schema() -> TypeSchema { T::type_schema() }
+
+                fn definitions(definitions: &mut Vec<Definition>) { T::definitions(definitions) }
+            }
+        )*
+    };
+}
+
+impl_schema_wrapper!(Box, Arc);
+
+/// Macro for implementing the schema of the collections encoded as lists
+macro_rules! impl_schema_list {
+    ($($(#[$Attr:meta])* $list:ident),*) => {
+        $(
+            $(#[$Attr])*
+            impl<T: Schema> Schema for $list<T> {
+                fn type_schema() -> TypeSchema { TypeSchema::List(Box::new(T::type_schema())) }
+
+                fn definitions(definitions: &mut Vec<Definition>) { T::definitions(definitions) }
+            }
+        )*
+    };
+}
+
+impl_schema_list!(Vec, VecDeque, BTreeSet, #[cfg(feature = "std")] HashSet);
+
+/// Macro for implementing the schema of the maps
+macro_rules! impl_schema_map {
+    ($($(#[$Attr:meta])* $map:ident),*) => {
+        $(
+            $(#[$Attr])*
+            impl<K: Schema, V: Schema> Schema for $map<K, V> {
+                fn type_schema() -> TypeSchema {
+                    TypeSchema::Map(Box::new(K::type_schema()), Box::new(V::type_schema()))
+                }
+
+                fn definitions(definitions: &mut Vec<Definition>) {
+                    K::definitions(definitions);
+                    V::definitions(definitions);
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_map!(BTreeMap, #[cfg(feature = "std")] HashMap);
+
+impl<T: Schema> Schema for Option<T> {
+    fn type_schema() -> TypeSchema { TypeSchema::Option(Box::new(T::type_schema())) }
+
+    fn definitions(definitions: &mut Vec<Definition>) { T::definitions(definitions) }
+}
+
+impl<T: Schema, E: Schema> Schema for Result<T, E> {
+    fn type_schema() -> TypeSchema {
+        TypeSchema::Result(Box::new(T::type_schema()), Box::new(E::type_schema()))
+    }
+
+    fn definitions(definitions: &mut Vec<Definition>) {
+        T::definitions(definitions);
+        E::definitions(definitions);
+    }
+}
+
+impl Schema for () {
+    fn type_schema() -> TypeSchema { TypeSchema::Unit }
+}
+
+/// Macro for implementing Schema for the address types which the generators
+/// don't have readers and writers for
+macro_rules! impl_schema_custom {
+    ($($type:ident),*) => {
+        $(
+            impl Schema for $type {
+                fn type_schema() -> TypeSchema { TypeSchema::Custom(stringify!($type)) }
+            }
+        )*
+    };
+}
+
+impl_schema_custom!(Ipv4Addr, Ipv6Addr, IpAddr, SocketAddr);
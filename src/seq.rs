@@ -0,0 +1,150 @@
+use core::fmt;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{Read, ReadResult, Readable, VarInt, Writable, Write, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{OrderedRepr, OrderedStyle};
+use crate::summary::Summary;
+
+/// Creates a new iterator over the values of a sequence
+type SeqSource<T> = Arc<dyn Fn() -> Box<dyn Iterator<Item=T>> + Send + Sync>;
+
+/// ## Seq Writer
+/// A collection field which is written from an iterator rather than a Vec so that
+/// huge collections (world snapshots, file chunks) can be streamed into the output
+/// without first being collected into memory. This is encoded the same as Vec.
+///
+/// The length must be known up front as it is written before the values. The
+/// iterator is cloned each time the field is written so the field can be written
+/// more than once. Reading a SeqWriter reads the values into a Vec.
+///
+/// ```
+/// use wsbps::{SeqWriter, Writable};
+/// let mut chunks = SeqWriter::new(1000, (0..1000u32).map(|i| i as u8));
+/// let mut o = Vec::new();
+/// chunks.write(&mut o).unwrap();
+/// ```
+pub struct SeqWriter<T> {
+    len: usize,
+    source: SeqSource<T>,
+}
+
+impl<T: 'static> SeqWriter<T> {
+    /// Creates a sequence of [len] values produced by [iter]
+    pub fn new<I>(len: usize, iter: I) -> Self
+        where I: IntoIterator<Item=T> + Clone + Send + Sync + 'static,
+              I::IntoIter: 'static {
+        SeqWriter { len, source: Arc::new(move || Box::new(iter.clone().into_iter())) }
+    }
+
+    /// The number of values in this sequence
+    pub fn len(&self) -> usize { self.len }
+
+    /// Whether this sequence has no values
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Creates an iterator over the values in this sequence
+    pub fn iter(&self) -> Box<dyn Iterator<Item=T>> {
+        (self.source)()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> From<Vec<T>> for SeqWriter<T> {
+    fn from(v: Vec<T>) -> Self { SeqWriter::new(v.len(), v) }
+}
+
+impl<T: Writable + 'static> Writable for SeqWriter<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        VarInt(self.len as u32).write(o)?;
+        let mut values = self.iter();
+        let mut count = 0;
+        for mut value in values.by_ref().take(self.len) {
+            value.write_with(ctx, o)?;
+            count += 1;
+        }
+        // Count any values past the length for the error
+        count += values.count();
+        if count != self.len {
+            Err(PacketError::LengthMismatch("SeqWriter", count, "len", self.len))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Readable + Clone + 'static> Readable for SeqWriter<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Vec::<T>::read(i).map(SeqWriter::from)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Vec::<T>::read_with(ctx, i).map(SeqWriter::from)
+    }
+}
+
+/// Clones share the same source of values
+impl<T> Clone for SeqWriter<T> {
+    fn clone(&self) -> Self {
+        SeqWriter { len: self.len, source: self.source.clone() }
+    }
+}
+
+/// Sequences are equal if they produce equal values
+impl<T: PartialEq + 'static> PartialEq for SeqWriter<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug + 'static> fmt::Debug for SeqWriter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: OrderedRepr + 'static> OrderedRepr for SeqWriter<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.iter().collect::<Vec<T>>().write_ordered(style, out)
+    }
+}
+
+impl<T: Summary + 'static> Summary for SeqWriter<T> {
+    fn write_summary(&self, out: &mut String) {
+        self.iter().collect::<Vec<T>>().write_summary(out)
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl<T: crate::Schema> crate::Schema for SeqWriter<T> {
+    fn type_schema() -> crate::TypeSchema { Vec::<T>::type_schema() }
+
+    fn definitions(definitions: &mut Vec<crate::Definition>) { T::definitions(definitions) }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a> + Clone + Send + Sync + 'static> arbitrary::Arbitrary<'a> for SeqWriter<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(SeqWriter::from(Vec::<T>::arbitrary(u)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + 'static> serde::Serialize for SeqWriter<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de> + Clone + Send + Sync + 'static> serde::Deserialize<'de> for SeqWriter<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<T>::deserialize(deserializer).map(SeqWriter::from)
+    }
+}
@@ -0,0 +1,219 @@
+use alloc::sync::Arc;
+use core::fmt::Debug;
+
+use crate::dictionary::CompressionDictionary;
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, VarInt, Writable, Write, WriteResult};
+use crate::limits::{ConnectionContext, DecodeDeadline, ReadContext, ReadLimits, WriteContext};
+use crate::string_enum::StringIds;
+use crate::text::TextOptions;
+
+/// ## Protocol State
+/// The states of a protocol where each state reads packets using a different
+/// packet group. This is implemented using the protocol_states macro
+pub trait ProtocolState: Copy + PartialEq + Debug {
+    /// The packets of all the states
+    type Packet;
+
+    /// The name of the state used in errors
+    fn name(self) -> &'static str;
+
+    /// Reads the packet with the provided [id] using the group of this state
+    /// failing with PacketError::UnknownPacket if the ID isn't part of the group
+    fn read_packet<B: Read>(self, ctx: &mut ReadContext, id: u32, i: &mut B) -> ReadResult<Self::Packet>;
+}
+
+/// ## Session
+/// Tracks the current state of a protocol and reads packets using the group of
+/// the current state. Packets which aren't part of the current state are rejected
+/// with PacketError::OutOfStatePacket. All the reads share the ConnectionContext
+/// of the session so strings are checked against the text options negotiated for
+/// the connection and string enum IDs and DictionaryStrings are read using the
+/// string IDs and compression dictionary the peer sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session<S: ProtocolState> {
+    state: S,
+    connection: Arc<ConnectionContext>,
+}
+
+impl<S: ProtocolState> Session<S> {
+    /// Creates a new session starting in the provided [state]
+    pub fn new(state: S) -> Self {
+        Session { state, connection: Arc::new(ConnectionContext::new()) }
+    }
+
+    /// Sets the [text] options (usually negotiated during the handshake) that the
+    /// strings of all the following packets are checked against
+    pub fn set_text_options(&mut self, text: TextOptions) {
+        Arc::make_mut(&mut self.connection).text = text;
+    }
+
+    /// The options strings are checked against
+    pub fn text_options(&self) -> &TextOptions { &self.connection.text }
+
+    /// Sets the [string_ids] the peer sent during the handshake which are used to
+    /// read the string enum values of all the following packets sent as IDs
+    pub fn set_peer_string_ids(&mut self, string_ids: StringIds) {
+        Arc::make_mut(&mut self.connection).string_ids = Some(string_ids);
+    }
+
+    /// The string IDs the peer sent
+    pub fn peer_string_ids(&self) -> Option<&StringIds> { self.connection.string_ids.as_ref() }
+
+    /// Sets the [string_ids] this side sent once the peer has acknowledged them.
+    /// The packets written with the session after this send the values of the
+    /// enums that have a table in them as their IDs in these tables
+    pub fn set_local_string_ids(&mut self, string_ids: StringIds) {
+        Arc::make_mut(&mut self.connection).local_string_ids = Some(string_ids);
+    }
+
+    /// The string IDs this side sent which the peer has acknowledged
+    pub fn local_string_ids(&self) -> Option<&StringIds> { self.connection.local_string_ids.as_ref() }
+
+    /// Applies the [dictionary] the peer sent which is used to read the
+    /// DictionaryStrings of all the following packets. Dictionaries that aren't
+    /// newer than the current one are rejected so a stale update can't replace it
+    pub fn set_dictionary(&mut self, dictionary: CompressionDictionary) -> ReadResult<()> {
+        if self.dictionary().is_some_and(|current| current.version() >= dictionary.version()) {
+            return Err(PacketError::UnexpectedValue("a dictionary version newer than the current one"));
+        }
+        Arc::make_mut(&mut self.connection).dictionary = Some(dictionary);
+        Ok(())
+    }
+
+    /// The latest compression dictionary the peer sent
+    pub fn dictionary(&self) -> Option<&CompressionDictionary> { self.connection.dictionary.as_ref() }
+
+    /// Sets the protocol [version] negotiated for the connection
+    pub fn set_version(&mut self, version: u32) {
+        Arc::make_mut(&mut self.connection).version = version;
+    }
+
+    /// The protocol version of the connection
+    pub fn version(&self) -> u32 { self.connection.version }
+
+    /// Sets the [limits] (usually one of the ReadLimits presets) that all the
+    /// following packets are read with instead of the global limits
+    pub fn set_limits(&mut self, limits: ReadLimits) {
+        Arc::make_mut(&mut self.connection).limits = Some(limits);
+    }
+
+    /// The limits packets are read with
+    pub fn limits(&self) -> ReadLimits {
+        self.connection.limits.unwrap_or_else(ReadLimits::global)
+    }
+
+    /// The context shared by the reads of the session which can be given to reads
+    /// made outside of the session (e.g. on a threadpool)
+    pub fn connection(&self) -> &Arc<ConnectionContext> { &self.connection }
+
+    /// The current state of the session
+    pub fn state(&self) -> S { self.state }
+
+    /// Moves the session to the provided [state] which is used to read all the
+    /// following packets
+    pub fn transition(&mut self, state: S) {
+        self.state = state;
+    }
+
+    /// Reads a packet (ID and contents) from [i] using the group of the current state
+    pub fn read<B: Read>(&self, i: &mut B) -> ReadResult<S::Packet> {
+        self.read_with_context(ReadContext::default(), i)
+    }
+
+    /// Reads a packet from [i] failing with PacketError::DeadlineExceeded if it
+    /// isn't decoded before the [deadline]
+    pub fn read_with_deadline<B: Read>(&self, deadline: DecodeDeadline, i: &mut B) -> ReadResult<S::Packet> {
+        self.read_with_context(ReadContext::default().with_deadline(deadline), i)
+    }
+
+    /// Reads a packet from [i] using the provided [ctx] along with the connection
+    /// context of the session
+    fn read_with_context<B: Read>(&self, ctx: ReadContext, i: &mut B) -> ReadResult<S::Packet> {
+        let mut ctx = ctx.with_connection(self.connection.clone());
+        let id = VarInt::read_with(&mut ctx, i)?.0;
+        match self.state.read_packet(&mut ctx, id, i) {
+            Err(PacketError::UnknownPacket(id)) => Err(PacketError::OutOfStatePacket(id, self.state.name())),
+            result => result
+        }
+    }
+
+    /// Writes the [packet] (ID and contents) to [o] with the connection context of
+    /// the session so string enums are written as IDs once the peer has
+    /// acknowledged the StringIds of this side
+    pub fn write<P: Writable, B: Write>(&self, packet: &mut P, o: &mut B) -> WriteResult {
+        let mut ctx = WriteContext::default().with_connection(self.connection.clone());
+        packet.write_with(&mut ctx, o)
+    }
+}
+
+/// # Protocol States Macro
+/// This macro declares the states of a protocol and the packet group that each
+/// state reads packets with. It generates an enum of the states which implements
+/// ProtocolState and an enum of the packets which has a variant for each state
+/// containing the packet group of that state
+///
+/// ## Example
+///
+/// ```
+/// use wsbps::{packets, protocol_states, Session};
+/// packets! {
+///     HandshakePackets (<-) {
+///         Hello (0x00) { version: u8 }
+///     }
+///
+///     PlayPackets (<-) {
+///         Move (0x00) { x: i32, y: i32 }
+///     }
+/// }
+///
+/// protocol_states! {
+///     ClientState => ClientPacket {
+///         Handshake => HandshakePackets,
+///         Play => PlayPackets,
+///     }
+/// }
+///
+/// let mut session = Session::new(ClientState::Handshake);
+/// assert!(matches!(session.read(&mut &[0x00, 0x01][..]), Ok(ClientPacket::Handshake(_))));
+/// session.transition(ClientState::Play);
+/// ```
+#[macro_export]
+macro_rules! protocol_states {
+    (
+        $State:ident => $Packet:ident {
+            $($Name:ident => $Group:ty),* $(,)?
+        }
+    ) => {
+        #[doc = concat!("The states of the protocol read by `", stringify!($Packet), "`")]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $State {
+            $(
+                #[doc = concat!("Packets are read using `", stringify!($Group), "`")]
+                $Name
+            ),*
+        }
+
+        #[doc = concat!("The packets of each `", stringify!($State), "` state")]
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum $Packet {
+            $($Name($Group)),*
+        }
+
+        impl $crate::ProtocolState for $State {
+            type Packet = $Packet;
+
+            fn name(self) -> &'static str {
+                match self {
+                    $($State::$Name => stringify!($Name)),*
+                }
+            }
+
+            fn read_packet<_ReadX: $crate::io::Read>(self, ctx: &mut $crate::ReadContext, id: u32, i: &mut _ReadX) -> $crate::ReadResult<$Packet> {
+                match self {
+                    $($State::$Name => <$Group as $crate::ReadPacket>::read_packet(ctx, id, i).map($Packet::$Name)),*
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,117 @@
+use alloc::collections::VecDeque;
+
+use crate::io::{Read, Readable, ReadResult};
+
+/// The default number of snapshots kept by a SnapshotBuffer
+pub const DEFAULT_SNAPSHOT_CAPACITY: usize = 32;
+
+/// ## Interpolate
+/// Trait for blending between two snapshots of a value. This is implemented for the
+/// float types and should be implemented for the state packets stored in a
+/// SnapshotBuffer (usually by interpolating the positions and taking the rest of
+/// the fields from one of the snapshots)
+pub trait Interpolate {
+    /// Blends self towards [to] where [t] is from 0.0 (self) to 1.0 ([to])
+    fn interpolate(&self, to: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, to: &Self, t: f64) -> Self { self + (to - self) * t }
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, to: &Self, t: f64) -> Self { self + (to - self) * t as f32 }
+}
+
+/// ## Snapshot Buffer
+/// Client side buffer of the timestamped state packets received from the server
+/// for rendering smoothly between them. Times are milliseconds provided by the
+/// caller (usually the server time of the snapshot). Snapshots older than the
+/// latest snapshot arrive too late to be used so they are dropped and only the
+/// most recent snapshots up to the capacity are kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotBuffer<P> {
+    snapshots: VecDeque<(u64, P)>,
+    capacity: usize,
+}
+
+impl<P> SnapshotBuffer<P> {
+    /// Creates a buffer keeping DEFAULT_SNAPSHOT_CAPACITY snapshots
+    pub fn new() -> Self {
+        SnapshotBuffer::with_capacity(DEFAULT_SNAPSHOT_CAPACITY)
+    }
+
+    /// Creates a buffer keeping up to [capacity] snapshots
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(2);
+        SnapshotBuffer { snapshots: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Adds the [snapshot] for the [time] returning false if it was dropped because
+    /// it wasn't newer than the latest snapshot
+    pub fn push(&mut self, time: u64, snapshot: P) -> bool {
+        if self.snapshots.back().is_some_and(|(latest, _)| *latest >= time) {
+            return false;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back((time, snapshot));
+        true
+    }
+
+    /// Reads a snapshot for the [time] from the source [i] and adds it returning
+    /// whether it was kept
+    pub fn read<B: Read>(&mut self, time: u64, i: &mut B) -> ReadResult<bool> where P: Readable {
+        let snapshot = P::read(i)?;
+        Ok(self.push(time, snapshot))
+    }
+
+    /// The latest snapshot along with its time
+    pub fn latest(&self) -> Option<(u64, &P)> {
+        self.snapshots.back().map(|(time, snapshot)| (*time, snapshot))
+    }
+
+    /// The state at the [time] interpolated between the snapshots either side of it.
+    /// Times before the first snapshot give the first snapshot and times after the
+    /// latest give the latest snapshot as the state isn't extrapolated
+    pub fn interpolate(&self, time: u64) -> Option<P> where P: Interpolate + Clone {
+        // The index of the first snapshot after the time
+        let after = self.snapshots.partition_point(|(snapshot_time, _)| *snapshot_time <= time);
+        if after > 0 && after < self.snapshots.len() {
+            let (from_time, from) = &self.snapshots[after - 1];
+            let (to_time, to) = &self.snapshots[after];
+            let t = (time - from_time) as f64 / (to_time - from_time) as f64;
+            return Some(from.interpolate(to, t));
+        }
+        let (first_time, first) = self.snapshots.front()?;
+        if time < *first_time {
+            Some(first.clone())
+        } else {
+            self.latest().map(|(_, latest)| latest.clone())
+        }
+    }
+
+    /// Drops the snapshots that are no longer needed for interpolating at the [time]
+    /// or later keeping the latest snapshot at or before it
+    pub fn discard_before(&mut self, time: u64) {
+        while self.snapshots.get(1).is_some_and(|(next, _)| *next <= time) {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// The number of snapshots in the buffer
+    pub fn len(&self) -> usize { self.snapshots.len() }
+
+    /// Whether there are no snapshots in the buffer
+    pub fn is_empty(&self) -> bool { self.snapshots.is_empty() }
+
+    /// Removes all the snapshots
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+impl<P> Default for SnapshotBuffer<P> {
+    fn default() -> Self { SnapshotBuffer::new() }
+}
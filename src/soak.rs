@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// The default maximum number of samples kept for each series
+pub const DEFAULT_MAX_SAMPLES: usize = 1024;
+
+/// ## Soak Monitor
+/// Long run instrumentation for finding leaks. The sizes of the connection layer
+/// structures (e.g. pools, queues, session tables or RequestTracker::len) are
+/// sampled periodically with the caller provided time in milliseconds and the
+/// report shows how each one grew over the run. To keep the monitor itself from
+/// growing forever every other sample is discarded once a series is full so the
+/// samples always cover the whole run
+#[derive(Debug, Clone)]
+pub struct SoakMonitor {
+    series: BTreeMap<&'static str, Vec<(u64, usize)>>,
+    max_samples: usize,
+}
+
+impl SoakMonitor {
+    /// Creates a monitor keeping up to DEFAULT_MAX_SAMPLES for each series
+    pub fn new() -> Self {
+        SoakMonitor::with_max_samples(DEFAULT_MAX_SAMPLES)
+    }
+
+    /// Creates a monitor keeping up to [max_samples] for each series
+    pub fn with_max_samples(max_samples: usize) -> Self {
+        SoakMonitor { series: BTreeMap::new(), max_samples: max_samples.max(2) }
+    }
+
+    /// Records the [value] (e.g. the number of entries or bytes) of the series with
+    /// the provided [name] at the time [now]
+    pub fn sample(&mut self, now: u64, name: &'static str, value: usize) {
+        let samples = self.series.entry(name).or_default();
+        if samples.len() >= self.max_samples {
+            let mut index = 0;
+            samples.retain(|_| {
+                index += 1;
+                index % 2 == 1
+            });
+        }
+        samples.push((now, value));
+    }
+
+    /// The samples recorded for the series with the provided [name]
+    pub fn samples(&self, name: &str) -> &[(u64, usize)] {
+        self.series.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Clears all the samples
+    pub fn clear(&mut self) {
+        self.series.clear();
+    }
+
+    /// Creates a report of the growth of each series
+    pub fn report(&self) -> SoakReport {
+        let trends = self.series.iter()
+            .filter_map(|(name, samples)| SeriesTrend::new(name, samples))
+            .collect();
+        SoakReport { trends }
+    }
+}
+
+impl Default for SoakMonitor {
+    fn default() -> Self { SoakMonitor::new() }
+}
+
+/// How a series changed over a soak test
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesTrend {
+    /// The name of the series
+    pub name: &'static str,
+    /// The number of samples the trend was calculated from
+    pub samples: usize,
+    /// The time of the first sample
+    pub start: u64,
+    /// The time of the last sample
+    pub end: u64,
+    /// The value of the first sample
+    pub first: usize,
+    /// The value of the last sample
+    pub last: usize,
+    /// The lowest value
+    pub min: usize,
+    /// The highest value
+    pub max: usize,
+    /// The growth per hour of the least squares line through the samples. A series
+    /// which keeps growing at a steady rate is likely leaking
+    pub growth_per_hour: f64,
+}
+
+impl SeriesTrend {
+    /// Calculates the trend of the [samples] returning None if there aren't any
+    fn new(name: &'static str, samples: &[(u64, usize)]) -> Option<Self> {
+        let (start, first) = *samples.first()?;
+        let (end, last) = *samples.last()?;
+        let min = samples.iter().map(|(_, value)| *value).min()?;
+        let max = samples.iter().map(|(_, value)| *value).max()?;
+        // The times are relative to the start so they don't lose precision
+        let count = samples.len() as f64;
+        let mean_time = samples.iter().map(|(time, _)| (time - start) as f64).sum::<f64>() / count;
+        let mean_value = samples.iter().map(|(_, value)| *value as f64).sum::<f64>() / count;
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for (time, value) in samples {
+            let time = (time - start) as f64 - mean_time;
+            covariance += time * (*value as f64 - mean_value);
+            variance += time * time;
+        }
+        let growth_per_hour = if variance > 0.0 { covariance / variance * 3_600_000.0 } else { 0.0 };
+        Some(SeriesTrend { name, samples: samples.len(), start, end, first, last, min, max, growth_per_hour })
+    }
+}
+
+/// ## Soak Report
+/// The trends of the series recorded by a SoakMonitor. The Display implementation
+/// prints a table of the series
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoakReport {
+    /// The trend of each series ordered by name
+    pub trends: Vec<SeriesTrend>,
+}
+
+impl SoakReport {
+    /// The trend of the series with the provided [name]
+    pub fn trend(&self, name: &str) -> Option<&SeriesTrend> {
+        self.trends.iter().find(|trend| trend.name == name)
+    }
+
+    /// The series which grew by more than [threshold] per hour and are suspected
+    /// of leaking
+    pub fn growing(&self, threshold: f64) -> impl Iterator<Item=&SeriesTrend> {
+        self.trends.iter().filter(move |trend| trend.growth_per_hour > threshold)
+    }
+}
+
+impl Display for SoakReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<24} {:>10} {:>10} {:>10} {:>10} {:>14}", "series", "first", "last", "min", "max", "growth/hour")?;
+        for trend in &self.trends {
+            writeln!(
+                f,
+                "{:<24} {:>10} {:>10} {:>10} {:>10} {:>14.2}",
+                trend.name, trend.first, trend.last, trend.min, trend.max, trend.growth_per_hour
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{read_string_with, Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{write_ordered_struct, OrderedRepr, OrderedStyle};
+use crate::summary::{write_summary_fields, Summary};
+
+/// ## String Enum
+/// Implemented for the enums declared with String values. These are written as
+/// their string value unless the peer being written to has acknowledged StringIds
+/// with a table for the enum that this side sent (see WriteContext) in which case
+/// they are written as an empty string (a zero length) followed by the index of
+/// the value in that table as a VarInt. The receiver maps the index back to the
+/// string using the StringIds the writer sent so the declarations of the two peers
+/// don't need to be in the same order. Each direction uses the tables of the side
+/// writing it so peers that never send StringIds (such as the generated JS and
+/// Kotlin clients) always receive the strings. Because of this the values must not
+/// be empty which is checked when the enum is declared
+///
+/// ```compile_fail
+/// use wsbps::packet_data;
+/// packet_data! {
+///     enum Mode (<->) (String) {
+///         None: "",
+///         Active: "active"
+///     }
+/// }
+/// ```
+pub trait StringEnum: Sized + 'static {
+    /// The name of the enum which is the key of its table in StringIds
+    const NAME: &'static str;
+    /// The values of the enum in declaration order
+    const VALUES: &'static [&'static str];
+
+    /// The string value of self
+    fn as_str(&self) -> &'static str;
+
+    /// The variant with the provided string [value]
+    fn from_value(value: &str) -> Option<Self>;
+}
+
+/// ## String IDs
+/// The tables mapping the IDs of string enum values to the values which a peer
+/// sends during the handshake. The receiver gives the tables of the peer to the
+/// ReadContext (see Session::set_peer_string_ids) so the compressed values it sends
+/// can be read and the sender only writes IDs from its own tables once the peer
+/// has acknowledged them (see Session::set_local_string_ids). This is encoded as a
+/// map of the enum names to their values
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StringIds {
+    tables: BTreeMap<String, Vec<String>>,
+}
+
+impl StringIds {
+    /// Creates empty tables
+    pub fn new() -> Self {
+        StringIds::default()
+    }
+
+    /// Adds the table for the string enum E
+    pub fn with<E: StringEnum>(mut self) -> Self {
+        self.insert(E::NAME, E::VALUES.iter().map(|value| String::from(*value)).collect());
+        self
+    }
+
+    /// Adds or replaces the table of [values] for the enum with the provided [name]
+    pub fn insert(&mut self, name: &str, values: Vec<String>) {
+        self.tables.insert(String::from(name), values);
+    }
+
+    /// The value with the [id] in the table of the enum with the provided [name]
+    pub fn get(&self, name: &str, id: u32) -> Option<&str> {
+        self.tables.get(name)?.get(id as usize).map(String::as_str)
+    }
+
+    /// The ID of the [value] in the table of the enum with the provided [name]
+    pub fn id(&self, name: &str, value: &str) -> Option<u32> {
+        self.tables.get(name)?.iter().position(|other| other == value).map(|id| id as u32)
+    }
+
+    /// Whether there is a table for the enum with the provided [name]
+    pub fn contains(&self, name: &str) -> bool {
+        self.tables.contains_key(name)
+    }
+}
+
+impl Writable for StringIds {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.tables.write(o)
+    }
+}
+
+impl Readable for StringIds {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Ok(StringIds { tables: BTreeMap::read_with(ctx, i)? })
+    }
+}
+
+impl OrderedRepr for StringIds {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_struct(style, out, "StringIds", &[
+            ("tables", &self.tables),
+        ]);
+    }
+}
+
+impl Summary for StringIds {
+    fn write_summary(&self, out: &mut String) {
+        write_summary_fields(out, "StringIds", None, &[
+            ("tables", &self.tables),
+        ]);
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for StringIds {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(StringIds { tables: BTreeMap::arbitrary(u)? })
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl crate::schema::Schema for StringIds {
+    fn type_schema() -> crate::schema::TypeSchema {
+        crate::schema::TypeSchema::Named("StringIds")
+    }
+
+    fn definitions(definitions: &mut Vec<crate::schema::Definition>) {
+        use alloc::boxed::Box;
+        use crate::schema::{add_definition, Definition, DefinitionKind, FieldSchema, TypeSchema};
+        add_definition(definitions, Definition {
+            name: "StringIds",
+            kind: DefinitionKind::Struct(alloc::vec![
+                FieldSchema::new("tables", TypeSchema::Map(
+                    Box::new(TypeSchema::String),
+                    Box::new(TypeSchema::List(Box::new(TypeSchema::String))),
+                )),
+            ]),
+        });
+    }
+}
+
+/// ## Write String Enum
+/// Writes the [value] to [o] as its ID in the StringIds of the context (the
+/// tables of this side the peer has acknowledged) when they contain the value and
+/// as its string otherwise
+pub fn write_string_enum<E: StringEnum, B: Write>(ctx: &mut WriteContext, value: &E, o: &mut B) -> WriteResult {
+    let string = value.as_str();
+    if let Some(id) = ctx.string_ids().and_then(|string_ids| string_ids.id(E::NAME, string)) {
+        VarInt(0).write(o)?;
+        return VarInt(id).write(o);
+    }
+    VarInt(string.len() as u32).write(o)?;
+    o.write_all(string.as_bytes())?;
+    Ok(())
+}
+
+/// ## Read String Enum
+/// Reads a value of E from the source [i] which is either its string or its ID.
+/// IDs are looked up in the StringIds the peer sent failing if there aren't any
+pub fn read_string_enum<E: StringEnum, B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<E> {
+    let length = VarInt::read_with(ctx, i)?.0 as usize;
+    if length == 0 {
+        let id = VarInt::read_with(ctx, i)?.0;
+        let value = ctx.string_ids()
+            .ok_or(PacketError::UnexpectedValue("string enum id without negotiated string ids"))?
+            .get(E::NAME, id)
+            .ok_or(PacketError::UnknownEnumValue)?;
+        return E::from_value(value).ok_or(PacketError::UnknownEnumValue);
+    }
+    let value = read_string_with(ctx, i, length)?;
+    E::from_value(&value).ok_or(PacketError::UnknownEnumValue)
+}
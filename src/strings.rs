@@ -0,0 +1,256 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::ops::Deref;
+
+use crate::error::PacketError;
+use crate::io::{read_string_with, Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::ReadContext;
+use crate::ordered::{OrderedRepr, OrderedStyle};
+use crate::summary::Summary;
+
+/// Macro for implementing the traits shared by the validated string types which
+/// are all encoded the same as a String and checked by their new function
+macro_rules! impl_validated_string {
+    ($Name:ident $(<const $Max:ident: usize>)?) => {
+        impl$(<const $Max: usize>)? $Name$(<$Max>)? {
+            /// The validated string
+            pub fn as_str(&self) -> &str { &self.value }
+
+            /// Consumes self returning the validated string
+            pub fn into_inner(self) -> String { self.value }
+        }
+
+        impl$(<const $Max: usize>)? Deref for $Name$(<$Max>)? {
+            type Target = str;
+
+            fn deref(&self) -> &Self::Target { &self.value }
+        }
+
+        impl$(<const $Max: usize>)? Display for $Name$(<$Max>)? {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                f.write_str(&self.value)
+            }
+        }
+
+        impl$(<const $Max: usize>)? TryFrom<String> for $Name$(<$Max>)? {
+            type Error = PacketError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> { Self::new(value) }
+        }
+
+        impl$(<const $Max: usize>)? TryFrom<&str> for $Name$(<$Max>)? {
+            type Error = PacketError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> { Self::new(value) }
+        }
+
+        impl$(<const $Max: usize>)? From<$Name$(<$Max>)?> for String {
+            fn from(value: $Name$(<$Max>)?) -> Self { value.value }
+        }
+
+        impl$(<const $Max: usize>)? Writable for $Name$(<$Max>)? {
+            fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+                self.value.write(o)
+            }
+        }
+
+        impl$(<const $Max: usize>)? OrderedRepr for $Name$(<$Max>)? {
+            fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+                self.value.write_ordered(style, out)
+            }
+        }
+
+        impl$(<const $Max: usize>)? Summary for $Name$(<$Max>)? {
+            fn write_summary(&self, out: &mut String) {
+                self.value.write_summary(out)
+            }
+        }
+
+        #[cfg(feature = "codegen")]
+        impl$(<const $Max: usize>)? crate::schema::Schema for $Name$(<$Max>)? {
+            fn type_schema() -> crate::schema::TypeSchema { crate::schema::TypeSchema::String }
+        }
+    };
+}
+
+/// ## ASCII String
+/// A string which only contains ASCII characters. Reading a string with any other
+/// characters fails with PacketError::NonAsciiString. This is encoded the same as
+/// a String
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct AsciiString {
+    value: String,
+}
+
+impl AsciiString {
+    /// Creates a new string from the [value] failing if it has non-ASCII characters
+    pub fn new(value: impl Into<String>) -> ReadResult<Self> {
+        let value = value.into();
+        if let Some(index) = value.bytes().position(|byte| !byte.is_ascii()) {
+            return Err(PacketError::NonAsciiString(index));
+        }
+        Ok(AsciiString { value })
+    }
+}
+
+impl_validated_string!(AsciiString);
+
+impl Readable for AsciiString {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        AsciiString::new(String::read_with(ctx, i)?)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for AsciiString {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let value = String::arbitrary(u)?.chars().filter(char::is_ascii).collect();
+        Ok(AsciiString { value })
+    }
+}
+
+/// The characters allowed in the namespace of an Identifier
+fn is_namespace_char(byte: u8) -> bool {
+    matches!(byte, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.')
+}
+
+/// The characters allowed in the path of an Identifier
+fn is_path_char(byte: u8) -> bool {
+    is_namespace_char(byte) || byte == b'/'
+}
+
+/// ## Identifier
+/// A namespaced identifier in the form `namespace:path` (e.g. `game:items/sword`).
+/// Both parts must be non-empty, the namespace may contain lowercase letters,
+/// digits, `_`, `-` and `.` and the path may also contain `/`. Reading an invalid
+/// identifier fails with PacketError::InvalidIdentifier. This is encoded the same
+/// as a String
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Identifier {
+    value: String,
+    separator: usize,
+}
+
+impl Identifier {
+    /// Creates a new identifier from the [value] failing if it isn't valid
+    pub fn new(value: impl Into<String>) -> ReadResult<Self> {
+        let value = value.into();
+        let separator = value.find(':')
+            .ok_or(PacketError::InvalidIdentifier("a ':' between the namespace and path"))?;
+        let (namespace, path) = (&value[..separator], &value[separator + 1..]);
+        if namespace.is_empty() {
+            return Err(PacketError::InvalidIdentifier("a non-empty namespace"));
+        }
+        if path.is_empty() {
+            return Err(PacketError::InvalidIdentifier("a non-empty path"));
+        }
+        if !namespace.bytes().all(is_namespace_char) {
+            return Err(PacketError::InvalidIdentifier("only a-z, 0-9, '_', '-' and '.' in the namespace"));
+        }
+        if !path.bytes().all(is_path_char) {
+            return Err(PacketError::InvalidIdentifier("only a-z, 0-9, '_', '-', '.' and '/' in the path"));
+        }
+        Ok(Identifier { value, separator })
+    }
+
+    /// Creates a new identifier from the [namespace] and [path]
+    pub fn from_parts(namespace: &str, path: &str) -> ReadResult<Self> {
+        let mut value = String::with_capacity(namespace.len() + path.len() + 1);
+        value.push_str(namespace);
+        value.push(':');
+        value.push_str(path);
+        Identifier::new(value)
+    }
+
+    /// The part before the ':'
+    pub fn namespace(&self) -> &str { &self.value[..self.separator] }
+
+    /// The part after the ':'
+    pub fn path(&self) -> &str { &self.value[self.separator + 1..] }
+}
+
+impl_validated_string!(Identifier);
+
+impl Readable for Identifier {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Identifier::new(String::read_with(ctx, i)?)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Identifier {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789_-./";
+        let mut part = |chars: &[u8]| -> arbitrary::Result<String> {
+            let length = u.int_in_range(1..=16)?;
+            (0..length).map(|_| Ok(*u.choose(chars)? as char)).collect()
+        };
+        let namespace = part(&CHARS[..CHARS.len() - 1])?;
+        let path = part(CHARS)?;
+        Identifier::from_parts(&namespace, &path).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+/// ## Bounded String
+/// A string of at most MAX bytes when encoded as UTF-8. The length is checked
+/// before the bytes are read so oversized strings are rejected without being
+/// allocated. Reading a longer string fails with PacketError::StringTooLong. This
+/// is encoded the same as a String
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct BoundedString<const MAX: usize> {
+    value: String,
+}
+
+impl<const MAX: usize> BoundedString<MAX> {
+    /// Creates a new string from the [value] failing if it is longer than MAX bytes
+    pub fn new(value: impl Into<String>) -> ReadResult<Self> {
+        let value = value.into();
+        if value.len() > MAX {
+            return Err(PacketError::StringTooLong(value.len(), MAX));
+        }
+        Ok(BoundedString { value })
+    }
+}
+
+impl_validated_string!(BoundedString<const MAX: usize>);
+
+impl<const MAX: usize> Readable for BoundedString<MAX> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let length = VarInt::read_with(ctx, i)?.0 as usize;
+        if length > MAX {
+            return Err(PacketError::StringTooLong(length, MAX));
+        }
+        Ok(BoundedString { value: read_string_with(ctx, i, length)? })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, const MAX: usize> arbitrary::Arbitrary<'a> for BoundedString<MAX> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut value = String::arbitrary(u)?;
+        let mut length = value.len().min(MAX);
+        while !value.is_char_boundary(length) {
+            length -= 1;
+        }
+        value.truncate(length);
+        Ok(BoundedString { value })
+    }
+}
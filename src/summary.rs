@@ -0,0 +1,210 @@
+use core::fmt::Write;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::boxed::Box;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::io::{VarInt, VarLong};
+
+/// Strings with more characters than this are abbreviated in summaries
+pub const SUMMARY_STRING_LENGTH: usize = 32;
+
+/// Lists with more items than this are abbreviated to their length in summaries
+pub const SUMMARY_LIST_LENGTH: usize = 8;
+
+/// ## Summary
+/// Trait for producing a concise one line summary of a value for log lines. Large
+/// strings and lists are abbreviated and bytes are summarized as their length so
+/// binary payloads don't flood the logs like the full Debug output does.
+///
+/// This is implemented for all the generated packets, structs and enums when the
+/// "summary" feature is enabled which also implements Display for the groups
+pub trait Summary {
+    /// Appends the summary of self to [out]
+    fn write_summary(&self, out: &mut String);
+
+    /// Appends the summary of a list of [values] of this type to [out]. Lists with
+    /// more than SUMMARY_LIST_LENGTH items are summarized as their length. This is
+    /// overridden for u8 so bytes are always summarized as their length
+    fn write_list_summary(values: &[Self], out: &mut String) where Self: Sized {
+        write_summary_list(out, values.len(), values.iter())
+    }
+}
+
+/// Creates the summary of the provided value
+pub fn to_summary<T: Summary + ?Sized>(value: &T) -> String {
+    let mut out = String::new();
+    value.write_summary(&mut out);
+    out
+}
+
+/// Writes a struct or packet with the provided fields in order. The output looks
+/// like `Name(id=0x01, a=1, b=2)` where the ID is only included for packets and the
+/// parentheses are left out when there is nothing to put in them
+pub fn write_summary_fields(out: &mut String, name: &str, id: Option<u32>, fields: &[(&str, &dyn Summary)]) {
+    out.push_str(name);
+    if id.is_none() && fields.is_empty() {
+        return;
+    }
+    out.push('(');
+    if let Some(id) = id {
+        let _ = write!(out, "id={:#04x}", id);
+    }
+    for (index, (field, value)) in fields.iter().enumerate() {
+        if index > 0 || id.is_some() {
+            out.push_str(", ");
+        }
+        out.push_str(field);
+        out.push('=');
+        value.write_summary(out);
+    }
+    out.push(')');
+}
+
+/// Writes the provided values as a list or as the number of items if there are
+/// more than SUMMARY_LIST_LENGTH
+fn write_summary_list<'a, T: Summary + 'a>(out: &mut String, len: usize, values: impl Iterator<Item=&'a T>) {
+    if len > SUMMARY_LIST_LENGTH {
+        let _ = write!(out, "[{} items]", len);
+        return;
+    }
+    out.push('[');
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        value.write_summary(out);
+    }
+    out.push(']');
+}
+
+/// Macro for implementing the summary for types whose Display output is already concise
+macro_rules! impl_summary_display {
+    ($($type:ty),*) => {
+        $(
+            impl Summary for $type {
+                fn write_summary(&self, out: &mut String) {
+                    let _ = write!(out, "{}", self);
+                }
+            }
+        )*
+    };
+}
+
+impl_summary_display!(bool, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+impl_summary_display!(Ipv4Addr, Ipv6Addr, IpAddr, SocketAddr);
+
+impl Summary for u8 {
+    fn write_summary(&self, out: &mut String) {
+        let _ = write!(out, "{}", self);
+    }
+
+    fn write_list_summary(values: &[Self], out: &mut String) {
+        let _ = write!(out, "[{} bytes]", values.len());
+    }
+}
+
+impl Summary for VarInt {
+    fn write_summary(&self, out: &mut String) { self.0.write_summary(out) }
+}
+
+impl Summary for VarLong {
+    fn write_summary(&self, out: &mut String) { self.0.write_summary(out) }
+}
+
+impl Summary for str {
+    fn write_summary(&self, out: &mut String) {
+        match self.char_indices().nth(SUMMARY_STRING_LENGTH) {
+            Some((end, _)) => {
+                let _ = write!(out, "{:?}... ({} chars)", &self[..end], self.chars().count());
+            }
+            None => { let _ = write!(out, "{:?}", self); }
+        }
+    }
+}
+
+impl Summary for String {
+    fn write_summary(&self, out: &mut String) { self.as_str().write_summary(out) }
+}
+
+impl<T: Summary> Summary for [T] {
+    fn write_summary(&self, out: &mut String) { T::write_list_summary(self, out) }
+}
+
+impl<T: Summary> Summary for Vec<T> {
+    fn write_summary(&self, out: &mut String) { T::write_list_summary(self, out) }
+}
+
+impl<T: Summary + ?Sized> Summary for Box<T> {
+    fn write_summary(&self, out: &mut String) { (**self).write_summary(out) }
+}
+
+impl<T: Summary + ?Sized> Summary for Arc<T> {
+    fn write_summary(&self, out: &mut String) { (**self).write_summary(out) }
+}
+
+impl<T: Summary + ToOwned + ?Sized> Summary for Cow<'_, T> {
+    fn write_summary(&self, out: &mut String) { (**self).write_summary(out) }
+}
+
+impl<T: Summary> Summary for Option<T> {
+    fn write_summary(&self, out: &mut String) {
+        match self {
+            Some(value) => {
+                out.push_str("Some(");
+                value.write_summary(out);
+                out.push(')');
+            }
+            None => out.push_str("None"),
+        }
+    }
+}
+
+impl<T: Summary, E: Summary> Summary for Result<T, E> {
+    fn write_summary(&self, out: &mut String) {
+        let (name, value): (&str, &dyn Summary) = match self {
+            Ok(value) => ("Ok(", value),
+            Err(error) => ("Err(", error),
+        };
+        out.push_str(name);
+        value.write_summary(out);
+        out.push(')');
+    }
+}
+
+impl Summary for () {
+    fn write_summary(&self, out: &mut String) { out.push_str("()") }
+}
+
+impl<T: Summary> Summary for VecDeque<T> {
+    fn write_summary(&self, out: &mut String) { write_summary_list(out, self.len(), self.iter()) }
+}
+
+impl<T: Summary> Summary for BTreeSet<T> {
+    fn write_summary(&self, out: &mut String) { write_summary_list(out, self.len(), self.iter()) }
+}
+
+#[cfg(feature = "std")]
+impl<T: Summary> Summary for HashSet<T> {
+    fn write_summary(&self, out: &mut String) { write_summary_list(out, self.len(), self.iter()) }
+}
+
+/// Maps are always summarized as their number of entries
+impl<K, V> Summary for BTreeMap<K, V> {
+    fn write_summary(&self, out: &mut String) {
+        let _ = write!(out, "{{{} entries}}", self.len());
+    }
+}
+
+/// Maps are always summarized as their number of entries
+#[cfg(feature = "std")]
+impl<K, V> Summary for HashMap<K, V> {
+    fn write_summary(&self, out: &mut String) {
+        let _ = write!(out, "{{{} entries}}", self.len());
+    }
+}
@@ -0,0 +1,158 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::error::PacketError;
+use crate::io::{FixedSize, Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::{check_collection_length, read_chunked, ReadContext, WriteContext};
+
+/// The largest tag a field of a tagged struct can have as the lowest three bits
+/// of the key hold the wire type
+pub const MAX_FIELD_TAG: u32 = u32::MAX >> 3;
+
+/// ## Wire Type
+/// How the value of a field in a tagged struct is delimited which lets readers
+/// skip the fields they don't know. The wire type is chosen from the type of the
+/// field, types which are FixedSize with 1, 2, 4 or 8 bytes use the fixed types
+/// and all other types are prefixed with their length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireType {
+    Fixed1 = 0,
+    Fixed2 = 1,
+    Fixed4 = 2,
+    Fixed8 = 3,
+    Delimited = 4,
+}
+
+impl WireType {
+    /// The wire type for a value which encodes to [length] bytes
+    pub fn for_length(length: usize) -> Self {
+        match length {
+            1 => WireType::Fixed1,
+            2 => WireType::Fixed2,
+            4 => WireType::Fixed4,
+            8 => WireType::Fixed8,
+            _ => WireType::Delimited,
+        }
+    }
+
+    /// The wire type stored in the lowest three bits of a field [key]
+    pub fn from_key(key: u32) -> ReadResult<Self> {
+        match key & 0b111 {
+            0 => Ok(WireType::Fixed1),
+            1 => Ok(WireType::Fixed2),
+            2 => Ok(WireType::Fixed4),
+            3 => Ok(WireType::Fixed8),
+            4 => Ok(WireType::Delimited),
+            _ => Err(PacketError::UnexpectedValue("a tagged field wire type from 0 to 4"))
+        }
+    }
+}
+
+/// ## Tagged Wire Type
+/// Used by the tagged struct macros to choose the wire type of a field from its
+/// type. Method resolution prefers FixedWireType which is only implemented for
+/// FixedSize types over DelimitedWireType (implemented on a reference) so this
+/// only works with concrete types, see the tagged_wire_type macro
+#[doc(hidden)]
+pub struct TaggedWireType<T>(pub PhantomData<T>);
+
+#[doc(hidden)]
+pub trait FixedWireType {
+    fn wire_type(&self) -> WireType;
+}
+
+impl<T: FixedSize> FixedWireType for TaggedWireType<T> {
+    fn wire_type(&self) -> WireType { WireType::for_length(T::ENCODED_SIZE) }
+}
+
+#[doc(hidden)]
+pub trait DelimitedWireType {
+    fn wire_type(&self) -> WireType;
+}
+
+impl<T> DelimitedWireType for &TaggedWireType<T> {
+    fn wire_type(&self) -> WireType { WireType::Delimited }
+}
+
+/// ## Write Tagged Field
+/// Writes the [value] of the field with the provided [tag] to [o] as its key (the
+/// tag and [wire_type] as a VarInt) followed by the value which is prefixed with
+/// its length when the wire type is delimited
+pub fn write_tagged_field<T: Writable, B: Write>(ctx: &mut WriteContext, o: &mut B, tag: u32, wire_type: WireType, value: &mut T) -> WriteResult {
+    let mut bytes = Vec::new();
+    value.write_with(ctx, &mut bytes)?;
+    if wire_type != WireType::Delimited && WireType::for_length(bytes.len()) != wire_type {
+        Err(PacketError::UnexpectedValue("a value encoded as the ENCODED_SIZE of its type"))?;
+    }
+    VarInt(tag << 3 | wire_type as u32).write(o)?;
+    if wire_type == WireType::Delimited {
+        VarInt(bytes.len() as u32).write(o)?;
+    }
+    o.write_all(&bytes)?;
+    Ok(())
+}
+
+/// ## Read Tagged Field
+/// Reads the key and bytes of the next field of a tagged struct from the source [i]
+/// returning the tag of the field, its wire type and its encoded value. The length
+/// of delimited values is checked against the collection length limit of [ctx]
+pub fn read_tagged_field<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<(u32, WireType, Vec<u8>)> {
+    let key = VarInt::read_with(ctx, i)?.0;
+    let wire_type = WireType::from_key(key)?;
+    let length = match wire_type {
+        WireType::Fixed1 => 1,
+        WireType::Fixed2 => 2,
+        WireType::Fixed4 => 4,
+        WireType::Fixed8 => 8,
+        WireType::Delimited => {
+            let length = VarInt::read_with(ctx, i)?.0 as usize;
+            check_collection_length(ctx, length)?;
+            length
+        }
+    };
+    Ok((key >> 3, wire_type, read_chunked(i, length)?))
+}
+
+/// ## Read Tagged Value
+/// Reads the value of a known field from its [payload] failing when the [wire_type]
+/// it was sent with isn't the [expected] wire type of the field or when the value
+/// doesn't use the whole payload
+pub fn read_tagged_value<T: Readable>(ctx: &mut ReadContext, expected: WireType, wire_type: WireType, payload: &[u8]) -> ReadResult<T> {
+    if wire_type != expected {
+        Err(PacketError::UnexpectedValue("the wire type of the tagged field to match its type"))?;
+    }
+    let mut payload = payload;
+    let value = T::read_with(ctx, &mut payload)?;
+    if !payload.is_empty() {
+        Err(PacketError::UnexpectedValue("the tagged field value to use its whole payload"))?;
+    }
+    Ok(value)
+}
+
+/// Reads the number of fields that follow in a tagged struct checking it against
+/// the collection length limit of [ctx]
+pub fn read_tagged_count<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<usize> {
+    let count = VarInt::read_with(ctx, i)?.0 as usize;
+    check_collection_length(ctx, count)?;
+    Ok(count)
+}
+
+/// Checks at compile time that the [tags] of the fields of a tagged struct are
+/// unique and no larger than MAX_FIELD_TAG
+pub const fn check_field_tags(tags: &[u32]) -> bool {
+    let mut index = 0;
+    while index < tags.len() {
+        if tags[index] > MAX_FIELD_TAG {
+            return false;
+        }
+        let mut other = index + 1;
+        while other < tags.len() {
+            if tags[index] == tags[other] {
+                return false;
+            }
+            other += 1;
+        }
+        index += 1;
+    }
+    true
+}
@@ -0,0 +1,155 @@
+use alloc::string::String;
+
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::ReadContext;
+use crate::ordered::{write_ordered_struct, OrderedRepr, OrderedStyle};
+use crate::summary::{write_summary_fields, Summary};
+
+/// ## Text Options
+/// The rules strings must follow on a connection. Each peer sends the options it
+/// accepts during the handshake and both use the negotiated options (the stricter
+/// of the two) so their validation rules stay in sync. The options are applied to
+/// string decoding through the ReadContext (see Session::set_text_options) and
+/// strings breaking them fail with PacketError::TextRejected or
+/// PacketError::InvalidStringLength. This is encoded as the maximum string length
+/// (VarInt) followed by the rules (u8)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextOptions {
+    /// The maximum length of strings in bytes
+    pub max_string_length: u32,
+    /// The rules strings must follow (ASCII_ONLY, NO_CONTROL, NO_NEWLINES and TRIMMED)
+    pub rules: u8,
+}
+
+impl TextOptions {
+    /// Strings may only contain ASCII characters
+    pub const ASCII_ONLY: u8 = 0x01;
+    /// Strings may not contain control characters other than tabs and new lines
+    pub const NO_CONTROL: u8 = 0x02;
+    /// Strings may not contain new lines
+    pub const NO_NEWLINES: u8 = 0x04;
+    /// Strings may not start or end with whitespace
+    pub const TRIMMED: u8 = 0x08;
+    /// All the rules understood by this version
+    pub const ALL_RULES: u8 = 0x0F;
+
+    /// Options which accept any string
+    pub const UNRESTRICTED: TextOptions = TextOptions { max_string_length: u32::MAX, rules: 0 };
+
+    /// Creates new options
+    pub fn new(max_string_length: u32, rules: u8) -> Self {
+        TextOptions { max_string_length, rules }
+    }
+
+    /// The options both this peer and the [peer] accept which is the shorter of the
+    /// maximum lengths along with the rules of both. Rules this version doesn't
+    /// understand are dropped so both sides enforce the same rules
+    pub fn negotiate(&self, peer: &TextOptions) -> TextOptions {
+        TextOptions {
+            max_string_length: self.max_string_length.min(peer.max_string_length),
+            rules: (self.rules | peer.rules) & TextOptions::ALL_RULES,
+        }
+    }
+
+    /// Whether the [rule] is enforced
+    pub fn has_rule(&self, rule: u8) -> bool {
+        self.rules & rule == rule
+    }
+
+    /// Checks that a string of [length] bytes isn't longer than the maximum length
+    pub fn check_length(&self, length: usize) -> ReadResult<()> {
+        if length > self.max_string_length as usize {
+            return Err(PacketError::InvalidStringLength(length, self.max_string_length as usize));
+        }
+        Ok(())
+    }
+
+    /// Checks that the [text] follows these options
+    pub fn check(&self, text: &str) -> ReadResult<()> {
+        self.check_length(text.len())?;
+        if self.rules == 0 {
+            return Ok(());
+        }
+        if self.has_rule(TextOptions::ASCII_ONLY) && !text.is_ascii() {
+            return Err(PacketError::TextRejected("non-ascii character"));
+        }
+        if self.has_rule(TextOptions::NO_NEWLINES) && text.contains(['\n', '\r']) {
+            return Err(PacketError::TextRejected("new line"));
+        }
+        if self.has_rule(TextOptions::NO_CONTROL) && text.chars().any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r')) {
+            return Err(PacketError::TextRejected("control character"));
+        }
+        if self.has_rule(TextOptions::TRIMMED) && text.trim() != text {
+            return Err(PacketError::TextRejected("surrounding whitespace"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for TextOptions {
+    fn default() -> Self { TextOptions::UNRESTRICTED }
+}
+
+impl Writable for TextOptions {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        VarInt(self.max_string_length).write(o)?;
+        self.rules.write(o)
+    }
+}
+
+impl Readable for TextOptions {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let max_string_length = VarInt::read_with(ctx, i)?.0;
+        let rules = u8::read(i)?;
+        Ok(TextOptions { max_string_length, rules })
+    }
+}
+
+impl OrderedRepr for TextOptions {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_struct(style, out, "TextOptions", &[
+            ("max_string_length", &self.max_string_length),
+            ("rules", &self.rules),
+        ]);
+    }
+}
+
+impl Summary for TextOptions {
+    fn write_summary(&self, out: &mut String) {
+        write_summary_fields(out, "TextOptions", None, &[
+            ("max_string_length", &self.max_string_length),
+            ("rules", &self.rules),
+        ]);
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for TextOptions {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(TextOptions::new(u32::arbitrary(u)?, u8::arbitrary(u)?))
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl crate::schema::Schema for TextOptions {
+    fn type_schema() -> crate::schema::TypeSchema {
+        crate::schema::TypeSchema::Named("TextOptions")
+    }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::schema::Definition>) {
+        use crate::schema::{add_definition, Definition, DefinitionKind, FieldSchema, TypeSchema};
+        add_definition(definitions, Definition {
+            name: "TextOptions",
+            kind: DefinitionKind::Struct(alloc::vec![
+                FieldSchema::new("max_string_length", TypeSchema::VarInt),
+                FieldSchema::new("rules", TypeSchema::U8),
+            ]),
+        });
+    }
+}
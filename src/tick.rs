@@ -0,0 +1,131 @@
+use alloc::collections::VecDeque;
+
+/// What happens to a packet pushed into a full TickLoop or OutboundQueue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The pushed packet is dropped
+    DropNewest,
+    /// The oldest queued packet is dropped to make room for the pushed packet
+    DropOldest,
+}
+
+/// ## Tick Limits
+/// Limits on the packets that a TickLoop queues and processes each tick so a
+/// flood of packets can't stall the ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickLimits {
+    /// The maximum number of packets handled before each tick. Packets over this
+    /// are left queued for the following ticks
+    pub max_packets_per_tick: usize,
+    /// The maximum number of packets waiting in the queue
+    pub max_queued: usize,
+    /// What happens to packets pushed when the queue is full
+    pub overflow: OverflowPolicy,
+}
+
+impl TickLimits {
+    /// The limits used by TickLoop::new
+    pub const DEFAULT: TickLimits = TickLimits {
+        max_packets_per_tick: 256,
+        max_queued: 4096,
+        overflow: OverflowPolicy::DropNewest,
+    };
+}
+
+impl Default for TickLimits {
+    fn default() -> Self { TickLimits::DEFAULT }
+}
+
+/// ## Tick Loop
+/// Deterministic main loop for game servers which interleaves fixed rate ticks with
+/// handling the received packets. Packets are pushed into a bounded queue as they
+/// arrive and each tick handles up to max_packets_per_tick of them in the order they
+/// were received before running the tick itself.
+///
+/// Times are milliseconds from any monotonic clock provided by the caller so this
+/// can be used without std and with any runtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickLoop<P> {
+    interval: u64,
+    limits: TickLimits,
+    tick: u64,
+    next_tick: u64,
+    queue: VecDeque<P>,
+    dropped: u64,
+}
+
+impl<P> TickLoop<P> {
+    /// Creates a new loop which ticks every [interval] milliseconds with the default
+    /// limits starting with the first tick at [now]
+    pub fn new(interval: u64, now: u64) -> Self {
+        TickLoop::with_limits(interval, TickLimits::DEFAULT, now)
+    }
+
+    /// Creates a new loop which ticks every [interval] milliseconds with the provided
+    /// [limits] starting with the first tick at [now]
+    pub fn with_limits(interval: u64, limits: TickLimits, now: u64) -> Self {
+        TickLoop {
+            interval,
+            limits,
+            tick: 0,
+            next_tick: now,
+            queue: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Queues the [packet] to be handled before the next tick returning whether it
+    /// was queued. When the queue is full the overflow policy decides which packet
+    /// is dropped
+    pub fn push(&mut self, packet: P) -> bool {
+        if self.queue.len() < self.limits.max_queued {
+            self.queue.push_back(packet);
+            return true;
+        }
+        self.dropped += 1;
+        match self.limits.overflow {
+            OverflowPolicy::DropNewest => false,
+            OverflowPolicy::DropOldest => {
+                if self.queue.pop_front().is_none() {
+                    return false;
+                }
+                self.queue.push_back(packet);
+                true
+            }
+        }
+    }
+
+    /// Runs all the ticks that are due at [now] returning the number of ticks run.
+    /// Before each tick the queued packets are passed to [handle] (up to the limit
+    /// per tick) and then [tick] is called with the tick number. Both are given the
+    /// [state] (e.g. the game world). Ticks that were missed are all run so the
+    /// simulation stays deterministic
+    pub fn poll<S, H, T>(&mut self, now: u64, state: &mut S, mut handle: H, mut tick: T) -> u64
+        where H: FnMut(&mut S, P),
+              T: FnMut(&mut S, u64) {
+        let mut ran = 0;
+        while now >= self.next_tick {
+            let count = self.queue.len().min(self.limits.max_packets_per_tick);
+            for packet in self.queue.drain(..count) {
+                handle(state, packet);
+            }
+            tick(state, self.tick);
+            self.tick += 1;
+            self.next_tick = self.next_tick.saturating_add(self.interval.max(1));
+            ran += 1;
+        }
+        ran
+    }
+
+    /// The number of the next tick to run
+    pub fn tick(&self) -> u64 { self.tick }
+
+    /// The time the next tick is due
+    pub fn next_tick_at(&self) -> u64 { self.next_tick }
+
+    /// The number of packets waiting to be handled
+    pub fn queued(&self) -> usize { self.queue.len() }
+
+    /// The number of packets dropped because the queue was full
+    pub fn dropped(&self) -> u64 { self.dropped }
+}
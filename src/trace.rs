@@ -0,0 +1,225 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter, Write as _};
+
+use crate::io::{Read, Readable, ReadResult, Write, Writable, WriteResult};
+use crate::limits::{ReadContext, WriteContext};
+use crate::ordered::{write_ordered_struct, OrderedRepr, OrderedStyle};
+use crate::summary::{write_summary_fields, Summary};
+
+/// ## Trace Context
+/// The W3C trace context of the span that sent a packet so distributed traces can
+/// follow a request from the browser through the socket into the backend handlers.
+/// This is encoded compactly as the raw bytes of the trace ID (16), span ID (8) and
+/// trace flags (1) rather than as the 55 character traceparent header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceContext {
+    /// The ID of the whole trace
+    pub trace_id: [u8; 16],
+    /// The ID of the span that sent the packet
+    pub span_id: [u8; 8],
+    /// The trace flags, the lowest bit is whether the trace is sampled
+    pub flags: u8,
+}
+
+impl TraceContext {
+    /// The flag for whether the trace is sampled
+    pub const SAMPLED: u8 = 0x01;
+
+    /// Creates a new context
+    pub fn new(trace_id: [u8; 16], span_id: [u8; 8], flags: u8) -> Self {
+        TraceContext { trace_id, span_id, flags }
+    }
+
+    /// Parses the value of a W3C traceparent header (version 00) e.g.
+    /// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01` returning None if
+    /// the header is invalid
+    pub fn from_traceparent(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let version = parts.next()?;
+        if version != "00" {
+            return None;
+        }
+        let mut context = TraceContext::new([0; 16], [0; 8], 0);
+        parse_hex(parts.next()?, &mut context.trace_id)?;
+        parse_hex(parts.next()?, &mut context.span_id)?;
+        let mut flags = [0u8];
+        parse_hex(parts.next()?, &mut flags)?;
+        context.flags = flags[0];
+        // IDs of all zeros are invalid
+        if parts.next().is_some() || context.trace_id == [0; 16] || context.span_id == [0; 8] {
+            return None;
+        }
+        Some(context)
+    }
+
+    /// The value of the W3C traceparent header for this context
+    pub fn traceparent(&self) -> String {
+        let mut out = String::with_capacity(55);
+        out.push_str("00-");
+        write_hex(&mut out, &self.trace_id);
+        out.push('-');
+        write_hex(&mut out, &self.span_id);
+        out.push('-');
+        write_hex(&mut out, &[self.flags]);
+        out
+    }
+
+    /// Whether the trace is sampled
+    pub fn is_sampled(&self) -> bool {
+        self.flags & TraceContext::SAMPLED != 0
+    }
+
+    /// Creates the context for a child span with the provided [span_id] in the same trace
+    pub fn child(&self, span_id: [u8; 8]) -> Self {
+        TraceContext { span_id, ..*self }
+    }
+}
+
+/// Parses the lowercase or uppercase [hex] into [out] which it must exactly fill
+fn parse_hex(hex: &str, out: &mut [u8]) -> Option<()> {
+    if hex.len() != out.len() * 2 {
+        return None;
+    }
+    for (byte, pair) in out.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = core::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    Some(())
+}
+
+/// Writes the [bytes] as lowercase hex to [out]
+fn write_hex(out: &mut String, bytes: &[u8]) {
+    for byte in bytes {
+        let _ = write!(out, "{:02x}", byte);
+    }
+}
+
+impl Display for TraceContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.traceparent())
+    }
+}
+
+impl Writable for TraceContext {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        o.write_all(&self.trace_id)?;
+        o.write_all(&self.span_id)?;
+        o.write_all(&[self.flags])?;
+        Ok(())
+    }
+}
+
+impl Readable for TraceContext {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let mut bytes = [0u8; 25];
+        i.read_exact(&mut bytes)?;
+        let mut context = TraceContext::new([0; 16], [0; 8], bytes[24]);
+        context.trace_id.copy_from_slice(&bytes[..16]);
+        context.span_id.copy_from_slice(&bytes[16..24]);
+        Ok(context)
+    }
+}
+
+impl OrderedRepr for TraceContext {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.traceparent().write_ordered(style, out)
+    }
+}
+
+impl Summary for TraceContext {
+    fn write_summary(&self, out: &mut String) {
+        out.push_str(&self.traceparent())
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for TraceContext {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(TraceContext::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+/// The IDs are fixed size byte arrays which have no schema type
+#[cfg(feature = "codegen")]
+impl crate::Schema for TraceContext {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("TraceContext") }
+}
+
+/// ## Traced
+/// A packet with an optional trace context header which is restored on the
+/// receiving side. This is encoded as the context (a bool for whether it is
+/// present followed by the 25 byte context) followed by the value so untraced
+/// packets only cost a single byte
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Traced<T> {
+    /// The trace context of the span that sent the packet
+    pub context: Option<TraceContext>,
+    /// The packet
+    pub value: T,
+}
+
+impl<T> Traced<T> {
+    /// Creates a new value with the provided trace [context]
+    pub fn new(context: Option<TraceContext>, value: T) -> Self {
+        Traced { context, value }
+    }
+
+    /// Creates a new value without a trace context
+    pub fn untraced(value: T) -> Self {
+        Traced { context: None, value }
+    }
+
+    /// Consumes self returning the wrapped value
+    pub fn into_inner(self) -> T { self.value }
+}
+
+impl<T: Writable> Writable for Traced<T> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        self.context.write(o)?;
+        self.value.write_with(ctx, o)
+    }
+}
+
+impl<T: Readable> Readable for Traced<T> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let context = Option::<TraceContext>::read(i)?;
+        let value = ctx.enter(|ctx| T::read_with(ctx, i))?;
+        Ok(Traced { context, value })
+    }
+}
+
+impl<T: OrderedRepr> OrderedRepr for Traced<T> {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_struct(style, out, "Traced", &[("context", &self.context), ("value", &self.value)])
+    }
+}
+
+impl<T: Summary> Summary for Traced<T> {
+    fn write_summary(&self, out: &mut String) {
+        write_summary_fields(out, "Traced", None, &[("context", &self.context), ("value", &self.value)])
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Traced<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Traced { context: Option::<TraceContext>::arbitrary(u)?, value: T::arbitrary(u)? })
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl<T: crate::Schema> crate::Schema for Traced<T> {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("Traced") }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::Definition>) { T::definitions(definitions) }
+}
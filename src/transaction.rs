@@ -0,0 +1,132 @@
+use alloc::vec::Vec;
+
+use crate::error::PacketError;
+use crate::io::{PacketResult, Read, Readable, ReadResult, Writable, Write, WriteResult};
+use crate::limits::{check_collection_length, ReadContext, WriteContext};
+
+/// Marker written before the first packet of a transaction
+const BEGIN: u8 = 0x01;
+/// Marker written before each packet contained in a transaction
+const PACKET: u8 = 0x02;
+/// Marker written once all the packets have been written
+const COMMIT: u8 = 0x03;
+/// Marker written if the sender gave up on the transaction
+const ABORT: u8 = 0x04;
+
+/// ## Transactions
+/// A transaction is a set of packets from the same group that must be applied
+/// together. When reading, every packet in the transaction is decoded before any
+/// of them are made available so a partially received or aborted transaction is
+/// never delivered to the handlers.
+///
+/// ## Encoding
+/// Begin: u8 (0x01)
+/// for each packet {
+///     Marker: u8 (0x02)
+///     Packet: G
+/// }
+/// Commit: u8 (0x03) or Abort: u8 (0x04)
+///
+/// The number of packets is checked against the collection length limit of the
+/// context while reading
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction<G>(pub Vec<G>);
+
+impl<G> Transaction<G> {
+    /// Delivers all the packets in this transaction to the provided handler
+    /// in the order they were written
+    pub fn dispatch<F: FnMut(G)>(self, handler: F) {
+        self.0.into_iter().for_each(handler)
+    }
+
+    /// Delivers all the packets in this transaction to the provided handler
+    /// stopping at the first packet the handler fails on
+    pub fn try_dispatch<E, F: FnMut(G) -> Result<(), E>>(self, handler: F) -> Result<(), E> {
+        self.0.into_iter().try_for_each(handler)
+    }
+}
+
+impl<G> From<Vec<G>> for Transaction<G> { fn from(v: Vec<G>) -> Self { Transaction(v) } }
+
+impl<G: Writable> Writable for Transaction<G> {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.write_with(&mut WriteContext::default(), o)
+    }
+
+    fn write_with<B: Write>(&mut self, ctx: &mut WriteContext, o: &mut B) -> WriteResult {
+        let mut writer = TransactionWriter::begin(o)?;
+        for packet in &mut self.0 {
+            writer.push(ctx, packet)?;
+        }
+        writer.commit()
+    }
+}
+
+impl<G: Readable> Readable for Transaction<G> {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        if u8::read(i)? != BEGIN {
+            Err(PacketError::UnexpectedValue("transaction begin marker"))?;
+        }
+        ctx.enter(|ctx| {
+            let mut packets = Vec::new();
+            loop {
+                match u8::read(i)? {
+                    PACKET => {
+                        check_collection_length(ctx, packets.len() + 1)?;
+                        packets.push(G::read_with(ctx, i)?)
+                    }
+                    COMMIT => break,
+                    ABORT => Err(PacketError::TransactionAborted)?,
+                    _ => Err(PacketError::UnexpectedValue("transaction packet, commit or abort marker"))?
+                }
+            }
+            Ok(Transaction(packets))
+        })
+    }
+}
+
+/// Transactions contain whole packets (ID and contents) rather than a type
+#[cfg(feature = "codegen")]
+impl<G> crate::Schema for Transaction<G> {
+    fn type_schema() -> crate::TypeSchema { crate::TypeSchema::Custom("Transaction") }
+}
+
+/// ## Transaction Writer
+/// Writes the packets of a transaction as they are produced rather than
+/// collecting them first. The transaction must be finished with either
+/// commit or abort, the receiving end will discard any packets that
+/// were pushed to an aborted transaction.
+pub struct TransactionWriter<'a, B: Write> {
+    o: &'a mut B,
+}
+
+impl<'a, B: Write> TransactionWriter<'a, B> {
+    /// Writes the begin marker to the provided output [o]
+    pub fn begin(o: &'a mut B) -> PacketResult<Self> {
+        o.write_all(&[BEGIN])?;
+        Ok(TransactionWriter { o })
+    }
+
+    /// Writes the provided packet as part of this transaction using the context
+    /// [ctx] of the connection being written to
+    pub fn push<G: Writable>(&mut self, ctx: &mut WriteContext, packet: &mut G) -> WriteResult {
+        self.o.write_all(&[PACKET])?;
+        packet.write_with(ctx, self.o)
+    }
+
+    /// Finishes the transaction so the receiver will apply it
+    pub fn commit(self) -> WriteResult {
+        self.o.write_all(&[COMMIT])?;
+        Ok(())
+    }
+
+    /// Finishes the transaction so the receiver will discard it
+    pub fn abort(self) -> WriteResult {
+        self.o.write_all(&[ABORT])?;
+        Ok(())
+    }
+}
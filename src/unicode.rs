@@ -0,0 +1,147 @@
+use alloc::string::String;
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::field::IntoField;
+use crate::io::{Read, Readable, ReadResult, Write, Writable, WriteResult};
+use crate::limits::ReadContext;
+use crate::ordered::{OrderedRepr, OrderedStyle};
+use crate::summary::Summary;
+
+/// Normalizes the [text] to Unicode Normalization Form C returning the normalized
+/// text along with whether it was changed
+pub fn normalize_nfc(text: String) -> (String, bool) {
+    if is_nfc(&text) {
+        (text, false)
+    } else {
+        (text.nfc().collect(), true)
+    }
+}
+
+/// ## NFC String
+/// A string which is normalized to Unicode Normalization Form C when it is read or
+/// created so visually identical text (e.g. "é" as one code point or as "e" and a
+/// combining accent) compares equal. Use this for usernames and identifiers to
+/// prevent spoofing and mismatches. This is encoded the same as a String so it
+/// can replace a String field without changing the protocol, the received value is
+/// normalized after the text options of the ReadContext are checked. Strings are
+/// equal when their normalized values are equal
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub struct NfcString {
+    value: String,
+    changed: bool,
+}
+
+impl NfcString {
+    /// Creates a new string normalizing the [value]
+    pub fn new(value: impl Into<String>) -> Self {
+        let (value, changed) = normalize_nfc(value.into());
+        NfcString { value, changed }
+    }
+
+    /// The normalized string
+    pub fn as_str(&self) -> &str { &self.value }
+
+    /// Consumes self returning the normalized string
+    pub fn into_inner(self) -> String { self.value }
+
+    /// Whether normalizing changed the string. Peers that normalize before sending
+    /// never set this so it can be used to flag suspicious input
+    pub fn changed(&self) -> bool { self.changed }
+}
+
+impl PartialEq for NfcString {
+    fn eq(&self, other: &Self) -> bool { self.value == other.value }
+}
+
+impl Eq for NfcString {}
+
+impl Hash for NfcString {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.value.hash(state) }
+}
+
+impl PartialOrd for NfcString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for NfcString {
+    fn cmp(&self, other: &Self) -> Ordering { self.value.cmp(&other.value) }
+}
+
+impl Deref for NfcString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target { &self.value }
+}
+
+impl Display for NfcString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl From<String> for NfcString {
+    fn from(value: String) -> Self { NfcString::new(value) }
+}
+
+impl From<&str> for NfcString {
+    fn from(value: &str) -> Self { NfcString::new(value) }
+}
+
+impl From<NfcString> for String {
+    fn from(value: NfcString) -> Self { value.value }
+}
+
+impl IntoField<NfcString> for String {
+    fn into_field(self) -> NfcString { NfcString::new(self) }
+}
+
+impl IntoField<NfcString> for &str {
+    fn into_field(self) -> NfcString { NfcString::new(self) }
+}
+
+impl Writable for NfcString {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        self.value.write(o)
+    }
+}
+
+impl Readable for NfcString {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Ok(NfcString::new(String::read_with(ctx, i)?))
+    }
+}
+
+impl OrderedRepr for NfcString {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        self.value.write_ordered(style, out)
+    }
+}
+
+impl Summary for NfcString {
+    fn write_summary(&self, out: &mut String) {
+        self.value.write_summary(out)
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl crate::schema::Schema for NfcString {
+    fn type_schema() -> crate::schema::TypeSchema { crate::schema::TypeSchema::String }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for NfcString {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(NfcString::new(String::arbitrary(u)?))
+    }
+}
@@ -0,0 +1,70 @@
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::ops::RangeBounds;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+use crate::error::PacketError;
+use crate::io::ReadResult;
+
+/// ## Validated Length
+/// The length of a value checked by the #[max_len(max)] field attribute. This is
+/// the number of bytes for strings and the number of elements for collections
+pub trait ValidatedLength {
+    /// The length of the value
+    fn validated_length(&self) -> usize;
+}
+
+impl ValidatedLength for String {
+    fn validated_length(&self) -> usize { self.len() }
+}
+
+/// Macro for implementing ValidatedLength for the collections using their len
+macro_rules! impl_validated_length {
+    ($($(#[$Attr:meta])* $Type:ident<$($Param:ident),*>),*) => {
+        $(
+            $(#[$Attr])*
+            impl<$($Param),*> ValidatedLength for $Type<$($Param),*> {
+                fn validated_length(&self) -> usize { self.len() }
+            }
+        )*
+    };
+}
+
+impl_validated_length!(
+    Vec<T>,
+    VecDeque<T>,
+    BTreeMap<K, V>,
+    BTreeSet<T>,
+    #[cfg(feature = "std")] HashMap<K, V>,
+    #[cfg(feature = "std")] HashSet<T>
+);
+
+/// ## Check Range
+/// Checks that the [value] of the [field] read for the [packet] is within the
+/// [range] failing with PacketError::ValidationFailed when it isn't. This is used
+/// by the #[range(range)] field attribute
+pub fn check_range<T, R>(packet: &'static str, field: &'static str, value: &T, range: &R) -> ReadResult<()>
+    where T: PartialOrd + Debug, R: RangeBounds<T> + Debug {
+    if !range.contains(value) {
+        let reason = format!("{:?} is outside of the range {:?}", value, range);
+        return Err(PacketError::ValidationFailed { packet, field, reason });
+    }
+    Ok(())
+}
+
+/// ## Check Max Length
+/// Checks that the length of the [value] of the [field] read for the [packet] is
+/// at most [max] failing with PacketError::ValidationFailed when it isn't. This is
+/// used by the #[max_len(max)] field attribute
+pub fn check_max_len<T: ValidatedLength>(packet: &'static str, field: &'static str, value: &T, max: usize) -> ReadResult<()> {
+    let length = value.validated_length();
+    if length > max {
+        let reason = format!("length of {} is greater than the max length of {}", length, max);
+        return Err(PacketError::ValidationFailed { packet, field, reason });
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use crate::error::PacketError;
+use crate::io::{Read, Readable, ReadResult, VarInt, Write, Writable, WriteResult};
+use crate::limits::ReadContext;
+use crate::ordered::{write_ordered_struct, OrderedRepr, OrderedStyle};
+use crate::summary::Summary;
+
+/// ## Version
+/// A semantic version of a protocol or application which is usually sent in the
+/// handshake. Versions are ordered by their major, minor and patch numbers. This
+/// is encoded as the three numbers as VarInts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    /// Incremented for incompatible changes
+    pub major: u32,
+    /// Incremented for backwards compatible additions
+    pub minor: u32,
+    /// Incremented for backwards compatible fixes
+    pub patch: u32,
+}
+
+impl Version {
+    /// Creates a new version from the [major], [minor] and [patch] numbers
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version { major, minor, patch }
+    }
+
+    /// Whether a peer with this version can talk to a peer with the [other]
+    /// version using the semver rules. The major numbers must match and while the
+    /// major number is 0 the minor numbers must match too
+    pub fn is_compatible_with(&self, other: &Version) -> bool {
+        self.major == other.major && (self.major != 0 || self.minor == other.minor)
+    }
+
+    /// Whether this version is compatible with the [required] version and
+    /// includes everything it does (i.e. it isn't older)
+    pub fn satisfies(&self, required: &Version) -> bool {
+        self.is_compatible_with(required) && self >= required
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Versions are parsed from the `major.minor.patch` form
+impl FromStr for Version {
+    type Err = PacketError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const EXPECTED: PacketError = PacketError::UnexpectedValue("a version in the form major.minor.patch");
+        let mut parts = value.split('.').map(|part| part.parse::<u32>().map_err(|_| EXPECTED));
+        let (Some(major), Some(minor), Some(patch), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(EXPECTED);
+        };
+        Ok(Version::new(major?, minor?, patch?))
+    }
+}
+
+impl Writable for Version {
+    fn write<B: Write>(&mut self, o: &mut B) -> WriteResult {
+        VarInt(self.major).write(o)?;
+        VarInt(self.minor).write(o)?;
+        VarInt(self.patch).write(o)
+    }
+}
+
+impl Readable for Version {
+    fn read<B: Read>(i: &mut B) -> ReadResult<Self> where Self: Sized {
+        Self::read_with(&mut ReadContext::default(), i)
+    }
+
+    fn read_with<B: Read>(ctx: &mut ReadContext, i: &mut B) -> ReadResult<Self> where Self: Sized {
+        let major = VarInt::read_with(ctx, i)?.0;
+        let minor = VarInt::read_with(ctx, i)?.0;
+        let patch = VarInt::read_with(ctx, i)?.0;
+        Ok(Version { major, minor, patch })
+    }
+}
+
+impl OrderedRepr for Version {
+    fn write_ordered(&self, style: OrderedStyle, out: &mut String) {
+        write_ordered_struct(style, out, "Version", &[
+            ("major", &self.major),
+            ("minor", &self.minor),
+            ("patch", &self.patch),
+        ]);
+    }
+}
+
+impl Summary for Version {
+    fn write_summary(&self, out: &mut String) {
+        use core::fmt::Write;
+        let _ = write!(out, "{}", self);
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Version {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Version::new(u32::arbitrary(u)?, u32::arbitrary(u)?, u32::arbitrary(u)?))
+    }
+}
+
+#[cfg(feature = "codegen")]
+impl crate::schema::Schema for Version {
+    fn type_schema() -> crate::schema::TypeSchema {
+        crate::schema::TypeSchema::Named("Version")
+    }
+
+    fn definitions(definitions: &mut alloc::vec::Vec<crate::schema::Definition>) {
+        use crate::schema::{add_definition, Definition, DefinitionKind, FieldSchema, TypeSchema};
+        add_definition(definitions, Definition {
+            name: "Version",
+            kind: DefinitionKind::Struct(alloc::vec![
+                FieldSchema::new("major", TypeSchema::VarInt),
+                FieldSchema::new("minor", TypeSchema::VarInt),
+                FieldSchema::new("patch", TypeSchema::VarInt),
+            ]),
+        });
+    }
+}
@@ -0,0 +1,21 @@
+use alloc::vec::Vec;
+use js_sys::Uint8Array;
+
+use crate::io::{PacketResult, Readable, ReadResult, Writable};
+
+/// ## Encode To JS
+/// Writes the provided value (usually a packet group) into a javascript
+/// Uint8Array which can be sent directly through a browser WebSocket
+pub fn encode_to_js<W: Writable>(value: &mut W) -> PacketResult<Uint8Array> {
+    let mut o = Vec::new();
+    value.write(&mut o)?;
+    Ok(Uint8Array::from(o.as_slice()))
+}
+
+/// ## Decode From JS
+/// Reads a value (usually a packet group) from the provided javascript
+/// Uint8Array such as the data of a browser WebSocket message
+pub fn decode_from_js<G: Readable>(bytes: &Uint8Array) -> ReadResult<G> {
+    let bytes = bytes.to_vec();
+    G::read(&mut bytes.as_slice())
+}